@@ -93,11 +93,14 @@ const jsonFetch = async <T>(
 
 export const createPaste = async (
   payload: CreatePastePayload,
+  sessionToken?: string | null,
 ): Promise<CreatePasteResponse> => {
   const url = `${API_BASE}/pastes`;
   return jsonFetch<CreatePasteResponse>(url, {
     method: "POST",
     body: JSON.stringify(payload),
+    // The server takes the paste owner from the session, not the payload.
+    headers: sessionToken ? { Authorization: `Bearer ${sessionToken}` } : {},
   });
 };
 
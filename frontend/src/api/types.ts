@@ -19,7 +19,11 @@ export type PasteFormat =
   | "sql"
   | "swift"
   | "html"
-  | "css";
+  | "css"
+  | "diff"
+  | "csv"
+  | "tsv"
+  | "toml";
 
 export type EncryptionAlgorithm =
   | "none"
@@ -56,6 +60,10 @@ export interface CreatePastePayload {
     burn_template?: string;
   };
   owner_pubkey_hash?: string;
+  /** Vanity id such as `team/deploy-runbook`; 409 if already taken. */
+  custom_id?: string;
+  /** `base64`: `content` is opaque bytes, served back as a download. */
+  content_encoding?: "base64";
 }
 
 export interface CreatePasteResponse {
@@ -65,6 +73,8 @@ export interface CreatePasteResponse {
   /** Only present when `live: true` was set in the request. */
   token?: string;
   isLive: boolean;
+  /** Set when the server picked the format from the content. */
+  detectedFormat?: boolean;
 }
 
 export type StegoRequest =
@@ -93,6 +103,8 @@ export interface UserPasteListItem {
 
 export interface UserPasteListResponse {
   pastes: UserPasteListItem[];
+  total: number;
+  next?: number | null;
 }
 
 export interface StatsSummary {
@@ -114,3 +126,9 @@ export interface StatsSummary {
     count: number;
   }>;
 }
+
+export interface PasteExistsResponse {
+  exists: boolean;
+  expired: boolean;
+  consumed: boolean;
+}
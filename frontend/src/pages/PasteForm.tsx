@@ -47,6 +47,10 @@ const formatOptions: Array<{ label: string; value: PasteFormat }> = [
   { label: "Swift", value: "swift" },
   { label: "HTML", value: "html" },
   { label: "CSS", value: "css" },
+  { label: "Diff", value: "diff" },
+  { label: "CSV", value: "csv" },
+  { label: "TSV", value: "tsv" },
+  { label: "TOML", value: "toml" },
 ];
 
 const encryptionOptions: Array<{ label: string; value: EncryptionAlgorithm }> =
@@ -168,7 +172,7 @@ const isPasteFormat = (value: unknown): value is PasteFormat =>
   formatOptions.some((option) => option.value === value);
 
 export const PasteFormPage = () => {
-  const { user } = useAuth();
+  const { user, token } = useAuth();
   const location = useLocation();
   // Seed the editor from router state (fork flow) on mount only; the lazy
   // initializers never re-run, so later navigation state changes can't loop.
@@ -265,7 +269,7 @@ export const PasteFormPage = () => {
         }
       }
 
-      return createPaste(payload);
+      return createPaste(payload, token);
     },
     onSuccess: (result) => {
       const usedEncryption = encryption;
@@ -780,7 +784,7 @@ export const PasteFormPage = () => {
                               <input
                                 id="stegoUpload"
                                 type="file"
-                                accept="image/png,image/bmp"
+                                accept="image/png,image/bmp,image/webp"
                                 onChange={handleStegoFileUpload}
                                 className="block w-full text-xs text-muted-foreground file:mr-3 file:rounded-md file:border file:border-solid file:border-border file:bg-surface file:px-2.5 file:py-1.5 file:text-xs file:font-medium file:text-text hover:file:bg-muted"
                               />
@@ -58,6 +58,14 @@ const formatLabel = (format: string) => {
       return "HTML";
     case "css":
       return "CSS";
+    case "diff":
+      return "Diff";
+    case "csv":
+      return "CSV";
+    case "tsv":
+      return "TSV";
+    case "toml":
+      return "TOML";
     default:
       return format;
   }
@@ -102,6 +110,14 @@ const formatAttestation = (attestation?: PasteViewResponse["attestation"]) => {
   if (attestation.kind === "shared_secret") {
     return "Shared secret";
   }
+  if (attestation.kind === "webauthn") {
+    return "Passkey";
+  }
+  if (attestation.kind === "all" && attestation.factors) {
+    return attestation.factors
+      .map((kind) => formatAttestation({ kind, issuer: attestation.issuer }))
+      .join(" + ");
+  }
   return attestation.kind;
 };
 
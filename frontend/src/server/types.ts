@@ -21,7 +21,11 @@ export interface PasteViewResponse {
     | "sql"
     | "swift"
     | "html"
-    | "css";
+    | "css"
+    | "diff"
+    | "csv"
+    | "tsv"
+    | "toml";
   content: string;
   createdAt: number;
   expiresAt?: number | null;
@@ -53,6 +57,8 @@ export interface PasteViewResponse {
   attestation?: {
     kind: string;
     issuer?: string | null;
+    /** Kind of each required factor when `kind` is `all`. */
+    factors?: string[];
   } | null;
   persistence?: {
     kind: string;
@@ -61,4 +67,6 @@ export interface PasteViewResponse {
   webhook?: {
     provider?: "slack" | "teams" | "generic" | null;
   } | null;
+  contentSha256?: string;
+  tampered?: boolean;
 }
@@ -12,6 +12,7 @@ type encryption_verification = {
   nonce: string option;
   salt: string option;
   aad: string option;
+  kdf: string;
 }
 
 type signature_verification = {
@@ -30,6 +31,12 @@ let derive_key ~salt_cs ~passphrase =
   Mirage_crypto.Hash.SHA256.digest
     (Cstruct.concat [salt_cs; Cstruct.of_string passphrase])
 
+(* Associated data the ciphertext is bound to; absent for legacy pastes. *)
+let adata_of ev =
+  match ev.aad with
+  | None -> Cstruct.empty
+  | Some aad_b64 -> Cstruct.of_string (Base64.decode_exn aad_b64)
+
 let verify_aes_gcm ev =
   match ev.nonce, ev.salt with
   | None, _ -> { valid = false; details = "Missing nonce"; timestamp = now () }
@@ -46,7 +53,7 @@ let verify_aes_gcm ev =
       let nonce_cs = Cstruct.of_string nonce_bytes in
       let ct_cs = Cstruct.of_string ct_bytes in
       (match Mirage_crypto.Cipher_block.AES.GCM.authenticate_decrypt
-        ~key ~nonce:nonce_cs ~adata:Cstruct.empty ct_cs with
+        ~key ~nonce:nonce_cs ~adata:(adata_of ev) ct_cs with
       | Some pt_cs ->
         let decrypted = Cstruct.to_string pt_cs in
         if decrypted = ev.plaintext then
@@ -74,7 +81,7 @@ let verify_chacha20_poly1305 ev =
       let ct_cs = Cstruct.of_string ct_bytes in
       (match Mirage_crypto.Chacha20.authenticate_decrypt
         ~key:(Mirage_crypto.Chacha20.of_secret key_material)
-        ~nonce:nonce_cs ct_cs with
+        ~nonce:nonce_cs ~adata:(adata_of ev) ct_cs with
       | Some pt_cs ->
         let decrypted = Cstruct.to_string pt_cs in
         if decrypted = ev.plaintext then
@@ -105,6 +112,9 @@ let verify_ed25519 sv =
     { valid = false; details = "Ed25519 verification error: " ^ Printexc.to_string e; timestamp = now () }
 
 let verify_encryption (ev : encryption_verification) : verification_result =
+  if ev.kdf <> "sha256" then
+    { valid = false; details = "Unsupported key derivation: " ^ ev.kdf; timestamp = now () }
+  else
   match String.lowercase_ascii ev.algorithm with
   | "aes256_gcm" | "aes-gcm" -> verify_aes_gcm ev
   | "chacha20_poly1305" | "chacha20-poly1305" -> verify_chacha20_poly1305 ev
@@ -131,6 +141,10 @@ let encryption_verification_of_json json =
     nonce = json |> member "nonce" |> to_option to_string;
     salt = json |> member "salt" |> to_option to_string;
     aad = json |> member "aad" |> to_option to_string;
+    (* Requests from before the Argon2id switch carry no "kdf" field. *)
+    kdf = (match json |> member "kdf" with
+           | `Null -> "sha256"
+           | kdf -> kdf |> member "name" |> to_string);
   }
 
 let encryption_verification_of_string s =
@@ -27,6 +27,7 @@ let test_aes_gcm_valid (_ctx : test_ctxt) =
     nonce = Some (Base64.encode_string nonce_str);
     salt = Some (Base64.encode_string salt_str);
     aad = None;
+    kdf = "sha256";
   } in
   let result = verify_encryption ev in
   assert_bool "AES-GCM valid ciphertext should verify" result.valid
@@ -40,6 +41,7 @@ let test_aes_gcm_tampered (_ctx : test_ctxt) =
     nonce = Some (Base64.encode_string (String.make 12 '\x00'));
     salt = Some (Base64.encode_string (String.make 16 '\x00'));
     aad = None;
+    kdf = "sha256";
   } in
   let result = verify_encryption ev in
   assert_bool "Tampered AES-GCM ciphertext should fail" (not result.valid)
@@ -53,6 +55,7 @@ let test_aes_gcm_missing_nonce (_ctx : test_ctxt) =
     nonce = None;
     salt = Some (Base64.encode_string (String.make 16 '\x00'));
     aad = None;
+    kdf = "sha256";
   } in
   let result = verify_encryption ev in
   assert_bool "Missing nonce should fail" (not result.valid)
@@ -66,6 +69,7 @@ let test_aes_gcm_missing_salt (_ctx : test_ctxt) =
     nonce = Some (Base64.encode_string (String.make 12 '\x00'));
     salt = None;
     aad = None;
+    kdf = "sha256";
   } in
   let result = verify_encryption ev in
   assert_bool "Missing salt should fail" (not result.valid)
@@ -91,6 +95,7 @@ let test_chacha20_valid (_ctx : test_ctxt) =
     nonce = Some (Base64.encode_string nonce_str);
     salt = Some (Base64.encode_string salt_str);
     aad = None;
+    kdf = "sha256";
   } in
   let result = verify_encryption ev in
   assert_bool "ChaCha20-Poly1305 valid ciphertext should verify" result.valid
@@ -104,6 +109,7 @@ let test_chacha20_tampered (_ctx : test_ctxt) =
     nonce = Some (Base64.encode_string (String.make 12 '\x00'));
     salt = Some (Base64.encode_string (String.make 16 '\x00'));
     aad = None;
+    kdf = "sha256";
   } in
   let result = verify_encryption ev in
   assert_bool "Tampered ChaCha20 ciphertext should fail" (not result.valid)
@@ -152,7 +158,8 @@ let test_json_parse_encryption (_ctx : test_ctxt) =
     assert_equal "hello" ev.plaintext;
     assert_equal "pass" ev.key;
     assert_equal (Some "AAAA") ev.nonce;
-    assert_equal (Some "AAAA") ev.salt
+    assert_equal (Some "AAAA") ev.salt;
+    assert_equal "sha256" ev.kdf
   | Error msg -> assert_failure ("JSON parse failed: " ^ msg))
 
 let test_json_parse_invalid (_ctx : test_ctxt) =
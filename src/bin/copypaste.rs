@@ -1,6 +1,8 @@
-use std::io::{self, IsTerminal, Read};
+use std::fmt;
+use std::io::{self, IsTerminal, Read, Write};
 
 use clap::{Parser, Subcommand, ValueEnum};
+use copypaste::PasteFormat;
 use serde::Serialize;
 use urlencoding::encode;
 
@@ -21,6 +23,10 @@ enum Command {
     },
     /// Submit text to a copypaste instance and print the resulting URL
     Send(SendArgs),
+    /// Fetch a paste's raw (decrypted) content and write it to stdout
+    Get(GetArgs),
+    /// Delete a paste before it expires, as its owner
+    Delete(DeleteArgs),
     /// Config file management
     Config {
         #[command(subcommand)]
@@ -61,21 +67,188 @@ struct SendArgs {
     #[arg(long, conflicts_with = "retention")]
     ttl: Option<String>,
 
-    /// Retention window in minutes (0 = no expiry). Use --ttl for human-friendly units.
-    #[arg(long, default_value_t = 0)]
-    retention: u64,
+    /// Retention window, e.g. 90m, 24h, 7d, 2w, or bare minutes (0 = no expiry).
+    #[arg(long, default_value = "0")]
+    retention: String,
 
     /// Encryption algorithm to use for this paste.
     #[arg(long, value_enum, default_value_t = CliEncryption::None)]
     encryption_mode: CliEncryption,
 
-    /// Encryption key (required when encryption is not "none").
+    /// Encryption key (required when encryption is not "none"). Prefer
+    /// --prompt-key or COPYPASTE_KEY to keep it out of shell history.
     #[arg(long = "key")]
     encryption_key: Option<String>,
 
+    /// Read the encryption key from the terminal without echo.
+    #[arg(long)]
+    prompt_key: bool,
+
     /// Delete the paste immediately after the first successful view.
     #[arg(long, alias = "burn")]
     burn_after_reading: bool,
+
+    /// Print `{"url", "id", "path"}` to stdout; human-facing text goes to stderr.
+    #[arg(long)]
+    json: bool,
+
+    /// Also copy the resulting URL to the system clipboard.
+    #[arg(long)]
+    copy: bool,
+}
+
+/// The paste `send` created, as printed by `--json`.
+#[derive(Debug, Serialize)]
+struct SentPaste {
+    /// Shareable URL, including `?key=` for encrypted pastes.
+    url: String,
+    id: String,
+    /// Server path of the paste, without the key.
+    path: String,
+}
+
+impl SentPaste {
+    /// Build from the location the server answered with, which is either a
+    /// path or an absolute URL.
+    fn from_location(base_url: &str, location: String) -> Self {
+        let (url, path) = match location
+            .strip_prefix("http://")
+            .or_else(|| location.strip_prefix("https://"))
+        {
+            Some(rest) => {
+                let path = rest.find('/').map_or("/", |at| &rest[at..]).to_string();
+                (location, path)
+            }
+            None => (format!("{base_url}{location}"), location),
+        };
+        let id = path
+            .split(['?', '#'])
+            .next()
+            .unwrap_or_default()
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        Self { url, id, path }
+    }
+}
+
+/// Arguments for the `get` subcommand.
+#[derive(Parser, Debug)]
+struct GetArgs {
+    /// Paste id, as in `/<id>` or `/raw/<id>`.
+    id: String,
+
+    /// Base URL of the copypaste server (e.g. http://127.0.0.1:8000).
+    #[arg(long, default_value = "http://127.0.0.1:8000")]
+    host: String,
+
+    /// Decryption key for encrypted pastes. Falls back to COPYPASTE_KEY.
+    #[arg(long)]
+    key: Option<String>,
+
+    /// Read the decryption key from the terminal without echo.
+    #[arg(long)]
+    prompt_key: bool,
+}
+
+/// Why `get` failed; each maps to its own exit code so scripts can branch.
+#[derive(Debug)]
+enum GetError {
+    KeyRequired,
+    Forbidden,
+    NotFound,
+    Gone,
+    Locked,
+    Status(reqwest::StatusCode),
+    Io(io::Error),
+}
+
+impl GetError {
+    fn exit_code(&self) -> i32 {
+        match self {
+            GetError::Io(_) | GetError::Status(_) => 1,
+            GetError::KeyRequired => 3,
+            GetError::Forbidden => 4,
+            GetError::NotFound => 5,
+            GetError::Gone => 6,
+            GetError::Locked => 7,
+        }
+    }
+}
+
+impl fmt::Display for GetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GetError::KeyRequired => {
+                f.write_str("This paste is encrypted or protected; pass --key.")
+            }
+            GetError::Forbidden => {
+                f.write_str("Access denied: the key (or access code) was rejected.")
+            }
+            GetError::NotFound => f.write_str("Paste not found."),
+            GetError::Gone => f.write_str("Paste has expired or was burned after reading."),
+            GetError::Locked => f.write_str("Paste is time-locked and not readable yet."),
+            GetError::Status(status) => write!(f, "Request failed with status: {status}"),
+            GetError::Io(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+/// Arguments for the `delete` subcommand.
+#[derive(Parser, Debug)]
+struct DeleteArgs {
+    /// Paste id, as in `/<id>`.
+    id: String,
+
+    /// Base URL of the copypaste server (e.g. http://127.0.0.1:8000).
+    #[arg(long, default_value = "http://127.0.0.1:8000")]
+    host: String,
+
+    /// Owner session token, sent as `Authorization: Bearer <token>`.
+    #[arg(long)]
+    token: Option<String>,
+}
+
+/// Why `delete` failed; exit codes line up with `get` where they overlap.
+#[derive(Debug)]
+enum DeleteError {
+    AuthRequired,
+    NotOwner,
+    NotFound,
+    Gone,
+    Status(reqwest::StatusCode),
+    Io(io::Error),
+}
+
+impl DeleteError {
+    fn exit_code(&self) -> i32 {
+        match self {
+            DeleteError::Io(_) | DeleteError::Status(_) => 1,
+            DeleteError::AuthRequired => 3,
+            DeleteError::NotOwner => 4,
+            DeleteError::NotFound => 5,
+            DeleteError::Gone => 6,
+        }
+    }
+}
+
+impl fmt::Display for DeleteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeleteError::AuthRequired => {
+                f.write_str("Authentication required: pass the owner's session with --token.")
+            }
+            DeleteError::NotOwner => {
+                f.write_str("Not the owner: this session cannot delete the paste.")
+            }
+            DeleteError::NotFound => f.write_str("Paste not found."),
+            DeleteError::Gone => f.write_str("Paste has already expired."),
+            DeleteError::Status(status) => write!(f, "Request failed with status: {status}"),
+            DeleteError::Io(error) => write!(f, "{error}"),
+        }
+    }
 }
 
 #[derive(ValueEnum, Clone, Debug, PartialEq, Eq, Default)]
@@ -89,6 +262,14 @@ enum CliFormat {
     Code,
     #[value(name = "json")]
     Json,
+    #[value(name = "javascript")]
+    Javascript,
+    #[value(name = "typescript")]
+    Typescript,
+    #[value(name = "python")]
+    Python,
+    #[value(name = "rust")]
+    Rust,
     #[value(name = "go")]
     Go,
     #[value(name = "cpp")]
@@ -97,6 +278,65 @@ enum CliFormat {
     Kotlin,
     #[value(name = "java")]
     Java,
+    #[value(name = "csharp")]
+    Csharp,
+    #[value(name = "php")]
+    Php,
+    #[value(name = "ruby")]
+    Ruby,
+    #[value(name = "bash")]
+    Bash,
+    #[value(name = "yaml")]
+    Yaml,
+    #[value(name = "sql")]
+    Sql,
+    #[value(name = "swift")]
+    Swift,
+    #[value(name = "html")]
+    Html,
+    #[value(name = "css")]
+    Css,
+    #[value(name = "diff")]
+    Diff,
+    #[value(name = "csv")]
+    Csv,
+    #[value(name = "tsv")]
+    Tsv,
+    #[value(name = "toml")]
+    Toml,
+}
+
+impl CliFormat {
+    /// The server-side format; its `Display` is the wire spelling.
+    fn paste_format(&self) -> PasteFormat {
+        match self {
+            CliFormat::PlainText => PasteFormat::PlainText,
+            CliFormat::Markdown => PasteFormat::Markdown,
+            CliFormat::Code => PasteFormat::Code,
+            CliFormat::Json => PasteFormat::Json,
+            CliFormat::Javascript => PasteFormat::Javascript,
+            CliFormat::Typescript => PasteFormat::Typescript,
+            CliFormat::Python => PasteFormat::Python,
+            CliFormat::Rust => PasteFormat::Rust,
+            CliFormat::Go => PasteFormat::Go,
+            CliFormat::Cpp => PasteFormat::Cpp,
+            CliFormat::Kotlin => PasteFormat::Kotlin,
+            CliFormat::Java => PasteFormat::Java,
+            CliFormat::Csharp => PasteFormat::Csharp,
+            CliFormat::Php => PasteFormat::Php,
+            CliFormat::Ruby => PasteFormat::Ruby,
+            CliFormat::Bash => PasteFormat::Bash,
+            CliFormat::Yaml => PasteFormat::Yaml,
+            CliFormat::Sql => PasteFormat::Sql,
+            CliFormat::Swift => PasteFormat::Swift,
+            CliFormat::Html => PasteFormat::Html,
+            CliFormat::Css => PasteFormat::Css,
+            CliFormat::Diff => PasteFormat::Diff,
+            CliFormat::Csv => PasteFormat::Csv,
+            CliFormat::Tsv => PasteFormat::Tsv,
+            CliFormat::Toml => PasteFormat::Toml,
+        }
+    }
 }
 
 #[derive(ValueEnum, Clone, Debug, PartialEq, Eq, Default)]
@@ -147,17 +387,48 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             tokio::runtime::Builder::new_multi_thread()
                 .enable_all()
                 .build()?
-                .block_on(handlers::launch())
+                .block_on(handlers::launch(config))
         }
         Command::Send(args) => {
-            let url = execute_send(args)?;
-            if io::stdout().is_terminal() {
-                println!("Paste link: {}", url);
+            let (json, copy) = (args.json, args.copy);
+            let sent = execute_send(args)?;
+            // With --json, stdout is for machines; only a terminal stderr
+            // gets the friendly line.
+            let interactive = if json {
+                io::stderr().is_terminal()
             } else {
-                println!("{}", url);
+                io::stdout().is_terminal()
+            };
+            report_send(
+                &sent,
+                json,
+                interactive,
+                &mut io::stdout().lock(),
+                &mut io::stderr().lock(),
+            )?;
+            if copy {
+                copy_link(&sent.url, system_clipboard, &mut io::stderr().lock())?;
+            }
+            Ok(())
+        }
+        Command::Get(args) => {
+            let mut stdout = io::stdout().lock();
+            if let Err(error) = execute_get(args, &mut stdout) {
+                drop(stdout);
+                eprintln!("copypaste get: {error}");
+                std::process::exit(error.exit_code());
             }
             Ok(())
         }
+        Command::Delete(args) => {
+            let id = args.id.clone();
+            if let Err(error) = execute_delete(args) {
+                eprintln!("copypaste delete: {error}");
+                std::process::exit(error.exit_code());
+            }
+            println!("Deleted paste {id}.");
+            Ok(())
+        }
         Command::Config { action } => match action {
             ConfigAction::Init { path } => {
                 let content = copypaste::server::config::EXAMPLE_CONFIG;
@@ -174,34 +445,92 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 }
 
-fn parse_ttl(s: &str) -> io::Result<u64> {
-    let s = s.trim();
-    if let Ok(n) = s.parse::<u64>() {
-        return Ok(n);
-    }
-    let (value, mult) = if let Some(rest) = s.strip_suffix('m') {
-        (rest, 1u64)
-    } else if let Some(rest) = s.strip_suffix('h') {
-        (rest, 60u64)
-    } else if let Some(rest) = s.strip_suffix('d') {
-        (rest, 60u64 * 24)
-    } else if let Some(rest) = s.strip_suffix('w') {
-        (rest, 60u64 * 24 * 7)
+/// Parse a retention/TTL into minutes with the server's duration grammar.
+fn parse_retention(s: &str) -> io::Result<u64> {
+    copypaste::server::time::parse_duration(s)
+        .map(|minutes| minutes as u64)
+        .map_err(|message| io::Error::new(io::ErrorKind::InvalidInput, message))
+}
+
+/// Environment variable consulted when neither --key nor --prompt-key is given.
+const KEY_ENV: &str = "COPYPASTE_KEY";
+
+/// Pick the key by precedence: `--key`, then `--prompt-key`, then
+/// `COPYPASTE_KEY`. Blank values count as absent.
+fn resolve_key(
+    flag: Option<String>,
+    prompt_key: bool,
+    env_key: Option<String>,
+    prompt: impl FnOnce() -> io::Result<String>,
+) -> io::Result<Option<String>> {
+    let present = |key: &String| !key.trim().is_empty();
+    if let Some(key) = flag.filter(present) {
+        return Ok(Some(key));
+    }
+    if prompt_key {
+        return Ok(Some(prompt()?).filter(present));
+    }
+    Ok(env_key.filter(present))
+}
+
+fn read_key_from_tty() -> io::Result<String> {
+    rpassword::prompt_password("Key: ")
+}
+
+fn cli_key(flag: Option<String>, prompt_key: bool) -> io::Result<Option<String>> {
+    resolve_key(
+        flag,
+        prompt_key,
+        std::env::var(KEY_ENV).ok(),
+        read_key_from_tty,
+    )
+}
+
+/// Print the result of `send`: the URL (with a "Paste link:" label when
+/// `interactive`), or with `json` a JSON object on `out` and any human text
+/// on `err`.
+fn report_send(
+    sent: &SentPaste,
+    json: bool,
+    interactive: bool,
+    out: &mut impl Write,
+    err: &mut impl Write,
+) -> io::Result<()> {
+    if json {
+        serde_json::to_writer(&mut *out, sent)?;
+        writeln!(out)?;
+        if interactive {
+            writeln!(err, "Paste link: {}", sent.url)?;
+        }
+    } else if interactive {
+        writeln!(out, "Paste link: {}", sent.url)?;
     } else {
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidInput,
-            format!("Invalid TTL '{s}'. Use e.g. 5m, 2h, 7d, 1w or a raw number of minutes."),
-        ));
-    };
-    value.trim().parse::<u64>().map(|n| n * mult).map_err(|_| {
-        io::Error::new(
-            io::ErrorKind::InvalidInput,
-            format!("Invalid TTL '{s}'. Use e.g. 5m, 2h, 7d, 1w or a raw number of minutes."),
-        )
-    })
+        writeln!(out, "{}", sent.url)?;
+    }
+    out.flush()
+}
+
+/// Put `url` on the clipboard with `copy` and confirm on `err`. A missing
+/// clipboard (headless session, no display) only warns; the link has
+/// already been printed.
+fn copy_link(
+    url: &str,
+    copy: impl FnOnce(&str) -> Result<(), String>,
+    err: &mut impl Write,
+) -> io::Result<()> {
+    match copy(url) {
+        Ok(()) => writeln!(err, "Link copied to clipboard."),
+        Err(error) => writeln!(err, "warning: could not copy link to clipboard: {error}"),
+    }
+}
+
+fn system_clipboard(text: &str) -> Result<(), String> {
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_text(text))
+        .map_err(|e| e.to_string())
 }
 
-fn execute_send(args: SendArgs) -> io::Result<String> {
+fn execute_send(args: SendArgs) -> io::Result<SentPaste> {
     let SendArgs {
         text,
         stdin,
@@ -211,7 +540,10 @@ fn execute_send(args: SendArgs) -> io::Result<String> {
         retention,
         encryption_mode,
         encryption_key,
+        prompt_key,
         burn_after_reading,
+        json: _,
+        copy: _,
     } = args;
 
     let content = if let Some(t) = text {
@@ -234,65 +566,43 @@ fn execute_send(args: SendArgs) -> io::Result<String> {
         ));
     }
 
-    let retention_minutes = if let Some(ttl_str) = ttl {
-        let mins = parse_ttl(&ttl_str)?;
-        if mins == 0 {
-            None
-        } else {
-            Some(mins)
-        }
-    } else if retention == 0 {
+    let retention = ttl.unwrap_or(retention);
+    let retention_minutes = if retention.trim().is_empty() {
         None
     } else {
-        Some(retention)
+        Some(parse_retention(&retention)?).filter(|&minutes| minutes > 0)
     };
 
-    let key_ref = encryption_key.as_deref().filter(|k| !k.trim().is_empty());
-    let encryption = match encryption_mode {
+    let algorithm = match encryption_mode {
         CliEncryption::None => None,
-        CliEncryption::Aes256Gcm => Some(EncryptionPayload {
-            algorithm: "aes256_gcm",
-            key: key_ref.ok_or_else(|| {
-                io::Error::new(
-                    io::ErrorKind::InvalidInput,
-                    "--key must be supplied when using --encryption-mode aes256_gcm",
-                )
-            })?,
-        }),
-        CliEncryption::ChaCha20Poly1305 => Some(EncryptionPayload {
-            algorithm: "chacha20_poly1305",
-            key: key_ref.ok_or_else(|| {
-                io::Error::new(
-                    io::ErrorKind::InvalidInput,
-                    "--key must be supplied when using --encryption-mode chacha20_poly1305",
-                )
-            })?,
-        }),
-        CliEncryption::XChaCha20Poly1305 => Some(EncryptionPayload {
-            algorithm: "xchacha20_poly1305",
-            key: key_ref.ok_or_else(|| {
-                io::Error::new(
-                    io::ErrorKind::InvalidInput,
-                    "--key must be supplied when using --encryption-mode xchacha20_poly1305",
-                )
-            })?,
-        }),
+        CliEncryption::Aes256Gcm => Some("aes256_gcm"),
+        CliEncryption::ChaCha20Poly1305 => Some("chacha20_poly1305"),
+        CliEncryption::XChaCha20Poly1305 => Some("xchacha20_poly1305"),
+    };
+    let encryption_key = match algorithm {
+        Some(_) => cli_key(encryption_key, prompt_key)?,
+        None => None,
+    };
+    let encryption = match (algorithm, encryption_key.as_deref()) {
+        (None, _) => None,
+        (Some(algorithm), Some(key)) => Some(EncryptionPayload { algorithm, key }),
+        (Some(algorithm), None) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "A key is required when using --encryption-mode {algorithm}: \
+                     pass --key, use --prompt-key, or set {KEY_ENV}"
+                ),
+            ))
+        }
     };
 
     let has_encryption = encryption.is_some();
 
+    let format = format.paste_format().to_string();
     let payload = PastePayload {
         content: &content,
-        format: match format {
-            CliFormat::PlainText => "plain_text",
-            CliFormat::Markdown => "markdown",
-            CliFormat::Code => "code",
-            CliFormat::Json => "json",
-            CliFormat::Go => "go",
-            CliFormat::Cpp => "cpp",
-            CliFormat::Kotlin => "kotlin",
-            CliFormat::Java => "java",
-        },
+        format: &format,
         retention_minutes,
         encryption: encryption.clone(),
         burn_after_reading: if burn_after_reading { Some(true) } else { None },
@@ -329,22 +639,88 @@ fn execute_send(args: SendArgs) -> io::Result<String> {
         ));
     }
 
-    let mut full_url = if path.starts_with("http://") || path.starts_with("https://") {
-        path
-    } else {
-        format!("{}{}", base_url, path)
-    };
+    let mut sent = SentPaste::from_location(&base_url, path);
 
     if has_encryption {
         if let Some(key) = encryption_key.as_deref() {
-            let separator = if full_url.contains('?') { '&' } else { '?' };
-            full_url.push(separator);
-            full_url.push_str("key=");
-            full_url.push_str(&encode(key));
+            let separator = if sent.url.contains('?') { '&' } else { '?' };
+            sent.url.push(separator);
+            sent.url.push_str("key=");
+            sent.url.push_str(&encode(key));
         }
     }
 
-    Ok(full_url)
+    Ok(sent)
+}
+
+/// Fetch `/raw/<id>` and stream the body to `out` byte for byte.
+fn execute_get(args: GetArgs, out: &mut impl Write) -> Result<(), GetError> {
+    let GetArgs {
+        id,
+        host,
+        key,
+        prompt_key,
+    } = args;
+    let key = cli_key(key, prompt_key).map_err(GetError::Io)?;
+
+    let mut url = format!("{}/raw/{}", host.trim_end_matches('/'), encode(id.trim()));
+    if let Some(key) = key.as_deref().filter(|k| !k.is_empty()) {
+        url.push_str("?key=");
+        url.push_str(&encode(key));
+    }
+
+    let client = reqwest::blocking::Client::builder()
+        .build()
+        .map_err(|e| GetError::Io(io::Error::other(e)))?;
+    let mut response = client
+        .get(&url)
+        .send()
+        .map_err(|e| GetError::Io(io::Error::other(e)))?;
+
+    match response.status().as_u16() {
+        200..=299 => {}
+        401 => return Err(GetError::KeyRequired),
+        403 => return Err(GetError::Forbidden),
+        404 => return Err(GetError::NotFound),
+        410 => return Err(GetError::Gone),
+        423 => return Err(GetError::Locked),
+        _ => return Err(GetError::Status(response.status())),
+    }
+
+    response
+        .copy_to(out)
+        .map_err(|e| GetError::Io(io::Error::other(e)))?;
+    out.flush().map_err(GetError::Io)
+}
+
+/// Issue `DELETE /api/pastes/<id>` with the owner's bearer token.
+fn execute_delete(args: DeleteArgs) -> Result<(), DeleteError> {
+    let DeleteArgs { id, host, token } = args;
+    let url = format!(
+        "{}/api/pastes/{}",
+        host.trim_end_matches('/'),
+        encode(id.trim())
+    );
+
+    let client = reqwest::blocking::Client::builder()
+        .build()
+        .map_err(|e| DeleteError::Io(io::Error::other(e)))?;
+    let mut request = client.delete(&url);
+    if let Some(token) = token.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+        request = request.bearer_auth(token);
+    }
+    let response = request
+        .send()
+        .map_err(|e| DeleteError::Io(io::Error::other(e)))?;
+
+    match response.status().as_u16() {
+        200..=299 => Ok(()),
+        401 => Err(DeleteError::AuthRequired),
+        403 => Err(DeleteError::NotOwner),
+        404 => Err(DeleteError::NotFound),
+        410 => Err(DeleteError::Gone),
+        _ => Err(DeleteError::Status(response.status())),
+    }
 }
 
 #[cfg(test)]
@@ -365,7 +741,7 @@ mod tests {
 
         let base = server.base_url();
         let args = SendArgs::parse_from(["copypaste-send", "hello", "--host", base.as_str()]);
-        let url = execute_send(args).expect("url");
+        let url = execute_send(args).expect("url").url;
         assert_eq!(url, format!("{}/paste/abc123", base));
         mock.assert();
     }
@@ -391,7 +767,7 @@ mod tests {
             "--key",
             "super key",
         ]);
-        let url = execute_send(args).expect("url");
+        let url = execute_send(args).expect("url").url;
         assert_eq!(url, format!("{}/secret?key=super%20key", base));
         mock.assert();
     }
@@ -406,9 +782,33 @@ mod tests {
         ]);
         let err = execute_send(args).expect_err("missing key should fail");
         assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
-        assert!(err
-            .to_string()
-            .contains("--key must be supplied when using --encryption-mode"));
+        let message = err.to_string();
+        assert!(message.contains("A key is required when using --encryption-mode aes256_gcm"));
+        for source in ["--key", "--prompt-key", KEY_ENV] {
+            assert!(message.contains(source), "error should mention {source}");
+        }
+    }
+
+    fn no_prompt() -> io::Result<String> {
+        panic!("prompt should not be consulted")
+    }
+
+    #[test]
+    fn key_falls_back_to_env_var() {
+        let key = resolve_key(None, false, Some("from-env".into()), no_prompt).unwrap();
+        assert_eq!(key.as_deref(), Some("from-env"));
+    }
+
+    #[test]
+    fn key_precedence_is_flag_then_prompt_then_env() {
+        let key = resolve_key(Some("flag".into()), true, Some("env".into()), no_prompt).unwrap();
+        assert_eq!(key.as_deref(), Some("flag"));
+
+        let key = resolve_key(None, true, Some("env".into()), || Ok("typed".into())).unwrap();
+        assert_eq!(key.as_deref(), Some("typed"));
+
+        let key = resolve_key(Some(" ".into()), false, None, no_prompt).unwrap();
+        assert_eq!(key, None);
     }
 
     #[test]
@@ -435,40 +835,423 @@ mod tests {
     }
 
     #[test]
-    fn parse_ttl_minutes() {
-        assert_eq!(parse_ttl("5m").unwrap(), 5);
-        assert_eq!(parse_ttl("30m").unwrap(), 30);
+    fn send_json_reports_url_id_and_path() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(POST).path("/");
+            then.status(200).body("/paste/abc123");
+        });
+
+        let base = server.base_url();
+        let args = SendArgs::parse_from([
+            "copypaste-send",
+            "hello",
+            "--host",
+            base.as_str(),
+            "--json",
+            "--encryption-mode",
+            "aes256_gcm",
+            "--key",
+            "k",
+        ]);
+        assert!(args.json);
+        let sent = execute_send(args).expect("sent");
+
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        report_send(&sent, true, true, &mut out, &mut err).unwrap();
+        let stdout = String::from_utf8(out).unwrap();
+        assert!(!stdout.contains("Paste link:"));
+        let value: serde_json::Value = serde_json::from_str(&stdout).expect("stdout is JSON");
+        assert_eq!(
+            value,
+            json!({
+                "url": format!("{base}/paste/abc123?key=k"),
+                "id": "abc123",
+                "path": "/paste/abc123",
+            })
+        );
+        assert_eq!(
+            String::from_utf8(err).unwrap(),
+            format!("Paste link: {base}/paste/abc123?key=k\n")
+        );
+    }
+
+    #[test]
+    fn report_send_labels_url_only_without_json() {
+        let sent = SentPaste::from_location("http://h", "/xyz".into());
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        report_send(&sent, false, true, &mut out, &mut err).unwrap();
+        assert_eq!(out, b"Paste link: http://h/xyz\n");
+        assert!(err.is_empty());
+
+        let mut out = Vec::new();
+        report_send(&sent, false, false, &mut out, &mut err).unwrap();
+        assert_eq!(out, b"http://h/xyz\n");
+    }
+
+    #[test]
+    fn sent_paste_parses_absolute_locations() {
+        let sent = SentPaste::from_location("http://h", "https://paste.example/p/abc?x=1".into());
+        assert_eq!(sent.url, "https://paste.example/p/abc?x=1");
+        assert_eq!(sent.path, "/p/abc?x=1");
+        assert_eq!(sent.id, "abc");
+    }
+
+    #[test]
+    fn copy_without_clipboard_warns_and_keeps_the_url() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(POST).path("/");
+            then.status(200).body("/paste/abc123");
+        });
+
+        let base = server.base_url();
+        let args =
+            SendArgs::parse_from(["copypaste-send", "hello", "--host", base.as_str(), "--copy"]);
+        assert!(args.copy);
+        let sent = execute_send(args).expect("sent");
+        assert_eq!(sent.url, format!("{base}/paste/abc123"));
+
+        let mut err = Vec::new();
+        copy_link(&sent.url, |_| Err("no display".into()), &mut err).expect("not fatal");
+        let err = String::from_utf8(err).unwrap();
+        assert!(err.contains("could not copy"), "{err}");
+        assert!(err.contains("no display"));
+    }
+
+    #[test]
+    fn copy_confirms_successful_clipboard_write() {
+        let mut copied = None;
+        let mut err = Vec::new();
+        copy_link(
+            "http://h/xyz",
+            |url| {
+                copied = Some(url.to_string());
+                Ok(())
+            },
+            &mut err,
+        )
+        .unwrap();
+        assert_eq!(copied.as_deref(), Some("http://h/xyz"));
+        assert_eq!(err, b"Link copied to clipboard.\n");
+    }
+
+    /// Writes the real clipboard, which needs a display, so it is ignored by
+    /// default. Run it on a desktop session with
+    /// `cargo test --bin copypaste copy_writes_the_system_clipboard -- --ignored`.
+    #[test]
+    #[ignore = "needs a system clipboard"]
+    fn copy_writes_the_system_clipboard() {
+        let url = "https://copypaste.fyi/clipboard-test";
+        system_clipboard(url).expect("clipboard available");
+        let read = arboard::Clipboard::new().unwrap().get_text().unwrap();
+        assert_eq!(read, url);
+    }
+
+    fn get(server: &MockServer, extra: &[&str]) -> Result<Vec<u8>, GetError> {
+        let base = server.base_url();
+        let mut argv = vec!["copypaste-get", "abc123", "--host", base.as_str()];
+        argv.extend_from_slice(extra);
+        let mut out = Vec::new();
+        execute_get(GetArgs::parse_from(argv), &mut out).map(|()| out)
+    }
+
+    #[test]
+    fn get_streams_raw_body_verbatim() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/raw/abc123")
+                .query_param("key", "super key");
+            then.status(200).body("line one\nline two");
+        });
+
+        let out = get(&server, &["--key", "super key"]).expect("body");
+        assert_eq!(out, b"line one\nline two");
+        mock.assert();
+    }
+
+    #[test]
+    fn get_reports_missing_key() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/raw/abc123");
+            then.status(401);
+        });
+
+        let err = get(&server, &[]).expect_err("401");
+        assert!(matches!(err, GetError::KeyRequired));
+        assert!(err.to_string().contains("--key"));
+        assert_ne!(err.exit_code(), 0);
     }
 
     #[test]
-    fn parse_ttl_hours() {
-        assert_eq!(parse_ttl("2h").unwrap(), 120);
-        assert_eq!(parse_ttl("1h").unwrap(), 60);
+    fn get_reports_wrong_key() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET)
+                .path("/raw/abc123")
+                .query_param("key", "nope");
+            then.status(403);
+        });
+
+        let err = get(&server, &["--key", "nope"]).expect_err("403");
+        assert!(matches!(err, GetError::Forbidden));
+        assert_ne!(err.exit_code(), GetError::KeyRequired.exit_code());
+    }
+
+    #[test]
+    fn get_reports_not_found_and_gone() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/raw/abc123");
+            then.status(404);
+        });
+        let err = get(&server, &[]).expect_err("404");
+        assert!(matches!(err, GetError::NotFound));
+        assert_eq!(err.to_string(), "Paste not found.");
+
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/raw/abc123");
+            then.status(410);
+        });
+        assert!(matches!(get(&server, &[]), Err(GetError::Gone)));
+    }
+
+    fn delete(server: &MockServer, extra: &[&str]) -> Result<(), DeleteError> {
+        let base = server.base_url();
+        let mut argv = vec!["copypaste-delete", "abc123", "--host", base.as_str()];
+        argv.extend_from_slice(extra);
+        execute_delete(DeleteArgs::parse_from(argv))
     }
 
     #[test]
-    fn parse_ttl_days() {
-        assert_eq!(parse_ttl("1d").unwrap(), 1440);
-        assert_eq!(parse_ttl("7d").unwrap(), 10080);
+    fn delete_sends_bearer_token() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(DELETE)
+                .path("/api/pastes/abc123")
+                .header("authorization", "Bearer owner-token");
+            then.status(204);
+        });
+
+        delete(&server, &["--token", "owner-token"]).expect("deleted");
+        mock.assert();
     }
 
     #[test]
-    fn parse_ttl_weeks() {
-        assert_eq!(parse_ttl("1w").unwrap(), 10080);
-        assert_eq!(parse_ttl("2w").unwrap(), 20160);
+    fn delete_maps_each_failure_to_its_own_exit_code() {
+        let failure = |status: u16| {
+            let server = MockServer::start();
+            server.mock(|when, then| {
+                when.method(DELETE).path("/api/pastes/abc123");
+                then.status(status);
+            });
+            delete(&server, &["--token", "t"]).expect_err("failure status")
+        };
+
+        let errors = [failure(401), failure(403), failure(404), failure(500)];
+        assert!(matches!(errors[0], DeleteError::AuthRequired));
+        assert!(matches!(errors[1], DeleteError::NotOwner));
+        assert!(matches!(errors[2], DeleteError::NotFound));
+        assert!(matches!(errors[3], DeleteError::Status(_)));
+
+        let mut codes: Vec<i32> = errors.iter().map(DeleteError::exit_code).collect();
+        assert!(codes.iter().all(|&code| code != 0));
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), 4, "exit codes must be distinct: {codes:?}");
     }
 
     #[test]
-    fn parse_ttl_raw_minutes() {
-        assert_eq!(parse_ttl("60").unwrap(), 60);
-        assert_eq!(parse_ttl("0").unwrap(), 0);
+    fn delete_messages_name_the_problem() {
+        assert!(DeleteError::AuthRequired.to_string().contains("--token"));
+        assert!(DeleteError::NotOwner.to_string().contains("Not the owner"));
+        assert_eq!(DeleteError::NotFound.to_string(), "Paste not found.");
     }
 
     #[test]
-    fn parse_ttl_invalid() {
-        assert!(parse_ttl("5x").is_err());
-        assert!(parse_ttl("abc").is_err());
-        assert!(parse_ttl("").is_err());
+    fn delete_without_token_sends_no_authorization() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(DELETE)
+                .path("/api/pastes/abc123")
+                .matches(|req| {
+                    !req.headers.as_ref().is_some_and(|h| {
+                        h.iter()
+                            .any(|(k, _)| k.eq_ignore_ascii_case("authorization"))
+                    })
+                });
+            then.status(401);
+        });
+
+        let err = delete(&server, &[]).expect_err("401");
+        assert!(matches!(err, DeleteError::AuthRequired));
+        mock.assert();
+    }
+
+    /// Exhaustive over `PasteFormat`, so a new server format fails to
+    /// compile here until the CLI learns it too.
+    fn cli_format_for(format: PasteFormat) -> CliFormat {
+        match format {
+            PasteFormat::PlainText => CliFormat::PlainText,
+            PasteFormat::Markdown => CliFormat::Markdown,
+            PasteFormat::Code => CliFormat::Code,
+            PasteFormat::Json => CliFormat::Json,
+            PasteFormat::Javascript => CliFormat::Javascript,
+            PasteFormat::Typescript => CliFormat::Typescript,
+            PasteFormat::Python => CliFormat::Python,
+            PasteFormat::Rust => CliFormat::Rust,
+            PasteFormat::Go => CliFormat::Go,
+            PasteFormat::Cpp => CliFormat::Cpp,
+            PasteFormat::Kotlin => CliFormat::Kotlin,
+            PasteFormat::Java => CliFormat::Java,
+            PasteFormat::Csharp => CliFormat::Csharp,
+            PasteFormat::Php => CliFormat::Php,
+            PasteFormat::Ruby => CliFormat::Ruby,
+            PasteFormat::Bash => CliFormat::Bash,
+            PasteFormat::Yaml => CliFormat::Yaml,
+            PasteFormat::Sql => CliFormat::Sql,
+            PasteFormat::Swift => CliFormat::Swift,
+            PasteFormat::Html => CliFormat::Html,
+            PasteFormat::Css => CliFormat::Css,
+            PasteFormat::Diff => CliFormat::Diff,
+            PasteFormat::Csv => CliFormat::Csv,
+            PasteFormat::Tsv => CliFormat::Tsv,
+            PasteFormat::Toml => CliFormat::Toml,
+        }
+    }
+
+    #[test]
+    fn every_cli_format_matches_the_server_wire_name() {
+        for variant in CliFormat::value_variants() {
+            let name = variant.to_possible_value().unwrap().get_name().to_string();
+            let format = variant.paste_format();
+            assert_eq!(format.to_string(), name);
+            assert_eq!(serde_json::to_value(format).unwrap(), json!(name));
+            assert_eq!(&cli_format_for(format), variant);
+        }
+        assert_eq!(CliFormat::value_variants().len(), 25);
+    }
+
+    #[test]
+    fn send_accepts_rust_format() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/")
+                .json_body_partial(json!({ "format": "rust" }).to_string());
+            then.status(200).body("/paste/crab");
+        });
+
+        let base = server.base_url();
+        let args = SendArgs::parse_from([
+            "copypaste-send",
+            "fn main() {}",
+            "--host",
+            base.as_str(),
+            "--format",
+            "rust",
+        ]);
+        execute_send(args).expect("url");
+        mock.assert();
+    }
+
+    #[test]
+    fn parse_retention_minutes() {
+        assert_eq!(parse_retention("5m").unwrap(), 5);
+        assert_eq!(parse_retention("30m").unwrap(), 30);
+    }
+
+    #[test]
+    fn parse_retention_hours() {
+        assert_eq!(parse_retention("2h").unwrap(), 120);
+        assert_eq!(parse_retention("1h").unwrap(), 60);
+    }
+
+    #[test]
+    fn parse_retention_days() {
+        assert_eq!(parse_retention("1d").unwrap(), 1440);
+        assert_eq!(parse_retention("7d").unwrap(), 10080);
+    }
+
+    #[test]
+    fn parse_retention_weeks() {
+        assert_eq!(parse_retention("1w").unwrap(), 10080);
+        assert_eq!(parse_retention("2w").unwrap(), 20160);
+    }
+
+    #[test]
+    fn parse_retention_raw_minutes() {
+        assert_eq!(parse_retention("60").unwrap(), 60);
+        assert_eq!(parse_retention("0").unwrap(), 0);
+    }
+
+    #[test]
+    fn parse_retention_invalid() {
+        assert!(parse_retention("5x").is_err());
+        assert!(parse_retention("abc").is_err());
+        assert!(parse_retention("").is_err());
+        let err = parse_retention("7 days").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn send_retention_accepts_suffixed_durations() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/")
+                .json_body_partial(json!({ "retention_minutes": 10080 }).to_string());
+            then.status(200).body("/paste/week");
+        });
+
+        let base = server.base_url();
+        let args = SendArgs::parse_from([
+            "copypaste-send",
+            "hello",
+            "--host",
+            base.as_str(),
+            "--retention",
+            "7d",
+        ]);
+        execute_send(args).expect("url");
+        mock.assert();
+    }
+
+    #[test]
+    fn send_retention_zero_or_empty_means_no_expiry() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST).path("/").matches(|req| {
+                let body: serde_json::Value =
+                    serde_json::from_slice(req.body.as_deref().unwrap_or_default()).unwrap();
+                body.get("retention_minutes").is_none()
+            });
+            then.status(200).body("/paste/forever");
+        });
+
+        let base = server.base_url();
+        for retention in ["0", ""] {
+            let args = SendArgs::parse_from([
+                "copypaste-send",
+                "hello",
+                "--host",
+                base.as_str(),
+                "--retention",
+                retention,
+            ]);
+            execute_send(args).expect("url");
+        }
+        mock.assert_hits(2);
+    }
+
+    #[test]
+    fn send_rejects_invalid_retention() {
+        let args = SendArgs::parse_from(["copypaste-send", "hello", "--retention", "5x"]);
+        let err = execute_send(args).expect_err("invalid retention");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
     }
 
     #[test]
@@ -490,7 +1273,7 @@ mod tests {
             "--ttl",
             "2h",
         ]);
-        let url = execute_send(args).expect("url");
+        let url = execute_send(args).expect("url").url;
         assert_eq!(url, format!("{}/paste/timed", base));
         mock.assert();
     }
@@ -508,7 +1291,7 @@ mod tests {
         let base = server.base_url();
         let args =
             SendArgs::parse_from(["copypaste-send", "hello", "--host", base.as_str(), "--burn"]);
-        let url = execute_send(args).expect("url");
+        let url = execute_send(args).expect("url").url;
         assert_eq!(url, format!("{}/paste/burned", base));
         mock.assert();
     }
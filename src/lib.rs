@@ -1,4 +1,4 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::env;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
@@ -15,7 +15,9 @@ use utoipa::ToSchema;
 
 pub mod server;
 
+use crate::server::fs::FilePersistenceAdapter;
 use crate::server::redis::RedisPersistenceAdapter;
+use crate::server::s3::S3PersistenceAdapter;
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, Hash, ToSchema)]
 #[serde(rename_all = "snake_case")]
@@ -52,6 +54,14 @@ pub enum PasteFormat {
     Swift,
     Html,
     Css,
+    #[serde(rename = "diff")]
+    Diff,
+    #[serde(rename = "csv")]
+    Csv,
+    #[serde(rename = "tsv")]
+    Tsv,
+    #[serde(rename = "toml")]
+    Toml,
 }
 
 impl std::fmt::Display for PasteFormat {
@@ -78,11 +88,64 @@ impl std::fmt::Display for PasteFormat {
             PasteFormat::Swift => "swift",
             PasteFormat::Html => "html",
             PasteFormat::Css => "css",
+            PasteFormat::Diff => "diff",
+            PasteFormat::Csv => "csv",
+            PasteFormat::Tsv => "tsv",
+            PasteFormat::Toml => "toml",
         };
         write!(f, "{}", s)
     }
 }
 
+impl PasteFormat {
+    /// File extension (without the dot) for downloads of this format.
+    pub fn file_extension(&self) -> &'static str {
+        match self {
+            PasteFormat::PlainText | PasteFormat::Code => "txt",
+            PasteFormat::Markdown => "md",
+            PasteFormat::Json => "json",
+            PasteFormat::Javascript => "js",
+            PasteFormat::Typescript => "ts",
+            PasteFormat::Python => "py",
+            PasteFormat::Rust => "rs",
+            PasteFormat::Go => "go",
+            PasteFormat::Cpp => "cpp",
+            PasteFormat::Kotlin => "kt",
+            PasteFormat::Java => "java",
+            PasteFormat::Csharp => "cs",
+            PasteFormat::Php => "php",
+            PasteFormat::Ruby => "rb",
+            PasteFormat::Bash => "sh",
+            PasteFormat::Yaml => "yaml",
+            PasteFormat::Sql => "sql",
+            PasteFormat::Swift => "swift",
+            PasteFormat::Html => "html",
+            PasteFormat::Css => "css",
+            PasteFormat::Diff => "diff",
+            PasteFormat::Csv => "csv",
+            PasteFormat::Tsv => "tsv",
+            PasteFormat::Toml => "toml",
+        }
+    }
+
+    /// Media type for downloads of this format; source code is `text/plain`.
+    pub fn media_type(&self) -> (&'static str, &'static str) {
+        match self {
+            PasteFormat::Markdown => ("text", "markdown"),
+            PasteFormat::Json => ("application", "json"),
+            PasteFormat::Javascript => ("text", "javascript"),
+            PasteFormat::Yaml => ("application", "yaml"),
+            PasteFormat::Html => ("text", "html"),
+            PasteFormat::Css => ("text", "css"),
+            PasteFormat::Diff => ("text", "x-diff"),
+            PasteFormat::Csv => ("text", "csv"),
+            PasteFormat::Tsv => ("text", "tab-separated-values"),
+            PasteFormat::Toml => ("application", "toml"),
+            _ => ("text", "plain"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, Hash, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum EncryptionAlgorithm {
@@ -95,6 +158,53 @@ pub enum EncryptionAlgorithm {
     XChaCha20Poly1305,
     #[serde(rename = "kyber_hybrid_aes256_gcm")]
     KyberHybridAes256Gcm,
+    /// `age` X25519: the request key is an `age1…` recipient and viewers
+    /// decrypt with the matching `AGE-SECRET-KEY-1…` identity.
+    Age,
+}
+
+/// How the symmetric key was derived from the passphrase and salt.
+///
+/// Records written before Argon2id was introduced carry no `kdf` field and
+/// deserialize as [`KdfKind::Sha256`]. Argon2id parameters are stored with
+/// the paste so changing the configured cost never strands old ciphertext.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, ToSchema)]
+#[serde(tag = "name", rename_all = "snake_case")]
+pub enum KdfKind {
+    /// Legacy `SHA256(salt || passphrase)`.
+    #[default]
+    Sha256,
+    Argon2id {
+        memory_kib: u32,
+        iterations: u32,
+        parallelism: u32,
+    },
+}
+
+/// Layout of an encrypted record, stored as `envelope_version`.
+///
+/// Version 1 is every record written so far: base64 `ciphertext`, `nonce`
+/// and `salt`, with the key derived per `kdf` (SHA-256(salt || passphrase)
+/// when the field is absent) and optional id/format associated data
+/// (`bound`). The ML-KEM hybrid packs `kem_ct|aes_ct|aes_nonce` (or the
+/// legacy 4/5-part simulation blob) into `ciphertext` and leaves `nonce` and
+/// `salt` empty, as does `age`, whose `ciphertext` is a base64 binary age
+/// file. A new layout gets a new version rather than a new guess.
+pub const ENVELOPE_V1: u8 = 1;
+
+/// The version 1 layout with the plaintext raw-deflate compressed before
+/// encryption. Readers inflate it through a reader capped at
+/// `COPYPASTE_MAX_DECOMPRESSED_BYTES`; nothing writes it yet.
+pub const ENVELOPE_V2_DEFLATE: u8 = 2;
+
+fn default_envelope_version() -> u8 {
+    ENVELOPE_V1
+}
+
+// Version 1 is left implicit on the wire so existing records, and anchor
+// manifests hashed over them, serialize exactly as before.
+fn is_envelope_v1(version: &u8) -> bool {
+    *version == ENVELOPE_V1
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -108,12 +218,33 @@ pub enum StoredContent {
         ciphertext: String,
         nonce: String,
         salt: String,
+        #[serde(default)]
+        kdf: KdfKind,
+        /// The AEAD tag also covers the paste id and format
+        /// (`server::crypto::PasteBinding`). Absent on older records.
+        #[serde(default, skip_serializing_if = "bool_is_false")]
+        bound: bool,
+        /// See [`ENVELOPE_V1`]; records without the field are version 1.
+        #[serde(
+            default = "default_envelope_version",
+            skip_serializing_if = "is_envelope_v1"
+        )]
+        envelope_version: u8,
     },
     Stego {
         algorithm: EncryptionAlgorithm,
         ciphertext: String,
         nonce: String,
         salt: String,
+        #[serde(default)]
+        kdf: KdfKind,
+        #[serde(default, skip_serializing_if = "bool_is_false")]
+        bound: bool,
+        #[serde(
+            default = "default_envelope_version",
+            skip_serializing_if = "is_envelope_v1"
+        )]
+        envelope_version: u8,
         carrier_mime: String,
         carrier_image: String,
         payload_digest: String,
@@ -128,6 +259,9 @@ pub struct StoredPaste {
     pub expires_at: Option<i64>,
     #[serde(default)]
     pub burn_after_reading: bool,
+    /// Number of successful reads after which the paste is deleted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_reads: Option<u32>,
     #[serde(default)]
     pub metadata: PasteMetadata,
     pub bundle: Option<BundleMetadata>,
@@ -145,6 +279,37 @@ pub struct StoredPaste {
     pub owner_token_hash: Option<String>,
 }
 
+impl StoredPaste {
+    /// Reads allowed in total: `max_reads`, or one for a burn-after-reading
+    /// paste stored before read limits existed.
+    pub fn read_limit(&self) -> Option<u32> {
+        self.max_reads.or(self.burn_after_reading.then_some(1))
+    }
+
+    /// Reads still allowed, given the successful reads counted so far.
+    pub fn reads_remaining(&self) -> Option<u32> {
+        self.read_limit()
+            .map(|limit| u64::from(limit).saturating_sub(self.metadata.access_count) as u32)
+    }
+}
+
+/// A successful read recorded by [`PasteStore::consume_read`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadReceipt {
+    /// Successful reads including this one.
+    pub access_count: u64,
+    /// Reads still allowed after this one; `None` when the paste has no
+    /// read limit.
+    pub reads_remaining: Option<u32>,
+}
+
+impl ReadReceipt {
+    /// Whether this read used up the last allowed read, deleting the paste.
+    pub fn consumed(&self) -> bool {
+        self.reads_remaining == Some(0)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct StoreStats {
@@ -196,6 +361,26 @@ pub struct PasteMetadata {
     pub access_count: u64,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub workspace: Option<String>,
+    /// Lowercase hex SHA-256 of the plaintext, recorded at creation so reads
+    /// can detect stored content that changed underneath us. Only plaintext
+    /// pastes get one; see [`recorded_digest`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_sha256: Option<String>,
+    /// Manifest hash from the last successful anchor, for later verification.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchored_hash: Option<String>,
+    /// Set for binary pastes, whose stored text is an encoding of opaque
+    /// bytes rather than the content itself.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_encoding: Option<ContentEncoding>,
+}
+
+/// How a binary paste's bytes are carried in its text.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentEncoding {
+    /// Standard base64 with padding.
+    Base64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, ToSchema)]
@@ -221,16 +406,69 @@ pub enum AttestationRequirement {
         allowed_drift: u32,
         #[serde(default)]
         issuer: Option<String>,
+        #[serde(default)]
+        algorithm: TotpAlgorithm,
     },
     SharedSecret {
         hash: String,
     },
+    /// A passkey assertion from one of `passkeys`, proven through the
+    /// `/api/pastes/{id}/webauthn` ceremony and presented as a grant token.
+    Webauthn {
+        rp_id: String,
+        /// base64url ids of the allowed credentials.
+        credential_ids: Vec<String>,
+        /// The allowed passkeys as serialized by `webauthn-rs`: credential
+        /// id plus the public key an assertion is checked against.
+        #[schema(value_type = Vec<Object>)]
+        passkeys: Vec<webauthn_rs::prelude::Passkey>,
+    },
+    /// Every listed requirement must pass. Holds at most one requirement of
+    /// each kind, so each has its own prompt field, and never nests.
+    All {
+        requirements: Vec<AttestationRequirement>,
+    },
+}
+
+impl AttestationRequirement {
+    /// The single requirements a viewer must satisfy: the members of
+    /// [`AttestationRequirement::All`], else the requirement itself.
+    pub fn factors(&self) -> &[AttestationRequirement] {
+        match self {
+            AttestationRequirement::All { requirements } => requirements,
+            single => std::slice::from_ref(single),
+        }
+    }
 }
 
 const fn default_attestation_drift() -> u32 {
     1
 }
 
+/// HMAC hash used to derive TOTP codes (RFC 6238).
+///
+/// Requirements stored before the field existed deserialize as
+/// [`TotpAlgorithm::Sha1`], matching what they were created with.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TotpAlgorithm {
+    #[default]
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl TotpAlgorithm {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().replace('-', "").as_str() {
+            "sha1" => Some(Self::Sha1),
+            "sha256" => Some(Self::Sha256),
+            "sha512" => Some(Self::Sha512),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(tag = "kind", rename_all = "snake_case")]
 pub enum PersistenceLocator {
@@ -260,6 +498,10 @@ pub struct WebhookConfig {
     pub provider: Option<WebhookProvider>,
     pub view_template: Option<String>,
     pub burn_template: Option<String>,
+    /// HMAC-SHA256 signing secret for this webhook; overrides
+    /// `COPYPASTE_WEBHOOK_SECRET`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
 }
 
 #[derive(Error, Debug)]
@@ -270,17 +512,98 @@ pub enum PasteError {
     Expired(String),
 }
 
+/// [`PasteStore::create_paste_with_id`] was asked for an id a live paste
+/// already holds.
+#[derive(Error, Debug, PartialEq, Eq)]
+#[error("paste id already taken: {0}")]
+pub struct IdTaken(pub String);
+
+/// Why [`PasteStore::create_paste_with_id`] stored nothing.
+#[derive(Error, Debug)]
+pub enum CreatePasteError {
+    #[error(transparent)]
+    IdTaken(#[from] IdTaken),
+    /// `COPYPASTE_PERSISTENCE_STRICT` is on and the backend rejected the save.
+    #[error(transparent)]
+    Persistence(#[from] PersistenceError),
+}
+
 #[async_trait]
 pub trait PasteStore: Send + Sync + 'static {
-    async fn create_paste(&self, paste: StoredPaste) -> String;
+    /// Store `paste` under a fresh id. Fails only in strict persistence
+    /// mode, when the backend rejected the save.
+    async fn create_paste(&self, paste: StoredPaste) -> Result<String, PersistenceError>;
+    /// Store `paste` under a caller-chosen `id`. Never overwrites a live
+    /// paste (cached or persisted); an expired one may be replaced.
+    async fn create_paste_with_id(
+        &self,
+        id: &str,
+        paste: StoredPaste,
+    ) -> Result<String, CreatePasteError>;
+    /// Pick an id no cached paste holds, without reserving it. Used when the
+    /// id must be known before the paste exists (it is bound into the
+    /// ciphertext); store it with [`PasteStore::create_paste_with_id`], which
+    /// settles a race with a concurrent create.
+    async fn next_paste_id(&self) -> String;
     async fn get_paste(&self, id: &str) -> Result<StoredPaste, PasteError>;
+    /// Inspect a stored paste without side effects: nothing is evicted,
+    /// cached or counted as a read. A paste held only by the persistence
+    /// backend is loaded but not inserted into the cache.
+    async fn peek_paste(&self, id: &str) -> Result<StoredPaste, PasteError>;
     async fn delete_paste(&self, id: &str) -> bool;
+    /// Atomically count one successful read of `id`. A paste whose read
+    /// limit this read reaches is deleted in the same step, so concurrent
+    /// readers can never exceed the limit. `None` means the paste was gone
+    /// (deleted, consumed or expired) and the read must not be served.
+    async fn consume_read(&self, id: &str) -> Option<ReadReceipt>;
+    /// Whether `id` was deleted by [`PasteStore::consume_read`] reaching its
+    /// read limit. Stores that keep no record of consumed pastes answer
+    /// `false`.
+    async fn was_consumed(&self, _id: &str) -> bool {
+        false
+    }
     async fn get_all_paste_ids(&self) -> Vec<String>;
     async fn stats(&self) -> StoreStats;
     /// Replace the content of a live paste (requires ownership token verification at handler level).
-    async fn update_paste(&self, id: &str, content: StoredContent) -> Result<(), PasteError>;
+    /// `content_sha256` is the digest of the new plaintext.
+    async fn update_paste(
+        &self,
+        id: &str,
+        content: StoredContent,
+        content_sha256: Option<String>,
+    ) -> Result<(), PasteError>;
     /// Mark a live paste as finalized (no longer live).
     async fn finalize_paste(&self, id: &str) -> Result<(), PasteError>;
+    /// Record `parent_id` as the bundle a child paste belongs to.
+    async fn set_bundle_parent(&self, id: &str, parent_id: &str) -> Result<(), PasteError>;
+    /// Record the manifest hash a paste was anchored with.
+    async fn set_anchored_hash(&self, id: &str, hash: &str) -> Result<(), PasteError>;
+    /// Run one eviction pass as of `now` and return the pastes it removed.
+    async fn sweep_once(&self, now: i64) -> Vec<(String, StoredPaste)>;
+    /// Probe the persistence backend, if any. Stores without one are
+    /// always healthy.
+    async fn health_check(&self) -> Result<(), PersistenceError> {
+        Ok(())
+    }
+    /// Name of the persistence backend in use, as reported by the health
+    /// endpoints.
+    fn persistence_backend(&self) -> &'static str {
+        "memory"
+    }
+    /// Save every live cached paste to the persistence backend, e.g. before
+    /// shutdown. Stores without a cache have nothing to flush.
+    async fn flush(&self) -> FlushReport {
+        FlushReport::default()
+    }
+}
+
+/// Outcome of [`PasteStore::flush`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FlushReport {
+    pub saved: usize,
+    pub failed: usize,
+    /// Live pastes held with no persistence backend to save them to.
+    pub unpersisted: usize,
 }
 
 #[derive(Error, Debug)]
@@ -291,6 +614,8 @@ pub enum PersistenceError {
     Load(String, String),
     #[error("persistence delete failed for {0}: {1}")]
     Delete(String, String),
+    #[error("persistence backend unreachable: {0}")]
+    Unavailable(String),
 }
 
 #[async_trait]
@@ -298,6 +623,19 @@ pub trait PersistenceAdapter: Send + Sync + 'static {
     async fn save(&self, id: &str, paste: &StoredPaste) -> Result<(), PersistenceError>;
     async fn load(&self, id: &str) -> Result<Option<StoredPaste>, PersistenceError>;
     async fn delete(&self, id: &str) -> Result<(), PersistenceError>;
+    /// Enumerate every paste id held by the backend. Backends that cannot
+    /// list their contents report none, leaving only cached pastes visible.
+    async fn list_ids(&self) -> Result<Vec<String>, PersistenceError> {
+        Ok(Vec::new())
+    }
+    /// Cheap reachability probe used by `/api/health`; must not touch pastes.
+    async fn health_check(&self) -> Result<(), PersistenceError> {
+        Ok(())
+    }
+    /// Short backend name (`redis`, `s3`, ...) for the health endpoints.
+    fn name(&self) -> &'static str {
+        "custom"
+    }
 }
 
 pub struct NoopPersistence;
@@ -315,6 +653,10 @@ impl PersistenceAdapter for NoopPersistence {
     async fn delete(&self, _id: &str) -> Result<(), PersistenceError> {
         Ok(())
     }
+
+    fn name(&self) -> &'static str {
+        "memory"
+    }
 }
 
 struct StatsCache {
@@ -322,293 +664,1109 @@ struct StatsCache {
     computed_at: Instant,
 }
 
-/// TTL for the stats cache used by `MemoryPasteStore::stats()`.
+/// Running totals behind [`PasteStore::stats`], kept in step with the cached
+/// entries by [`PasteMap`] so a stats request never walks every paste.
 ///
-/// Health-check endpoints call `stats()` on every request.  Recomputing the full
-/// O(N) scan each time holds the entries read-lock for the duration and blocks
-/// writers (paste creation/deletion).  Caching with a short TTL keeps the endpoint
-/// responsive while bounding staleness to an acceptable window.
-const STATS_CACHE_TTL: Duration = Duration::from_secs(5);
-
-pub struct MemoryPasteStore {
-    entries: RwLock<HashMap<String, StoredPaste>>,
-    persistence: Option<Arc<dyn PersistenceAdapter>>,
-    stats_cache: Mutex<Option<StatsCache>>,
+/// Expiry depends on the clock, so rather than an active/expired split the
+/// aggregates keep a histogram of expiry timestamps; [`Self::snapshot`]
+/// counts the entries whose expiry has passed at the time it is asked.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct StatsAggregates {
+    total: usize,
+    burn_after_reading: usize,
+    time_locked: usize,
+    expiries: BTreeMap<i64, usize>,
+    formats: HashMap<PasteFormat, usize>,
+    encryption: HashMap<EncryptionAlgorithm, usize>,
+    daily: BTreeMap<String, usize>,
 }
 
-impl MemoryPasteStore {
-    pub fn new() -> Self {
-        Self {
-            entries: RwLock::new(HashMap::new()),
-            persistence: None,
-            stats_cache: Mutex::new(None),
-        }
-    }
+/// The fields of a paste that [`StatsAggregates`] counts, small enough to
+/// keep for pastes whose content is not cached.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct StatsEntry {
+    burn_after_reading: bool,
+    time_locked: bool,
+    expires_at: Option<i64>,
+    format: PasteFormat,
+    algorithm: EncryptionAlgorithm,
+    created_at: i64,
+}
 
-    pub fn with_persistence(adapter: Arc<dyn PersistenceAdapter>) -> Self {
+impl StatsEntry {
+    fn of(paste: &StoredPaste) -> Self {
         Self {
-            entries: RwLock::new(HashMap::new()),
-            persistence: Some(adapter),
-            stats_cache: Mutex::new(None),
+            burn_after_reading: paste.burn_after_reading,
+            time_locked: paste.metadata.not_before.is_some() || paste.metadata.not_after.is_some(),
+            expires_at: paste.expires_at,
+            format: paste.format,
+            algorithm: paste_algorithm(paste),
+            created_at: paste.created_at,
         }
     }
 }
 
-impl Default for MemoryPasteStore {
-    fn default() -> Self {
-        Self::new()
+impl StatsAggregates {
+    fn add(&mut self, paste: &StoredPaste) {
+        self.apply(&StatsEntry::of(paste), true);
     }
-}
 
-fn is_expired(paste: &StoredPaste) -> bool {
-    if let Some(expires_at) = paste.expires_at {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map(|d| d.as_secs() as i64)
-            .unwrap_or_default();
-        now > expires_at
-    } else {
-        false
+    fn remove(&mut self, paste: &StoredPaste) {
+        self.apply(&StatsEntry::of(paste), false);
     }
-}
 
-pub(crate) fn bool_is_false(value: &bool) -> bool {
-    !*value
-}
+    fn apply(&mut self, paste: &StatsEntry, adding: bool) {
+        let step = |count: &mut usize| {
+            if adding {
+                *count += 1;
+            } else {
+                *count = count.saturating_sub(1);
+            }
+        };
 
-const PASTE_ID_ADJECTIVES: &[&str] = &[
-    "stellar", "quantum", "luminous", "neon", "orbital", "cosmic", "radiant", "sonic", "velvet",
-    "ember",
-];
+        step(&mut self.total);
+        if paste.burn_after_reading {
+            step(&mut self.burn_after_reading);
+        }
+        if paste.time_locked {
+            step(&mut self.time_locked);
+        }
+        if let Some(expires_at) = paste.expires_at {
+            bump_ordered(&mut self.expiries, expires_at, adding);
+        }
+        bump(&mut self.formats, paste.format, adding);
+        bump(&mut self.encryption, paste.algorithm, adding);
+        if let Some(dt) = DateTime::<Utc>::from_timestamp(paste.created_at, 0) {
+            let date = dt.date_naive().format("%Y-%m-%d").to_string();
+            bump_ordered(&mut self.daily, date, adding);
+        }
+    }
 
-const PASTE_ID_NOUNS: &[&str] = &[
-    "otter", "phoenix", "nebula", "cipher", "comet", "matrix", "falcon", "vertex", "galaxy",
-    "aurora",
-];
+    fn snapshot(&self, now: i64) -> StoreStats {
+        let expired: usize = self.expiries.range(..now).map(|(_, count)| count).sum();
 
-fn generate_paste_id(map: &HashMap<String, StoredPaste>) -> String {
-    let mut rng = rand::thread_rng();
+        // Most-used first; ties broken by name so the order is stable.
+        let mut formats: Vec<FormatUsage> = self
+            .formats
+            .iter()
+            .map(|(format, count)| FormatUsage {
+                format: *format,
+                count: *count,
+            })
+            .collect();
+        formats.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then_with(|| a.format.to_string().cmp(&b.format.to_string()))
+        });
 
-    for _ in 0..12 {
-        let adjective = PASTE_ID_ADJECTIVES
-            .choose(&mut rng)
-            .unwrap_or(&PASTE_ID_ADJECTIVES[0]);
-        let noun = PASTE_ID_NOUNS
-            .choose(&mut rng)
-            .unwrap_or(&PASTE_ID_NOUNS[0]);
-        let number: u16 = rng.gen_range(10..100);
-        let candidate = format!("{adjective}-{noun}-{number}");
-        if !map.contains_key(&candidate) {
-            return candidate;
+        StoreStats {
+            total_pastes: self.total,
+            active_pastes: self.total.saturating_sub(expired),
+            expired_pastes: expired,
+            burn_after_reading_count: self.burn_after_reading,
+            time_locked_count: self.time_locked,
+            formats,
+            encryption_usage: self
+                .encryption
+                .iter()
+                .map(|(algorithm, count)| EncryptionUsage {
+                    algorithm: *algorithm,
+                    count: *count,
+                })
+                .collect(),
+            created_by_day: self
+                .daily
+                .iter()
+                .map(|(date, count)| DailyCount {
+                    date: date.clone(),
+                    count: *count,
+                })
+                .collect(),
         }
     }
-
-    nanoid!(10)
 }
 
-#[async_trait]
-impl PasteStore for MemoryPasteStore {
-    async fn create_paste(&self, paste: StoredPaste) -> String {
-        let mut map = self.entries.write().await;
-        let id = generate_paste_id(&map);
-        map.insert(id.clone(), paste.clone());
-        if let Some(adapter) = &self.persistence {
-            let _ = adapter.save(&id, &paste).await;
+/// Add or remove one occurrence of `key`, dropping keys that reach zero so
+/// the breakdowns only list values still in use.
+fn bump<K: std::hash::Hash + Eq>(map: &mut HashMap<K, usize>, key: K, adding: bool) {
+    if adding {
+        *map.entry(key).or_default() += 1;
+    } else if let Some(count) = map.get_mut(&key) {
+        *count -= 1;
+        if *count == 0 {
+            map.remove(&key);
         }
-        id
     }
+}
 
-    async fn get_paste(&self, id: &str) -> Result<StoredPaste, PasteError> {
-        let mut map = self.entries.write().await;
-        match map.get(id) {
-            Some(paste) if !is_expired(paste) => Ok(paste.clone()),
-            Some(_) => {
-                map.remove(id);
-                Err(PasteError::Expired(id.to_string()))
-            }
-            None => {
-                if let Some(adapter) = &self.persistence {
-                    match adapter.load(id).await {
-                        Ok(Some(paste)) => {
-                            if is_expired(&paste) {
-                                return Err(PasteError::Expired(id.to_string()));
-                            }
-                            map.insert(id.to_string(), paste.clone());
-                            Ok(paste)
-                        }
-                        Ok(None) => Err(PasteError::NotFound(id.to_string())),
-                        Err(_) => Err(PasteError::NotFound(id.to_string())),
-                    }
-                } else {
-                    Err(PasteError::NotFound(id.to_string()))
-                }
-            }
+/// [`bump`] for the ordered breakdowns.
+fn bump_ordered<K: Ord>(map: &mut BTreeMap<K, usize>, key: K, adding: bool) {
+    if adding {
+        *map.entry(key).or_default() += 1;
+    } else if let Some(count) = map.get_mut(&key) {
+        *count -= 1;
+        if *count == 0 {
+            map.remove(&key);
         }
     }
+}
 
-    async fn delete_paste(&self, id: &str) -> bool {
-        let mut map = self.entries.write().await;
-        let existed = map.remove(id).is_some();
-        if let Some(adapter) = &self.persistence {
-            let _ = adapter.delete(id).await;
+fn paste_algorithm(paste: &StoredPaste) -> EncryptionAlgorithm {
+    match &paste.content {
+        StoredContent::Plain { .. } => EncryptionAlgorithm::None,
+        StoredContent::Encrypted { algorithm, .. } | StoredContent::Stego { algorithm, .. } => {
+            *algorithm
         }
-        existed
     }
+}
 
-    async fn stats(&self) -> StoreStats {
-        // Return cached result if still within TTL (O(1) fast path).
-        {
-            let cache = self.stats_cache.lock().unwrap();
-            if let Some(ref cached) = *cache {
-                if cached.computed_at.elapsed() < STATS_CACHE_TTL {
-                    return cached.stats.clone();
-                }
-            }
-        }
-
-        // Cache miss or expired: perform the full O(N) scan.
-        let stats = {
-            let map = self.entries.read().await;
-            let mut total = 0usize;
-            let mut active = 0usize;
-            let mut expired = 0usize;
-            let mut burn_count = 0usize;
-            let mut time_locked = 0usize;
-            let mut format_counts: HashMap<PasteFormat, usize> = HashMap::new();
-            let mut encryption_counts: HashMap<EncryptionAlgorithm, usize> = HashMap::new();
-            let mut daily_counts: BTreeMap<String, usize> = BTreeMap::new();
-
-            for paste in map.values() {
-                total += 1;
-                let paste_expired = is_expired(paste);
-                if paste_expired {
-                    expired += 1;
-                } else {
-                    active += 1;
-                }
-
-                if paste.burn_after_reading {
-                    burn_count += 1;
-                }
+/// The cached pastes plus the [`StatsAggregates`] describing them. Reads go
+/// through `Deref`; every mutation goes through a method here so the
+/// aggregates cannot drift from the entries.
+#[derive(Default)]
+struct PasteMap {
+    pastes: HashMap<String, StoredPaste>,
+    aggregates: StatsAggregates,
+    /// `(created_at, id)` sorted oldest first, for capacity eviction.
+    /// Removals leave stale entries behind; they are skipped when popped and
+    /// compacted away once they outnumber the live ones.
+    by_age: VecDeque<(i64, String)>,
+    /// Ids of pastes deleted by reaching their read limit, oldest first,
+    /// capped at [`CONSUMED_TOMBSTONES`].
+    consumed: HashSet<String>,
+    consumed_order: VecDeque<String>,
+    /// Pastes only the persistence backend holds, counted in `aggregates`
+    /// since [`MemoryPasteStore::seed_uncached_stats`] loaded them. An entry
+    /// leaves once its paste is cached, removed or expired.
+    uncached: HashMap<String, StatsEntry>,
+    /// Ids cached or removed while that one-time load runs, so it does not
+    /// count them as backend-only afterwards.
+    seeding: Option<HashSet<String>>,
+}
 
-                if paste.metadata.not_before.is_some() || paste.metadata.not_after.is_some() {
-                    time_locked += 1;
-                }
+/// How many consumed ids the memory store remembers for
+/// [`PasteStore::was_consumed`].
+const CONSUMED_TOMBSTONES: usize = 10_000;
 
-                *format_counts.entry(paste.format).or_default() += 1;
+impl std::ops::Deref for PasteMap {
+    type Target = HashMap<String, StoredPaste>;
 
-                let algorithm = match &paste.content {
-                    StoredContent::Plain { .. } => EncryptionAlgorithm::None,
-                    StoredContent::Encrypted { algorithm, .. }
-                    | StoredContent::Stego { algorithm, .. } => *algorithm,
-                };
-                *encryption_counts.entry(algorithm).or_default() += 1;
+    fn deref(&self) -> &Self::Target {
+        &self.pastes
+    }
+}
 
-                if let Some(dt) = DateTime::<Utc>::from_timestamp(paste.created_at, 0) {
-                    let date = dt.date_naive().format("%Y-%m-%d").to_string();
-                    *daily_counts.entry(date).or_default() += 1;
+impl PasteMap {
+    fn insert(&mut self, id: String, paste: StoredPaste) {
+        self.consumed.remove(&id);
+        self.forget_uncached(&id);
+        self.aggregates.add(&paste);
+        let created_at = paste.created_at;
+        match self.pastes.insert(id.clone(), paste) {
+            Some(previous) => {
+                self.aggregates.remove(&previous);
+                if previous.created_at != created_at {
+                    self.track_age(created_at, id);
                 }
             }
+            None => self.track_age(created_at, id),
+        }
+    }
 
-            StoreStats {
-                total_pastes: total,
-                active_pastes: active,
-                expired_pastes: expired,
-                burn_after_reading_count: burn_count,
-                time_locked_count: time_locked,
-                formats: format_counts
-                    .into_iter()
-                    .map(|(format, count)| FormatUsage { format, count })
-                    .collect(),
-                encryption_usage: encryption_counts
-                    .into_iter()
-                    .map(|(algorithm, count)| EncryptionUsage { algorithm, count })
-                    .collect(),
-                created_by_day: daily_counts
-                    .into_iter()
-                    .map(|(date, count)| DailyCount { date, count })
-                    .collect(),
+    /// New pastes are the newest, so this is a push to the back; only pastes
+    /// loaded from persistence need a sorted insert.
+    fn track_age(&mut self, created_at: i64, id: String) {
+        if self.by_age.len() > 2 * self.pastes.len() + 16 {
+            let pastes = &self.pastes;
+            self.by_age
+                .retain(|(at, id)| pastes.get(id).is_some_and(|p| p.created_at == *at));
+        }
+        match self.by_age.back() {
+            Some((newest, _)) if *newest > created_at => {
+                let at = self.by_age.partition_point(|(at, _)| *at <= created_at);
+                self.by_age.insert(at, (created_at, id));
             }
-        };
+            _ => self.by_age.push_back((created_at, id)),
+        }
+    }
 
-        // Store in cache for subsequent requests within the TTL window.
-        *self.stats_cache.lock().unwrap() = Some(StatsCache {
-            stats: stats.clone(),
-            computed_at: Instant::now(),
-        });
+    /// Remove the paste with the oldest `created_at`.
+    fn pop_oldest(&mut self) -> Option<(String, StoredPaste)> {
+        while let Some((created_at, id)) = self.by_age.pop_front() {
+            if self
+                .pastes
+                .get(&id)
+                .is_some_and(|paste| paste.created_at == created_at)
+            {
+                let paste = self.remove(&id)?;
+                return Some((id, paste));
+            }
+        }
+        None
+    }
 
-        stats
+    fn remove(&mut self, id: &str) -> Option<StoredPaste> {
+        self.forget_uncached(id);
+        let removed = self.pastes.remove(id)?;
+        self.aggregates.remove(&removed);
+        Some(removed)
     }
 
-    async fn get_all_paste_ids(&self) -> Vec<String> {
-        let map = self.entries.read().await;
-        map.keys().cloned().collect()
+    fn set_content(&mut self, id: &str, content: StoredContent) -> bool {
+        let Some(paste) = self.pastes.get_mut(id) else {
+            return false;
+        };
+        self.aggregates.remove(paste);
+        paste.content = content;
+        self.aggregates.add(paste);
+        true
     }
 
-    async fn update_paste(&self, id: &str, content: StoredContent) -> Result<(), PasteError> {
-        let mut map = self.entries.write().await;
-        match map.get_mut(id) {
-            Some(paste) if !is_expired(paste) => {
-                paste.content = content;
-                Ok(())
-            }
-            Some(_) => {
-                map.remove(id);
-                Err(PasteError::Expired(id.to_string()))
-            }
-            None => Err(PasteError::NotFound(id.to_string())),
+    /// Count a paste only the backend holds.
+    fn add_uncached(&mut self, id: String, entry: StatsEntry) {
+        if let Some(previous) = self.uncached.insert(id, entry) {
+            self.aggregates.apply(&previous, false);
         }
+        self.aggregates.apply(&entry, true);
     }
 
-    async fn finalize_paste(&self, id: &str) -> Result<(), PasteError> {
-        let mut map = self.entries.write().await;
-        match map.get_mut(id) {
-            Some(paste) if !is_expired(paste) => {
-                paste.is_live = false;
-                Ok(())
-            }
-            Some(_) => {
-                map.remove(id);
-                Err(PasteError::Expired(id.to_string()))
-            }
-            None => Err(PasteError::NotFound(id.to_string())),
+    /// Stop counting `id` as backend-only: it is being cached or removed.
+    fn forget_uncached(&mut self, id: &str) {
+        if let Some(seeding) = &mut self.seeding {
+            seeding.insert(id.to_string());
+        }
+        if let Some(entry) = self.uncached.remove(id) {
+            self.aggregates.apply(&entry, false);
         }
     }
-}
 
-pub type SharedPasteStore = Arc<dyn PasteStore>;
+    /// Drop backend-only pastes expired as of `now`; the backend expires
+    /// them natively and no read can return them any more.
+    fn prune_uncached(&mut self, now: i64) -> bool {
+        let expired: Vec<String> = self
+            .uncached
+            .iter()
+            .filter(|(_, entry)| entry.expires_at.is_some_and(|at| at < now))
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in &expired {
+            self.forget_uncached(id);
+        }
+        !expired.is_empty()
+    }
 
-pub fn create_paste_store() -> SharedPasteStore {
-    match env::var("COPYPASTE_PERSISTENCE_BACKEND") {
-        Ok(value) if value.eq_ignore_ascii_case("vault") => {
-            if let Ok(adapter) = vault::VaultPersistenceAdapter::from_env() {
-                return Arc::new(MemoryPasteStore::with_persistence(adapter));
-            }
-            Arc::new(MemoryPasteStore::new())
+    /// Record that `id` was deleted by its final permitted read.
+    fn mark_consumed(&mut self, id: &str) {
+        if self.consumed.insert(id.to_string()) {
+            self.consumed_order.push_back(id.to_string());
         }
-        Ok(value) if value.eq_ignore_ascii_case("redis") => {
-            if let Ok(adapter) = RedisPersistenceAdapter::from_env() {
-                return Arc::new(MemoryPasteStore::with_persistence(adapter));
+        while self.consumed_order.len() > CONSUMED_TOMBSTONES {
+            if let Some(oldest) = self.consumed_order.pop_front() {
+                self.consumed.remove(&oldest);
             }
-            Arc::new(MemoryPasteStore::new())
-        }
-        Ok(value) if value.eq_ignore_ascii_case("memory") || value.trim().is_empty() => {
-            Arc::new(MemoryPasteStore::new())
         }
-        _ => Arc::new(MemoryPasteStore::new()),
     }
-}
 
-pub mod vault {
-    use super::{PersistenceAdapter, PersistenceError, StoredPaste};
-    use async_trait::async_trait;
-    use reqwest::Client;
-    use serde::Deserialize;
-    use serde_json::json;
-    use std::env;
-    use std::sync::Arc;
+    /// Mutable access for changes that leave every aggregated field
+    /// (format, content, burn flag, time lock, expiry, creation time) alone.
+    fn get_mut(&mut self, id: &str) -> Option<&mut StoredPaste> {
+        self.pastes.get_mut(id)
+    }
+}
 
-    #[derive(Clone)]
+/// TTL for the stats cache used by `MemoryPasteStore::stats()`.
+///
+/// The totals are incremental, so a refresh is only a snapshot of them; the
+/// result is still cached briefly to keep polled endpoints cheap.
+const STATS_CACHE_TTL: Duration = Duration::from_secs(5);
+
+pub struct MemoryPasteStore {
+    entries: RwLock<PasteMap>,
+    persistence: Option<Arc<dyn PersistenceAdapter>>,
+    stats_cache: Mutex<Option<StatsCache>>,
+    /// Set once pastes only the backend holds have been counted into the
+    /// aggregates (see [`Self::seed_uncached_stats`]).
+    uncached_seeded: tokio::sync::OnceCell<()>,
+    /// Ceiling on `expires_at - created_at` applied to pastes loaded from
+    /// persistence (`COPYPASTE_ABSOLUTE_MAX_TTL`, seconds).
+    absolute_max_ttl: Option<i64>,
+    /// Age in seconds after which an unread burn-after-reading paste is
+    /// evicted by the sweeper (`COPYPASTE_BURN_MAX_AGE_SECS`).
+    burn_max_age: Option<i64>,
+    /// Fail creates whose persistence save fails instead of serving them
+    /// from memory only (`COPYPASTE_PERSISTENCE_STRICT`).
+    strict_persistence: bool,
+    /// Most pastes held at once (`COPYPASTE_MAX_ENTRIES`); creating one more
+    /// deletes the oldest by `created_at`, from persistence too.
+    capacity: Option<usize>,
+    /// Treat a paste whose time-lock `not_after` has passed as expired, and
+    /// delete it from persistence (`COPYPASTE_DELETE_ELAPSED_WINDOWS`).
+    delete_elapsed_windows: bool,
+}
+
+impl MemoryPasteStore {
+    pub fn new() -> Self {
+        Self {
+            entries: RwLock::new(PasteMap::default()),
+            persistence: None,
+            stats_cache: Mutex::new(None),
+            uncached_seeded: tokio::sync::OnceCell::new(),
+            absolute_max_ttl: absolute_max_ttl_from_env(),
+            burn_max_age: positive_secs_from_env("COPYPASTE_BURN_MAX_AGE_SECS"),
+            strict_persistence: false,
+            capacity: capacity_from_env(),
+            delete_elapsed_windows: delete_elapsed_windows_from_env(),
+        }
+    }
+
+    pub fn with_persistence(adapter: Arc<dyn PersistenceAdapter>) -> Self {
+        Self {
+            entries: RwLock::new(PasteMap::default()),
+            persistence: Some(adapter),
+            stats_cache: Mutex::new(None),
+            uncached_seeded: tokio::sync::OnceCell::new(),
+            absolute_max_ttl: absolute_max_ttl_from_env(),
+            burn_max_age: positive_secs_from_env("COPYPASTE_BURN_MAX_AGE_SECS"),
+            strict_persistence: env::var("COPYPASTE_PERSISTENCE_STRICT")
+                .is_ok_and(|v| matches!(v.trim(), "1" | "true" | "on" | "yes")),
+            capacity: capacity_from_env(),
+            delete_elapsed_windows: delete_elapsed_windows_from_env(),
+        }
+    }
+
+    /// Override `COPYPASTE_DELETE_ELAPSED_WINDOWS`.
+    pub fn with_delete_elapsed_windows(mut self, delete: bool) -> Self {
+        self.delete_elapsed_windows = delete;
+        self
+    }
+
+    /// Override the entry limit read from `COPYPASTE_MAX_ENTRIES`.
+    pub fn with_capacity(mut self, capacity: Option<usize>) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Override the strict mode read from `COPYPASTE_PERSISTENCE_STRICT`.
+    pub fn with_strict_persistence(mut self, strict: bool) -> Self {
+        self.strict_persistence = strict;
+        self
+    }
+
+    /// Override the TTL ceiling read from `COPYPASTE_ABSOLUTE_MAX_TTL`.
+    pub fn with_absolute_max_ttl(mut self, max_ttl_secs: Option<i64>) -> Self {
+        self.absolute_max_ttl = max_ttl_secs;
+        self
+    }
+
+    /// Start a background task that calls [`PasteStore::sweep_once`] every
+    /// `interval` until the returned handle is shut down or dropped.
+    pub fn spawn_sweeper(self: &Arc<Self>, interval: Duration) -> SweeperHandle {
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let store: SharedPasteStore = self.clone();
+        let task = tokio::spawn(run_sweeper(store, interval, stopped, |_| {}));
+        SweeperHandle {
+            stop: Some(stop),
+            task,
+        }
+    }
+
+    /// Override the burn-paste age limit read from `COPYPASTE_BURN_MAX_AGE_SECS`.
+    pub fn with_burn_max_age(mut self, max_age_secs: Option<i64>) -> Self {
+        self.burn_max_age = max_age_secs;
+        self
+    }
+
+    /// Whether `paste` is dead as of `now`: expired, or past its time-lock
+    /// window when those are deleted. A paste with only `not_before` never is.
+    fn is_gone_at(&self, paste: &StoredPaste, now: i64) -> bool {
+        is_expired_at(paste, now) || (self.delete_elapsed_windows && window_elapsed_at(paste, now))
+    }
+
+    fn is_gone(&self, paste: &StoredPaste) -> bool {
+        self.is_gone_at(paste, current_unix_time())
+    }
+
+    /// Backends expire pastes natively but know nothing of time locks, so a
+    /// paste dropped for its window must be deleted there too.
+    async fn unpersist_elapsed(&self, id: &str, paste: &StoredPaste) {
+        if self.delete_elapsed_windows && window_elapsed_at(paste, current_unix_time()) {
+            self.unpersist(id).await;
+        }
+    }
+
+    /// Load `id` from the persistence backend, applying the absolute TTL
+    /// ceiling. Load errors are treated as a missing paste.
+    async fn load_persisted(&self, id: &str) -> Option<StoredPaste> {
+        let adapter = self.persistence.as_ref()?;
+        let mut paste = adapter.load(id).await.ok()??;
+        if let Some(max_ttl) = self.absolute_max_ttl {
+            let original = paste.expires_at;
+            if clamp_to_absolute_max_ttl(&mut paste, max_ttl) {
+                log::warn!(
+                    "Clamped expiry of paste {id} from {original:?} to {:?} (COPYPASTE_ABSOLUTE_MAX_TTL={max_ttl})",
+                    paste.expires_at
+                );
+            }
+        }
+        Some(paste)
+    }
+
+    /// Cache a newly created paste and save it. In strict mode a failed save
+    /// un-caches it again and is returned, so no caller is handed the id of
+    /// a paste that would not survive a restart. On success, returns the ids
+    /// evicted to stay within capacity; the caller unpersists them once it
+    /// has released the write lock.
+    async fn insert_new(
+        &self,
+        map: &mut PasteMap,
+        id: &str,
+        paste: StoredPaste,
+    ) -> Result<Vec<String>, PersistenceError> {
+        if let Some(adapter) = &self.persistence {
+            match adapter.save(id, &paste).await {
+                Ok(()) => {}
+                Err(error) if self.strict_persistence => return Err(error),
+                Err(error) => log::warn!("{error}"),
+            }
+        }
+        map.insert(id.to_string(), paste);
+        Ok(Self::evict_over_capacity(map, self.capacity))
+    }
+
+    /// Drop the oldest pastes from the cache until at most `capacity`
+    /// remain, returning their ids.
+    fn evict_over_capacity(map: &mut PasteMap, capacity: Option<usize>) -> Vec<String> {
+        let Some(capacity) = capacity else {
+            return Vec::new();
+        };
+        let mut evicted = Vec::new();
+        while map.len() > capacity {
+            let Some((id, _)) = map.pop_oldest() else {
+                break;
+            };
+            log::info!("Evicted paste {id}: store is at its {capacity}-entry capacity");
+            evicted.push(id);
+        }
+        evicted
+    }
+
+    /// Write `paste` through to the persistence backend, if any. A failure
+    /// leaves the paste cached only, so it is logged rather than surfaced.
+    async fn persist(&self, id: &str, paste: &StoredPaste) {
+        if let Some(adapter) = &self.persistence {
+            if let Err(error) = adapter.save(id, paste).await {
+                log::warn!("{error}");
+            }
+        }
+    }
+
+    async fn unpersist(&self, id: &str) {
+        if let Some(adapter) = &self.persistence {
+            if let Err(error) = adapter.delete(id).await {
+                log::warn!("{error}");
+            }
+        }
+    }
+
+    /// Count the pastes only the backend holds (typically after a restart)
+    /// into the aggregates. Listing and loading them is a full scan, so it
+    /// runs once; [`PasteMap`] keeps their entries in step afterwards.
+    async fn seed_uncached_stats(&self) {
+        if self.persistence.is_none() {
+            return;
+        }
+        self.entries.write().await.seeding = Some(HashSet::new());
+        let persisted_ids = self.persisted_ids().await;
+        let missing: Vec<String> = {
+            let map = self.entries.read().await;
+            persisted_ids
+                .into_iter()
+                .filter(|id| !map.contains_key(id))
+                .collect()
+        };
+        let mut loaded = Vec::with_capacity(missing.len());
+        for id in missing {
+            if let Some(paste) = self.load_persisted(&id).await {
+                loaded.push((id, StatsEntry::of(&paste)));
+            }
+        }
+
+        let mut map = self.entries.write().await;
+        let touched = map.seeding.take().unwrap_or_default();
+        for (id, entry) in loaded {
+            if !touched.contains(&id) && !map.contains_key(&id) {
+                map.add_uncached(id, entry);
+            }
+        }
+    }
+
+    /// Ids the persistence backend holds, or none if it cannot list them.
+    async fn persisted_ids(&self) -> Vec<String> {
+        let Some(adapter) = &self.persistence else {
+            return Vec::new();
+        };
+        adapter.list_ids().await.unwrap_or_else(|error| {
+            log::warn!("Failed to list persisted paste ids: {error}");
+            Vec::new()
+        })
+    }
+}
+
+/// Stops the task started by [`MemoryPasteStore::spawn_sweeper`].
+pub struct SweeperHandle {
+    stop: Option<tokio::sync::oneshot::Sender<()>>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl SweeperHandle {
+    /// Signal the sweeper to stop and wait for its current pass to finish.
+    pub async fn shutdown(mut self) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+        let _ = (&mut self.task).await;
+    }
+}
+
+impl Drop for SweeperHandle {
+    fn drop(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+    }
+}
+
+/// Sweep `store` every `interval` until `shutdown` resolves, handing each
+/// removed paste to `on_removed`. A pass in progress always completes.
+pub async fn run_sweeper<S, F>(
+    store: SharedPasteStore,
+    interval: Duration,
+    shutdown: S,
+    on_removed: F,
+) where
+    S: std::future::Future + Send,
+    F: Fn(&(String, StoredPaste)) + Send,
+{
+    let mut ticks = tokio::time::interval(interval);
+    ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    tokio::pin!(shutdown);
+    loop {
+        tokio::select! {
+            _ = ticks.tick() => {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs() as i64)
+                    .unwrap_or_default();
+                for removed in &store.sweep_once(now).await {
+                    on_removed(removed);
+                }
+            }
+            _ = &mut shutdown => break,
+        }
+    }
+}
+
+fn delete_elapsed_windows_from_env() -> bool {
+    env::var("COPYPASTE_DELETE_ELAPSED_WINDOWS")
+        .is_ok_and(|v| matches!(v.trim(), "1" | "true" | "on" | "yes"))
+}
+
+fn capacity_from_env() -> Option<usize> {
+    env::var("COPYPASTE_MAX_ENTRIES")
+        .ok()
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .filter(|max| *max > 0)
+}
+
+fn absolute_max_ttl_from_env() -> Option<i64> {
+    positive_secs_from_env("COPYPASTE_ABSOLUTE_MAX_TTL")
+}
+
+fn positive_secs_from_env(name: &str) -> Option<i64> {
+    env::var(name)
+        .ok()
+        .and_then(|v| v.trim().parse::<i64>().ok())
+        .filter(|secs| *secs > 0)
+}
+
+/// Pull `expires_at` down to `created_at + max_ttl_secs` when it lies beyond
+/// that ceiling; a paste with no expiry counts as beyond it. Returns whether
+/// the paste was clamped.
+fn clamp_to_absolute_max_ttl(paste: &mut StoredPaste, max_ttl_secs: i64) -> bool {
+    let ceiling = paste.created_at.saturating_add(max_ttl_secs);
+    match paste.expires_at {
+        Some(expires_at) if expires_at <= ceiling => false,
+        _ => {
+            paste.expires_at = Some(ceiling);
+            true
+        }
+    }
+}
+
+impl Default for MemoryPasteStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn current_unix_time() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+fn is_expired_at(paste: &StoredPaste, now: i64) -> bool {
+    paste.expires_at.is_some_and(|expires_at| now > expires_at)
+}
+
+/// Whether the paste's time-lock `not_after` has passed; it can never be
+/// read again.
+fn window_elapsed_at(paste: &StoredPaste, now: i64) -> bool {
+    paste
+        .metadata
+        .not_after
+        .is_some_and(|not_after| now > not_after)
+}
+
+/// Lowercase hex SHA-256 of a paste's plaintext, as stored in
+/// [`PasteMetadata::content_sha256`].
+pub fn content_digest(text: &str) -> String {
+    use sha2::{Digest, Sha256};
+    hex::encode(Sha256::digest(text.as_bytes()))
+}
+
+/// The [`PasteMetadata::content_sha256`] to store for `content` whose
+/// plaintext hashes to `digest`. Encrypted content gets none: an unsalted
+/// digest of its plaintext would let anyone holding the record confirm
+/// guesses offline without the key, and the AEAD tag already detects
+/// tampering with the ciphertext.
+pub fn recorded_digest(content: &StoredContent, digest: String) -> Option<String> {
+    matches!(content, StoredContent::Plain { .. }).then_some(digest)
+}
+
+/// Seconds until `paste` expires, for backends with native expiry. `None`
+/// when the paste never expires or already has.
+pub(crate) fn remaining_ttl_secs(paste: &StoredPaste) -> Option<u64> {
+    remaining_ttl_secs_at(paste, current_unix_time())
+}
+
+fn remaining_ttl_secs_at(paste: &StoredPaste, now: i64) -> Option<u64> {
+    let remaining = paste.expires_at? - now;
+    (remaining > 0).then_some(remaining as u64)
+}
+
+pub(crate) fn bool_is_false(value: &bool) -> bool {
+    !*value
+}
+
+const PASTE_ID_ADJECTIVES: &[&str] = &[
+    "stellar", "quantum", "luminous", "neon", "orbital", "cosmic", "radiant", "sonic", "velvet",
+    "ember",
+];
+
+const PASTE_ID_NOUNS: &[&str] = &[
+    "otter", "phoenix", "nebula", "cipher", "comet", "matrix", "falcon", "vertex", "galaxy",
+    "aurora",
+];
+
+fn generate_paste_id(map: &HashMap<String, StoredPaste>) -> String {
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..12 {
+        let adjective = PASTE_ID_ADJECTIVES
+            .choose(&mut rng)
+            .unwrap_or(&PASTE_ID_ADJECTIVES[0]);
+        let noun = PASTE_ID_NOUNS
+            .choose(&mut rng)
+            .unwrap_or(&PASTE_ID_NOUNS[0]);
+        let number: u16 = rng.gen_range(10..100);
+        let candidate = format!("{adjective}-{noun}-{number}");
+        if !map.contains_key(&candidate) {
+            return candidate;
+        }
+    }
+
+    nanoid!(10)
+}
+
+#[async_trait]
+impl PasteStore for MemoryPasteStore {
+    async fn create_paste(&self, paste: StoredPaste) -> Result<String, PersistenceError> {
+        // Evictions are unpersisted after the write lock is released so
+        // readers aren't blocked on network I/O.
+        let (id, evicted) = {
+            let mut map = self.entries.write().await;
+            let id = generate_paste_id(&map);
+            let evicted = self.insert_new(&mut map, &id, paste).await?;
+            (id, evicted)
+        };
+        for evicted_id in &evicted {
+            self.unpersist(evicted_id).await;
+        }
+        Ok(id)
+    }
+
+    async fn create_paste_with_id(
+        &self,
+        id: &str,
+        paste: StoredPaste,
+    ) -> Result<String, CreatePasteError> {
+        let evicted = {
+            let mut map = self.entries.write().await;
+            match map.get(id) {
+                Some(existing) if !self.is_gone(existing) => {
+                    return Err(IdTaken(id.to_string()).into())
+                }
+                Some(_) => {
+                    map.remove(id);
+                }
+                None => {
+                    if self
+                        .load_persisted(id)
+                        .await
+                        .is_some_and(|existing| !self.is_gone(&existing))
+                    {
+                        return Err(IdTaken(id.to_string()).into());
+                    }
+                }
+            }
+            self.insert_new(&mut map, id, paste).await?
+        };
+        for evicted_id in &evicted {
+            self.unpersist(evicted_id).await;
+        }
+        Ok(id.to_string())
+    }
+
+    async fn next_paste_id(&self) -> String {
+        generate_paste_id(&self.entries.read().await.pastes)
+    }
+
+    async fn get_paste(&self, id: &str) -> Result<StoredPaste, PasteError> {
+        // Hot path: a cached, unexpired paste only needs a shared lock.
+        {
+            let map = self.entries.read().await;
+            if let Some(paste) = map.get(id) {
+                if !self.is_gone(paste) {
+                    return Ok(paste.clone());
+                }
+            }
+        }
+
+        // Eviction or a persistence fallback needs the exclusive lock. The
+        // entry is re-checked under it because another caller may have
+        // evicted, loaded or replaced it since the read lock was released.
+        // The lock is held across the load so a concurrent delete cannot
+        // be undone by re-inserting what the backend returned.
+        let mut map = self.entries.write().await;
+        match map.get(id) {
+            Some(paste) if !self.is_gone(paste) => Ok(paste.clone()),
+            Some(_) => {
+                if let Some(paste) = map.remove(id) {
+                    self.unpersist_elapsed(id, &paste).await;
+                }
+                Err(PasteError::Expired(id.to_string()))
+            }
+            None => match self.load_persisted(id).await {
+                Some(paste) if self.is_gone(&paste) => {
+                    self.unpersist_elapsed(id, &paste).await;
+                    Err(PasteError::Expired(id.to_string()))
+                }
+                Some(paste) => {
+                    map.insert(id.to_string(), paste.clone());
+                    Ok(paste)
+                }
+                None => Err(PasteError::NotFound(id.to_string())),
+            },
+        }
+    }
+
+    async fn sweep_once(&self, now: i64) -> Vec<(String, StoredPaste)> {
+        // A burn paste is deleted on its first read, so any still present
+        // has never been read.
+        let unread_burn_too_old = |paste: &StoredPaste| {
+            paste.burn_after_reading
+                && self
+                    .burn_max_age
+                    .is_some_and(|max_age| now.saturating_sub(paste.created_at) >= max_age)
+        };
+
+        // Collect under the write lock, then release it before the
+        // persistence deletes so readers aren't blocked on network I/O.
+        let removed: Vec<(String, StoredPaste)> = {
+            let mut map = self.entries.write().await;
+            if map.prune_uncached(now) {
+                *self.stats_cache.lock().unwrap() = None;
+            }
+            let stale: Vec<String> = map
+                .iter()
+                .filter(|(_, paste)| self.is_gone_at(paste, now) || unread_burn_too_old(paste))
+                .map(|(id, _)| id.clone())
+                .collect();
+            stale
+                .into_iter()
+                .filter_map(|id| map.remove(&id).map(|paste| (id, paste)))
+                .collect()
+        };
+        if removed.is_empty() {
+            return removed;
+        }
+        *self.stats_cache.lock().unwrap() = None;
+
+        for (id, _) in &removed {
+            self.unpersist(id).await;
+        }
+        removed
+    }
+
+    async fn delete_paste(&self, id: &str) -> bool {
+        let mut map = self.entries.write().await;
+        let existed = map.remove(id).is_some();
+        self.unpersist(id).await;
+        existed
+    }
+
+    async fn consume_read(&self, id: &str) -> Option<ReadReceipt> {
+        let mut map = self.entries.write().await;
+        let paste = map.get_mut(id).filter(|paste| !self.is_gone(paste))?;
+        paste.metadata.access_count += 1;
+        let receipt = ReadReceipt {
+            access_count: paste.metadata.access_count,
+            reads_remaining: paste.reads_remaining(),
+        };
+        if receipt.consumed() {
+            map.remove(id);
+            map.mark_consumed(id);
+            self.unpersist(id).await;
+        } else {
+            self.persist(id, paste).await;
+        }
+        Some(receipt)
+    }
+
+    async fn was_consumed(&self, id: &str) -> bool {
+        self.entries.read().await.consumed.contains(id)
+    }
+
+    async fn stats(&self) -> StoreStats {
+        // Return cached result if still within TTL (O(1) fast path).
+        {
+            let cache = self.stats_cache.lock().unwrap();
+            if let Some(ref cached) = *cache {
+                if cached.computed_at.elapsed() < STATS_CACHE_TTL {
+                    return cached.stats.clone();
+                }
+            }
+        }
+
+        self.uncached_seeded
+            .get_or_init(|| self.seed_uncached_stats())
+            .await;
+        let stats = self
+            .entries
+            .read()
+            .await
+            .aggregates
+            .snapshot(current_unix_time());
+
+        // Store in cache for subsequent requests within the TTL window.
+        *self.stats_cache.lock().unwrap() = Some(StatsCache {
+            stats: stats.clone(),
+            computed_at: Instant::now(),
+        });
+
+        stats
+    }
+
+    async fn get_all_paste_ids(&self) -> Vec<String> {
+        let persisted = self.persisted_ids().await;
+        let map = self.entries.read().await;
+        let mut ids: Vec<String> = map.keys().cloned().collect();
+        ids.extend(persisted.into_iter().filter(|id| !map.contains_key(id)));
+        ids
+    }
+
+    async fn peek_paste(&self, id: &str) -> Result<StoredPaste, PasteError> {
+        {
+            let map = self.entries.read().await;
+            match map.get(id) {
+                Some(paste) if !self.is_gone(paste) => return Ok(paste.clone()),
+                Some(_) => return Err(PasteError::Expired(id.to_string())),
+                None => {}
+            }
+        }
+        match self.load_persisted(id).await {
+            Some(paste) if self.is_gone(&paste) => Err(PasteError::Expired(id.to_string())),
+            Some(paste) => Ok(paste),
+            None => Err(PasteError::NotFound(id.to_string())),
+        }
+    }
+
+    async fn update_paste(
+        &self,
+        id: &str,
+        content: StoredContent,
+        content_sha256: Option<String>,
+    ) -> Result<(), PasteError> {
+        let mut map = self.entries.write().await;
+        match map.get(id) {
+            Some(paste) if !self.is_gone(paste) => {
+                map.set_content(id, content);
+                if let Some(paste) = map.get_mut(id) {
+                    paste.metadata.content_sha256 = content_sha256;
+                    self.persist(id, paste).await;
+                }
+                Ok(())
+            }
+            Some(_) => {
+                map.remove(id);
+                Err(PasteError::Expired(id.to_string()))
+            }
+            None => Err(PasteError::NotFound(id.to_string())),
+        }
+    }
+
+    async fn finalize_paste(&self, id: &str) -> Result<(), PasteError> {
+        let mut map = self.entries.write().await;
+        match map.get_mut(id) {
+            Some(paste) if !self.is_gone(paste) => {
+                paste.is_live = false;
+                self.persist(id, paste).await;
+                Ok(())
+            }
+            Some(_) => {
+                map.remove(id);
+                Err(PasteError::Expired(id.to_string()))
+            }
+            None => Err(PasteError::NotFound(id.to_string())),
+        }
+    }
+
+    async fn set_bundle_parent(&self, id: &str, parent_id: &str) -> Result<(), PasteError> {
+        let mut map = self.entries.write().await;
+        match map.get_mut(id) {
+            Some(paste) if !self.is_gone(paste) => {
+                paste.bundle_parent = Some(parent_id.to_string());
+                paste.metadata.bundle_parent = Some(parent_id.to_string());
+                self.persist(id, paste).await;
+                Ok(())
+            }
+            Some(_) => {
+                map.remove(id);
+                Err(PasteError::Expired(id.to_string()))
+            }
+            None => Err(PasteError::NotFound(id.to_string())),
+        }
+    }
+
+    async fn set_anchored_hash(&self, id: &str, hash: &str) -> Result<(), PasteError> {
+        let mut map = self.entries.write().await;
+        match map.get_mut(id) {
+            Some(paste) if !self.is_gone(paste) => {
+                paste.metadata.anchored_hash = Some(hash.to_string());
+                self.persist(id, paste).await;
+                Ok(())
+            }
+            Some(_) => {
+                map.remove(id);
+                Err(PasteError::Expired(id.to_string()))
+            }
+            None => Err(PasteError::NotFound(id.to_string())),
+        }
+    }
+
+    async fn health_check(&self) -> Result<(), PersistenceError> {
+        match &self.persistence {
+            Some(adapter) => adapter.health_check().await,
+            None => Ok(()),
+        }
+    }
+
+    fn persistence_backend(&self) -> &'static str {
+        self.persistence
+            .as_ref()
+            .map_or("memory", |adapter| adapter.name())
+    }
+
+    async fn flush(&self) -> FlushReport {
+        // Snapshot under the read lock; saves go over the network.
+        let live: Vec<(String, StoredPaste)> = {
+            let map = self.entries.read().await;
+            map.iter()
+                .filter(|(_, paste)| !self.is_gone(paste))
+                .map(|(id, paste)| (id.clone(), paste.clone()))
+                .collect()
+        };
+        let Some(adapter) = &self.persistence else {
+            return FlushReport {
+                unpersisted: live.len(),
+                ..FlushReport::default()
+            };
+        };
+        let mut report = FlushReport::default();
+        for (id, paste) in &live {
+            match adapter.save(id, paste).await {
+                Ok(()) => report.saved += 1,
+                Err(error) => {
+                    log::warn!("{error}");
+                    report.failed += 1;
+                }
+            }
+        }
+        report
+    }
+}
+
+pub type SharedPasteStore = Arc<dyn PasteStore>;
+
+pub fn create_paste_store() -> SharedPasteStore {
+    match env::var("COPYPASTE_PERSISTENCE_BACKEND") {
+        Ok(value) if value.eq_ignore_ascii_case("vault") => {
+            if let Ok(adapter) = vault::VaultPersistenceAdapter::from_env() {
+                return Arc::new(MemoryPasteStore::with_persistence(adapter));
+            }
+            Arc::new(MemoryPasteStore::new())
+        }
+        Ok(value) if value.eq_ignore_ascii_case("redis") => {
+            if let Ok(adapter) = RedisPersistenceAdapter::from_env() {
+                return Arc::new(MemoryPasteStore::with_persistence(adapter));
+            }
+            Arc::new(MemoryPasteStore::new())
+        }
+        Ok(value) if value.eq_ignore_ascii_case("s3") => {
+            if let Ok(adapter) = S3PersistenceAdapter::from_env() {
+                return Arc::new(MemoryPasteStore::with_persistence(adapter));
+            }
+            Arc::new(MemoryPasteStore::new())
+        }
+        Ok(value) if value.eq_ignore_ascii_case("file") || value.eq_ignore_ascii_case("fs") => {
+            match FilePersistenceAdapter::from_env() {
+                Ok(adapter) => Arc::new(MemoryPasteStore::with_persistence(adapter)),
+                Err(error) => {
+                    log::warn!("file persistence unavailable, using memory only: {error}");
+                    Arc::new(MemoryPasteStore::new())
+                }
+            }
+        }
+        Ok(value) if value.eq_ignore_ascii_case("memory") || value.trim().is_empty() => {
+            Arc::new(MemoryPasteStore::new())
+        }
+        _ => Arc::new(MemoryPasteStore::new()),
+    }
+}
+
+pub mod vault {
+    use super::{remaining_ttl_secs, PersistenceAdapter, PersistenceError, StoredPaste};
+    use async_trait::async_trait;
+    use reqwest::Client;
+    use serde::Deserialize;
+    use serde_json::json;
+    use std::env;
+    use std::sync::Arc;
+
+    #[derive(Clone)]
     pub struct VaultPersistenceAdapter {
         client: Client,
         addr: String,
@@ -717,6 +1875,19 @@ pub mod vault {
                 .map_err(|e| PersistenceError::Save(id.to_string(), e.to_string()))?
                 .error_for_status()
                 .map_err(|e| PersistenceError::Save(id.to_string(), e.to_string()))?;
+
+            // KV v2 has no per-write TTL; have Vault delete the versions
+            // once the paste expires, as SETEX does for Redis.
+            if let Some(ttl) = remaining_ttl_secs(paste) {
+                let metadata = json!({ "delete_version_after": format!("{ttl}s") });
+                self.auth_headers(self.client.post(self.metadata_path(id)))
+                    .json(&metadata)
+                    .send()
+                    .await
+                    .map_err(|e| PersistenceError::Save(id.to_string(), e.to_string()))?
+                    .error_for_status()
+                    .map_err(|e| PersistenceError::Save(id.to_string(), e.to_string()))?;
+            }
             Ok(())
         }
 
@@ -761,6 +1932,127 @@ pub mod vault {
                 ))
             }
         }
+
+        /// Look up our own token: proves Vault is reachable and the token is live.
+        async fn health_check(&self) -> Result<(), PersistenceError> {
+            let url = format!(
+                "{}/v1/auth/token/lookup-self",
+                self.addr.trim_end_matches('/')
+            );
+            let response = self
+                .auth_headers(self.client.get(url))
+                .send()
+                .await
+                .map_err(|e| PersistenceError::Unavailable(e.to_string()))?;
+            if response.status().is_success() {
+                Ok(())
+            } else {
+                Err(PersistenceError::Unavailable(format!(
+                    "Vault token lookup returned {}",
+                    response.status()
+                )))
+            }
+        }
+
+        fn name(&self) -> &'static str {
+            "vault"
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::{current_unix_time, PasteFormat, PasteMetadata, StoredContent};
+        use httpmock::prelude::*;
+
+        fn test_adapter(server: &MockServer) -> VaultPersistenceAdapter {
+            VaultPersistenceAdapter {
+                client: Client::new(),
+                addr: server.base_url(),
+                token: "vault-token".to_string(),
+                mount: "secret".to_string(),
+                namespace: None,
+                key_prefix: "copypaste".to_string(),
+            }
+        }
+
+        fn paste_expiring_at(expires_at: Option<i64>) -> StoredPaste {
+            StoredPaste {
+                content: StoredContent::Plain {
+                    text: "hello".into(),
+                },
+                format: PasteFormat::PlainText,
+                created_at: current_unix_time(),
+                expires_at,
+                burn_after_reading: false,
+                max_reads: None,
+                bundle: None,
+                bundle_parent: None,
+                bundle_label: None,
+                not_before: None,
+                not_after: None,
+                persistence: None,
+                webhook: None,
+                metadata: PasteMetadata::default(),
+                is_live: false,
+                owner_token_hash: None,
+            }
+        }
+
+        #[tokio::test]
+        async fn save_sets_delete_version_after_from_expiry() {
+            let server = MockServer::start();
+            let data = server.mock(|when, then| {
+                when.method(POST)
+                    .path("/v1/secret/data/copypaste/abc")
+                    .header("X-Vault-Token", "vault-token");
+                then.status(200);
+            });
+            let metadata = server.mock(|when, then| {
+                when.method(POST)
+                    .path("/v1/secret/metadata/copypaste/abc")
+                    .header("X-Vault-Token", "vault-token")
+                    .matches(|req| {
+                        let body: serde_json::Value =
+                            serde_json::from_slice(req.body.as_deref().unwrap_or_default())
+                                .unwrap_or_default();
+                        // The clock may tick between computing the expiry and saving.
+                        matches!(
+                            body["delete_version_after"].as_str(),
+                            Some("3600s" | "3599s")
+                        )
+                    });
+                then.status(204);
+            });
+
+            let paste = paste_expiring_at(Some(current_unix_time() + 3600));
+            test_adapter(&server)
+                .save("abc", &paste)
+                .await
+                .expect("save");
+            data.assert();
+            metadata.assert();
+        }
+
+        #[tokio::test]
+        async fn save_without_expiry_skips_metadata() {
+            let server = MockServer::start();
+            let data = server.mock(|when, then| {
+                when.method(POST).path("/v1/secret/data/copypaste/abc");
+                then.status(200);
+            });
+            let metadata = server.mock(|when, then| {
+                when.path_contains("/metadata/");
+                then.status(204);
+            });
+
+            test_adapter(&server)
+                .save("abc", &paste_expiring_at(None))
+                .await
+                .expect("save");
+            data.assert();
+            metadata.assert_hits(0);
+        }
     }
 }
 
@@ -820,6 +2112,9 @@ mod tests {
                     PersistenceError::Save(_, msg) => PersistenceError::Load(id.to_string(), msg),
                     PersistenceError::Load(_, msg) => PersistenceError::Load(id.to_string(), msg),
                     PersistenceError::Delete(_, msg) => PersistenceError::Load(id.to_string(), msg),
+                    PersistenceError::Unavailable(msg) => {
+                        PersistenceError::Load(id.to_string(), msg)
+                    }
                 }),
             }
         }
@@ -830,6 +2125,15 @@ mod tests {
         }
     }
 
+    #[test]
+    fn remaining_ttl_counts_down_to_expiry() {
+        let mut paste = build_paste(StoredContent::Plain { text: "ttl".into() });
+        assert_eq!(remaining_ttl_secs_at(&paste, 1_000), None);
+        paste.expires_at = Some(4_600);
+        assert_eq!(remaining_ttl_secs_at(&paste, 1_000), Some(3_600));
+        assert_eq!(remaining_ttl_secs_at(&paste, 4_600), None);
+    }
+
     fn build_paste(content: StoredContent) -> StoredPaste {
         StoredPaste {
             content,
@@ -837,6 +2141,7 @@ mod tests {
             created_at: 1_700_000_000,
             expires_at: None,
             burn_after_reading: false,
+            max_reads: None,
             bundle: None,
             bundle_parent: None,
             bundle_label: None,
@@ -862,6 +2167,7 @@ mod tests {
             created_at: 1234,
             expires_at: None,
             burn_after_reading: false,
+            max_reads: None,
             bundle: metadata.bundle.clone(),
             bundle_parent: metadata.bundle_parent.clone(),
             bundle_label: metadata.bundle_label.clone(),
@@ -874,7 +2180,7 @@ mod tests {
             owner_token_hash: None,
         };
 
-        let id = store.create_paste(paste).await;
+        let id = store.create_paste(paste).await.unwrap();
         let stored = store.get_paste(&id).await.expect("paste should exist");
 
         match stored.content {
@@ -895,6 +2201,48 @@ mod tests {
             created_at: 100,
             expires_at: Some(50),
             burn_after_reading: false,
+            max_reads: None,
+            bundle: metadata.bundle.clone(),
+            bundle_parent: metadata.bundle_parent.clone(),
+            bundle_label: metadata.bundle_label.clone(),
+            not_before: metadata.not_before,
+            not_after: metadata.not_after,
+            persistence: metadata.persistence.clone(),
+            webhook: metadata.webhook.clone(),
+            metadata,
+            is_live: false,
+            owner_token_hash: None,
+        };
+
+        let id = store.create_paste(paste).await.unwrap();
+        let result = store.get_paste(&id).await;
+
+        assert!(matches!(result, Err(PasteError::Expired(_))));
+        assert!(matches!(
+            store.get_paste(&id).await,
+            Err(PasteError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn stores_encrypted_content() {
+        let store = MemoryPasteStore::default();
+        let metadata = PasteMetadata::default();
+        let paste = StoredPaste {
+            content: StoredContent::Encrypted {
+                algorithm: EncryptionAlgorithm::Aes256Gcm,
+                ciphertext: "abc".into(),
+                nonce: "nonce".into(),
+                salt: "salt".into(),
+                kdf: KdfKind::default(),
+                bound: false,
+                envelope_version: ENVELOPE_V1,
+            },
+            format: PasteFormat::Code,
+            created_at: 0,
+            expires_at: None,
+            burn_after_reading: false,
+            max_reads: None,
             bundle: metadata.bundle.clone(),
             bundle_parent: metadata.bundle_parent.clone(),
             bundle_label: metadata.bundle_label.clone(),
@@ -907,10 +2255,263 @@ mod tests {
             owner_token_hash: None,
         };
 
-        let id = store.create_paste(paste).await;
-        let result = store.get_paste(&id).await;
+        let id = store.create_paste(paste).await.unwrap();
+        let stored = store.get_paste(&id).await.expect("paste should exist");
+        assert!(matches!(stored.content, StoredContent::Encrypted { .. }));
+    }
+
+    #[tokio::test]
+    async fn create_with_id_refuses_live_ids_and_reuses_expired_ones() {
+        let store = MemoryPasteStore::new();
+        let paste = build_paste(StoredContent::Plain { text: "one".into() });
+        assert_eq!(
+            store
+                .create_paste_with_id("notes", paste.clone())
+                .await
+                .unwrap(),
+            "notes"
+        );
+        assert!(matches!(
+            store.create_paste_with_id("notes", paste.clone()).await,
+            Err(CreatePasteError::IdTaken(IdTaken(id))) if id == "notes"
+        ));
+
+        let mut expired = paste.clone();
+        expired.expires_at = Some(current_unix_time() - 10);
+        store.create_paste_with_id("old", expired).await.unwrap();
+        assert!(store.create_paste_with_id("old", paste).await.is_ok());
+        assert!(store.get_paste("old").await.is_ok());
+    }
+
+    struct FailingAdapter;
+
+    #[async_trait]
+    impl PersistenceAdapter for FailingAdapter {
+        async fn save(&self, id: &str, _paste: &StoredPaste) -> Result<(), PersistenceError> {
+            Err(PersistenceError::Save(
+                id.to_string(),
+                "backend down".into(),
+            ))
+        }
+
+        async fn load(&self, _id: &str) -> Result<Option<StoredPaste>, PersistenceError> {
+            Ok(None)
+        }
+
+        async fn delete(&self, id: &str) -> Result<(), PersistenceError> {
+            Err(PersistenceError::Delete(
+                id.to_string(),
+                "backend down".into(),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn strict_persistence_fails_creates_that_are_not_durable() {
+        let store = MemoryPasteStore::with_persistence(Arc::new(FailingAdapter))
+            .with_strict_persistence(true);
+        let paste = build_paste(StoredContent::Plain {
+            text: "fragile".into(),
+        });
+
+        assert!(matches!(
+            store.create_paste(paste.clone()).await,
+            Err(PersistenceError::Save(..))
+        ));
+        assert!(matches!(
+            store.create_paste_with_id("named", paste).await,
+            Err(CreatePasteError::Persistence(_))
+        ));
+        assert!(store.get_all_paste_ids().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn lenient_persistence_keeps_creates_that_fail_to_save() {
+        let store = MemoryPasteStore::with_persistence(Arc::new(FailingAdapter))
+            .with_strict_persistence(false);
+        let id = store
+            .create_paste(build_paste(StoredContent::Plain {
+                text: "cached only".into(),
+            }))
+            .await
+            .expect("lenient create succeeds");
+        assert!(store.get_paste(&id).await.is_ok());
+        assert!(store.delete_paste(&id).await);
+        assert_eq!(
+            store.flush().await,
+            FlushReport::default(),
+            "nothing left to flush"
+        );
+    }
+
+    #[tokio::test]
+    async fn flush_saves_every_live_paste() {
+        let adapter = Arc::new(RecordingAdapter::default());
+        let store = MemoryPasteStore::with_persistence(adapter.clone());
+        let mut ids = Vec::new();
+        for text in ["one", "two", "three"] {
+            let paste = build_paste(StoredContent::Plain { text: text.into() });
+            ids.push(store.create_paste(paste).await.unwrap());
+        }
+        let mut expired = build_paste(StoredContent::Plain {
+            text: "stale".into(),
+        });
+        expired.expires_at = Some(current_unix_time() - 10);
+        store.create_paste_with_id("stale", expired).await.unwrap();
+        adapter.take_saved();
+
+        let report = store.flush().await;
+
+        assert_eq!(
+            report,
+            FlushReport {
+                saved: 3,
+                ..FlushReport::default()
+            }
+        );
+        let mut saved = adapter.take_saved();
+        saved.sort();
+        ids.sort();
+        assert_eq!(saved, ids);
+
+        let memory_only = MemoryPasteStore::new();
+        memory_only
+            .create_paste(build_paste(StoredContent::Plain { text: "x".into() }))
+            .await
+            .unwrap();
+        assert_eq!(memory_only.flush().await.unpersisted, 1);
+    }
+
+    #[tokio::test]
+    async fn capacity_evicts_the_oldest_paste_from_cache_and_persistence() {
+        let adapter = Arc::new(RecordingAdapter::default());
+        let store = MemoryPasteStore::with_persistence(adapter.clone()).with_capacity(Some(3));
+        // Creation order differs from age order on purpose.
+        let mut ids = Vec::new();
+        for created_at in [200, 100, 300, 400] {
+            let mut paste = build_paste(StoredContent::Plain {
+                text: format!("paste {created_at}"),
+            });
+            paste.created_at = created_at;
+            ids.push(store.create_paste(paste).await.unwrap());
+        }
+
+        assert_eq!(adapter.take_deleted(), vec![ids[1].clone()]);
+        let mut remaining = store.get_all_paste_ids().await;
+        remaining.sort();
+        let mut newest = vec![ids[0].clone(), ids[2].clone(), ids[3].clone()];
+        newest.sort();
+        assert_eq!(remaining, newest);
+
+        // A deleted paste frees its slot; its stale ordering entry is skipped.
+        assert!(store.delete_paste(&ids[0]).await);
+        adapter.take_deleted();
+        let mut paste = build_paste(StoredContent::Plain { text: "new".into() });
+        paste.created_at = 500;
+        store.create_paste(paste).await.unwrap();
+        assert!(adapter.take_deleted().is_empty());
+        let mut paste = build_paste(StoredContent::Plain {
+            text: "newer".into(),
+        });
+        paste.created_at = 600;
+        store.create_paste(paste).await.unwrap();
+        assert_eq!(adapter.take_deleted(), vec![ids[2].clone()]);
+    }
+
+    /// Parks every delete until released, so a test can observe what the
+    /// store holds while the backend call is in flight.
+    #[derive(Default)]
+    struct ParkedDeleteAdapter {
+        deleting: tokio::sync::Notify,
+        release: tokio::sync::Notify,
+    }
+
+    #[async_trait]
+    impl PersistenceAdapter for ParkedDeleteAdapter {
+        async fn save(&self, _id: &str, _paste: &StoredPaste) -> Result<(), PersistenceError> {
+            Ok(())
+        }
+
+        async fn load(&self, _id: &str) -> Result<Option<StoredPaste>, PersistenceError> {
+            Ok(None)
+        }
+
+        async fn delete(&self, _id: &str) -> Result<(), PersistenceError> {
+            self.deleting.notify_one();
+            self.release.notified().await;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn capacity_eviction_unpersists_after_releasing_the_lock() {
+        let adapter = Arc::new(ParkedDeleteAdapter::default());
+        let store =
+            Arc::new(MemoryPasteStore::with_persistence(adapter.clone()).with_capacity(Some(1)));
+        store
+            .create_paste(build_paste(StoredContent::Plain { text: "old".into() }))
+            .await
+            .unwrap();
+
+        let creating = tokio::spawn({
+            let store = store.clone();
+            async move {
+                store
+                    .create_paste(build_paste(StoredContent::Plain { text: "new".into() }))
+                    .await
+            }
+        });
+        adapter.deleting.notified().await;
+        let readable =
+            tokio::time::timeout(std::time::Duration::from_secs(1), store.entries.read()).await;
+        assert!(
+            readable.is_ok(),
+            "store lock held across the eviction delete"
+        );
+        drop(readable);
+
+        adapter.release.notify_one();
+        creating.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn delete_paste_invokes_persistence_adapter() {
+        let adapter = Arc::new(RecordingAdapter::default());
+        let store = MemoryPasteStore::with_persistence(adapter.clone());
+        let paste = build_paste(StoredContent::Plain {
+            text: "tracked".into(),
+        });
+
+        let id = store.create_paste(paste).await.unwrap();
+        assert!(store.delete_paste(&id).await);
+        assert_eq!(adapter.take_deleted(), vec![id.clone()]);
+
+        // Second deletion still triggers adapter delete but reports false
+        assert!(!store.delete_paste(&id).await);
+        assert_eq!(adapter.take_deleted(), vec![id.clone()]);
+        assert_eq!(adapter.take_saved(), vec![id]);
+    }
 
-        assert!(matches!(result, Err(PasteError::Expired(_))));
+    #[tokio::test]
+    async fn consume_read_lets_exactly_one_burn_reader_win() {
+        let store = Arc::new(MemoryPasteStore::default());
+        let mut paste = build_paste(StoredContent::Plain {
+            text: "once".into(),
+        });
+        paste.burn_after_reading = true;
+        let id = store.create_paste(paste).await.unwrap();
+
+        let mut tasks = tokio::task::JoinSet::new();
+        for _ in 0..16 {
+            let store = store.clone();
+            let id = id.clone();
+            tasks.spawn(async move { store.consume_read(&id).await.is_some() });
+        }
+        let mut winners = 0;
+        while let Some(won) = tasks.join_next().await {
+            winners += usize::from(won.unwrap());
+        }
+        assert_eq!(winners, 1);
         assert!(matches!(
             store.get_paste(&id).await,
             Err(PasteError::NotFound(_))
@@ -918,53 +2519,98 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn stores_encrypted_content() {
+    async fn consume_read_leaves_unlimited_pastes_alone() {
         let store = MemoryPasteStore::default();
-        let metadata = PasteMetadata::default();
-        let paste = StoredPaste {
-            content: StoredContent::Encrypted {
-                algorithm: EncryptionAlgorithm::Aes256Gcm,
-                ciphertext: "abc".into(),
-                nonce: "nonce".into(),
-                salt: "salt".into(),
-            },
-            format: PasteFormat::Code,
-            created_at: 0,
-            expires_at: None,
-            burn_after_reading: false,
-            bundle: metadata.bundle.clone(),
-            bundle_parent: metadata.bundle_parent.clone(),
-            bundle_label: metadata.bundle_label.clone(),
-            not_before: metadata.not_before,
-            not_after: metadata.not_after,
-            persistence: metadata.persistence.clone(),
-            webhook: metadata.webhook.clone(),
-            metadata,
-            is_live: false,
-            owner_token_hash: None,
-        };
+        let id = store
+            .create_paste(build_paste(StoredContent::Plain {
+                text: "keep".into(),
+            }))
+            .await
+            .unwrap();
 
-        let id = store.create_paste(paste).await;
-        let stored = store.get_paste(&id).await.expect("paste should exist");
-        assert!(matches!(stored.content, StoredContent::Encrypted { .. }));
+        let receipt = store.consume_read(&id).await.expect("paste is live");
+        assert_eq!(receipt.reads_remaining, None);
+        assert!(!receipt.consumed());
+        assert!(store.get_paste(&id).await.is_ok());
+        assert!(store.consume_read("missing").await.is_none());
     }
 
     #[tokio::test]
-    async fn delete_paste_invokes_persistence_adapter() {
+    async fn consume_read_deletes_once_max_reads_is_reached() {
         let adapter = Arc::new(RecordingAdapter::default());
         let store = MemoryPasteStore::with_persistence(adapter.clone());
-        let paste = build_paste(StoredContent::Plain {
-            text: "tracked".into(),
+        let mut paste = build_paste(StoredContent::Plain {
+            text: "thrice".into(),
+        });
+        paste.max_reads = Some(3);
+        let id = store.create_paste(paste).await.unwrap();
+
+        let remaining: Vec<Option<u32>> = [
+            store.consume_read(&id).await,
+            store.consume_read(&id).await,
+            store.consume_read(&id).await,
+        ]
+        .iter()
+        .map(|receipt| receipt.expect("within the limit").reads_remaining)
+        .collect();
+        assert_eq!(remaining, vec![Some(2), Some(1), Some(0)]);
+        assert!(store.consume_read(&id).await.is_none());
+        assert!(matches!(
+            store.get_paste(&id).await,
+            Err(PasteError::NotFound(_))
+        ));
+        assert_eq!(adapter.take_deleted(), vec![id]);
+    }
+
+    #[tokio::test]
+    async fn consumed_pastes_are_remembered_until_the_id_is_reused() {
+        let store = MemoryPasteStore::new();
+        let mut paste = build_paste(StoredContent::Plain {
+            text: "once".into(),
         });
+        paste.burn_after_reading = true;
+        let id = store.create_paste(paste.clone()).await.unwrap();
+        assert!(!store.was_consumed(&id).await);
 
-        let id = store.create_paste(paste).await;
-        assert!(store.delete_paste(&id).await);
-        assert_eq!(adapter.take_deleted(), vec![id.clone()]);
+        store.consume_read(&id).await.expect("first read");
+        assert!(store.was_consumed(&id).await);
 
-        // Second deletion still triggers adapter delete but reports false
-        assert!(!store.delete_paste(&id).await);
-        assert_eq!(adapter.take_deleted(), vec![id.clone()]);
-        assert_eq!(adapter.take_saved(), vec![id]);
+        store.create_paste_with_id(&id, paste).await.unwrap();
+        assert!(!store.was_consumed(&id).await);
+    }
+
+    #[tokio::test]
+    async fn concurrent_readers_never_exceed_max_reads() {
+        let store = Arc::new(MemoryPasteStore::default());
+        let mut paste = build_paste(StoredContent::Plain {
+            text: "shared".into(),
+        });
+        paste.max_reads = Some(4);
+        let id = store.create_paste(paste).await.unwrap();
+
+        let mut tasks = tokio::task::JoinSet::new();
+        for _ in 0..16 {
+            let store = store.clone();
+            let id = id.clone();
+            tasks.spawn(async move { store.consume_read(&id).await.is_some() });
+        }
+        let mut served = 0;
+        while let Some(won) = tasks.join_next().await {
+            served += usize::from(won.unwrap());
+        }
+        assert_eq!(served, 4);
+    }
+
+    #[test]
+    fn burn_after_reading_implies_a_single_read() {
+        let mut paste = build_paste(StoredContent::Plain { text: "x".into() });
+        assert_eq!(paste.read_limit(), None);
+        paste.burn_after_reading = true;
+        assert_eq!(paste.read_limit(), Some(1));
+        assert_eq!(paste.reads_remaining(), Some(1));
+        paste.max_reads = Some(5);
+        paste.metadata.access_count = 2;
+        assert_eq!(paste.reads_remaining(), Some(3));
     }
 
     #[tokio::test]
@@ -994,6 +2640,63 @@ mod tests {
         assert!(matches!(again.content, StoredContent::Plain { .. }));
     }
 
+    #[tokio::test]
+    async fn cached_reads_share_the_lock() {
+        let store = Arc::new(MemoryPasteStore::default());
+        let id = store
+            .create_paste(build_paste(StoredContent::Plain {
+                text: "shared".into(),
+            }))
+            .await
+            .unwrap();
+
+        // With a reader already holding the lock, further reads must still
+        // complete rather than queue behind an exclusive lock.
+        let _held = store.entries.read().await;
+        let mut readers = tokio::task::JoinSet::new();
+        for _ in 0..32 {
+            let store = store.clone();
+            let id = id.clone();
+            readers.spawn(async move { store.get_paste(&id).await });
+        }
+        let finished = tokio::time::timeout(Duration::from_secs(5), async {
+            let mut results = Vec::new();
+            while let Some(result) = readers.join_next().await {
+                results.push(result.unwrap());
+            }
+            results
+        })
+        .await
+        .expect("concurrent cached reads should not block each other");
+        assert_eq!(finished.len(), 32);
+        assert!(finished.iter().all(|result| result.is_ok()));
+    }
+
+    #[tokio::test]
+    async fn expired_entry_is_evicted_once_by_racing_readers() {
+        let store = Arc::new(MemoryPasteStore::default());
+        let mut paste = build_paste(StoredContent::Plain {
+            text: "stale".into(),
+        });
+        paste.expires_at = Some(1);
+        let id = store.create_paste(paste).await.unwrap();
+
+        let mut readers = tokio::task::JoinSet::new();
+        for _ in 0..16 {
+            let store = store.clone();
+            let id = id.clone();
+            readers.spawn(async move { store.get_paste(&id).await });
+        }
+        while let Some(result) = readers.join_next().await {
+            // Whoever loses the eviction race sees the entry gone.
+            assert!(matches!(
+                result.unwrap(),
+                Err(PasteError::Expired(_) | PasteError::NotFound(_))
+            ));
+        }
+        assert!(!store.entries.read().await.contains_key(&id));
+    }
+
     #[tokio::test]
     async fn get_paste_reports_expired_from_persistence() {
         let adapter = Arc::new(RecordingAdapter::default());
@@ -1010,6 +2713,180 @@ mod tests {
         assert!(matches!(err, PasteError::Expired(id) if id == "old-id"));
     }
 
+    #[tokio::test]
+    async fn elapsed_time_lock_windows_are_deleted_when_enabled() {
+        let adapter = Arc::new(RecordingAdapter::default());
+        let store =
+            MemoryPasteStore::with_persistence(adapter.clone()).with_delete_elapsed_windows(true);
+        let now = current_unix_time();
+
+        let mut elapsed = build_paste(StoredContent::Plain {
+            text: "closed".into(),
+        });
+        elapsed.metadata.not_after = Some(now - 60);
+        store
+            .create_paste_with_id("closed-id", elapsed.clone())
+            .await
+            .expect("create");
+        let mut opens_later = build_paste(StoredContent::Plain {
+            text: "pending".into(),
+        });
+        opens_later.metadata.not_before = Some(now + 3600);
+        store
+            .create_paste_with_id("pending-id", opens_later)
+            .await
+            .expect("create");
+
+        let err = store.get_paste("closed-id").await.expect_err("deleted");
+        assert!(matches!(err, PasteError::Expired(id) if id == "closed-id"));
+        assert_eq!(adapter.take_deleted(), vec!["closed-id".to_string()]);
+
+        assert!(store.sweep_once(now).await.is_empty());
+        assert!(store.get_paste("pending-id").await.is_ok());
+        assert!(adapter.take_deleted().is_empty());
+
+        // Off by default: the paste stays so views can answer 410.
+        let keeping = MemoryPasteStore::new();
+        keeping
+            .create_paste_with_id("closed-id", elapsed)
+            .await
+            .expect("create");
+        assert!(keeping.sweep_once(now).await.is_empty());
+        assert!(keeping.get_paste("closed-id").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn get_paste_clamps_expiry_beyond_absolute_max_ttl() {
+        let adapter = Arc::new(RecordingAdapter::default());
+        let store =
+            MemoryPasteStore::with_persistence(adapter.clone()).with_absolute_max_ttl(Some(3600));
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+
+        let mut imported = build_paste(StoredContent::Plain {
+            text: "legacy".into(),
+        });
+        imported.created_at = now;
+        let created_at = imported.created_at;
+        imported.expires_at = Some(created_at + 10 * 365 * 24 * 3600);
+        adapter.push_load_result(Ok(Some(imported)));
+
+        let mut forever = build_paste(StoredContent::Plain {
+            text: "no expiry".into(),
+        });
+        forever.created_at = now;
+        forever.expires_at = None;
+        adapter.push_load_result(Ok(Some(forever.clone())));
+
+        let mut within = build_paste(StoredContent::Plain {
+            text: "short".into(),
+        });
+        within.created_at = now;
+        within.expires_at = Some(now + 60);
+        adapter.push_load_result(Ok(Some(within.clone())));
+
+        let fetched = store.get_paste("legacy-id").await.expect("loaded");
+        assert_eq!(fetched.expires_at, Some(created_at + 3600));
+        let cached = store.get_paste("legacy-id").await.expect("cached");
+        assert_eq!(cached.expires_at, Some(created_at + 3600));
+
+        let fetched = store.get_paste("forever-id").await.expect("loaded");
+        assert_eq!(fetched.expires_at, Some(forever.created_at + 3600));
+
+        let fetched = store.get_paste("within-id").await.expect("loaded");
+        assert_eq!(fetched.expires_at, within.expires_at);
+    }
+
+    #[tokio::test]
+    async fn sweep_once_purges_unread_burn_pastes_past_max_age() {
+        let adapter = Arc::new(RecordingAdapter::default());
+        let store =
+            MemoryPasteStore::with_persistence(adapter.clone()).with_burn_max_age(Some(3600));
+
+        let mut old_burn = build_paste(StoredContent::Plain {
+            text: "secret".into(),
+        });
+        old_burn.burn_after_reading = true;
+        old_burn.created_at = 1_000;
+        let mut fresh_burn = old_burn.clone();
+        fresh_burn.created_at = 4_000;
+        let mut old_plain = old_burn.clone();
+        old_plain.burn_after_reading = false;
+
+        let old_id = store.create_paste(old_burn).await.unwrap();
+        let fresh_id = store.create_paste(fresh_burn).await.unwrap();
+        let plain_id = store.create_paste(old_plain).await.unwrap();
+        adapter.take_deleted();
+
+        let removed = store.sweep_once(5_000).await;
+        assert_eq!(
+            removed
+                .iter()
+                .map(|(id, _)| id.as_str())
+                .collect::<Vec<_>>(),
+            vec![old_id.as_str()]
+        );
+        assert_eq!(adapter.take_deleted(), vec![old_id.clone()]);
+
+        let mut remaining = store.get_all_paste_ids().await;
+        remaining.sort();
+        let mut expected = vec![fresh_id, plain_id];
+        expected.sort();
+        assert_eq!(remaining, expected);
+
+        let unconfigured = MemoryPasteStore::new().with_burn_max_age(None);
+        let mut burn = build_paste(StoredContent::Plain { text: "x".into() });
+        burn.burn_after_reading = true;
+        burn.created_at = 0;
+        unconfigured.create_paste(burn).await.unwrap();
+        assert!(unconfigured.sweep_once(i64::MAX).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn sweep_once_removes_expired_pastes() {
+        let adapter = Arc::new(RecordingAdapter::default());
+        let store = MemoryPasteStore::with_persistence(adapter.clone()).with_burn_max_age(None);
+
+        let mut expired = build_paste(StoredContent::Plain { text: "old".into() });
+        expired.expires_at = Some(1_000);
+        let mut live = build_paste(StoredContent::Plain { text: "new".into() });
+        live.expires_at = Some(10_000);
+        let expired_id = store.create_paste(expired).await.unwrap();
+        let live_id = store.create_paste(live).await.unwrap();
+        adapter.take_deleted();
+        assert_eq!(store.stats().await.total_pastes, 2);
+
+        let removed = store.sweep_once(5_000).await;
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].0, expired_id);
+        assert_eq!(adapter.take_deleted(), vec![expired_id]);
+        assert_eq!(store.get_all_paste_ids().await, vec![live_id]);
+        assert_eq!(store.stats().await.total_pastes, 1);
+    }
+
+    #[tokio::test]
+    async fn spawned_sweeper_evicts_and_stops_on_shutdown() {
+        let store = Arc::new(MemoryPasteStore::new());
+        let mut expired = build_paste(StoredContent::Plain { text: "old".into() });
+        expired.expires_at = Some(0);
+        store.create_paste(expired).await.unwrap();
+
+        let sweeper = store.spawn_sweeper(Duration::from_millis(10));
+        for _ in 0..100 {
+            if store.get_all_paste_ids().await.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(store.get_all_paste_ids().await.is_empty());
+
+        tokio::time::timeout(Duration::from_secs(1), sweeper.shutdown())
+            .await
+            .expect("sweeper stops promptly");
+    }
+
     #[tokio::test]
     async fn get_paste_returns_not_found_on_adapter_error() {
         let adapter = Arc::new(RecordingAdapter::with_load_results(vec![Err(
@@ -1030,7 +2907,7 @@ mod tests {
         let paste = build_paste(StoredContent::Plain {
             text: "original".into(),
         });
-        let id = store.create_paste(paste).await;
+        let id = store.create_paste(paste).await.unwrap();
 
         store
             .update_paste(
@@ -1038,11 +2915,16 @@ mod tests {
                 StoredContent::Plain {
                     text: "updated".into(),
                 },
+                Some(content_digest("updated")),
             )
             .await
             .expect("update should succeed");
 
         let fetched = store.get_paste(&id).await.expect("paste should exist");
+        assert_eq!(
+            fetched.metadata.content_sha256.as_deref(),
+            Some("27eb5e51506c911f6fc4bb345c0d9db6f60415fceab7c18e1e9b862637415777")
+        );
         match fetched.content {
             StoredContent::Plain { text } => assert_eq!(text, "updated"),
             _ => panic!("unexpected content variant"),
@@ -1053,7 +2935,11 @@ mod tests {
     async fn update_paste_not_found_returns_error() {
         let store = MemoryPasteStore::default();
         let err = store
-            .update_paste("nonexistent", StoredContent::Plain { text: "x".into() })
+            .update_paste(
+                "nonexistent",
+                StoredContent::Plain { text: "x".into() },
+                None,
+            )
             .await
             .expect_err("should fail");
         assert!(matches!(err, PasteError::NotFound(_)));
@@ -1066,7 +2952,7 @@ mod tests {
             text: "live log".into(),
         });
         paste.is_live = true;
-        let id = store.create_paste(paste).await;
+        let id = store.create_paste(paste).await.unwrap();
 
         assert!(store.get_paste(&id).await.unwrap().is_live);
 
@@ -1078,6 +2964,171 @@ mod tests {
         assert!(!store.get_paste(&id).await.unwrap().is_live);
     }
 
+    #[tokio::test]
+    async fn peek_paste_leaves_expired_entries_in_place() {
+        let store = MemoryPasteStore::default();
+        let mut paste = build_paste(StoredContent::Plain {
+            text: "stale".into(),
+        });
+        paste.expires_at = Some(1);
+        let id = store.create_paste(paste).await.unwrap();
+
+        assert!(matches!(
+            store.peek_paste(&id).await,
+            Err(PasteError::Expired(_))
+        ));
+        assert!(store.get_all_paste_ids().await.contains(&id));
+        assert!(matches!(
+            store.peek_paste("missing").await,
+            Err(PasteError::NotFound(_))
+        ));
+    }
+
+    /// Backend holding pastes the in-memory cache has never seen, as after
+    /// a restart against Redis.
+    struct ListingAdapter {
+        pastes: HashMap<String, StoredPaste>,
+        loads: std::sync::atomic::AtomicUsize,
+    }
+
+    impl ListingAdapter {
+        fn new(pastes: HashMap<String, StoredPaste>) -> Self {
+            Self {
+                pastes,
+                loads: Default::default(),
+            }
+        }
+
+        fn loads(&self) -> usize {
+            self.loads.load(std::sync::atomic::Ordering::SeqCst)
+        }
+    }
+
+    #[async_trait]
+    impl PersistenceAdapter for ListingAdapter {
+        async fn save(&self, _id: &str, _paste: &StoredPaste) -> Result<(), PersistenceError> {
+            Ok(())
+        }
+
+        async fn load(&self, id: &str) -> Result<Option<StoredPaste>, PersistenceError> {
+            self.loads.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(self.pastes.get(id).cloned())
+        }
+
+        async fn delete(&self, _id: &str) -> Result<(), PersistenceError> {
+            Ok(())
+        }
+
+        async fn list_ids(&self) -> Result<Vec<String>, PersistenceError> {
+            Ok(self.pastes.keys().cloned().collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn ids_and_stats_include_pastes_only_the_backend_holds() {
+        let mut persisted = build_paste(StoredContent::Plain {
+            text: "from redis".into(),
+        });
+        persisted.format = PasteFormat::Rust;
+        let adapter = Arc::new(ListingAdapter::new(HashMap::from([(
+            "persisted-1".to_string(),
+            persisted,
+        )])));
+        let store = MemoryPasteStore::with_persistence(adapter);
+        let cached = store
+            .create_paste(build_paste(StoredContent::Plain { text: "x".into() }))
+            .await
+            .unwrap();
+
+        let mut ids = store.get_all_paste_ids().await;
+        ids.sort();
+        let mut expected = vec![cached, "persisted-1".to_string()];
+        expected.sort();
+        assert_eq!(ids, expected);
+
+        let stats = store.stats().await;
+        assert_eq!(stats.total_pastes, 2);
+        assert!(stats
+            .formats
+            .iter()
+            .any(|usage| usage.format == PasteFormat::Rust && usage.count == 1));
+
+        // Peeking reads through to the backend without caching the paste.
+        assert_eq!(
+            store.peek_paste("persisted-1").await.unwrap().format,
+            PasteFormat::Rust
+        );
+        assert!(!store.entries.read().await.contains_key("persisted-1"));
+    }
+
+    #[tokio::test]
+    async fn stats_load_backend_only_pastes_once() {
+        let pastes = ["persisted-1", "persisted-2"].map(|id| {
+            (
+                id.to_string(),
+                build_paste(StoredContent::Plain { text: id.into() }),
+            )
+        });
+        let adapter = Arc::new(ListingAdapter::new(HashMap::from(pastes)));
+        let store = MemoryPasteStore::with_persistence(adapter.clone());
+        let refresh = || async {
+            *store.stats_cache.lock().unwrap() = None;
+            store.stats().await.total_pastes
+        };
+
+        assert_eq!(refresh().await, 2);
+        let loads = adapter.loads();
+        assert_eq!(refresh().await, 2);
+        assert_eq!(adapter.loads(), loads, "a refresh must not reload pastes");
+
+        // Caching a backend-only paste must not count it twice.
+        store.get_paste("persisted-1").await.unwrap();
+        assert_eq!(refresh().await, 2);
+
+        store.delete_paste("persisted-2").await;
+        assert_eq!(refresh().await, 1);
+        assert!(store.delete_paste("persisted-1").await);
+        assert_eq!(refresh().await, 0);
+    }
+
+    #[tokio::test]
+    async fn consume_read_counts_reads() {
+        let store = MemoryPasteStore::default();
+        let id = store
+            .create_paste(build_paste(StoredContent::Plain { text: "x".into() }))
+            .await
+            .unwrap();
+
+        assert_eq!(store.consume_read(&id).await.unwrap().access_count, 1);
+        assert_eq!(store.consume_read(&id).await.unwrap().access_count, 2);
+        assert_eq!(
+            store.peek_paste(&id).await.unwrap().metadata.access_count,
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn set_bundle_parent_links_child_to_parent() {
+        let store = MemoryPasteStore::default();
+        let paste = build_paste(StoredContent::Plain {
+            text: "child".into(),
+        });
+        let id = store.create_paste(paste).await.unwrap();
+
+        store
+            .set_bundle_parent(&id, "parent-id")
+            .await
+            .expect("link should succeed");
+
+        let fetched = store.get_paste(&id).await.unwrap();
+        assert_eq!(fetched.bundle_parent.as_deref(), Some("parent-id"));
+        assert_eq!(fetched.metadata.bundle_parent.as_deref(), Some("parent-id"));
+        assert!(matches!(
+            store.set_bundle_parent("missing", "parent-id").await,
+            Err(PasteError::NotFound(_))
+        ));
+    }
+
     #[tokio::test]
     async fn finalize_paste_not_found_returns_error() {
         let store = MemoryPasteStore::default();
@@ -1093,14 +3144,14 @@ mod tests {
         let store = MemoryPasteStore::default();
 
         let paste = build_paste(StoredContent::Plain { text: "one".into() });
-        store.create_paste(paste).await;
+        store.create_paste(paste).await.unwrap();
 
         let stats1 = store.stats().await;
         assert_eq!(stats1.total_pastes, 1);
 
         // Create a second paste — should not be visible within the TTL window.
         let paste2 = build_paste(StoredContent::Plain { text: "two".into() });
-        store.create_paste(paste2).await;
+        store.create_paste(paste2).await.unwrap();
 
         let stats2 = store.stats().await;
         assert_eq!(
@@ -1109,6 +3160,115 @@ mod tests {
         );
     }
 
+    fn recomputed_aggregates(store: &MemoryPasteStore) -> StatsAggregates {
+        let map = store.entries.try_read().expect("no writer in tests");
+        let mut fresh = StatsAggregates::default();
+        for paste in map.values() {
+            fresh.add(paste);
+        }
+        fresh
+    }
+
+    fn incremental_aggregates(store: &MemoryPasteStore) -> StatsAggregates {
+        store
+            .entries
+            .try_read()
+            .expect("no writer in tests")
+            .aggregates
+            .clone()
+    }
+
+    #[tokio::test]
+    async fn incremental_stats_match_a_full_recount() {
+        let store = MemoryPasteStore::default();
+        let mut ids = Vec::new();
+        for i in 0..12i64 {
+            let mut paste = if i % 3 == 0 {
+                build_paste(StoredContent::Plain {
+                    text: format!("plain {i}"),
+                })
+            } else {
+                build_paste(StoredContent::Encrypted {
+                    algorithm: if i % 2 == 0 {
+                        EncryptionAlgorithm::Aes256Gcm
+                    } else {
+                        EncryptionAlgorithm::ChaCha20Poly1305
+                    },
+                    ciphertext: "c".into(),
+                    nonce: "n".into(),
+                    salt: "s".into(),
+                    kdf: KdfKind::default(),
+                    bound: false,
+                    envelope_version: ENVELOPE_V1,
+                })
+            };
+            paste.format = if i % 2 == 0 {
+                PasteFormat::Rust
+            } else {
+                PasteFormat::Markdown
+            };
+            paste.created_at = 1_700_000_000 + i * 86_400;
+            paste.burn_after_reading = i % 4 == 0;
+            if i % 5 == 0 {
+                paste.metadata.not_after = Some(i64::MAX);
+            }
+            // A few already-expired entries exercise the lazy expiry count.
+            paste.expires_at = Some(if i % 6 == 0 { 1 } else { i64::MAX });
+            ids.push(store.create_paste(paste).await.unwrap());
+        }
+        assert_eq!(
+            incremental_aggregates(&store),
+            recomputed_aggregates(&store)
+        );
+
+        assert!(store.delete_paste(&ids[1]).await);
+        assert!(!store.delete_paste(&ids[1]).await);
+        assert!(store.consume_read(&ids[4]).await.unwrap().consumed());
+        store
+            .update_paste(&ids[2], StoredContent::Plain { text: "x".into() }, None)
+            .await
+            .unwrap();
+        // Reading an expired entry evicts it.
+        assert!(store.get_paste(&ids[6]).await.is_err());
+        assert_eq!(
+            incremental_aggregates(&store),
+            recomputed_aggregates(&store)
+        );
+
+        let swept = store.sweep_once(current_unix_time()).await;
+        assert_eq!(swept.len(), 1);
+        assert_eq!(
+            incremental_aggregates(&store),
+            recomputed_aggregates(&store)
+        );
+
+        let stats = store.stats().await;
+        assert_eq!(stats.total_pastes, 8);
+        assert_eq!(stats.expired_pastes, 0);
+        assert_eq!(stats.active_pastes, 8);
+    }
+
+    #[tokio::test]
+    async fn stats_count_expired_entries_lazily() {
+        let store = MemoryPasteStore::default();
+        let mut stale = build_paste(StoredContent::Plain { text: "a".into() });
+        stale.expires_at = Some(1);
+        store.create_paste(stale).await.unwrap();
+        let expires_at = current_unix_time() + 3600;
+        let mut fresh = build_paste(StoredContent::Plain { text: "b".into() });
+        fresh.expires_at = Some(expires_at);
+        store.create_paste(fresh).await.unwrap();
+
+        let snapshot = incremental_aggregates(&store).snapshot(current_unix_time());
+        assert_eq!(snapshot.total_pastes, 2);
+        assert_eq!(snapshot.expired_pastes, 1);
+        assert_eq!(snapshot.active_pastes, 1);
+
+        // Once the clock passes every expiry, both count as expired.
+        let later = incremental_aggregates(&store).snapshot(expires_at + 1);
+        assert_eq!(later.expired_pastes, 2);
+    }
+
     #[tokio::test]
     async fn stats_reports_counts_and_breakdowns() {
         let store = MemoryPasteStore::default();
@@ -1123,6 +3283,9 @@ mod tests {
             ciphertext: "cipher".into(),
             nonce: "nonce".into(),
             salt: "salt".into(),
+            kdf: KdfKind::default(),
+            bound: false,
+            envelope_version: ENVELOPE_V1,
         });
         encrypted.format = PasteFormat::Json;
         encrypted.expires_at = Some(0);
@@ -1133,6 +3296,9 @@ mod tests {
             ciphertext: "payload".into(),
             nonce: "nonce".into(),
             salt: "salt".into(),
+            kdf: KdfKind::default(),
+            bound: false,
+            envelope_version: ENVELOPE_V1,
             carrier_mime: "image/png".into(),
             carrier_image: "data".into(),
             payload_digest: "digest".into(),
@@ -1140,9 +3306,9 @@ mod tests {
         stego.format = PasteFormat::Markdown;
         stego.created_at = 1_700_086_400;
 
-        let id1 = store.create_paste(plain).await;
-        let id2 = store.create_paste(encrypted).await;
-        let id3 = store.create_paste(stego).await;
+        let id1 = store.create_paste(plain).await.unwrap();
+        let id2 = store.create_paste(encrypted).await.unwrap();
+        let id3 = store.create_paste(stego).await.unwrap();
 
         let stats = store.stats().await;
 
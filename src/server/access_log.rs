@@ -0,0 +1,216 @@
+//! One-line-per-request access logging.
+//!
+//! The [`AccessLog`] fairing writes a line to stdout for every response:
+//! method, path, status, latency, content length and whether the request
+//! came in over the onion service. `COPYPASTE_LOG_FORMAT=json` (the default)
+//! emits a JSON object per line for structured sinks; `text` (or `pretty`)
+//! a space-separated line for humans. The query string is never logged,
+//! since share links carry keys in it, and neither is the host.
+//!
+//! Onion requests whose logs are suppressed (`COPYPASTE_TOR_SUPPRESS_LOGS`,
+//! see [`logs_suppressed`]) are omitted entirely.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::{Data, Request, Response};
+use serde::Serialize;
+
+use super::config::LoggingConfig;
+use super::tor::{logs_suppressed, OnionAccess};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessLogFormat {
+    Json,
+    Text,
+}
+
+impl AccessLogFormat {
+    /// `json`, or `text`/`pretty`; anything else is `None`.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "text" | "pretty" => Some(Self::Text),
+            _ => None,
+        }
+    }
+}
+
+/// Where finished lines go; stdout unless replaced with [`AccessLog::with_sink`].
+type Sink = Arc<dyn Fn(&str) + Send + Sync>;
+
+#[derive(Clone)]
+pub struct AccessLog {
+    format: AccessLogFormat,
+    sink: Sink,
+}
+
+impl AccessLog {
+    pub fn new(format: AccessLogFormat) -> Self {
+        Self {
+            format,
+            sink: Arc::new(|line| println!("{line}")),
+        }
+    }
+
+    pub fn from_config(config: &LoggingConfig) -> Self {
+        Self::new(AccessLogFormat::parse(&config.format).unwrap_or(AccessLogFormat::Json))
+    }
+
+    /// Send lines to `sink` instead of stdout.
+    pub fn with_sink(mut self, sink: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        self.sink = Arc::new(sink);
+        self
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct AccessEntry<'a> {
+    method: &'a str,
+    path: &'a str,
+    status: u16,
+    latency_ms: f64,
+    /// `None` for streamed bodies whose size is not known up front.
+    content_length: Option<u64>,
+    onion: bool,
+}
+
+impl AccessEntry<'_> {
+    fn render(&self, format: AccessLogFormat) -> String {
+        match format {
+            AccessLogFormat::Json => serde_json::to_string(self).unwrap_or_default(),
+            AccessLogFormat::Text => {
+                let length = self
+                    .content_length
+                    .map_or_else(|| "-".to_string(), |n| n.to_string());
+                let mut line = format!(
+                    "{} {} {} {:.3}ms {length}",
+                    self.method, self.path, self.status, self.latency_ms
+                );
+                if self.onion {
+                    line.push_str(" onion");
+                }
+                line
+            }
+        }
+    }
+}
+
+/// Request start time, stashed in the request-local cache.
+struct AccessStart(Instant);
+
+#[rocket::async_trait]
+impl Fairing for AccessLog {
+    fn info(&self) -> Info {
+        Info {
+            name: "Access log",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        request.local_cache(|| AccessStart(Instant::now()));
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        // Routes without an `OnionAccess` guard never ran it; doing so here
+        // sets the suppression flag for them too.
+        let onion = request
+            .guard::<OnionAccess>()
+            .await
+            .succeeded()
+            .is_some_and(|access| access.is_onion());
+        if logs_suppressed(request) {
+            return;
+        }
+        let started = request.local_cache(|| AccessStart(Instant::now())).0;
+        let entry = AccessEntry {
+            method: request.method().as_str(),
+            path: request.uri().path().as_str(),
+            status: response.status().code,
+            latency_ms: started.elapsed().as_secs_f64() * 1000.0,
+            content_length: response.body().preset_size().map(|n| n as u64),
+            onion,
+        };
+        (self.sink)(&entry.render(self.format));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::tor::TorConfig;
+    use rocket::http::{Header, Status};
+    use rocket::local::blocking::Client;
+    use std::sync::Mutex;
+
+    #[rocket::get("/paste/<id>")]
+    fn paste(id: &str) -> String {
+        format!("paste {id}")
+    }
+
+    fn client(format: AccessLogFormat) -> (Client, Arc<Mutex<Vec<String>>>) {
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let captured = lines.clone();
+        let rocket = rocket::build()
+            .manage(TorConfig {
+                onion_host: Some("secure.onion".into()),
+                suppress_logs: true,
+            })
+            .attach(
+                AccessLog::new(format)
+                    .with_sink(move |line| captured.lock().unwrap().push(line.to_string())),
+            )
+            .mount("/", rocket::routes![paste]);
+        (Client::tracked(rocket).expect("client"), lines)
+    }
+
+    #[test]
+    fn json_lines_carry_request_fields_without_the_query() {
+        let (client, lines) = client(AccessLogFormat::Json);
+        let response = client.get("/paste/abc?key=secret").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        client.get("/missing").dispatch();
+
+        let lines = lines.lock().unwrap();
+        assert_eq!(lines.len(), 2);
+        assert!(!lines[0].contains("secret"));
+        let entry: serde_json::Value = serde_json::from_str(&lines[0]).expect("json line");
+        assert_eq!(entry["method"], "GET");
+        assert_eq!(entry["path"], "/paste/abc");
+        assert_eq!(entry["status"], 200);
+        assert_eq!(entry["content_length"], "paste abc".len());
+        assert_eq!(entry["onion"], false);
+        assert!(entry["latency_ms"].as_f64().is_some_and(|ms| ms >= 0.0));
+
+        let missing: serde_json::Value = serde_json::from_str(&lines[1]).expect("json line");
+        assert_eq!(missing["status"], 404);
+    }
+
+    #[test]
+    fn suppressed_onion_requests_are_not_logged() {
+        let (client, lines) = client(AccessLogFormat::Text);
+        client
+            .get("/paste/abc")
+            .header(Header::new("X-Forwarded-Host", "secure.onion"))
+            .dispatch();
+        assert!(lines.lock().unwrap().is_empty());
+
+        client.get("/paste/abc").dispatch();
+        let lines = lines.lock().unwrap();
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].starts_with("GET /paste/abc 200 "), "{}", lines[0]);
+    }
+
+    #[test]
+    fn format_parses_json_and_text_aliases() {
+        assert_eq!(AccessLogFormat::parse("JSON"), Some(AccessLogFormat::Json));
+        assert_eq!(AccessLogFormat::parse("text"), Some(AccessLogFormat::Text));
+        assert_eq!(
+            AccessLogFormat::parse("pretty"),
+            Some(AccessLogFormat::Text)
+        );
+        assert_eq!(AccessLogFormat::parse("xml"), None);
+    }
+}
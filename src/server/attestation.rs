@@ -1,13 +1,17 @@
-use crate::AttestationRequirement;
+use crate::{AttestationRequirement, TotpAlgorithm};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use base64::Engine;
 use data_encoding::BASE32;
-use hmac::{Hmac, Mac};
+use hmac::{digest::KeyInit, Hmac, Mac};
 use rocket::serde::{Deserialize, Serialize};
 use sha1::Sha1;
-use sha2::{Digest, Sha256};
+use sha2::{Digest, Sha256, Sha512};
+use subtle::ConstantTimeEq;
 use utoipa::ToSchema;
 
+use super::download_tokens::hmac_secret_from_env;
 use super::models::PasteViewQuery;
+use super::webauthn::{relying_party, PasskeyProof};
 
 #[derive(Serialize, Deserialize, ToSchema)]
 #[serde(tag = "kind", rename_all = "snake_case")]
@@ -22,10 +26,26 @@ pub enum AttestationRequest {
         allowed_drift: Option<u32>,
         #[serde(default)]
         issuer: Option<String>,
+        /// `sha1` (default), `sha256` or `sha512`.
+        #[serde(default)]
+        algorithm: Option<String>,
     },
     SharedSecret {
         secret: String,
     },
+    /// Require a passkey assertion from one of `passkeys` (as serialized by
+    /// `webauthn-rs`, e.g. stored after registration) for relying party
+    /// `rp_id`.
+    Webauthn {
+        rp_id: String,
+        #[schema(value_type = Vec<Object>)]
+        passkeys: Vec<webauthn_rs::prelude::Passkey>,
+    },
+    /// Require every listed factor, e.g. a TOTP code and a shared secret.
+    /// Each kind may appear once; lists do not nest.
+    All {
+        requirements: Vec<AttestationRequest>,
+    },
 }
 
 #[derive(Copy, Clone)]
@@ -34,26 +54,64 @@ pub enum AttestationVerdict {
     Prompt { invalid: bool },
 }
 
-type HmacSha1 = Hmac<Sha1>;
-
+/// Checks the credentials in `query` against `requirement`.
+///
+/// Both the shared-secret digest and the TOTP code are compared in constant
+/// time, so response timing does not reveal how much of a guess was correct.
+/// An [`AttestationRequirement::All`] is granted only when every factor is;
+/// all of them are checked, and any wrong entry makes the prompt invalid.
+/// A passkey requirement is met by `passkey`, the handler's check of the
+/// grant token the read presented.
 pub fn verify_attestation(
     requirement: &AttestationRequirement,
     query: &PasteViewQuery,
+    passkey: PasskeyProof,
     now: i64,
 ) -> AttestationVerdict {
     match requirement {
+        AttestationRequirement::Webauthn { .. } => match passkey {
+            PasskeyProof::Verified => AttestationVerdict::Granted,
+            PasskeyProof::Missing => AttestationVerdict::Prompt { invalid: false },
+            PasskeyProof::Invalid => AttestationVerdict::Prompt { invalid: true },
+        },
+        AttestationRequirement::All { requirements } => {
+            let mut granted = true;
+            let mut invalid = false;
+            for factor in requirements {
+                if let AttestationVerdict::Prompt { invalid: wrong } =
+                    verify_attestation(factor, query, passkey, now)
+                {
+                    granted = false;
+                    invalid |= wrong;
+                }
+            }
+            if granted {
+                AttestationVerdict::Granted
+            } else {
+                AttestationVerdict::Prompt { invalid }
+            }
+        }
         AttestationRequirement::Totp {
             secret,
             digits,
             step,
             allowed_drift,
+            algorithm,
             ..
         } => {
             let code = match query.code.as_deref() {
                 Some(value) if !value.trim().is_empty() => value.trim(),
                 _ => return AttestationVerdict::Prompt { invalid: false },
             };
-            if verify_totp(secret, code, *digits, *step, *allowed_drift, now) {
+            if verify_totp(
+                secret,
+                code,
+                *digits,
+                *step,
+                *allowed_drift,
+                *algorithm,
+                now,
+            ) {
                 AttestationVerdict::Granted
             } else {
                 AttestationVerdict::Prompt { invalid: true }
@@ -67,8 +125,12 @@ pub fn verify_attestation(
             let mut hasher = Sha256::new();
             hasher.update(provided.as_bytes());
             let digest = hasher.finalize();
-            let encoded = base64::engine::general_purpose::STANDARD.encode(digest);
-            if &encoded == hash {
+            // Compare raw digest bytes rather than base64 strings; a stored
+            // hash that fails to decode can never match.
+            let expected = base64::engine::general_purpose::STANDARD
+                .decode(hash)
+                .unwrap_or_default();
+            if bool::from(digest[..].ct_eq(&expected)) {
                 AttestationVerdict::Granted
             } else {
                 AttestationVerdict::Prompt { invalid: true }
@@ -87,6 +149,7 @@ pub fn requirement_from_request(
             step,
             allowed_drift,
             issuer,
+            algorithm,
         } => {
             let secret = secret.trim();
             if secret.is_empty() {
@@ -108,12 +171,19 @@ pub fn requirement_from_request(
                     "TOTP allowed_drift must be at most {MAX_ALLOWED_DRIFT}"
                 ));
             }
+            let algorithm = match algorithm.as_deref() {
+                None => TotpAlgorithm::default(),
+                Some(value) => TotpAlgorithm::parse(value).ok_or_else(|| {
+                    format!("TOTP algorithm must be sha1, sha256 or sha512 (got {value:?})")
+                })?,
+            };
             AttestationRequirement::Totp {
                 secret: secret.to_string(),
                 digits,
                 step,
                 allowed_drift,
                 issuer: issuer.clone(),
+                algorithm,
             }
         }
         AttestationRequest::SharedSecret { secret } => {
@@ -128,15 +198,110 @@ pub fn requirement_from_request(
                 hash: base64::engine::general_purpose::STANDARD.encode(digest),
             }
         }
+        AttestationRequest::Webauthn { rp_id, passkeys } => {
+            let rp_id = rp_id.trim();
+            if rp_id.is_empty() {
+                return Err("WebAuthn rp_id cannot be empty".into());
+            }
+            relying_party(rp_id).map_err(|error| format!("Invalid WebAuthn rp_id: {error}"))?;
+            if passkeys.is_empty() {
+                return Err("WebAuthn attestation needs at least one passkey".into());
+            }
+            AttestationRequirement::Webauthn {
+                rp_id: rp_id.to_string(),
+                credential_ids: passkeys
+                    .iter()
+                    .map(|passkey| URL_SAFE_NO_PAD.encode(passkey.cred_id()))
+                    .collect(),
+                passkeys: passkeys.clone(),
+            }
+        }
+        AttestationRequest::All { requirements } => {
+            if requirements.is_empty() {
+                return Err("Attestation `all` needs at least one requirement".into());
+            }
+            let mut factors = Vec::with_capacity(requirements.len());
+            for request in requirements {
+                let factor = requirement_from_request(request)?;
+                if matches!(factor, AttestationRequirement::All { .. }) {
+                    return Err("Attestation `all` requirements cannot nest".into());
+                }
+                if factors
+                    .iter()
+                    .any(|seen| std::mem::discriminant(seen) == std::mem::discriminant(&factor))
+                {
+                    return Err(
+                        "Attestation `all` may list each kind of requirement only once".into(),
+                    );
+                }
+                factors.push(factor);
+            }
+            AttestationRequirement::All {
+                requirements: factors,
+            }
+        }
     })
 }
 
+/// How long an attestation form stays valid after it was rendered.
+pub const PROMPT_TOKEN_TTL_SECS: i64 = 10 * 60;
+
+/// Signs the `state` token carried by the HTML attestation form.
+///
+/// The form used to echo the viewer's `?key=` back in a hidden input, which
+/// put the encryption key into a page that may be cached or screenshotted.
+/// It now carries `issued_at "." base64url(HMAC-SHA256(id "." issued_at))`
+/// instead and asks for the key again on submit. The secret comes from
+/// `COPYPASTE_ATTESTATION_TOKEN_SECRET`, or is random per process.
+pub struct PromptTokens {
+    secret: Vec<u8>,
+}
+
+impl PromptTokens {
+    pub fn new(secret: Vec<u8>) -> Self {
+        Self { secret }
+    }
+
+    pub fn from_env() -> Self {
+        let secret = hmac_secret_from_env("COPYPASTE_ATTESTATION_TOKEN_SECRET");
+        Self::new(secret)
+    }
+
+    /// Token for a form showing paste `id`, rendered at `now`.
+    pub fn mint(&self, id: &str, now: i64) -> String {
+        format!("{now}.{}", URL_SAFE_NO_PAD.encode(self.sign(id, now)))
+    }
+
+    /// Whether `token` was minted by [`PromptTokens::mint`] for `id` within
+    /// the last [`PROMPT_TOKEN_TTL_SECS`].
+    pub fn verify(&self, token: &str, id: &str, now: i64) -> bool {
+        let Some((issued_at, signature)) = token.split_once('.') else {
+            return false;
+        };
+        let (Ok(issued_at), Ok(signature)) =
+            (issued_at.parse::<i64>(), URL_SAFE_NO_PAD.decode(signature))
+        else {
+            return false;
+        };
+        let fresh = issued_at <= now && now - issued_at < PROMPT_TOKEN_TTL_SECS;
+        fresh && bool::from(self.sign(id, issued_at).ct_eq(&signature))
+    }
+
+    fn sign(&self, id: &str, issued_at: i64) -> Vec<u8> {
+        let mut mac =
+            <Hmac<Sha256> as Mac>::new_from_slice(&self.secret).expect("HMAC accepts any key size");
+        mac.update(format!("{id}.{issued_at}").as_bytes());
+        mac.finalize().into_bytes().to_vec()
+    }
+}
+
 fn verify_totp(
     secret: &str,
     code: &str,
     digits: u32,
     step: u64,
     allowed_drift: u32,
+    algorithm: TotpAlgorithm,
     now: i64,
 ) -> bool {
     let secret_bytes = match decode_totp_secret(secret) {
@@ -166,8 +331,9 @@ fn verify_totp(
         let Some(candidate_counter) = adjusted_counter else {
             continue;
         };
-        if let Some(candidate) = totp_code(&secret_bytes, candidate_counter, digits) {
-            if candidate == sanitized_code {
+        if let Some(candidate) = totp_code(&secret_bytes, candidate_counter, digits, algorithm) {
+            // Lengths are equal (checked above), so this leaks nothing.
+            if bool::from(candidate.as_bytes().ct_eq(sanitized_code.as_bytes())) {
                 return true;
             }
         }
@@ -185,10 +351,18 @@ fn decode_totp_secret(secret: &str) -> Option<Vec<u8>> {
     BASE32.decode(normalized.as_bytes()).ok()
 }
 
-fn totp_code(secret: &[u8], counter: u64, digits: u32) -> Option<String> {
-    let mut mac = <HmacSha1 as Mac>::new_from_slice(secret).ok()?;
+fn hmac_counter<M: Mac + KeyInit>(secret: &[u8], counter: u64) -> Option<Vec<u8>> {
+    let mut mac = <M as Mac>::new_from_slice(secret).ok()?;
     mac.update(&counter.to_be_bytes());
-    let result = mac.finalize().into_bytes();
+    Some(mac.finalize().into_bytes().to_vec())
+}
+
+fn totp_code(secret: &[u8], counter: u64, digits: u32, algorithm: TotpAlgorithm) -> Option<String> {
+    let result = match algorithm {
+        TotpAlgorithm::Sha1 => hmac_counter::<Hmac<Sha1>>(secret, counter)?,
+        TotpAlgorithm::Sha256 => hmac_counter::<Hmac<Sha256>>(secret, counter)?,
+        TotpAlgorithm::Sha512 => hmac_counter::<Hmac<Sha512>>(secret, counter)?,
+    };
     let offset = (result[result.len() - 1] & 0x0f) as usize;
     if offset + 4 > result.len() {
         return None;
@@ -209,19 +383,150 @@ mod tests {
 
     const SECRET: &str = "JBSWY3DPEHPK3PXP"; // base32 for "Hello!"
 
+    #[test]
+    fn prompt_tokens_are_bound_to_paste_and_age() {
+        let tokens = PromptTokens::new(b"secret".to_vec());
+        let token = tokens.mint("abc123", 1_000);
+
+        assert!(tokens.verify(&token, "abc123", 1_000));
+        assert!(tokens.verify(&token, "abc123", 1_000 + PROMPT_TOKEN_TTL_SECS - 1));
+        assert!(!tokens.verify(&token, "abc123", 1_000 + PROMPT_TOKEN_TTL_SECS));
+        assert!(!tokens.verify(&token, "abc123", 999));
+        assert!(!tokens.verify(&token, "other", 1_010));
+        assert!(!PromptTokens::new(b"different".to_vec()).verify(&token, "abc123", 1_010));
+        assert!(!tokens.verify("not-a-token", "abc123", 1_010));
+        let forged = token.replacen("1000", "1005", 1);
+        assert!(!tokens.verify(&forged, "abc123", 1_010));
+    }
+
     #[test]
     fn totp_verification_accepts_valid_code() {
         let now = 30 * 1_000; // align with step window
         let bytes = decode_totp_secret(SECRET).expect("base32 secret");
         let counter = (now as u64) / 30;
-        let code = totp_code(&bytes, counter, 6).expect("code generation");
-        assert!(verify_totp(SECRET, &code, 6, 30, 1, now));
+        let code = totp_code(&bytes, counter, 6, TotpAlgorithm::Sha1).expect("code generation");
+        assert!(verify_totp(
+            SECRET,
+            &code,
+            6,
+            30,
+            1,
+            TotpAlgorithm::Sha1,
+            now
+        ));
     }
 
     #[test]
     fn totp_verification_rejects_invalid_code() {
         let now = 30 * 1_234;
-        assert!(!verify_totp(SECRET, "000000", 6, 30, 0, now));
+        assert!(!verify_totp(
+            SECRET,
+            "000000",
+            6,
+            30,
+            0,
+            TotpAlgorithm::Sha1,
+            now
+        ));
+    }
+
+    /// RFC 6238 Appendix B: 8-digit codes, 30-second step, with the seed
+    /// length matching each hash's output size.
+    #[test]
+    fn totp_code_matches_rfc6238_vectors() {
+        let sha1_seed = b"12345678901234567890".to_vec();
+        let sha256_seed = b"12345678901234567890123456789012".to_vec();
+        let sha512_seed =
+            b"1234567890123456789012345678901234567890123456789012345678901234".to_vec();
+        let vectors: [(u64, &str, &str, &str); 6] = [
+            (59, "94287082", "46119246", "90693936"),
+            (1_111_111_109, "07081804", "68084774", "25091201"),
+            (1_111_111_111, "14050471", "67062674", "99943326"),
+            (1_234_567_890, "89005924", "91819424", "93441116"),
+            (2_000_000_000, "69279037", "90698825", "38618901"),
+            (20_000_000_000, "65353130", "77737706", "47863826"),
+        ];
+        for (time, sha1, sha256, sha512) in vectors {
+            let counter = time / 30;
+            for (seed, algorithm, expected) in [
+                (&sha1_seed, TotpAlgorithm::Sha1, sha1),
+                (&sha256_seed, TotpAlgorithm::Sha256, sha256),
+                (&sha512_seed, TotpAlgorithm::Sha512, sha512),
+            ] {
+                assert_eq!(
+                    totp_code(seed, counter, 8, algorithm).as_deref(),
+                    Some(expected),
+                    "{algorithm:?} at T={time}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn sha256_totp_generates_and_verifies() {
+        let request = AttestationRequest::Totp {
+            secret: SECRET.into(),
+            digits: None,
+            step: None,
+            allowed_drift: Some(0),
+            issuer: None,
+            algorithm: Some("SHA256".into()),
+        };
+        let requirement = requirement_from_request(&request).expect("valid request");
+        let AttestationRequirement::Totp { algorithm, .. } = requirement else {
+            panic!("unexpected variant");
+        };
+        assert_eq!(algorithm, TotpAlgorithm::Sha256);
+
+        let now = 30 * 3_000;
+        let bytes = decode_totp_secret(SECRET).expect("base32 secret");
+        let code = totp_code(&bytes, (now as u64) / 30, 6, TotpAlgorithm::Sha256)
+            .expect("code generation");
+        let sha1_code =
+            totp_code(&bytes, (now as u64) / 30, 6, TotpAlgorithm::Sha1).expect("code generation");
+        assert_ne!(code, sha1_code);
+        assert!(verify_totp(
+            SECRET,
+            &code,
+            6,
+            30,
+            0,
+            TotpAlgorithm::Sha256,
+            now
+        ));
+        assert!(!verify_totp(
+            SECRET,
+            &sha1_code,
+            6,
+            30,
+            0,
+            TotpAlgorithm::Sha256,
+            now
+        ));
+    }
+
+    #[test]
+    fn requirement_from_request_rejects_unknown_algorithm() {
+        let request = AttestationRequest::Totp {
+            secret: SECRET.into(),
+            digits: None,
+            step: None,
+            allowed_drift: None,
+            issuer: None,
+            algorithm: Some("md5".into()),
+        };
+        let err = requirement_from_request(&request).expect_err("md5 should fail");
+        assert!(err.contains("algorithm"));
+    }
+
+    #[test]
+    fn stored_totp_requirement_without_algorithm_defaults_to_sha1() {
+        let json = r#"{"kind":"totp","secret":"JBSWY3DPEHPK3PXP","digits":6,"step":30}"#;
+        let requirement: AttestationRequirement = serde_json::from_str(json).unwrap();
+        let AttestationRequirement::Totp { algorithm, .. } = requirement else {
+            panic!("unexpected variant");
+        };
+        assert_eq!(algorithm, TotpAlgorithm::Sha1);
     }
 
     #[test]
@@ -232,6 +537,7 @@ mod tests {
             step: Some(30),
             allowed_drift: Some(1),
             issuer: Some("Test Issuer".into()),
+            algorithm: None,
         };
 
         let requirement = requirement_from_request(&request).expect("valid request");
@@ -260,6 +566,7 @@ mod tests {
             step: Some(30),
             allowed_drift: None,
             issuer: None,
+            algorithm: None,
         };
 
         let err = requirement_from_request(&request).expect_err("digits > 10 should fail");
@@ -274,6 +581,7 @@ mod tests {
             step: Some(30),
             allowed_drift: Some(3),
             issuer: None,
+            algorithm: None,
         };
         let err = requirement_from_request(&request).expect_err("drift > 2 should fail");
         assert!(err.contains("allowed_drift"));
@@ -287,6 +595,7 @@ mod tests {
             step: Some(30),
             allowed_drift: Some(2),
             issuer: None,
+            algorithm: None,
         };
         let requirement = requirement_from_request(&request).expect("drift == 2 should succeed");
         match requirement {
@@ -297,6 +606,199 @@ mod tests {
         }
     }
 
+    #[test]
+    fn shared_secret_grants_correct_and_prompts_incorrect() {
+        let requirement = requirement_from_request(&AttestationRequest::SharedSecret {
+            secret: "topsecret".into(),
+        })
+        .expect("hashable");
+
+        let query = |attest: &str| PasteViewQuery {
+            attest: Some(attest.into()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            verify_attestation(&requirement, &query("topsecret"), PasskeyProof::Missing, 0),
+            AttestationVerdict::Granted
+        ));
+        assert!(matches!(
+            verify_attestation(&requirement, &query("topsecreT"), PasskeyProof::Missing, 0),
+            AttestationVerdict::Prompt { invalid: true }
+        ));
+        assert!(matches!(
+            verify_attestation(
+                &requirement,
+                &PasteViewQuery::default(),
+                PasskeyProof::Missing,
+                0
+            ),
+            AttestationVerdict::Prompt { invalid: false }
+        ));
+    }
+
+    #[test]
+    fn all_attestation_grants_only_when_every_factor_passes() {
+        let requirement = requirement_from_request(&AttestationRequest::All {
+            requirements: vec![
+                AttestationRequest::Totp {
+                    secret: SECRET.into(),
+                    digits: None,
+                    step: None,
+                    allowed_drift: Some(0),
+                    issuer: None,
+                    algorithm: None,
+                },
+                AttestationRequest::SharedSecret {
+                    secret: "team-secret".into(),
+                },
+            ],
+        })
+        .expect("valid request");
+        let now = 30 * 2_000;
+        let bytes = decode_totp_secret(SECRET).expect("base32 secret");
+        let code =
+            totp_code(&bytes, (now as u64) / 30, 6, TotpAlgorithm::Sha1).expect("code generation");
+        let wrong = format!("{:06}", (code.parse::<u32>().unwrap() + 1) % 1_000_000);
+
+        let query = |code: &str, attest: &str| PasteViewQuery {
+            code: Some(code.into()),
+            attest: Some(attest.into()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            verify_attestation(
+                &requirement,
+                &query(&code, "team-secret"),
+                PasskeyProof::Missing,
+                now
+            ),
+            AttestationVerdict::Granted
+        ));
+        assert!(matches!(
+            verify_attestation(
+                &requirement,
+                &query(&wrong, "team-secret"),
+                PasskeyProof::Missing,
+                now
+            ),
+            AttestationVerdict::Prompt { invalid: true }
+        ));
+        assert!(matches!(
+            verify_attestation(
+                &requirement,
+                &query(&code, "other"),
+                PasskeyProof::Missing,
+                now
+            ),
+            AttestationVerdict::Prompt { invalid: true }
+        ));
+        let code_only = PasteViewQuery {
+            code: Some(code.clone()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            verify_attestation(&requirement, &code_only, PasskeyProof::Missing, now),
+            AttestationVerdict::Prompt { invalid: false }
+        ));
+    }
+
+    #[test]
+    fn webauthn_attestation_follows_the_passkey_grant() {
+        let passkey = serde_json::from_str(crate::server::webauthn::TEST_PASSKEY).unwrap();
+        let requirement = requirement_from_request(&AttestationRequest::All {
+            requirements: vec![
+                AttestationRequest::Webauthn {
+                    rp_id: "copypaste.fyi".into(),
+                    passkeys: vec![passkey],
+                },
+                AttestationRequest::SharedSecret {
+                    secret: "team-secret".into(),
+                },
+            ],
+        })
+        .expect("valid request");
+        let AttestationRequirement::Webauthn { credential_ids, .. } = &requirement.factors()[0]
+        else {
+            panic!("unexpected factor");
+        };
+        assert_eq!(
+            credential_ids,
+            &["uZcVDBVS68E_MtAgeQpElJxldF_6cY9sSvbWqx_qRh8wiu42lyRBRmh5yFeD_r9k130dMbFHBHI9RTFgdJQIzQ"]
+        );
+
+        let query = PasteViewQuery {
+            attest: Some("team-secret".into()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            verify_attestation(&requirement, &query, PasskeyProof::Verified, 0),
+            AttestationVerdict::Granted
+        ));
+        assert!(matches!(
+            verify_attestation(&requirement, &query, PasskeyProof::Missing, 0),
+            AttestationVerdict::Prompt { invalid: false }
+        ));
+        assert!(matches!(
+            verify_attestation(&requirement, &query, PasskeyProof::Invalid, 0),
+            AttestationVerdict::Prompt { invalid: true }
+        ));
+
+        for (rp_id, passkeys) in [("", 1), ("copypaste.fyi", 0)] {
+            let request = AttestationRequest::Webauthn {
+                rp_id: rp_id.into(),
+                passkeys: (0..passkeys)
+                    .map(|_| serde_json::from_str(crate::server::webauthn::TEST_PASSKEY).unwrap())
+                    .collect(),
+            };
+            assert!(requirement_from_request(&request).is_err());
+        }
+    }
+
+    #[test]
+    fn all_attestation_rejects_empty_nested_and_repeated_factors() {
+        let secret = || AttestationRequest::SharedSecret { secret: "s".into() };
+        for requirements in [
+            vec![],
+            vec![secret(), secret()],
+            vec![AttestationRequest::All {
+                requirements: vec![secret()],
+            }],
+        ] {
+            assert!(requirement_from_request(&AttestationRequest::All { requirements }).is_err());
+        }
+    }
+
+    #[test]
+    fn totp_attestation_grants_correct_and_prompts_incorrect() {
+        let requirement = requirement_from_request(&AttestationRequest::Totp {
+            secret: SECRET.into(),
+            digits: None,
+            step: None,
+            allowed_drift: Some(0),
+            issuer: None,
+            algorithm: None,
+        })
+        .expect("valid request");
+        let now = 30 * 2_000;
+        let bytes = decode_totp_secret(SECRET).expect("base32 secret");
+        let code =
+            totp_code(&bytes, (now as u64) / 30, 6, TotpAlgorithm::Sha1).expect("code generation");
+        let wrong = format!("{:06}", (code.parse::<u32>().unwrap() + 1) % 1_000_000);
+
+        let query = |code: &str| PasteViewQuery {
+            code: Some(code.into()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            verify_attestation(&requirement, &query(&code), PasskeyProof::Missing, now),
+            AttestationVerdict::Granted
+        ));
+        assert!(matches!(
+            verify_attestation(&requirement, &query(&wrong), PasskeyProof::Missing, now),
+            AttestationVerdict::Prompt { invalid: true }
+        ));
+    }
+
     #[test]
     fn shared_secret_hashes_to_base64() {
         let request = AttestationRequest::SharedSecret {
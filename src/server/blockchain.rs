@@ -1,4 +1,6 @@
-use std::{env, sync::Arc};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use std::{env, mem};
 
 use async_trait::async_trait;
 use hex::encode as hex_encode;
@@ -6,6 +8,7 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use thiserror::Error;
+use tokio::sync::oneshot;
 
 use crate::{AttestationRequirement, PasteFormat, PasteMetadata, StoredContent, StoredPaste};
 use utoipa::ToSchema;
@@ -19,24 +22,64 @@ pub struct AnchorManifest {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub expires_at: Option<i64>,
     pub burn_after_reading: bool,
-    pub content: StoredContent,
+    /// Stored content as-is; absent in [`AnchorContentMode::DigestOnly`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<StoredContent>,
+    /// Hex SHA-256 of the serialized stored content, in place of `content`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_digest: Option<String>,
     pub metadata: PasteMetadata,
 }
 
+/// What an anchor manifest carries of the paste body.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AnchorContentMode {
+    /// The stored content (ciphertext for encrypted pastes).
+    #[default]
+    Full,
+    /// Only a SHA-256 of the stored content, so the relayer never sees it.
+    DigestOnly,
+}
+
 impl AnchorManifest {
+    /// Snapshot of `paste` for anchoring. The read counter and the anchor
+    /// record itself are bookkeeping rather than paste state, so they are
+    /// left out and viewing or anchoring a paste never changes its hash.
     pub fn from_paste(id: impl Into<String>, paste: &StoredPaste) -> Self {
+        Self::from_paste_with_mode(id, paste, AnchorContentMode::Full)
+    }
+
+    pub fn from_paste_with_mode(
+        id: impl Into<String>,
+        paste: &StoredPaste,
+        mode: AnchorContentMode,
+    ) -> Self {
+        let mut metadata = paste.metadata.clone();
+        metadata.access_count = 0;
+        metadata.anchored_hash = None;
+        let (content, content_digest) = match mode {
+            AnchorContentMode::Full => (Some(paste.content.clone()), None),
+            AnchorContentMode::DigestOnly => (None, Some(stored_content_digest(&paste.content))),
+        };
         Self {
             id: id.into(),
             format: paste.format,
             created_at: paste.created_at,
             expires_at: paste.expires_at,
             burn_after_reading: paste.burn_after_reading,
-            content: paste.content.clone(),
-            metadata: paste.metadata.clone(),
+            content,
+            content_digest,
+            metadata,
         }
     }
 }
 
+fn stored_content_digest(content: &StoredContent) -> String {
+    let bytes = serde_json::to_vec(content).expect("stored content serializes");
+    hex_encode(Sha256::digest(bytes))
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AnchorPayload {
@@ -69,6 +112,23 @@ impl AnchorPayload {
 pub struct AnchorReceipt {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub transaction_id: Option<String>,
+    /// Sibling hashes from this paste's manifest hash up to `batch_root`,
+    /// for receipts from a [`BatchingAnchorRelayer`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub merkle_proof: Vec<String>,
+    /// Merkle root that was actually submitted for the batch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch_root: Option<String>,
+}
+
+/// One Merkle root covering several manifest hashes, submitted in place of
+/// the individual payloads.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnchorBatch {
+    pub batch_root: String,
+    /// Leaf manifest hashes, in tree order.
+    pub hashes: Vec<String>,
 }
 
 #[derive(Debug, Error)]
@@ -110,19 +170,30 @@ pub fn infer_retention_class(manifest: &AnchorManifest) -> Option<u8> {
         .map(|(_, class)| *class)
 }
 
+/// Reference for each attestation factor, joined with `+` when a paste
+/// requires several.
 pub fn infer_attestation_ref(metadata: &PasteMetadata) -> Option<String> {
-    match metadata.attestation.as_ref() {
-        Some(AttestationRequirement::Totp { issuer, .. }) => issuer.clone(),
-        Some(AttestationRequirement::SharedSecret { hash }) => {
-            Some(format!("shared_secret:{}", hash))
-        }
-        None => None,
-    }
+    let refs: Vec<String> = metadata
+        .attestation
+        .as_ref()?
+        .factors()
+        .iter()
+        .filter_map(|factor| match factor {
+            AttestationRequirement::Totp { issuer, .. } => issuer.clone(),
+            AttestationRequirement::SharedSecret { hash } => {
+                Some(format!("shared_secret:{}", hash))
+            }
+            AttestationRequirement::Webauthn { rp_id, .. } => Some(format!("webauthn:{rp_id}")),
+            AttestationRequirement::All { .. } => None,
+        })
+        .collect();
+    (!refs.is_empty()).then(|| refs.join("+"))
 }
 
 #[async_trait]
 pub trait AnchorRelayer: Send + Sync + 'static {
     async fn submit(&self, payload: AnchorPayload) -> Result<AnchorReceipt, AnchorError>;
+    async fn submit_batch(&self, batch: AnchorBatch) -> Result<AnchorReceipt, AnchorError>;
 }
 
 pub type SharedAnchorRelayer = Arc<dyn AnchorRelayer>;
@@ -139,16 +210,215 @@ impl AnchorRelayer for NoopAnchorRelayer {
         );
         Ok(AnchorReceipt::default())
     }
+
+    async fn submit_batch(&self, batch: AnchorBatch) -> Result<AnchorReceipt, AnchorError> {
+        println!(
+            "[anchor] noop relayer invoked for batch root {} ({} pastes)",
+            batch.batch_root,
+            batch.hashes.len()
+        );
+        Ok(AnchorReceipt::default())
+    }
 }
 
+/// Default flush interval for `ANCHOR_BATCH_SIZE` batching.
+const DEFAULT_BATCH_INTERVAL_SECS: u64 = 10;
+
+/// The relayer configured by `ANCHOR_RELAY_ENDPOINT`, wrapped in a
+/// [`BatchingAnchorRelayer`] when `ANCHOR_BATCH_SIZE` is above 1.
 pub fn default_anchor_relayer() -> SharedAnchorRelayer {
-    match env::var("ANCHOR_RELAY_ENDPOINT") {
+    let relayer: SharedAnchorRelayer = match env::var("ANCHOR_RELAY_ENDPOINT") {
         Ok(endpoint) if !endpoint.trim().is_empty() => {
             let api_key = env::var("ANCHOR_RELAY_API_KEY").ok();
             Arc::new(HttpAnchorRelayer::new(endpoint, api_key))
         }
         _ => Arc::new(NoopAnchorRelayer),
+    };
+    let batch_size = env::var("ANCHOR_BATCH_SIZE")
+        .ok()
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .unwrap_or(1);
+    if batch_size <= 1 {
+        return relayer;
     }
+    let interval = env::var("ANCHOR_BATCH_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .unwrap_or(DEFAULT_BATCH_INTERVAL_SECS);
+    Arc::new(BatchingAnchorRelayer::new(
+        relayer,
+        batch_size,
+        Duration::from_secs(interval),
+    ))
+}
+
+/// Accumulates payloads and anchors them as one Merkle root.
+///
+/// A batch is flushed once it holds `max_batch` payloads, or `max_delay`
+/// after its first payload arrived, whichever comes first. Every `submit`
+/// waits for its batch and gets the inner relayer's receipt plus the Merkle
+/// proof for its own hash (see [`verify_merkle_proof`]).
+#[derive(Clone)]
+pub struct BatchingAnchorRelayer {
+    state: Arc<BatchState>,
+}
+
+struct BatchState {
+    inner: SharedAnchorRelayer,
+    max_batch: usize,
+    max_delay: Duration,
+    pending: Mutex<Vec<PendingAnchor>>,
+}
+
+struct PendingAnchor {
+    hash: String,
+    leaf: [u8; 32],
+    reply: oneshot::Sender<Result<AnchorReceipt, AnchorError>>,
+}
+
+impl BatchingAnchorRelayer {
+    pub fn new(inner: SharedAnchorRelayer, max_batch: usize, max_delay: Duration) -> Self {
+        Self {
+            state: Arc::new(BatchState {
+                inner,
+                max_batch: max_batch.max(1),
+                max_delay,
+                pending: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Submit everything queued so far as one batch.
+    pub async fn flush(&self) {
+        self.state.flush().await;
+    }
+}
+
+impl BatchState {
+    async fn flush(&self) {
+        let batch = mem::take(&mut *self.pending.lock().expect("anchor batch lock"));
+        if batch.is_empty() {
+            return;
+        }
+        let leaves: Vec<[u8; 32]> = batch.iter().map(|pending| pending.leaf).collect();
+        let levels = merkle_levels(leaves);
+        let root = hex_encode(levels.last().expect("non-empty tree")[0]);
+        let result = self
+            .inner
+            .submit_batch(AnchorBatch {
+                batch_root: root.clone(),
+                hashes: batch.iter().map(|pending| pending.hash.clone()).collect(),
+            })
+            .await;
+
+        for (index, pending) in batch.into_iter().enumerate() {
+            let outcome = match &result {
+                Ok(receipt) => Ok(AnchorReceipt {
+                    transaction_id: receipt.transaction_id.clone(),
+                    merkle_proof: merkle_proof(&levels, index),
+                    batch_root: Some(root.clone()),
+                }),
+                Err(error) => Err(AnchorError::Relayer(error.to_string())),
+            };
+            let _ = pending.reply.send(outcome);
+        }
+    }
+}
+
+#[async_trait]
+impl AnchorRelayer for BatchingAnchorRelayer {
+    async fn submit(&self, payload: AnchorPayload) -> Result<AnchorReceipt, AnchorError> {
+        let leaf: [u8; 32] = hex::decode(&payload.hash)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| AnchorError::Relayer("manifest hash is not SHA-256 hex".into()))?;
+        let (reply, receipt) = oneshot::channel();
+        let queued = {
+            let mut pending = self.state.pending.lock().expect("anchor batch lock");
+            pending.push(PendingAnchor {
+                hash: payload.hash,
+                leaf,
+                reply,
+            });
+            pending.len()
+        };
+
+        if queued >= self.state.max_batch {
+            self.state.flush().await;
+        } else if queued == 1 {
+            let state = self.state.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(state.max_delay).await;
+                state.flush().await;
+            });
+        }
+
+        receipt
+            .await
+            .map_err(|_| AnchorError::Relayer("anchor batch was dropped".into()))?
+    }
+
+    async fn submit_batch(&self, batch: AnchorBatch) -> Result<AnchorReceipt, AnchorError> {
+        self.state.inner.submit_batch(batch).await
+    }
+}
+
+/// Parent of two nodes. Pairs are hashed in sorted order, so proofs need no
+/// left/right flags (the OpenZeppelin `MerkleProof` convention).
+fn merkle_parent(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (low, high) = if a <= b { (a, b) } else { (b, a) };
+    let mut hasher = Sha256::new();
+    hasher.update(low);
+    hasher.update(high);
+    hasher.finalize().into()
+}
+
+/// Every level of the tree, leaves first and the root last. An odd node is
+/// carried up to the next level unchanged.
+fn merkle_levels(leaves: Vec<[u8; 32]>) -> Vec<Vec<[u8; 32]>> {
+    let mut levels = vec![leaves];
+    while levels.last().expect("levels").len() > 1 {
+        let next = levels
+            .last()
+            .expect("levels")
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => merkle_parent(a, b),
+                [a] => *a,
+                _ => unreachable!("chunks(2)"),
+            })
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+fn merkle_proof(levels: &[Vec<[u8; 32]>], mut index: usize) -> Vec<String> {
+    let mut proof = Vec::new();
+    for level in &levels[..levels.len() - 1] {
+        if let Some(sibling) = level.get(index ^ 1) {
+            proof.push(hex_encode(sibling));
+        }
+        index /= 2;
+    }
+    proof
+}
+
+/// Whether `proof` (from [`AnchorReceipt::merkle_proof`]) links the hex
+/// manifest hash `leaf` to the hex `root`.
+pub fn verify_merkle_proof(leaf: &str, proof: &[String], root: &str) -> bool {
+    let decode = |hex_str: &str| -> Option<[u8; 32]> { hex::decode(hex_str).ok()?.try_into().ok() };
+    let Some(mut node) = decode(leaf) else {
+        return false;
+    };
+    for sibling in proof {
+        let Some(sibling) = decode(sibling) else {
+            return false;
+        };
+        node = merkle_parent(&node, &sibling);
+    }
+    decode(root) == Some(node)
 }
 
 #[derive(Clone)]
@@ -193,11 +463,31 @@ impl AnchorRelayer for HttpAnchorRelayer {
             .await
             .map_err(|error| AnchorError::Relayer(error.to_string()))
     }
+
+    async fn submit_batch(&self, batch: AnchorBatch) -> Result<AnchorReceipt, AnchorError> {
+        let mut request = self.client.post(&self.endpoint).json(&batch);
+        if let Some(token) = &self.api_key {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|error| AnchorError::Relayer(error.to_string()))?
+            .error_for_status()
+            .map_err(|error| AnchorError::Relayer(error.to_string()))?;
+
+        response
+            .json::<AnchorReceipt>()
+            .await
+            .map_err(|error| AnchorError::Relayer(error.to_string()))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::TotpAlgorithm;
 
     fn make_manifest(created_at: i64, expires_at: Option<i64>) -> AnchorManifest {
         AnchorManifest {
@@ -206,7 +496,8 @@ mod tests {
             created_at,
             expires_at,
             burn_after_reading: false,
-            content: StoredContent::Plain { text: "x".into() },
+            content: Some(StoredContent::Plain { text: "x".into() }),
+            content_digest: None,
             metadata: PasteMetadata::default(),
         }
     }
@@ -222,6 +513,7 @@ mod tests {
             created_at: 42,
             expires_at: Some(84),
             burn_after_reading: false,
+            max_reads: None,
             bundle: metadata.bundle.clone(),
             bundle_parent: metadata.bundle_parent.clone(),
             bundle_label: metadata.bundle_label.clone(),
@@ -242,6 +534,26 @@ mod tests {
 
         let same_hash = manifest_hash(&manifest).expect("hash");
         assert_eq!(hash, same_hash);
+
+        let digest_only =
+            AnchorManifest::from_paste_with_mode("abc123", &paste, AnchorContentMode::DigestOnly);
+        let digest_hash = manifest_hash(&digest_only).expect("hash");
+        assert_ne!(digest_hash, hash);
+        assert_eq!(
+            digest_hash,
+            manifest_hash(&AnchorManifest::from_paste_with_mode(
+                "abc123",
+                &paste,
+                AnchorContentMode::DigestOnly
+            ))
+            .expect("hash")
+        );
+
+        let payload = AnchorPayload::new(digest_only, digest_hash, None, None);
+        let serialized = serde_json::to_string(&payload).expect("payload json");
+        assert!(!serialized.contains("hello world"));
+        assert!(!serialized.contains("\"content\""));
+        assert!(serialized.contains("\"contentDigest\""));
     }
 
     #[test]
@@ -311,6 +623,7 @@ mod tests {
                 step: 30,
                 allowed_drift: 1,
                 issuer: Some("Acme Corp".into()),
+                algorithm: TotpAlgorithm::Sha1,
             }),
             ..Default::default()
         };
@@ -326,6 +639,7 @@ mod tests {
                 step: 30,
                 allowed_drift: 1,
                 issuer: None,
+                algorithm: TotpAlgorithm::Sha1,
             }),
             ..Default::default()
         };
@@ -358,6 +672,102 @@ mod tests {
         assert!(receipt.transaction_id.is_none());
     }
 
+    #[derive(Default)]
+    struct RecordingRelayer {
+        batches: Mutex<Vec<AnchorBatch>>,
+    }
+
+    #[async_trait]
+    impl AnchorRelayer for RecordingRelayer {
+        async fn submit(&self, _payload: AnchorPayload) -> Result<AnchorReceipt, AnchorError> {
+            panic!("batched payloads must not be submitted one by one");
+        }
+
+        async fn submit_batch(&self, batch: AnchorBatch) -> Result<AnchorReceipt, AnchorError> {
+            self.batches.lock().unwrap().push(batch);
+            Ok(AnchorReceipt {
+                transaction_id: Some("0xbatch".into()),
+                ..Default::default()
+            })
+        }
+    }
+
+    fn payload_with_hash(n: u8) -> AnchorPayload {
+        let hash = hex_encode(Sha256::digest([n]));
+        AnchorPayload::new(make_manifest(n.into(), None), hash, None, None)
+    }
+
+    async fn submit_all(
+        relayer: &BatchingAnchorRelayer,
+        count: u8,
+    ) -> Vec<(String, AnchorReceipt)> {
+        let handles: Vec<_> = (0..count)
+            .map(|n| {
+                let relayer = relayer.clone();
+                let payload = payload_with_hash(n);
+                let hash = payload.hash.clone();
+                tokio::spawn(async move { (hash, relayer.submit(payload).await) })
+            })
+            .collect();
+        let mut receipts = Vec::new();
+        for handle in handles {
+            let (hash, receipt) = handle.await.unwrap();
+            receipts.push((hash, receipt.expect("receipt")));
+        }
+        receipts
+    }
+
+    #[tokio::test]
+    async fn batching_relayer_submits_one_root_with_verifiable_proofs() {
+        let inner = Arc::new(RecordingRelayer::default());
+        let relayer = BatchingAnchorRelayer::new(inner.clone(), 7, Duration::from_secs(60));
+
+        let receipts = submit_all(&relayer, 7).await;
+
+        let batches = inner.batches.lock().unwrap().clone();
+        assert_eq!(batches.len(), 1);
+        let root = &batches[0].batch_root;
+        assert_eq!(batches[0].hashes.len(), 7);
+        for (hash, receipt) in &receipts {
+            assert_eq!(receipt.batch_root.as_ref(), Some(root));
+            assert_eq!(receipt.transaction_id.as_deref(), Some("0xbatch"));
+            assert!(!receipt.merkle_proof.is_empty());
+            assert!(verify_merkle_proof(hash, &receipt.merkle_proof, root));
+        }
+        let (other_hash, _) = &receipts[1];
+        assert!(!verify_merkle_proof(
+            other_hash,
+            &receipts[0].1.merkle_proof,
+            root
+        ));
+    }
+
+    #[tokio::test]
+    async fn batching_relayer_flushes_partial_batch_after_delay() {
+        let inner = Arc::new(RecordingRelayer::default());
+        let relayer = BatchingAnchorRelayer::new(inner.clone(), 100, Duration::from_millis(20));
+
+        let receipts = submit_all(&relayer, 3).await;
+
+        let batches = inner.batches.lock().unwrap().clone();
+        assert_eq!(batches.len(), 1);
+        for (hash, receipt) in &receipts {
+            assert!(verify_merkle_proof(
+                hash,
+                &receipt.merkle_proof,
+                &batches[0].batch_root
+            ));
+        }
+    }
+
+    #[test]
+    fn single_leaf_tree_is_its_own_root() {
+        let leaf = hex_encode(Sha256::digest(b"only"));
+        let levels = merkle_levels(vec![hex::decode(&leaf).unwrap().try_into().unwrap()]);
+        assert!(merkle_proof(&levels, 0).is_empty());
+        assert!(verify_merkle_proof(&leaf, &[], &leaf));
+    }
+
     #[test]
     fn default_anchor_relayer_without_endpoint_is_noop() {
         std::env::remove_var("ANCHOR_RELAY_ENDPOINT");
@@ -1,5 +1,12 @@
-use crate::{BundleMetadata, PasteError, SharedPasteStore};
+use std::collections::HashSet;
+use std::io::{Cursor, Write};
 
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::{BundleMetadata, PasteError, PasteFormat, SharedPasteStore};
+
+use super::crypto::{decrypt_content, DecryptError, PasteBinding};
 use super::models::PasteViewQuery;
 
 pub async fn build_bundle_overview(
@@ -20,8 +27,24 @@ pub async fn build_bundle_overview(
             label.to_string()
         };
 
-        let status = match store.get_paste(&child.id).await {
-            Ok(_) => ("available", "Available"),
+        // `peek_paste` has no side effects, so rendering the overview
+        // neither evicts an expired child nor caches a persisted one.
+        let status = match store.peek_paste(&child.id).await {
+            Ok(paste) => match decrypt_content(
+                &paste.content,
+                query.key.as_deref(),
+                PasteBinding::new(&child.id, paste.format),
+            )
+            .await
+            {
+                Ok(_) => ("available", "Available"),
+                Err(DecryptError::MissingKey) => ("locked", "Locked (key required)"),
+                // Like the read routes, corrupted shares don't reveal that
+                // the key itself was right.
+                Err(DecryptError::InvalidKey | DecryptError::Corrupted) => {
+                    ("locked", "Locked (different key)")
+                }
+            },
             Err(PasteError::Expired(_)) => ("expired", "Expired"),
             Err(PasteError::NotFound(_)) => ("consumed", "Consumed"),
         };
@@ -63,3 +86,78 @@ fn build_child_url(child_id: &str, query: &PasteViewQuery) -> String {
         format!("/{child_id}")
     }
 }
+
+/// A bundle being packed for `GET /api/bundles/<id>/zip`. Every file gets a
+/// unique, filesystem-safe name, and `MANIFEST.txt` records what was packed
+/// and which shares were skipped.
+pub struct BundleArchive {
+    files: Vec<(String, String)>,
+    names: HashSet<String>,
+    manifest: String,
+}
+
+impl BundleArchive {
+    pub fn new(parent_id: &str) -> Self {
+        Self {
+            files: Vec::new(),
+            names: HashSet::new(),
+            manifest: format!("Bundle {parent_id}\n\n"),
+        }
+    }
+
+    /// Add a decrypted paste as `<stem>.<ext>`, returning the entry name.
+    pub fn add(&mut self, label: &str, stem: &str, format: PasteFormat, text: String) -> String {
+        let name = self.unique_name(&safe_stem(stem), format.file_extension());
+        self.manifest.push_str(&format!("{label}: {name}\n"));
+        self.files.push((name.clone(), text));
+        name
+    }
+
+    /// Note a share that could not be packed.
+    pub fn skip(&mut self, label: &str, reason: &str) {
+        self.manifest
+            .push_str(&format!("{label}: skipped ({reason})\n"));
+    }
+
+    pub fn finish(self) -> zip::result::ZipResult<Vec<u8>> {
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, text) in &self.files {
+            writer.start_file(name.as_str(), options)?;
+            writer.write_all(text.as_bytes())?;
+        }
+        writer.start_file("MANIFEST.txt", options)?;
+        writer.write_all(self.manifest.as_bytes())?;
+        Ok(writer.finish()?.into_inner())
+    }
+
+    fn unique_name(&mut self, stem: &str, extension: &str) -> String {
+        let mut name = format!("{stem}.{extension}");
+        let mut n = 2;
+        while name == "MANIFEST.txt" || !self.names.insert(name.clone()) {
+            name = format!("{stem}-{n}.{extension}");
+            n += 1;
+        }
+        name
+    }
+}
+
+/// `stem` reduced to characters that are safe in a file name.
+fn safe_stem(stem: &str) -> String {
+    let cleaned: String = stem
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if cleaned.is_empty() {
+        "share".to_string()
+    } else {
+        cleaned
+    }
+}
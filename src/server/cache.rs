@@ -0,0 +1,166 @@
+//! Conditional GET support for paste views.
+//!
+//! A paste without a read limit, time lock, bundle or live updates never
+//! changes after creation, so `show` and `show_raw` tag it with a strong
+//! `ETag` and answer a matching `If-None-Match` with `304 Not Modified`
+//! instead of re-rendering. Such responses carry
+//! `Cache-Control: private, no-cache`: the paste may still be deleted or
+//! expire, and may sit behind a key or attestation, so every reuse is
+//! revalidated and shared caches never hold a copy. Everything else —
+//! burn-after-reading, read-limited and time-locked pastes, and the prompt
+//! pages in front of them — is sent with `Cache-Control: no-store`.
+
+use std::convert::Infallible;
+
+use rocket::http::{Header, Status};
+use rocket::request::{FromRequest, Outcome};
+use rocket::response::{self, Responder};
+use rocket::{Request, Response};
+use sha2::{Digest, Sha256};
+
+use crate::StoredPaste;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CachePolicy {
+    /// Immutable paste, tagged with this quoted `ETag`.
+    Revalidate(String),
+    NoStore,
+}
+
+impl CachePolicy {
+    /// Policy for serving `paste` as `representation` (`"html"`, `"raw"`,
+    /// ...), where `digest` is the hex SHA-256 of the content being served.
+    pub fn for_paste(id: &str, paste: &StoredPaste, digest: &str, representation: &str) -> Self {
+        if !is_immutable(paste) {
+            return CachePolicy::NoStore;
+        }
+        let mut hasher = Sha256::new();
+        for part in [id, digest, &paste.format.to_string(), representation] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        let tag = hex::encode(&hasher.finalize()[..16]);
+        CachePolicy::Revalidate(format!("\"{tag}\""))
+    }
+
+    pub fn etag(&self) -> Option<&str> {
+        match self {
+            CachePolicy::Revalidate(etag) => Some(etag),
+            CachePolicy::NoStore => None,
+        }
+    }
+}
+
+/// Whether every view of `paste` is guaranteed to render the same bytes.
+fn is_immutable(paste: &StoredPaste) -> bool {
+    paste.read_limit().is_none()
+        && paste.metadata.not_before.is_none()
+        && paste.metadata.not_after.is_none()
+        && paste.metadata.bundle.is_none()
+        && !paste.is_live
+}
+
+/// The request's `If-None-Match` header, if any.
+pub struct IfNoneMatch(Option<String>);
+
+impl IfNoneMatch {
+    pub fn new(value: Option<&str>) -> Self {
+        Self(value.map(str::to_string))
+    }
+
+    /// Whether the client already holds the representation tagged by
+    /// `policy`. Uses the weak comparison RFC 9110 prescribes for
+    /// `If-None-Match`, so `W/"tag"` matches `"tag"`.
+    pub fn matches(&self, policy: &CachePolicy) -> bool {
+        let (Some(header), Some(etag)) = (self.0.as_deref(), policy.etag()) else {
+            return false;
+        };
+        header.split(',').map(str::trim).any(|candidate| {
+            candidate == "*" || candidate.strip_prefix("W/").unwrap_or(candidate) == etag
+        })
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for IfNoneMatch {
+    type Error = Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(IfNoneMatch::new(request.headers().get_one("If-None-Match")))
+    }
+}
+
+/// A paste view response with its caching headers; `body: None` is a
+/// `304 Not Modified`.
+pub struct Cached<R> {
+    body: Option<R>,
+    policy: CachePolicy,
+    status: Option<Status>,
+}
+
+impl<R> Cached<R> {
+    pub fn new(body: R, policy: CachePolicy) -> Self {
+        Self {
+            body: Some(body),
+            policy,
+            status: None,
+        }
+    }
+
+    /// Send the body with `status` instead of the one it would pick.
+    pub fn with_status(mut self, status: Status) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    pub fn no_store(body: R) -> Self {
+        Self::new(body, CachePolicy::NoStore)
+    }
+
+    pub fn not_modified(policy: CachePolicy) -> Self {
+        Self {
+            body: None,
+            policy,
+            status: None,
+        }
+    }
+}
+
+impl<'r, 'o: 'r, R: Responder<'r, 'o>> Responder<'r, 'o> for Cached<R> {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'o> {
+        let mut response = match self.body {
+            Some(body) => body.respond_to(request)?,
+            None => Response::build().status(Status::NotModified).finalize(),
+        };
+        if let Some(status) = self.status {
+            response.set_status(status);
+        }
+        match self.policy {
+            CachePolicy::Revalidate(etag) => {
+                response.set_header(Header::new("ETag", etag));
+                response.set_header(Header::new("Cache-Control", "private, no-cache"));
+            }
+            CachePolicy::NoStore => {
+                response.set_header(Header::new("Cache-Control", "no-store"));
+            }
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn if_none_match_uses_weak_comparison_and_lists() {
+        let policy = CachePolicy::Revalidate("\"abc\"".to_string());
+        assert!(IfNoneMatch::new(Some("\"abc\"")).matches(&policy));
+        assert!(IfNoneMatch::new(Some("W/\"abc\"")).matches(&policy));
+        assert!(IfNoneMatch::new(Some("\"x\", \"abc\"")).matches(&policy));
+        assert!(IfNoneMatch::new(Some("*")).matches(&policy));
+        assert!(!IfNoneMatch::new(Some("\"abcd\"")).matches(&policy));
+        assert!(!IfNoneMatch::new(None).matches(&policy));
+        assert!(!IfNoneMatch::new(Some("*")).matches(&CachePolicy::NoStore));
+    }
+}
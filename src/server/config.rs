@@ -3,6 +3,8 @@ use std::path::PathBuf;
 use serde::Deserialize;
 use thiserror::Error;
 
+use super::access_log::AccessLogFormat;
+
 #[derive(Debug, Error)]
 pub enum ConfigError {
     #[error("Failed to read config file '{path}': {source}")]
@@ -28,6 +30,9 @@ pub struct Config {
     pub retention: RetentionConfig,
     pub rate_limit: RateLimitConfig,
     pub logging: LoggingConfig,
+    pub cors: CorsConfig,
+    pub tor: TorSection,
+    pub webhook: WebhookDefaults,
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -73,6 +78,29 @@ pub struct LoggingConfig {
     pub level: String,
 }
 
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct CorsConfig {
+    /// Allowed origins; empty allows any origin.
+    pub origins: Vec<String>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct TorSection {
+    pub onion_host: Option<String>,
+    pub suppress_logs: bool,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct WebhookDefaults {
+    /// Signing secret for webhooks that do not set their own.
+    pub secret: Option<String>,
+    pub retries: u32,
+    pub retry_base_ms: u64,
+}
+
 // — Defaults ————————————————————————————————————————————
 
 impl Default for ServerConfig {
@@ -113,6 +141,25 @@ impl Default for RateLimitConfig {
     }
 }
 
+impl Default for TorSection {
+    fn default() -> Self {
+        TorSection {
+            onion_host: None,
+            suppress_logs: true,
+        }
+    }
+}
+
+impl Default for WebhookDefaults {
+    fn default() -> Self {
+        WebhookDefaults {
+            secret: None,
+            retries: 3,
+            retry_base_ms: 1000,
+        }
+    }
+}
+
 impl Default for LoggingConfig {
     fn default() -> Self {
         LoggingConfig {
@@ -143,6 +190,14 @@ impl Config {
         Ok(config)
     }
 
+    /// Built-in defaults with env var overrides and no config file, for
+    /// callers that never went through [`Config::load`].
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        config.apply_env_overrides();
+        config
+    }
+
     fn find_config_file(explicit_path: Option<&str>) -> Option<PathBuf> {
         // 1. Explicit --config flag
         if let Some(p) = explicit_path {
@@ -221,6 +276,34 @@ impl Config {
         if let Ok(v) = std::env::var("COPYPASTE_LOG_LEVEL") {
             self.logging.level = v;
         }
+        if let Ok(v) = std::env::var("COPYPASTE_CORS_ORIGINS") {
+            self.cors.origins = v
+                .split(',')
+                .map(str::trim)
+                .filter(|origin| !origin.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        if let Ok(v) = std::env::var("COPYPASTE_ONION_HOST") {
+            let host = v.trim();
+            self.tor.onion_host = (!host.is_empty()).then(|| host.to_string());
+        }
+        if let Ok(v) = std::env::var("COPYPASTE_TOR_SUPPRESS_LOGS") {
+            self.tor.suppress_logs = !matches!(v.trim(), "0" | "false" | "off");
+        }
+        if let Ok(v) = std::env::var("COPYPASTE_WEBHOOK_SECRET") {
+            self.webhook.secret = Some(v).filter(|s| !s.is_empty());
+        }
+        if let Ok(v) = std::env::var("COPYPASTE_WEBHOOK_RETRIES") {
+            if let Ok(n) = v.trim().parse() {
+                self.webhook.retries = n;
+            }
+        }
+        if let Ok(v) = std::env::var("COPYPASTE_WEBHOOK_RETRY_BASE_MS") {
+            if let Ok(n) = v.trim().parse() {
+                self.webhook.retry_base_ms = n;
+            }
+        }
     }
 
     fn validate(&self) -> Result<(), ConfigError> {
@@ -241,10 +324,15 @@ impl Config {
                 self.retention.max
             )));
         }
-        let valid_formats = ["json", "pretty"];
-        if !valid_formats.contains(&self.logging.format.as_str()) {
+        if parse_size_bytes(&self.server.max_paste_size).is_none_or(|n| n == 0) {
+            return Err(ConfigError::ValidationError(format!(
+                "server.max_paste_size must be a size like '512kb' or '10mb', got '{}'",
+                self.server.max_paste_size
+            )));
+        }
+        if AccessLogFormat::parse(&self.logging.format).is_none() {
             return Err(ConfigError::ValidationError(format!(
-                "logging.format must be 'json' or 'pretty', got '{}'",
+                "logging.format must be 'json' or 'text', got '{}'",
                 self.logging.format
             )));
         }
@@ -255,6 +343,40 @@ impl Config {
                 valid_levels, self.logging.level
             )));
         }
+        if let Some(url) = &self.storage.url {
+            if !is_http_url(url) && !url.starts_with("redis://") && !url.starts_with("rediss://") {
+                return Err(ConfigError::ValidationError(format!(
+                    "storage.url must be an http(s) or redis URL, got '{url}'"
+                )));
+            }
+        }
+        for origin in &self.cors.origins {
+            let parsed = url::Url::parse(origin).ok();
+            let bare_origin = parsed.as_ref().is_some_and(|url| {
+                matches!(url.scheme(), "http" | "https")
+                    && url.host_str().is_some()
+                    && url.path() == "/"
+                    && url.query().is_none()
+            });
+            if !bare_origin {
+                return Err(ConfigError::ValidationError(format!(
+                    "cors.origins entries must look like 'https://app.example.com', got '{origin}'"
+                )));
+            }
+        }
+        if let Some(host) = &self.tor.onion_host {
+            if !host.trim().to_ascii_lowercase().ends_with(".onion") {
+                return Err(ConfigError::ValidationError(format!(
+                    "tor.onion_host must be a .onion hostname, got '{host}'"
+                )));
+            }
+        }
+        if self.webhook.retries > 10 {
+            return Err(ConfigError::ValidationError(format!(
+                "webhook.retries must be at most 10, got {}",
+                self.webhook.retries
+            )));
+        }
         Ok(())
     }
 
@@ -311,6 +433,10 @@ impl Config {
                 }
             }
         }
+        // Paste size cap consumed by paste creation and Rocket's body limits.
+        if std::env::var("COPYPASTE_MAX_PASTE_SIZE").is_err() {
+            std::env::set_var("COPYPASTE_MAX_PASTE_SIZE", &self.server.max_paste_size);
+        }
         // Rate-limit knobs consumed by rate_limit::PasteRateLimiter::from_env.
         if std::env::var("COPYPASTE_RATE_LIMIT_CREATES").is_err()
             && self.rate_limit.creates_per_minute > 0
@@ -328,37 +454,66 @@ impl Config {
                 self.rate_limit.reads_per_minute.to_string(),
             );
         }
+        // Webhook defaults consumed at delivery time (`webhook::trigger_webhook`).
+        // CORS and Tor are passed to `handlers::build_rocket_with_config` instead.
+        if std::env::var("COPYPASTE_WEBHOOK_SECRET").is_err() {
+            if let Some(secret) = &self.webhook.secret {
+                std::env::set_var("COPYPASTE_WEBHOOK_SECRET", secret);
+            }
+        }
+        if std::env::var("COPYPASTE_WEBHOOK_RETRIES").is_err() {
+            std::env::set_var(
+                "COPYPASTE_WEBHOOK_RETRIES",
+                self.webhook.retries.to_string(),
+            );
+        }
+        if std::env::var("COPYPASTE_WEBHOOK_RETRY_BASE_MS").is_err() {
+            std::env::set_var(
+                "COPYPASTE_WEBHOOK_RETRY_BASE_MS",
+                self.webhook.retry_base_ms.to_string(),
+            );
+        }
     }
 }
 
+fn is_http_url(input: &str) -> bool {
+    url::Url::parse(input)
+        .is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.host_str().is_some())
+}
+
 /// Parse a human-friendly duration string into minutes.
 ///
 /// Accepts a raw number of minutes (`"90"`) or a number with an `m`/`h`/`d`/`w`
 /// suffix (`"30m"`, `"24h"`, `"30d"`, `"2w"`).
 pub fn parse_duration_minutes(input: &str) -> Option<u64> {
+    super::time::parse_duration(input)
+        .ok()
+        .map(|minutes| minutes as u64)
+}
+
+/// Parse a human-friendly byte size.
+///
+/// Accepts a raw number of bytes (`"1048576"`) or a number with a `b`, `kb`,
+/// `mb` or `gb` suffix (`"512kb"`, `"10mb"`), case-insensitive. Units are
+/// binary: `1kb` is 1024 bytes.
+pub fn parse_size_bytes(input: &str) -> Option<u64> {
     let s = input.trim().to_ascii_lowercase();
-    if s.is_empty() {
-        return None;
-    }
-    if let Ok(minutes) = s.parse::<u64>() {
-        return Some(minutes);
-    }
-    let (value, multiplier) = if let Some(rest) = s.strip_suffix('m') {
-        (rest, 1u64)
-    } else if let Some(rest) = s.strip_suffix('h') {
-        (rest, 60)
-    } else if let Some(rest) = s.strip_suffix('d') {
-        (rest, 60 * 24)
-    } else if let Some(rest) = s.strip_suffix('w') {
-        (rest, 60 * 24 * 7)
+    let (value, multiplier) = if let Some(rest) = s.strip_suffix("kb") {
+        (rest, 1u64 << 10)
+    } else if let Some(rest) = s.strip_suffix("mb") {
+        (rest, 1 << 20)
+    } else if let Some(rest) = s.strip_suffix("gb") {
+        (rest, 1 << 30)
+    } else if let Some(rest) = s.strip_suffix('b') {
+        (rest, 1)
     } else {
-        return None;
+        (s.as_str(), 1)
     };
-    value
-        .trim()
-        .parse::<u64>()
-        .ok()
-        .and_then(|n| n.checked_mul(multiplier))
+    let value = value.trim();
+    if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    value.parse::<u64>().ok()?.checked_mul(multiplier)
 }
 
 // — Example config ——————————————————————————————————————
@@ -393,8 +548,20 @@ creates_per_minute = 60     # COPYPASTE_RATE_LIMIT_CREATES
 reads_per_minute = 300      # COPYPASTE_RATE_LIMIT_READS
 
 [logging]
-format = "json"             # COPYPASTE_LOG_FORMAT — "json" or "pretty"
+format = "json"             # COPYPASTE_LOG_FORMAT — "json" or "text" (access log lines)
 level = "info"              # COPYPASTE_LOG_LEVEL  — error | warn | info | debug | trace
+
+[cors]
+origins = []                # COPYPASTE_CORS_ORIGINS — comma-separated; empty allows any origin
+
+[tor]
+# onion_host = "example.onion"  # COPYPASTE_ONION_HOST
+suppress_logs = true        # COPYPASTE_TOR_SUPPRESS_LOGS
+
+[webhook]
+# secret = ""               # COPYPASTE_WEBHOOK_SECRET — default signing secret
+retries = 3                 # COPYPASTE_WEBHOOK_RETRIES (max 10)
+retry_base_ms = 1000        # COPYPASTE_WEBHOOK_RETRY_BASE_MS
 "#;
 
 // — Tests ———————————————————————————————————————————————
@@ -500,6 +667,104 @@ address = "127.0.0.1"
         assert!(msg.contains("port"), "error should mention port: {msg}");
     }
 
+    #[test]
+    fn load_full_sample_toml() {
+        let _lock = ENV_LOCK.lock().unwrap();
+        for var in [
+            "COPYPASTE_CORS_ORIGINS",
+            "COPYPASTE_ONION_HOST",
+            "COPYPASTE_TOR_SUPPRESS_LOGS",
+            "COPYPASTE_WEBHOOK_SECRET",
+            "COPYPASTE_WEBHOOK_RETRIES",
+            "COPYPASTE_WEBHOOK_RETRY_BASE_MS",
+            "COPYPASTE_RETENTION_DEFAULT",
+        ] {
+            std::env::remove_var(var);
+        }
+        let path = write_temp_config(
+            r#"
+[storage]
+backend = "redis"
+url = "https://upstash.example.com"
+
+[retention]
+default = "1h"
+max = "7d"
+
+[rate_limit]
+creates_per_minute = 10
+
+[cors]
+origins = ["https://app.example.com", "http://localhost:5173"]
+
+[tor]
+onion_host = "pasteabc.onion"
+suppress_logs = false
+
+[webhook]
+secret = "shh"
+retries = 5
+retry_base_ms = 250
+"#,
+        );
+        let config = Config::load(Some(path.to_str().unwrap())).expect("load");
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(config.storage.backend, "redis");
+        assert_eq!(parse_duration_minutes(&config.retention.default), Some(60));
+        assert_eq!(config.rate_limit.creates_per_minute, 10);
+        assert_eq!(config.rate_limit.reads_per_minute, 300);
+        assert_eq!(
+            config.cors.origins,
+            ["https://app.example.com", "http://localhost:5173"]
+        );
+        assert_eq!(config.tor.onion_host.as_deref(), Some("pasteabc.onion"));
+        assert!(!config.tor.suppress_logs);
+        assert_eq!(config.webhook.secret.as_deref(), Some("shh"));
+        assert_eq!(config.webhook.retries, 5);
+        assert_eq!(config.webhook.retry_base_ms, 250);
+    }
+
+    #[test]
+    fn example_config_is_valid() {
+        let config: Config = toml::from_str(EXAMPLE_CONFIG).expect("example parses");
+        config.validate().expect("example validates");
+    }
+
+    #[test]
+    fn validation_rejects_bad_default_duration() {
+        let _lock = ENV_LOCK.lock().unwrap();
+        std::env::remove_var("COPYPASTE_RETENTION_DEFAULT");
+        let path = write_temp_config("[retention]\ndefault = \"3 fortnights\"\n");
+        let result = Config::load(Some(path.to_str().unwrap()));
+        let _ = std::fs::remove_file(&path);
+
+        let msg = result.unwrap_err().to_string();
+        assert!(
+            msg.contains("retention.default"),
+            "error should mention field: {msg}"
+        );
+    }
+
+    #[test]
+    fn validation_rejects_malformed_urls() {
+        let mut config = Config::default();
+        config.cors.origins = vec!["https://app.example.com/path".into()];
+        assert!(config.validate().unwrap_err().to_string().contains("cors"));
+
+        let mut config = Config::default();
+        config.storage.url = Some("upstash.example.com".into());
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("storage.url"));
+
+        let mut config = Config::default();
+        config.tor.onion_host = Some("example.com".into());
+        assert!(config.validate().unwrap_err().to_string().contains("tor"));
+    }
+
     #[test]
     fn parse_duration_minutes_accepts_supported_units() {
         assert_eq!(parse_duration_minutes("90"), Some(90));
@@ -518,6 +783,19 @@ address = "127.0.0.1"
         assert_eq!(parse_duration_minutes("-5m"), None);
     }
 
+    #[test]
+    fn parse_size_bytes_accepts_binary_units() {
+        assert_eq!(parse_size_bytes("1048576"), Some(1 << 20));
+        assert_eq!(parse_size_bytes("100b"), Some(100));
+        assert_eq!(parse_size_bytes("512kb"), Some(512 * 1024));
+        assert_eq!(parse_size_bytes(" 10MB "), Some(10 << 20));
+        assert_eq!(parse_size_bytes("1gb"), Some(1 << 30));
+        assert_eq!(parse_size_bytes(""), None);
+        assert_eq!(parse_size_bytes("mb"), None);
+        assert_eq!(parse_size_bytes("1.5mb"), None);
+        assert_eq!(parse_size_bytes("10tb"), None);
+    }
+
     #[test]
     fn validation_rejects_unparsable_retention() {
         let _lock = ENV_LOCK.lock().unwrap();
@@ -587,6 +865,8 @@ address = "127.0.0.1"
             "COPYPASTE_RATE_LIMIT_READS",
             "REDIS_URL",
             "UPSTASH_REDIS_REST_URL",
+            "COPYPASTE_WEBHOOK_RETRIES",
+            "COPYPASTE_WEBHOOK_RETRY_BASE_MS",
         ] {
             std::env::remove_var(var);
         }
@@ -611,6 +891,8 @@ address = "127.0.0.1"
         std::env::remove_var("COPYPASTE_RETENTION_DEFAULT_MINUTES");
         std::env::remove_var("COPYPASTE_RATE_LIMIT_CREATES");
         std::env::remove_var("COPYPASTE_RATE_LIMIT_READS");
+        std::env::remove_var("COPYPASTE_WEBHOOK_RETRIES");
+        std::env::remove_var("COPYPASTE_WEBHOOK_RETRY_BASE_MS");
     }
 
     #[test]
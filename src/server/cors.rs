@@ -1,3 +1,12 @@
+//! CORS headers for the REST API.
+//!
+//! With `COPYPASTE_CORS_ORIGINS` unset every origin is allowed via
+//! `Access-Control-Allow-Origin: *`. Setting it to a comma-separated list of
+//! origins (`https://app.example.com,https://intranet.example.com`) switches
+//! to an allowlist: a listed request `Origin` is echoed back together with
+//! `Access-Control-Allow-Credentials: true`, and any other origin gets no
+//! allow-origin header at all.
+
 use rocket::fairing::{Fairing, Info, Kind};
 use rocket::http::{Header, Method, Status};
 use rocket::{options, Request, Response};
@@ -7,8 +16,41 @@ const ALLOWED_HEADERS: &str = "Content-Type,Authorization,X-Requested-With,X-Pas
 const EXPOSED_HEADERS: &str = "Content-Type";
 const MAX_AGE_SECONDS: &str = "86400";
 
-#[derive(Clone, Copy)]
-pub struct Cors;
+#[derive(Clone, Default)]
+pub struct Cors {
+    /// Allowed origins; empty means any origin (`*`).
+    origins: Vec<String>,
+}
+
+impl Cors {
+    pub fn new<I, S>(origins: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self {
+            origins: origins
+                .into_iter()
+                .map(|origin| origin.as_ref().trim().trim_end_matches('/').to_string())
+                .filter(|origin| !origin.is_empty())
+                .collect(),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let raw = std::env::var("COPYPASTE_CORS_ORIGINS").unwrap_or_default();
+        Self::new(raw.split(','))
+    }
+
+    /// The origin to echo back, if `origin` is on the allowlist. Scheme and
+    /// host compare case-insensitively.
+    fn allowed_origin<'a>(&self, origin: &'a str) -> Option<&'a str> {
+        self.origins
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(origin))
+            .then_some(origin)
+    }
+}
 
 #[rocket::async_trait]
 impl Fairing for Cors {
@@ -20,7 +62,22 @@ impl Fairing for Cors {
     }
 
     async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
-        response.set_header(Header::new("Access-Control-Allow-Origin", "*"));
+        if self.origins.is_empty() {
+            response.set_header(Header::new("Access-Control-Allow-Origin", "*"));
+        } else {
+            response.adjoin_header(Header::new("Vary", "Origin"));
+            if let Some(origin) = request
+                .headers()
+                .get_one("Origin")
+                .and_then(|origin| self.allowed_origin(origin))
+            {
+                response.set_header(Header::new(
+                    "Access-Control-Allow-Origin",
+                    origin.to_string(),
+                ));
+                response.set_header(Header::new("Access-Control-Allow-Credentials", "true"));
+            }
+        }
         response.set_header(Header::new("Access-Control-Allow-Methods", ALLOWED_METHODS));
         response.set_header(Header::new("Access-Control-Allow-Headers", ALLOWED_HEADERS));
         response.set_header(Header::new(
@@ -40,3 +97,83 @@ impl Fairing for Cors {
 pub fn api_preflight() -> Status {
     Status::NoContent
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::local::blocking::Client;
+
+    #[rocket::get("/api/ping")]
+    fn ping() -> &'static str {
+        "pong"
+    }
+
+    fn client(cors: Cors) -> Client {
+        let rocket = rocket::build()
+            .attach(cors)
+            .mount("/", rocket::routes![ping, api_preflight]);
+        Client::untracked(rocket).expect("client")
+    }
+
+    #[test]
+    fn wildcard_when_no_origins_configured() {
+        let client = client(Cors::default());
+        let response = client
+            .get("/api/ping")
+            .header(Header::new("Origin", "https://anywhere.example"))
+            .dispatch();
+        assert_eq!(
+            response.headers().get_one("Access-Control-Allow-Origin"),
+            Some("*")
+        );
+        assert!(response
+            .headers()
+            .get_one("Access-Control-Allow-Credentials")
+            .is_none());
+    }
+
+    #[test]
+    fn allowlisted_origin_is_echoed_with_credentials() {
+        let client = client(Cors::new([
+            "https://app.example.com/",
+            " https://b.example ",
+        ]));
+        for request in [client.get("/api/ping"), client.options("/api/pastes")] {
+            let response = request
+                .header(Header::new("Origin", "https://app.example.com"))
+                .dispatch();
+            let headers = response.headers();
+            assert_eq!(
+                headers.get_one("Access-Control-Allow-Origin"),
+                Some("https://app.example.com")
+            );
+            assert_eq!(
+                headers.get_one("Access-Control-Allow-Credentials"),
+                Some("true")
+            );
+            assert_eq!(headers.get_one("Vary"), Some("Origin"));
+        }
+    }
+
+    #[test]
+    fn unlisted_origin_gets_no_allow_origin_header() {
+        let client = client(Cors::new(["https://app.example.com"]));
+        let response = client
+            .get("/api/ping")
+            .header(Header::new("Origin", "https://evil.example"))
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let headers = response.headers();
+        assert!(headers.get_one("Access-Control-Allow-Origin").is_none());
+        assert!(headers
+            .get_one("Access-Control-Allow-Credentials")
+            .is_none());
+        assert_eq!(headers.get_one("Vary"), Some("Origin"));
+
+        let no_origin = client.get("/api/ping").dispatch();
+        assert!(no_origin
+            .headers()
+            .get_one("Access-Control-Allow-Origin")
+            .is_none());
+    }
+}
@@ -1,14 +1,16 @@
-use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::aead::{Aead, KeyInit, Payload};
 use aes_gcm::{Aes256Gcm, Nonce as AesNonce};
 use base64::engine::general_purpose;
 use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
 use base64::Engine;
 use chacha20poly1305::{ChaCha20Poly1305, Nonce as ChaNonce, XChaCha20Poly1305, XNonce};
+use flate2::read::DeflateDecoder;
 use rand::rngs::OsRng;
 use rand::RngCore;
 use serde::Serialize;
 use sha2::{Digest, Sha256};
 use std::convert::TryInto;
+use std::io::Read;
 use std::sync::Once;
 use zeroize::Zeroizing;
 
@@ -16,12 +18,79 @@ use hkdf::Hkdf;
 use ml_kem::kem::{Decapsulate, Encapsulate};
 use ml_kem::{Ciphertext, KemCore, MlKem768, B32};
 
-use crate::{EncryptionAlgorithm, StoredContent};
+use argon2::{Algorithm, Argon2, Params, Version};
+
+use crate::{
+    EncryptionAlgorithm, KdfKind, PasteFormat, StoredContent, ENVELOPE_V1, ENVELOPE_V2_DEFLATE,
+};
+
+use super::stego::extract_from_image_bytes;
 
 #[derive(Debug)]
 pub enum DecryptError {
     MissingKey,
     InvalidKey,
+    /// The key was accepted but the plaintext could not be decoded, e.g. an
+    /// [`ENVELOPE_V2_DEFLATE`] payload that is malformed or inflates past
+    /// `COPYPASTE_MAX_DECOMPRESSED_BYTES`.
+    Corrupted,
+}
+
+/// Decompression cap used when `COPYPASTE_MAX_DECOMPRESSED_BYTES` is unset.
+const DEFAULT_MAX_DECOMPRESSED_BYTES: u64 = 10_485_760; // 10 MB
+
+fn max_decompressed_bytes() -> u64 {
+    std::env::var("COPYPASTE_MAX_DECOMPRESSED_BYTES")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(DEFAULT_MAX_DECOMPRESSED_BYTES)
+}
+
+/// Inflate the plaintext of an [`ENVELOPE_V2_DEFLATE`] record through a
+/// reader capped at `max_bytes`, so a crafted blob can't exhaust memory.
+fn inflate_plaintext(compressed: &[u8], max_bytes: u64) -> Result<String, DecryptError> {
+    let mut inflated = Vec::new();
+    DeflateDecoder::new(compressed)
+        .take(max_bytes.saturating_add(1))
+        .read_to_end(&mut inflated)
+        .map_err(|_| DecryptError::Corrupted)?;
+    if inflated.len() as u64 > max_bytes {
+        log::warn!("Compressed paste exceeds decompression limit of {max_bytes} bytes");
+        return Err(DecryptError::Corrupted);
+    }
+    String::from_utf8(inflated).map_err(|_| DecryptError::Corrupted)
+}
+
+/// The paste record a ciphertext belongs to, authenticated as AEAD associated
+/// data. Copying a bound ciphertext into another paste's record (or changing
+/// its format) makes decryption fail as if the key were wrong.
+///
+/// The id must therefore be known before encrypting: handlers pick it with
+/// [`crate::PasteStore::next_paste_id`] and store with
+/// [`crate::PasteStore::create_paste_with_id`].
+#[derive(Debug, Clone, Copy)]
+pub struct PasteBinding<'a> {
+    pub id: &'a str,
+    pub format: PasteFormat,
+}
+
+impl<'a> PasteBinding<'a> {
+    pub fn new(id: &'a str, format: PasteFormat) -> Self {
+        Self { id, format }
+    }
+
+    fn aad(&self) -> Vec<u8> {
+        format!("copypaste.fyi/paste\0{}\0{}", self.id, self.format).into_bytes()
+    }
+}
+
+/// Associated data for decrypting `bound`/unbound content.
+fn aad_for(bound: bool, binding: PasteBinding<'_>) -> Vec<u8> {
+    if bound {
+        binding.aad()
+    } else {
+        Vec::new()
+    }
 }
 
 /// Arguments needed to call the OCaml verification service after CPU-bound encryption.
@@ -32,6 +101,8 @@ struct OcamlVerifyArgs {
     key: String,
     nonce: Option<String>,
     salt: Option<String>,
+    aad: Option<String>,
+    kdf: KdfKind,
 }
 
 /// CPU-bound encryption work, suitable for running inside `spawn_blocking`.
@@ -42,7 +113,12 @@ fn encrypt_content_sync(
     text: &str,
     key: &str,
     algorithm: EncryptionAlgorithm,
+    aad: &[u8],
 ) -> Result<(StoredContent, Option<OcamlVerifyArgs>), String> {
+    let payload = Payload {
+        msg: text.as_bytes(),
+        aad,
+    };
     match algorithm {
         EncryptionAlgorithm::None => Ok((
             StoredContent::Plain {
@@ -53,7 +129,8 @@ fn encrypt_content_sync(
         EncryptionAlgorithm::Aes256Gcm => {
             let mut salt = [0u8; 16];
             OsRng.fill_bytes(&mut salt);
-            let derived = derive_key_material(key, &salt);
+            let kdf = argon2_kdf_from_env();
+            let derived = derive_key_material(key, &salt, kdf)?;
 
             let cipher = Aes256Gcm::new_from_slice(&*derived)
                 .map_err(|_| "failed to initialise cipher".to_string())?;
@@ -62,21 +139,22 @@ fn encrypt_content_sync(
             let nonce = AesNonce::from(nonce_bytes);
 
             let ciphertext = cipher
-                .encrypt(&nonce, text.as_bytes())
+                .encrypt(&nonce, payload)
                 .map_err(|_| "failed to encrypt content".to_string())?;
 
             let ciphertext_b64 = general_purpose::STANDARD.encode(&ciphertext);
             let nonce_b64 = general_purpose::STANDARD.encode(nonce_bytes);
             let salt_b64 = general_purpose::STANDARD.encode(salt);
 
-            let verify = OcamlVerifyArgs {
+            let verify = ocaml_verify_args(
                 algorithm,
-                plaintext: text.to_owned(),
-                ciphertext: ciphertext_b64.clone(),
-                key: key.to_owned(),
-                nonce: Some(nonce_b64.clone()),
-                salt: Some(salt_b64.clone()),
-            };
+                kdf,
+                text,
+                key,
+                &ciphertext_b64,
+                &nonce_b64,
+                &salt_b64,
+            );
 
             Ok((
                 StoredContent::Encrypted {
@@ -84,14 +162,18 @@ fn encrypt_content_sync(
                     ciphertext: ciphertext_b64,
                     nonce: nonce_b64,
                     salt: salt_b64,
+                    kdf,
+                    bound: true,
+                    envelope_version: ENVELOPE_V1,
                 },
-                Some(verify),
+                verify,
             ))
         }
         EncryptionAlgorithm::ChaCha20Poly1305 => {
             let mut salt = [0u8; 16];
             OsRng.fill_bytes(&mut salt);
-            let derived = derive_key_material(key, &salt);
+            let kdf = argon2_kdf_from_env();
+            let derived = derive_key_material(key, &salt, kdf)?;
 
             let cipher = ChaCha20Poly1305::new_from_slice(&*derived)
                 .map_err(|_| "failed to initialise cipher".to_string())?;
@@ -100,21 +182,22 @@ fn encrypt_content_sync(
             let nonce = ChaNonce::from(nonce_bytes);
 
             let ciphertext = cipher
-                .encrypt(&nonce, text.as_bytes())
+                .encrypt(&nonce, payload)
                 .map_err(|_| "failed to encrypt content".to_string())?;
 
             let ciphertext_b64 = general_purpose::STANDARD.encode(&ciphertext);
             let nonce_b64 = general_purpose::STANDARD.encode(nonce_bytes);
             let salt_b64 = general_purpose::STANDARD.encode(salt);
 
-            let verify = OcamlVerifyArgs {
+            let verify = ocaml_verify_args(
                 algorithm,
-                plaintext: text.to_owned(),
-                ciphertext: ciphertext_b64.clone(),
-                key: key.to_owned(),
-                nonce: Some(nonce_b64.clone()),
-                salt: Some(salt_b64.clone()),
-            };
+                kdf,
+                text,
+                key,
+                &ciphertext_b64,
+                &nonce_b64,
+                &salt_b64,
+            );
 
             Ok((
                 StoredContent::Encrypted {
@@ -122,14 +205,18 @@ fn encrypt_content_sync(
                     ciphertext: ciphertext_b64,
                     nonce: nonce_b64,
                     salt: salt_b64,
+                    kdf,
+                    bound: true,
+                    envelope_version: ENVELOPE_V1,
                 },
-                Some(verify),
+                verify,
             ))
         }
         EncryptionAlgorithm::XChaCha20Poly1305 => {
             let mut salt = [0u8; 16];
             OsRng.fill_bytes(&mut salt);
-            let derived = derive_key_material(key, &salt);
+            let kdf = argon2_kdf_from_env();
+            let derived = derive_key_material(key, &salt, kdf)?;
 
             let cipher = XChaCha20Poly1305::new_from_slice(&*derived)
                 .map_err(|_| "failed to initialise cipher".to_string())?;
@@ -138,21 +225,22 @@ fn encrypt_content_sync(
             let nonce = XNonce::from(nonce_bytes);
 
             let ciphertext = cipher
-                .encrypt(&nonce, text.as_bytes())
+                .encrypt(&nonce, payload)
                 .map_err(|_| "failed to encrypt content".to_string())?;
 
             let ciphertext_b64 = general_purpose::STANDARD.encode(&ciphertext);
             let nonce_b64 = general_purpose::STANDARD.encode(nonce_bytes);
             let salt_b64 = general_purpose::STANDARD.encode(salt);
 
-            let verify = OcamlVerifyArgs {
+            let verify = ocaml_verify_args(
                 algorithm,
-                plaintext: text.to_owned(),
-                ciphertext: ciphertext_b64.clone(),
-                key: key.to_owned(),
-                nonce: Some(nonce_b64.clone()),
-                salt: Some(salt_b64.clone()),
-            };
+                kdf,
+                text,
+                key,
+                &ciphertext_b64,
+                &nonce_b64,
+                &salt_b64,
+            );
 
             Ok((
                 StoredContent::Encrypted {
@@ -160,8 +248,11 @@ fn encrypt_content_sync(
                     ciphertext: ciphertext_b64,
                     nonce: nonce_b64,
                     salt: salt_b64,
+                    kdf,
+                    bound: true,
+                    envelope_version: ENVELOPE_V1,
                 },
-                Some(verify),
+                verify,
             ))
         }
         EncryptionAlgorithm::KyberHybridAes256Gcm => {
@@ -201,7 +292,7 @@ fn encrypt_content_sync(
             OsRng.fill_bytes(&mut nonce_bytes);
             let nonce = AesNonce::from(nonce_bytes);
             let aes_ciphertext = cipher
-                .encrypt(&nonce, text.as_bytes())
+                .encrypt(&nonce, payload)
                 .map_err(|_| "failed to encrypt content with AES".to_string())?;
 
             // 3-part storage format (new ML-KEM-768, distinct from legacy 4/5-part blobs):
@@ -221,6 +312,36 @@ fn encrypt_content_sync(
                     ciphertext: combined,
                     nonce: String::new(),
                     salt: String::new(),
+                    kdf: KdfKind::default(),
+                    bound: true,
+                    envelope_version: ENVELOPE_V1,
+                },
+                None,
+            ))
+        }
+        EncryptionAlgorithm::Age => {
+            // The key is the viewer's public recipient, so the creator never
+            // holds anything that decrypts the paste.
+            let recipient: age::x25519::Recipient = key
+                .trim()
+                .parse()
+                .map_err(|_| "age encryption requires an age1… X25519 recipient".to_string())?;
+            // age has no associated data, so the binding travels inside the
+            // encrypted payload and is checked on decryption.
+            let mut plaintext = Zeroizing::new(aad.to_vec());
+            plaintext.extend_from_slice(text.as_bytes());
+            let ciphertext = age::encrypt(&recipient, &plaintext)
+                .map_err(|e| format!("age encryption failed: {e}"))?;
+
+            Ok((
+                StoredContent::Encrypted {
+                    algorithm,
+                    ciphertext: BASE64_STANDARD.encode(ciphertext),
+                    nonce: String::new(),
+                    salt: String::new(),
+                    kdf: KdfKind::default(),
+                    bound: true,
+                    envelope_version: ENVELOPE_V1,
                 },
                 None,
             ))
@@ -228,28 +349,51 @@ fn encrypt_content_sync(
     }
 }
 
-/// One-time warning that XChaCha20-Poly1305 and the ML-KEM hybrid are not
-/// covered by the OCaml dual-verification service (mirage-crypto exposes
-/// neither XChaCha20/HChaCha20 nor ML-KEM). Emitted the first time such an
+/// One-time warning that XChaCha20-Poly1305, the ML-KEM hybrid and `age` are
+/// not covered by the OCaml dual-verification service (mirage-crypto exposes
+/// neither XChaCha20/HChaCha20 nor ML-KEM, and has no age implementation). Emitted the first time such an
 /// algorithm is used so operators know these are Rust-verified only.
 static DUAL_VERIFY_GAP_WARNING: Once = Once::new();
 
 pub(crate) fn warn_dual_verification_gap(algorithm: EncryptionAlgorithm) {
     if matches!(
         algorithm,
-        EncryptionAlgorithm::XChaCha20Poly1305 | EncryptionAlgorithm::KyberHybridAes256Gcm
+        EncryptionAlgorithm::XChaCha20Poly1305
+            | EncryptionAlgorithm::KyberHybridAes256Gcm
+            | EncryptionAlgorithm::Age
     ) {
         DUAL_VERIFY_GAP_WARNING.call_once(|| {
             log::warn!(
                 "{:?} is not covered by the OCaml dual-verification service; \
-                 XChaCha20-Poly1305 and ML-KEM-768 hybrid ciphertexts are verified \
-                 by the Rust implementation only (see docs/encryption.md)",
+                 XChaCha20-Poly1305, ML-KEM-768 hybrid and age ciphertexts are \
+                 verified by the Rust implementation only (see docs/encryption.md)",
                 algorithm
             );
         });
     }
 }
 
+static KDF_VERIFY_GAP_WARNING: Once = Once::new();
+
+/// Whether the OCaml verifier can reproduce keys derived with `kdf`. It
+/// only implements the legacy SHA-256 derivation, so Argon2id records are
+/// skipped like the algorithms it lacks instead of being sent for a
+/// guaranteed `Unsupported key derivation`, which would fail every create
+/// under `COPYPASTE_REQUIRE_CRYPTO_VERIFICATION=true`.
+fn ocaml_verifies_kdf(kdf: KdfKind) -> bool {
+    if matches!(kdf, KdfKind::Sha256) {
+        return true;
+    }
+    KDF_VERIFY_GAP_WARNING.call_once(|| {
+        log::warn!(
+            "Argon2id-derived ciphertexts are not covered by the OCaml \
+             dual-verification service and are verified by the Rust \
+             implementation only (see docs/encryption.md)"
+        );
+    });
+    false
+}
+
 /// Encrypt content using the specified algorithm.
 ///
 /// CPU-bound cipher work runs inside `tokio::task::spawn_blocking` so it does not
@@ -259,228 +403,454 @@ pub async fn encrypt_content(
     text: &str,
     key: &str,
     algorithm: EncryptionAlgorithm,
+    binding: PasteBinding<'_>,
 ) -> Result<StoredContent, String> {
     warn_dual_verification_gap(algorithm);
     let text = text.to_owned();
     let key = key.to_owned();
+    let aad = binding.aad();
 
     let (content, verify_args) =
-        tokio::task::spawn_blocking(move || encrypt_content_sync(&text, &key, algorithm))
+        tokio::task::spawn_blocking(move || encrypt_content_sync(&text, &key, algorithm, &aad))
             .await
             .map_err(|_| "encryption thread panicked".to_string())??;
 
     // Defense-in-depth OCaml verification (configurable via COPYPASTE_REQUIRE_CRYPTO_VERIFICATION)
-    if let Some(args) = verify_args {
-        verify_encryption_with_ocaml(
-            args.algorithm,
-            &args.plaintext,
-            &args.ciphertext,
-            &args.key,
-            args.nonce.as_deref(),
-            args.salt.as_deref(),
-        )
-        .await?;
+    if let Some(mut args) = verify_args {
+        args.aad = Some(BASE64_STANDARD.encode(binding.aad()));
+        verify_encryption_with_ocaml(args).await?;
     }
 
     Ok(content)
 }
 
-pub fn decrypt_content(content: &StoredContent, key: Option<&str>) -> Result<String, DecryptError> {
+/// Decrypt `content` as stored in the paste described by `binding`.
+///
+/// Key derivation and cipher work run inside `tokio::task::spawn_blocking`,
+/// like [`encrypt_content`], so a read does not occupy an async worker for
+/// the length of an Argon2id derivation. Plaintext and key-less reads are
+/// answered inline.
+pub async fn decrypt_content(
+    content: &StoredContent,
+    key: Option<&str>,
+    binding: PasteBinding<'_>,
+) -> Result<String, DecryptError> {
+    let (StoredContent::Encrypted { .. } | StoredContent::Stego { .. }, Some(key)) = (content, key)
+    else {
+        return decrypt_content_sync(content, key, binding);
+    };
+    let content = content.clone();
+    let key = key.to_owned();
+    let id = binding.id.to_owned();
+    let format = binding.format;
+    tokio::task::spawn_blocking(move || {
+        decrypt_content_sync(&content, Some(&key), PasteBinding::new(&id, format))
+    })
+    .await
+    // A decryption that panicked is as unreadable as a corrupted one.
+    .unwrap_or(Err(DecryptError::Corrupted))
+}
+
+/// CPU-bound half of [`decrypt_content`], for callers outside the async
+/// runtime.
+pub fn decrypt_content_sync(
+    content: &StoredContent,
+    key: Option<&str>,
+    binding: PasteBinding<'_>,
+) -> Result<String, DecryptError> {
     match content {
         StoredContent::Plain { text } => Ok(text.clone()),
-        StoredContent::Encrypted {
+        StoredContent::Stego {
             algorithm,
-            ciphertext,
             nonce,
             salt,
+            kdf,
+            bound,
+            envelope_version,
+            carrier_image,
+            payload_digest,
+            ..
+        } => {
+            key.ok_or(DecryptError::MissingKey)?;
+            // The carrier is the source of truth: decrypt what it actually
+            // hides rather than the copy stored alongside it.
+            let ciphertext = recover_stego_ciphertext(carrier_image, payload_digest)?;
+            decrypt_content_sync(
+                &StoredContent::Encrypted {
+                    algorithm: *algorithm,
+                    ciphertext,
+                    nonce: nonce.clone(),
+                    salt: salt.clone(),
+                    kdf: *kdf,
+                    bound: *bound,
+                    envelope_version: *envelope_version,
+                },
+                key,
+                binding,
+            )
         }
-        | StoredContent::Stego {
+        StoredContent::Encrypted {
             algorithm,
             ciphertext,
             nonce,
             salt,
-            ..
+            kdf,
+            bound,
+            envelope_version,
         } => {
             let extracted_key = key.ok_or(DecryptError::MissingKey)?;
-            log::info!("Starting decryption for algorithm: {:?}", algorithm);
-
-            // KyberHybridAes256Gcm uses a different storage layout; handle it separately.
-            if matches!(algorithm, EncryptionAlgorithm::KyberHybridAes256Gcm) {
-                let key_str = extracted_key;
-                let parts: Vec<&str> = ciphertext.split('|').collect();
-
-                match parts.len() {
-                    3 => {
-                        // New ML-KEM-768 format: kem_ct_b64|aes_ct_b64|aes_nonce_b64
-                        let hk = Hkdf::<Sha256>::new(None, key_str.as_bytes());
-                        // Passphrase-derived seed material — wiped on drop.
-                        let mut d_bytes = Zeroizing::new([0u8; 32]);
-                        let mut z_bytes = Zeroizing::new([0u8; 32]);
-                        hk.expand(b"ml-kem-768-keygen-d", &mut *d_bytes)
-                            .map_err(|_| DecryptError::InvalidKey)?;
-                        hk.expand(b"ml-kem-768-keygen-z", &mut *z_bytes)
-                            .map_err(|_| DecryptError::InvalidKey)?;
-                        let d: B32 = (*d_bytes).into();
-                        let z: B32 = (*z_bytes).into();
-                        let (dk, _) = MlKem768::generate_deterministic(&d, &z);
-
-                        let kem_ct_bytes = BASE64_STANDARD
-                            .decode(parts[0])
-                            .map_err(|_| DecryptError::InvalidKey)?;
-                        // ML-KEM-768 ciphertext is exactly 1088 bytes.
-                        let kem_ct_arr: [u8; 1088] = kem_ct_bytes
-                            .try_into()
-                            .map_err(|_| DecryptError::InvalidKey)?;
-                        let kem_ct: Ciphertext<MlKem768> = kem_ct_arr.into();
-
-                        let shared_secret = dk
-                            .decapsulate(&kem_ct)
-                            .map_err(|_| DecryptError::InvalidKey)?;
-
-                        let hk2 = Hkdf::<Sha256>::new(None, &shared_secret);
-                        let mut aes_key = Zeroizing::new([0u8; 32]);
-                        hk2.expand(b"aes-256-gcm-key", &mut *aes_key)
-                            .map_err(|_| DecryptError::InvalidKey)?;
-
-                        let aes_ciphertext = BASE64_STANDARD
-                            .decode(parts[1])
-                            .map_err(|_| DecryptError::InvalidKey)?;
-                        let aes_nonce_bytes = BASE64_STANDARD
-                            .decode(parts[2])
-                            .map_err(|_| DecryptError::InvalidKey)?;
-
-                        let cipher = Aes256Gcm::new_from_slice(&*aes_key)
-                            .map_err(|_| DecryptError::InvalidKey)?;
-                        let nonce_arr: [u8; 12] = aes_nonce_bytes
-                            .try_into()
-                            .map_err(|_| DecryptError::InvalidKey)?;
-                        let nonce = AesNonce::from(nonce_arr);
-
-                        return cipher
-                            .decrypt(&nonce, aes_ciphertext.as_ref())
-                            .map_err(|_| DecryptError::InvalidKey)
-                            .and_then(|bytes| {
-                                String::from_utf8(bytes).map_err(|_| DecryptError::InvalidKey)
-                            });
-                    }
-                    4 | 5 => {
-                        // Legacy simulation format (4 or 5 parts):
-                        //   pq_ct_b64 | pub_key_b64 | aes_ct_b64 | aes_nonce_b64 [| ignored]
-                        // Re-derive the SHA-256 simulation shared secret for backward compat.
-                        let aes_ciphertext = BASE64_STANDARD
-                            .decode(parts[2])
-                            .map_err(|_| DecryptError::InvalidKey)?;
-                        let aes_nonce_bytes = BASE64_STANDARD
-                            .decode(parts[3])
-                            .map_err(|_| DecryptError::InvalidKey)?;
-
-                        let mut secret_hasher = Sha256::new();
-                        secret_hasher.update(b"kem_shared_secret");
-                        secret_hasher.update(key_str.as_bytes());
-                        // Passphrase-derived shared secret — wiped on drop.
-                        let shared_secret: Zeroizing<[u8; 32]> =
-                            Zeroizing::new(secret_hasher.finalize().into());
-
-                        let mut key_hasher = Sha256::new();
-                        key_hasher.update(*shared_secret);
-                        key_hasher.update(key_str.as_bytes());
-                        let aes_key: Zeroizing<[u8; 32]> =
-                            Zeroizing::new(key_hasher.finalize().into());
-
-                        let cipher = Aes256Gcm::new_from_slice(&*aes_key)
-                            .map_err(|_| DecryptError::InvalidKey)?;
-                        let nonce_arr: [u8; 12] = aes_nonce_bytes
-                            .try_into()
-                            .map_err(|_| DecryptError::InvalidKey)?;
-                        let nonce = AesNonce::from(nonce_arr);
-
-                        return cipher
-                            .decrypt(&nonce, aes_ciphertext.as_ref())
-                            .map_err(|_| DecryptError::InvalidKey)
-                            .and_then(|bytes| {
-                                String::from_utf8(bytes).map_err(|_| DecryptError::InvalidKey)
-                            });
-                    }
-                    _ => return Err(DecryptError::InvalidKey),
+            let aad = aad_for(*bound, binding);
+            match *envelope_version {
+                ENVELOPE_V1 => decrypt_v1(
+                    *algorithm,
+                    ciphertext,
+                    nonce,
+                    salt,
+                    *kdf,
+                    extracted_key,
+                    &aad,
+                )
+                .and_then(|bytes| String::from_utf8(bytes).map_err(|_| DecryptError::InvalidKey)),
+                ENVELOPE_V2_DEFLATE => {
+                    let compressed = Zeroizing::new(decrypt_v1(
+                        *algorithm,
+                        ciphertext,
+                        nonce,
+                        salt,
+                        *kdf,
+                        extracted_key,
+                        &aad,
+                    )?);
+                    inflate_plaintext(&compressed, max_decompressed_bytes())
+                }
+                other => {
+                    log::error!("Unsupported ciphertext envelope version {other}");
+                    Err(DecryptError::Corrupted)
                 }
             }
+        }
+    }
+}
+
+/// Salt the key is derived with when the stored one doesn't decode; long
+/// enough for Argon2id, so the derivation costs what a real one does.
+const PLACEHOLDER_SALT: [u8; 16] = [0; 16];
+
+/// Decrypt a version 1 envelope (see [`ENVELOPE_V1`]) to its plaintext
+/// bytes; [`ENVELOPE_V2_DEFLATE`] shares the layout.
+fn decrypt_v1(
+    algorithm: EncryptionAlgorithm,
+    ciphertext: &str,
+    nonce: &str,
+    salt: &str,
+    kdf: KdfKind,
+    extracted_key: &str,
+    aad: &[u8],
+) -> Result<Vec<u8>, DecryptError> {
+    log::info!("Starting decryption for algorithm: {:?}", algorithm);
+
+    // KyberHybridAes256Gcm uses a different storage layout; handle it separately.
+    if matches!(algorithm, EncryptionAlgorithm::KyberHybridAes256Gcm) {
+        let key_str = extracted_key;
+        let parts: Vec<&str> = ciphertext.split('|').collect();
+
+        match parts.len() {
+            3 => {
+                // New ML-KEM-768 format: kem_ct_b64|aes_ct_b64|aes_nonce_b64
+                let hk = Hkdf::<Sha256>::new(None, key_str.as_bytes());
+                // Passphrase-derived seed material — wiped on drop.
+                let mut d_bytes = Zeroizing::new([0u8; 32]);
+                let mut z_bytes = Zeroizing::new([0u8; 32]);
+                hk.expand(b"ml-kem-768-keygen-d", &mut *d_bytes)
+                    .map_err(|_| DecryptError::InvalidKey)?;
+                hk.expand(b"ml-kem-768-keygen-z", &mut *z_bytes)
+                    .map_err(|_| DecryptError::InvalidKey)?;
+                let d: B32 = (*d_bytes).into();
+                let z: B32 = (*z_bytes).into();
+                let (dk, _) = MlKem768::generate_deterministic(&d, &z);
+
+                let kem_ct_bytes = BASE64_STANDARD
+                    .decode(parts[0])
+                    .map_err(|_| DecryptError::InvalidKey)?;
+                // ML-KEM-768 ciphertext is exactly 1088 bytes.
+                let kem_ct_arr: [u8; 1088] = kem_ct_bytes
+                    .try_into()
+                    .map_err(|_| DecryptError::InvalidKey)?;
+                let kem_ct: Ciphertext<MlKem768> = kem_ct_arr.into();
+
+                let shared_secret = dk
+                    .decapsulate(&kem_ct)
+                    .map_err(|_| DecryptError::InvalidKey)?;
+
+                let hk2 = Hkdf::<Sha256>::new(None, &shared_secret);
+                let mut aes_key = Zeroizing::new([0u8; 32]);
+                hk2.expand(b"aes-256-gcm-key", &mut *aes_key)
+                    .map_err(|_| DecryptError::InvalidKey)?;
+
+                let aes_ciphertext = BASE64_STANDARD
+                    .decode(parts[1])
+                    .map_err(|_| DecryptError::InvalidKey)?;
+                let aes_nonce_bytes = BASE64_STANDARD
+                    .decode(parts[2])
+                    .map_err(|_| DecryptError::InvalidKey)?;
+
+                let cipher =
+                    Aes256Gcm::new_from_slice(&*aes_key).map_err(|_| DecryptError::InvalidKey)?;
+                let nonce_arr: [u8; 12] = aes_nonce_bytes
+                    .try_into()
+                    .map_err(|_| DecryptError::InvalidKey)?;
+                let nonce = AesNonce::from(nonce_arr);
+
+                return cipher
+                    .decrypt(
+                        &nonce,
+                        Payload {
+                            msg: &aes_ciphertext,
+                            aad,
+                        },
+                    )
+                    .map_err(|_| DecryptError::InvalidKey);
+            }
+            4 | 5 => {
+                // Legacy simulation format (4 or 5 parts):
+                //   pq_ct_b64 | pub_key_b64 | aes_ct_b64 | aes_nonce_b64 [| ignored]
+                // Re-derive the SHA-256 simulation shared secret for backward compat.
+                let aes_ciphertext = BASE64_STANDARD
+                    .decode(parts[2])
+                    .map_err(|_| DecryptError::InvalidKey)?;
+                let aes_nonce_bytes = BASE64_STANDARD
+                    .decode(parts[3])
+                    .map_err(|_| DecryptError::InvalidKey)?;
+
+                let mut secret_hasher = Sha256::new();
+                secret_hasher.update(b"kem_shared_secret");
+                secret_hasher.update(key_str.as_bytes());
+                // Passphrase-derived shared secret — wiped on drop.
+                let shared_secret: Zeroizing<[u8; 32]> =
+                    Zeroizing::new(secret_hasher.finalize().into());
+
+                let mut key_hasher = Sha256::new();
+                key_hasher.update(*shared_secret);
+                key_hasher.update(key_str.as_bytes());
+                let aes_key: Zeroizing<[u8; 32]> = Zeroizing::new(key_hasher.finalize().into());
+
+                let cipher =
+                    Aes256Gcm::new_from_slice(&*aes_key).map_err(|_| DecryptError::InvalidKey)?;
+                let nonce_arr: [u8; 12] = aes_nonce_bytes
+                    .try_into()
+                    .map_err(|_| DecryptError::InvalidKey)?;
+                let nonce = AesNonce::from(nonce_arr);
+
+                return cipher
+                    .decrypt(
+                        &nonce,
+                        Payload {
+                            msg: &aes_ciphertext,
+                            aad,
+                        },
+                    )
+                    .map_err(|_| DecryptError::InvalidKey);
+            }
+            _ => return Err(DecryptError::InvalidKey),
+        }
+    }
+
+    if matches!(algorithm, EncryptionAlgorithm::Age) {
+        return decrypt_age(ciphertext, extracted_key, aad);
+    }
 
-            // Normal algorithms that use base64 encoding
-            let salt_bytes = general_purpose::STANDARD
-                .decode(salt)
+    // Normal algorithms that use base64 encoding. A field that fails to
+    // decode must look exactly like a wrong key, in timing as well as in the
+    // error, so the key is derived regardless and the decode failures only
+    // surface afterwards, where an AEAD tag failure would.
+    let salt_bytes = general_purpose::STANDARD.decode(salt);
+    let nonce_bytes_vec = general_purpose::STANDARD.decode(nonce);
+    let cipher_bytes = general_purpose::STANDARD.decode(ciphertext);
+
+    let derived = derive_key_material(
+        extracted_key,
+        salt_bytes.as_deref().unwrap_or(&PLACEHOLDER_SALT),
+        kdf,
+    );
+    let (Ok(_), Ok(nonce_bytes_vec), Ok(cipher_bytes), Ok(derived)) =
+        (salt_bytes, nonce_bytes_vec, cipher_bytes, derived)
+    else {
+        return Err(DecryptError::InvalidKey);
+    };
+
+    match algorithm {
+        EncryptionAlgorithm::None => Ok(cipher_bytes),
+        EncryptionAlgorithm::Aes256Gcm => {
+            let cipher =
+                Aes256Gcm::new_from_slice(&*derived).map_err(|_| DecryptError::InvalidKey)?;
+            let nonce_array: [u8; 12] = nonce_bytes_vec
+                .try_into()
                 .map_err(|_| DecryptError::InvalidKey)?;
-            let nonce_bytes_vec = general_purpose::STANDARD
-                .decode(nonce)
+            let nonce = AesNonce::from(nonce_array);
+
+            cipher
+                .decrypt(
+                    &nonce,
+                    Payload {
+                        msg: &cipher_bytes,
+                        aad,
+                    },
+                )
+                .map_err(|_| DecryptError::InvalidKey)
+        }
+        EncryptionAlgorithm::ChaCha20Poly1305 => {
+            let cipher = ChaCha20Poly1305::new_from_slice(&*derived)
+                .map_err(|_| DecryptError::InvalidKey)?;
+            let nonce_array: [u8; 12] = nonce_bytes_vec
+                .try_into()
+                .map_err(|_| DecryptError::InvalidKey)?;
+            let nonce = ChaNonce::from(nonce_array);
+
+            cipher
+                .decrypt(
+                    &nonce,
+                    Payload {
+                        msg: &cipher_bytes,
+                        aad,
+                    },
+                )
+                .map_err(|_| DecryptError::InvalidKey)
+        }
+        EncryptionAlgorithm::XChaCha20Poly1305 => {
+            let cipher = XChaCha20Poly1305::new_from_slice(&*derived)
                 .map_err(|_| DecryptError::InvalidKey)?;
-            let cipher_bytes = general_purpose::STANDARD
-                .decode(ciphertext)
+            let nonce_array: [u8; 24] = nonce_bytes_vec
+                .try_into()
                 .map_err(|_| DecryptError::InvalidKey)?;
+            let nonce = XNonce::from(nonce_array);
+
+            cipher
+                .decrypt(
+                    &nonce,
+                    Payload {
+                        msg: &cipher_bytes,
+                        aad,
+                    },
+                )
+                .map_err(|_| DecryptError::InvalidKey)
+        }
+        EncryptionAlgorithm::KyberHybridAes256Gcm | EncryptionAlgorithm::Age => {
+            // This should never be reached due to early returns above
+            Err(DecryptError::InvalidKey)
+        }
+    }
+}
 
-            let derived = derive_key_material(extracted_key, &salt_bytes);
+/// Decrypt an `age` file with the viewer's X25519 identity, then check and
+/// strip the binding that [`encrypt_content`] prefixed to the plaintext.
+fn decrypt_age(ciphertext: &str, identity: &str, aad: &[u8]) -> Result<Vec<u8>, DecryptError> {
+    let identity: age::x25519::Identity = identity
+        .trim()
+        .parse()
+        .map_err(|_| DecryptError::InvalidKey)?;
+    let file = BASE64_STANDARD
+        .decode(ciphertext)
+        .map_err(|_| DecryptError::InvalidKey)?;
+    let plaintext =
+        Zeroizing::new(age::decrypt(&identity, &file).map_err(|_| DecryptError::InvalidKey)?);
+    let text = plaintext
+        .strip_prefix(aad)
+        .ok_or(DecryptError::InvalidKey)?;
+    Ok(text.to_vec())
+}
 
-            match algorithm {
-                EncryptionAlgorithm::None => {
-                    String::from_utf8(cipher_bytes).map_err(|_| DecryptError::InvalidKey)
-                }
-                EncryptionAlgorithm::Aes256Gcm => {
-                    let cipher = Aes256Gcm::new_from_slice(&*derived)
-                        .map_err(|_| DecryptError::InvalidKey)?;
-                    let nonce_array: [u8; 12] = nonce_bytes_vec
-                        .try_into()
-                        .map_err(|_| DecryptError::InvalidKey)?;
-                    let nonce = AesNonce::from(nonce_array);
-
-                    cipher
-                        .decrypt(&nonce, cipher_bytes.as_ref())
-                        .map_err(|_| DecryptError::InvalidKey)
-                        .and_then(|bytes| {
-                            String::from_utf8(bytes).map_err(|_| DecryptError::InvalidKey)
-                        })
-                }
-                EncryptionAlgorithm::ChaCha20Poly1305 => {
-                    let cipher = ChaCha20Poly1305::new_from_slice(&*derived)
-                        .map_err(|_| DecryptError::InvalidKey)?;
-                    let nonce_array: [u8; 12] = nonce_bytes_vec
-                        .try_into()
-                        .map_err(|_| DecryptError::InvalidKey)?;
-                    let nonce = ChaNonce::from(nonce_array);
-
-                    cipher
-                        .decrypt(&nonce, cipher_bytes.as_ref())
-                        .map_err(|_| DecryptError::InvalidKey)
-                        .and_then(|bytes| {
-                            String::from_utf8(bytes).map_err(|_| DecryptError::InvalidKey)
-                        })
-                }
-                EncryptionAlgorithm::XChaCha20Poly1305 => {
-                    let cipher = XChaCha20Poly1305::new_from_slice(&*derived)
-                        .map_err(|_| DecryptError::InvalidKey)?;
-                    let nonce_array: [u8; 24] = nonce_bytes_vec
-                        .try_into()
-                        .map_err(|_| DecryptError::InvalidKey)?;
-                    let nonce = XNonce::from(nonce_array);
-
-                    cipher
-                        .decrypt(&nonce, cipher_bytes.as_ref())
-                        .map_err(|_| DecryptError::InvalidKey)
-                        .and_then(|bytes| {
-                            String::from_utf8(bytes).map_err(|_| DecryptError::InvalidKey)
-                        })
-                }
-                EncryptionAlgorithm::KyberHybridAes256Gcm => {
-                    // This should never be reached due to early return above
-                    Err(DecryptError::InvalidKey)
-                }
-            }
+/// OWASP-recommended Argon2id baseline: 19 MiB, 2 passes, 1 lane.
+const DEFAULT_ARGON2_MEMORY_KIB: u32 = 19 * 1024;
+const DEFAULT_ARGON2_ITERATIONS: u32 = 2;
+const DEFAULT_ARGON2_PARALLELISM: u32 = 1;
+
+/// Argon2id cost for new pastes, from `COPYPASTE_ARGON2_MEMORY_KIB`,
+/// `COPYPASTE_ARGON2_ITERATIONS` and `COPYPASTE_ARGON2_PARALLELISM`.
+pub fn argon2_kdf_from_env() -> KdfKind {
+    let read = |name: &str, default: u32| {
+        std::env::var(name)
+            .ok()
+            .and_then(|v| v.trim().parse::<u32>().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(default)
+    };
+    KdfKind::Argon2id {
+        memory_kib: read("COPYPASTE_ARGON2_MEMORY_KIB", DEFAULT_ARGON2_MEMORY_KIB),
+        iterations: read("COPYPASTE_ARGON2_ITERATIONS", DEFAULT_ARGON2_ITERATIONS),
+        parallelism: read("COPYPASTE_ARGON2_PARALLELISM", DEFAULT_ARGON2_PARALLELISM),
+    }
+}
+
+fn derive_key_material(
+    key: &str,
+    salt: &[u8],
+    kdf: KdfKind,
+) -> Result<Zeroizing<[u8; 32]>, String> {
+    match kdf {
+        KdfKind::Sha256 => {
+            let mut hasher = Sha256::new();
+            hasher.update(salt);
+            hasher.update(key.as_bytes());
+            Ok(Zeroizing::new(hasher.finalize().into()))
+        }
+        KdfKind::Argon2id {
+            memory_kib,
+            iterations,
+            parallelism,
+        } => {
+            let params = Params::new(memory_kib, iterations, parallelism, Some(32))
+                .map_err(|e| format!("invalid Argon2id parameters: {e}"))?;
+            let mut derived = Zeroizing::new([0u8; 32]);
+            Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                .hash_password_into(key.as_bytes(), salt, &mut *derived)
+                .map_err(|e| format!("Argon2id key derivation failed: {e}"))?;
+            Ok(derived)
         }
     }
 }
 
-fn derive_key_material(key: &str, salt: &[u8]) -> Zeroizing<[u8; 32]> {
-    let mut hasher = Sha256::new();
-    hasher.update(salt);
-    hasher.update(key.as_bytes());
-    Zeroizing::new(hasher.finalize().into())
+/// Extract the ciphertext hidden in a base64 stego carrier, checking it
+/// against the SHA-256 digest recorded when it was embedded.
+fn recover_stego_ciphertext(
+    carrier_image: &str,
+    payload_digest: &str,
+) -> Result<String, DecryptError> {
+    let image = BASE64_STANDARD
+        .decode(carrier_image)
+        .map_err(|_| DecryptError::Corrupted)?;
+    let payload = extract_from_image_bytes(&image).map_err(|error| {
+        log::error!("Failed to extract stego payload: {error}");
+        DecryptError::Corrupted
+    })?;
+    if hex::encode(Sha256::digest(&payload)) != payload_digest {
+        log::error!("Stego payload digest mismatch");
+        return Err(DecryptError::Corrupted);
+    }
+    Ok(BASE64_STANDARD.encode(payload))
+}
+
+/// The KDF travels with the request so the verifier can tell which key
+/// derivation to reproduce; one that can't re-derive Argon2id keys answers
+/// `valid: false`, which only blocks encryption in strict mode.
+fn ocaml_verify_args(
+    algorithm: EncryptionAlgorithm,
+    kdf: KdfKind,
+    plaintext: &str,
+    key: &str,
+    ciphertext_b64: &str,
+    nonce_b64: &str,
+    salt_b64: &str,
+) -> Option<OcamlVerifyArgs> {
+    Some(OcamlVerifyArgs {
+        algorithm,
+        plaintext: plaintext.to_owned(),
+        ciphertext: ciphertext_b64.to_owned(),
+        key: key.to_owned(),
+        nonce: Some(nonce_b64.to_owned()),
+        salt: Some(salt_b64.to_owned()),
+        aad: None,
+        kdf,
+    })
 }
 
 #[derive(Serialize)]
@@ -492,6 +862,7 @@ struct EncryptionVerificationRequest {
     nonce: Option<String>,
     salt: Option<String>,
     aad: Option<String>,
+    kdf: KdfKind,
 }
 
 #[derive(Serialize)]
@@ -624,30 +995,28 @@ async fn verify_with_ocaml_crypto_service(
 }
 
 /// Verify encryption operation with OCaml service (optional)
-pub async fn verify_encryption_with_ocaml(
-    algorithm: EncryptionAlgorithm,
-    plaintext: &str,
-    ciphertext: &str,
-    key: &str,
-    nonce: Option<&str>,
-    salt: Option<&str>,
-) -> Result<(), String> {
-    let algorithm_str = match algorithm {
+async fn verify_encryption_with_ocaml(args: OcamlVerifyArgs) -> Result<(), String> {
+    let algorithm_str = match args.algorithm {
         EncryptionAlgorithm::Aes256Gcm => "aes256_gcm",
         EncryptionAlgorithm::ChaCha20Poly1305 => "chacha20_poly1305",
         EncryptionAlgorithm::XChaCha20Poly1305 => "xchacha20_poly1305",
         EncryptionAlgorithm::KyberHybridAes256Gcm => "aes256_gcm", // Verify AES portion of hybrid
         EncryptionAlgorithm::None => return Ok(()), // No verification needed for plaintext
+        EncryptionAlgorithm::Age => return Ok(()),  // Not supported by the OCaml verifier
     };
+    if !ocaml_verifies_kdf(args.kdf) {
+        return Ok(());
+    }
 
     let request = EncryptionVerificationRequest {
         algorithm: algorithm_str.to_string(),
-        plaintext: plaintext.to_string(),
-        ciphertext: ciphertext.to_string(),
-        key: key.to_string(),
-        nonce: nonce.map(|s| s.to_string()),
-        salt: salt.map(|s| s.to_string()),
-        aad: None,
+        plaintext: args.plaintext,
+        ciphertext: args.ciphertext,
+        key: args.key,
+        nonce: args.nonce,
+        salt: args.salt,
+        aad: args.aad,
+        kdf: args.kdf,
     };
 
     let request_body = serde_json::to_string(&request)
@@ -690,4 +1059,296 @@ mod tests {
         warn_dual_verification_gap(EncryptionAlgorithm::Aes256Gcm);
         warn_dual_verification_gap(EncryptionAlgorithm::None);
     }
+
+    #[test]
+    fn ocaml_verification_covers_only_the_legacy_kdf() {
+        assert!(ocaml_verifies_kdf(KdfKind::Sha256));
+        assert!(!ocaml_verifies_kdf(argon2_kdf_from_env()));
+        assert!(!ocaml_verifies_kdf(KdfKind::Argon2id {
+            memory_kib: 8,
+            iterations: 1,
+            parallelism: 1,
+        }));
+    }
+
+    fn binding() -> PasteBinding<'static> {
+        PasteBinding::new("test-paste", PasteFormat::PlainText)
+    }
+
+    /// Encrypts without associated data, like records from before binding.
+    fn encrypt_bytes_aes(plaintext: &[u8], key: &str) -> StoredContent {
+        let salt = [7u8; 16];
+        let nonce_bytes = [9u8; 12];
+        let cipher =
+            Aes256Gcm::new_from_slice(&*derive_key_material(key, &salt, KdfKind::Sha256).unwrap())
+                .unwrap();
+        let ciphertext = cipher
+            .encrypt(&AesNonce::from(nonce_bytes), plaintext)
+            .unwrap();
+        StoredContent::Encrypted {
+            algorithm: EncryptionAlgorithm::Aes256Gcm,
+            ciphertext: BASE64_STANDARD.encode(ciphertext),
+            nonce: BASE64_STANDARD.encode(nonce_bytes),
+            salt: BASE64_STANDARD.encode(salt),
+            kdf: KdfKind::Sha256,
+            bound: false,
+            envelope_version: ENVELOPE_V1,
+        }
+    }
+
+    fn to_stego(content: StoredContent) -> StoredContent {
+        use crate::server::stego::{embed_payload, StegoCarrierSource};
+
+        let StoredContent::Encrypted {
+            algorithm,
+            ciphertext,
+            nonce,
+            salt,
+            kdf,
+            bound,
+            envelope_version,
+        } = content
+        else {
+            panic!("expected encrypted content");
+        };
+        let payload = BASE64_STANDARD.decode(&ciphertext).unwrap();
+        let carrier =
+            embed_payload(StegoCarrierSource::BuiltIn("aurora".into()), &payload).unwrap();
+        StoredContent::Stego {
+            algorithm,
+            // Decryption must rely on the carrier, not this copy.
+            ciphertext: String::new(),
+            nonce,
+            salt,
+            kdf,
+            bound,
+            envelope_version,
+            carrier_mime: carrier.mime,
+            carrier_image: BASE64_STANDARD.encode(carrier.image_data),
+            payload_digest: hex::encode(Sha256::digest(&payload)),
+        }
+    }
+
+    #[test]
+    fn stego_content_decrypts_from_the_carrier() {
+        let stego = to_stego(encrypt_bytes_aes(b"hidden", "stego-key"));
+        assert_eq!(
+            decrypt_content_sync(&stego, Some("stego-key"), binding()).unwrap(),
+            "hidden"
+        );
+        assert!(matches!(
+            decrypt_content_sync(&stego, None, binding()),
+            Err(DecryptError::MissingKey)
+        ));
+        assert!(matches!(
+            decrypt_content_sync(&stego, Some("wrong"), binding()),
+            Err(DecryptError::InvalidKey)
+        ));
+    }
+
+    #[test]
+    fn stego_digest_mismatch_is_corrupted() {
+        let StoredContent::Stego {
+            algorithm,
+            ciphertext,
+            nonce,
+            salt,
+            kdf,
+            bound,
+            envelope_version,
+            carrier_mime,
+            carrier_image,
+            ..
+        } = to_stego(encrypt_bytes_aes(b"hidden", "stego-key"))
+        else {
+            unreachable!();
+        };
+        let tampered = StoredContent::Stego {
+            algorithm,
+            ciphertext,
+            nonce,
+            salt,
+            kdf,
+            bound,
+            envelope_version,
+            carrier_mime,
+            carrier_image,
+            payload_digest: "0".repeat(64),
+        };
+        assert!(matches!(
+            decrypt_content_sync(&tampered, Some("stego-key"), binding()),
+            Err(DecryptError::Corrupted)
+        ));
+    }
+
+    #[tokio::test]
+    async fn new_pastes_use_argon2id_and_round_trip() {
+        let stored = encrypt_content(
+            "argon secret",
+            "passphrase",
+            EncryptionAlgorithm::Aes256Gcm,
+            binding(),
+        )
+        .await
+        .unwrap();
+        let StoredContent::Encrypted { kdf, .. } = &stored else {
+            panic!("expected encrypted content");
+        };
+        assert!(matches!(kdf, KdfKind::Argon2id { .. }));
+        assert_eq!(
+            decrypt_content_sync(&stored, Some("passphrase"), binding()).unwrap(),
+            "argon secret"
+        );
+        assert!(matches!(
+            decrypt_content_sync(&stored, Some("wrong"), binding()),
+            Err(DecryptError::InvalidKey)
+        ));
+    }
+
+    #[tokio::test]
+    async fn bound_ciphertext_only_decrypts_as_its_own_paste() {
+        for algorithm in [
+            EncryptionAlgorithm::Aes256Gcm,
+            EncryptionAlgorithm::ChaCha20Poly1305,
+            EncryptionAlgorithm::XChaCha20Poly1305,
+            EncryptionAlgorithm::KyberHybridAes256Gcm,
+        ] {
+            let stored = encrypt_content("bound secret", "k", algorithm, binding())
+                .await
+                .unwrap();
+            assert_eq!(
+                decrypt_content_sync(&stored, Some("k"), binding()).unwrap(),
+                "bound secret"
+            );
+            for other in [
+                PasteBinding::new("other-paste", PasteFormat::PlainText),
+                PasteBinding::new("test-paste", PasteFormat::Markdown),
+            ] {
+                assert!(
+                    matches!(
+                        decrypt_content_sync(&stored, Some("k"), other),
+                        Err(DecryptError::InvalidKey)
+                    ),
+                    "{algorithm:?} decrypted under {other:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn unbound_records_decrypt_under_any_binding() {
+        let legacy = encrypt_bytes_aes(b"from before binding", "k");
+        let json = serde_json::to_value(&legacy).unwrap();
+        assert!(json.get("bound").is_none());
+        let restored: StoredContent = serde_json::from_value(json).unwrap();
+        let anywhere = PasteBinding::new("moved", PasteFormat::Json);
+        assert_eq!(
+            decrypt_content_sync(&restored, Some("k"), anywhere).unwrap(),
+            "from before binding"
+        );
+    }
+
+    #[test]
+    fn records_without_envelope_version_are_v1() {
+        let stored = encrypt_bytes_aes(b"versioned", "k");
+        let json = serde_json::to_value(&stored).unwrap();
+        assert!(json.get("envelope_version").is_none());
+
+        let restored: StoredContent = serde_json::from_value(json.clone()).unwrap();
+        let StoredContent::Encrypted {
+            envelope_version, ..
+        } = &restored
+        else {
+            panic!("expected encrypted content");
+        };
+        assert_eq!(*envelope_version, ENVELOPE_V1);
+        assert_eq!(
+            decrypt_content_sync(&restored, Some("k"), binding()).unwrap(),
+            "versioned"
+        );
+
+        let mut future = json;
+        future["envelope_version"] = 3.into();
+        let future: StoredContent = serde_json::from_value(future).unwrap();
+        assert!(matches!(
+            decrypt_content_sync(&future, Some("k"), binding()),
+            Err(DecryptError::Corrupted)
+        ));
+    }
+
+    #[test]
+    fn legacy_records_without_kdf_still_decrypt_with_sha256() {
+        let legacy = encrypt_bytes_aes(b"from before argon2", "old-key");
+        let mut json = serde_json::to_value(&legacy).unwrap();
+        json.as_object_mut().unwrap().remove("kdf");
+
+        let restored: StoredContent = serde_json::from_value(json).unwrap();
+        let StoredContent::Encrypted { kdf, .. } = &restored else {
+            panic!("expected encrypted content");
+        };
+        assert_eq!(*kdf, KdfKind::Sha256);
+        assert_eq!(
+            decrypt_content_sync(&restored, Some("old-key"), binding()).unwrap(),
+            "from before argon2"
+        );
+    }
+
+    fn deflate(text: &str) -> Vec<u8> {
+        use flate2::{write::DeflateEncoder, Compression};
+        use std::io::Write;
+
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(text.as_bytes()).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    /// An [`ENVELOPE_V2_DEFLATE`] record of `text` compressed.
+    fn encrypt_deflated(text: &str, key: &str) -> StoredContent {
+        let mut stored = encrypt_bytes_aes(&deflate(text), key);
+        if let StoredContent::Encrypted {
+            envelope_version, ..
+        } = &mut stored
+        {
+            *envelope_version = ENVELOPE_V2_DEFLATE;
+        }
+        stored
+    }
+
+    #[test]
+    fn compressed_plaintext_round_trips_through_decrypt() {
+        let text = "hello compressed world\n".repeat(100);
+        let stored = encrypt_deflated(&text, "k");
+        assert_eq!(
+            decrypt_content_sync(&stored, Some("k"), binding()).unwrap(),
+            text
+        );
+
+        // Compression is recorded in the envelope, never sniffed from the
+        // plaintext: a version 1 paste of deflate-looking bytes stays as is.
+        let verbatim = "\0cpz1\0not inflated";
+        let stored = encrypt_bytes_aes(verbatim.as_bytes(), "k");
+        assert_eq!(
+            decrypt_content_sync(&stored, Some("k"), binding()).unwrap(),
+            verbatim
+        );
+    }
+
+    #[test]
+    fn decompression_bomb_is_rejected_cleanly() {
+        // ~20 MB of zeros deflates to a few KB but exceeds the 10 MB default cap.
+        let bomb = "\0".repeat(20 * 1024 * 1024);
+        assert!(deflate(&bomb).len() < 64 * 1024);
+        let stored = encrypt_deflated(&bomb, "k");
+        assert!(matches!(
+            decrypt_content_sync(&stored, Some("k"), binding()),
+            Err(DecryptError::Corrupted)
+        ));
+
+        let small = deflate(&"a".repeat(1025));
+        assert!(matches!(
+            inflate_plaintext(&small, 1024),
+            Err(DecryptError::Corrupted)
+        ));
+        assert_eq!(inflate_plaintext(&small, 1025).unwrap().len(), 1025);
+    }
 }
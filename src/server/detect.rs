@@ -0,0 +1,166 @@
+//! Conservative language detection for pastes created without a specific
+//! format.
+//!
+//! Only unmistakable markers count: a shebang, a whole-document JSON value
+//! or HTML page, or a line that is idiomatic in exactly one language
+//! (`package main`, `fn main()`, `def f():`, ...). Content that matches no
+//! marker, or markers from more than one language, is left alone.
+
+use crate::PasteFormat;
+
+/// Line prefixes (after leading whitespace) that identify one language.
+const LINE_MARKERS: &[(PasteFormat, &[&str])] = &[
+    (
+        PasteFormat::Rust,
+        &["fn main()", "use std::", "#[derive(", "let mut ", "impl<"],
+    ),
+    (
+        PasteFormat::Go,
+        &["package main", "func main()", "import \"fmt\""],
+    ),
+    (PasteFormat::Python, &["if __name__ == \"__main__\":"]),
+    (
+        PasteFormat::Java,
+        &["public static void main(String", "System.out.println("],
+    ),
+    (
+        PasteFormat::Csharp,
+        &["using System;", "Console.WriteLine("],
+    ),
+    (PasteFormat::Cpp, &["#include <", "std::cout"]),
+    (PasteFormat::Javascript, &["console.log(", "module.exports"]),
+];
+
+/// Guess the format of `content`, or `None` when there is no unambiguous
+/// signal.
+pub fn detect_language(content: &str) -> Option<PasteFormat> {
+    let trimmed = content.trim_start();
+    // Markdown routinely embeds code in fences; never reclassify it.
+    if content.contains("```") {
+        return None;
+    }
+    if let Some(format) = detect_document(trimmed) {
+        return Some(format);
+    }
+
+    let mut found = None;
+    for line in content.lines().map(str::trim) {
+        let Some(format) = line_marker(line) else {
+            continue;
+        };
+        match found {
+            None => found = Some(format),
+            Some(seen) if seen == format => {}
+            Some(_) => return None,
+        }
+    }
+    found
+}
+
+/// Signals that hold for the document as a whole.
+fn detect_document(trimmed: &str) -> Option<PasteFormat> {
+    if let Some(shebang) = trimmed.strip_prefix("#!") {
+        let interpreter = shebang.lines().next().unwrap_or_default();
+        return [
+            ("python", PasteFormat::Python),
+            ("node", PasteFormat::Javascript),
+            ("ruby", PasteFormat::Ruby),
+            ("php", PasteFormat::Php),
+            ("bash", PasteFormat::Bash),
+            ("/sh", PasteFormat::Bash),
+            ("zsh", PasteFormat::Bash),
+        ]
+        .into_iter()
+        .find(|(needle, _)| interpreter.contains(needle))
+        .map(|(_, format)| format);
+    }
+    // Checked before line markers, which the diffed source would trip.
+    let has_line = |prefix: &str| trimmed.lines().any(|line| line.starts_with(prefix));
+    if has_line("diff --git ") || (has_line("--- ") && has_line("+++ ") && has_line("@@ -")) {
+        return Some(PasteFormat::Diff);
+    }
+    if trimmed.starts_with("<?php") {
+        return Some(PasteFormat::Php);
+    }
+    let head = trimmed
+        .get(..trimmed.len().min(16))
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    if head.starts_with("<!doctype html") || head.starts_with("<html") {
+        return Some(PasteFormat::Html);
+    }
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        let parsed = serde_json::from_str::<serde_json::Value>(trimmed).ok()?;
+        return (parsed.is_object() || parsed.is_array()).then_some(PasteFormat::Json);
+    }
+    None
+}
+
+fn line_marker(line: &str) -> Option<PasteFormat> {
+    if is_python_def(line) {
+        return Some(PasteFormat::Python);
+    }
+    LINE_MARKERS
+        .iter()
+        .find(|(_, prefixes)| prefixes.iter().any(|prefix| line.starts_with(prefix)))
+        .map(|(format, _)| *format)
+}
+
+/// `def name(...):`, which Ruby (no colon) and other languages lack.
+fn is_python_def(line: &str) -> bool {
+    line.strip_prefix("def ")
+        .is_some_and(|rest| rest.contains('(') && rest.trim_end().ends_with(':'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unambiguous_snippets_are_detected() {
+        let cases = [
+            (
+                "use std::io;\n\nfn main() {\n    println!(\"hi\");\n}\n",
+                PasteFormat::Rust,
+            ),
+            (
+                "import os\n\ndef main(argv):\n    print(argv)\n",
+                PasteFormat::Python,
+            ),
+            ("#!/usr/bin/env python3\nprint('x')\n", PasteFormat::Python),
+            (
+                "  {\"name\": \"copypaste\", \"tags\": [1, 2]}",
+                PasteFormat::Json,
+            ),
+            (
+                "<!DOCTYPE html>\n<html><body>hi</body></html>",
+                PasteFormat::Html,
+            ),
+            (
+                "package main\n\nfunc main() {\n\tfmt.Println(1)\n}\n",
+                PasteFormat::Go,
+            ),
+            ("#!/bin/sh\necho hi\n", PasteFormat::Bash),
+            (
+                "--- a/main.rs\n+++ b/main.rs\n@@ -1 +1 @@\n fn main() {}\n-old\n+new\n",
+                PasteFormat::Diff,
+            ),
+        ];
+        for (content, expected) in cases {
+            assert_eq!(detect_language(content), Some(expected), "{content}");
+        }
+    }
+
+    #[test]
+    fn ambiguous_or_plain_content_is_left_alone() {
+        for content in [
+            "Meeting notes\n- ship it\n- def not friday",
+            "{ not json",
+            "42",
+            "fn main() {}\ndef helper(x):\n",
+            "# Notes\n\n```rust\nfn main() {}\n```\n",
+        ] {
+            assert_eq!(detect_language(content), None, "{content}");
+        }
+    }
+}
@@ -0,0 +1,209 @@
+//! Signed, expiring, one-time download tokens.
+//!
+//! `POST /api/pastes/<id>/token` lets a paste's owner mint a token that
+//! authorises exactly one `GET /raw/<id>?token=...`. The token is
+//! `base64url(id.nonce.expires_at) "." base64url(HMAC-SHA256)`; the signing
+//! secret comes from `COPYPASTE_DOWNLOAD_TOKEN_SECRET` (a random per-process
+//! secret otherwise, so tokens don't survive a restart). Issued nonces are
+//! tracked in memory and removed on first use, which is what makes a token
+//! single-use.
+//!
+//! A token stands in for the attestation code, not the encryption key: the
+//! server never holds paste keys, so encrypted pastes still need `?key=`.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use rand::{rngs::OsRng, RngCore};
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Token lifetime used when the caller doesn't ask for one.
+pub const DEFAULT_TOKEN_TTL_SECS: u64 = 60 * 60;
+/// Longest lifetime a caller may request.
+pub const MAX_TOKEN_TTL_SECS: u64 = 24 * 60 * 60;
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum DownloadTokenError {
+    #[error("malformed download token")]
+    Malformed,
+    #[error("download token signature is invalid")]
+    BadSignature,
+    #[error("download token was issued for a different paste")]
+    WrongPaste,
+    #[error("download token has expired")]
+    Expired,
+    #[error("download token has already been used")]
+    AlreadyUsed,
+}
+
+/// A token whose signature, paste id and expiry have been checked but which
+/// has not been consumed yet.
+pub struct VerifiedToken {
+    nonce: String,
+}
+
+/// HMAC signing secret from the env var `var`, or 32 random bytes when it is
+/// unset or empty, in which case tokens don't survive a restart.
+pub fn hmac_secret_from_env(var: &str) -> Vec<u8> {
+    std::env::var(var)
+        .ok()
+        .filter(|v| !v.is_empty())
+        .map(String::into_bytes)
+        .unwrap_or_else(|| {
+            let mut bytes = vec![0u8; 32];
+            OsRng.fill_bytes(&mut bytes);
+            bytes
+        })
+}
+
+/// Signing secret plus the set of issued-but-unused nonces, kept on Rocket
+/// managed state.
+pub struct DownloadTokens {
+    secret: Vec<u8>,
+    issued: Mutex<HashMap<String, i64>>,
+}
+
+impl DownloadTokens {
+    pub fn new(secret: Vec<u8>) -> Self {
+        Self {
+            secret,
+            issued: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Build from `COPYPASTE_DOWNLOAD_TOKEN_SECRET`, falling back to a random
+    /// 32-byte secret.
+    pub fn from_env() -> Self {
+        let secret = hmac_secret_from_env("COPYPASTE_DOWNLOAD_TOKEN_SECRET");
+        Self::new(secret)
+    }
+
+    /// Mint a token for paste `id` valid for `ttl_secs` from `now`.
+    /// Returns the token and its expiry timestamp.
+    pub fn mint(&self, id: &str, ttl_secs: u64, now: i64) -> (String, i64) {
+        let mut nonce_bytes = [0u8; 16];
+        OsRng.fill_bytes(&mut nonce_bytes);
+        let nonce = hex::encode(nonce_bytes);
+        let expires_at = now + ttl_secs.min(MAX_TOKEN_TTL_SECS) as i64;
+
+        let payload = format!("{id}.{nonce}.{expires_at}");
+        let token = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(payload.as_bytes()),
+            URL_SAFE_NO_PAD.encode(self.sign(payload.as_bytes()))
+        );
+
+        let mut issued = self.issued.lock().unwrap();
+        issued.retain(|_, exp| *exp > now);
+        issued.insert(nonce, expires_at);
+        (token, expires_at)
+    }
+
+    /// Check the token's signature, paste id and expiry without consuming it.
+    pub fn verify(
+        &self,
+        token: &str,
+        id: &str,
+        now: i64,
+    ) -> Result<VerifiedToken, DownloadTokenError> {
+        let (payload_b64, sig_b64) = token.split_once('.').ok_or(DownloadTokenError::Malformed)?;
+        let payload = URL_SAFE_NO_PAD
+            .decode(payload_b64)
+            .map_err(|_| DownloadTokenError::Malformed)?;
+        let signature = URL_SAFE_NO_PAD
+            .decode(sig_b64)
+            .map_err(|_| DownloadTokenError::Malformed)?;
+        if !bool::from(self.sign(&payload).ct_eq(&signature)) {
+            return Err(DownloadTokenError::BadSignature);
+        }
+
+        let payload = String::from_utf8(payload).map_err(|_| DownloadTokenError::Malformed)?;
+        // Paste ids never contain '.', but split from the right to be safe.
+        let mut parts = payload.rsplitn(3, '.');
+        let expires_at = parts
+            .next()
+            .and_then(|v| v.parse::<i64>().ok())
+            .ok_or(DownloadTokenError::Malformed)?;
+        let nonce = parts.next().ok_or(DownloadTokenError::Malformed)?;
+        let token_id = parts.next().ok_or(DownloadTokenError::Malformed)?;
+
+        if token_id != id {
+            return Err(DownloadTokenError::WrongPaste);
+        }
+        if expires_at <= now {
+            return Err(DownloadTokenError::Expired);
+        }
+        Ok(VerifiedToken {
+            nonce: nonce.to_owned(),
+        })
+    }
+
+    /// Burn the token's nonce. Fails if it was already used (or never issued
+    /// by this process).
+    pub fn consume(&self, token: VerifiedToken) -> Result<(), DownloadTokenError> {
+        match self.issued.lock().unwrap().remove(&token.nonce) {
+            Some(_) => Ok(()),
+            None => Err(DownloadTokenError::AlreadyUsed),
+        }
+    }
+
+    fn sign(&self, payload: &[u8]) -> Vec<u8> {
+        let mut mac =
+            <HmacSha256 as Mac>::new_from_slice(&self.secret).expect("HMAC accepts any key size");
+        mac.update(payload);
+        mac.finalize().into_bytes().to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_is_single_use() {
+        let tokens = DownloadTokens::new(b"secret".to_vec());
+        let (token, expires_at) = tokens.mint("abc123", 60, 1_000);
+        assert_eq!(expires_at, 1_060);
+
+        let verified = tokens.verify(&token, "abc123", 1_010).expect("valid");
+        tokens.consume(verified).expect("first use");
+
+        let verified = tokens
+            .verify(&token, "abc123", 1_020)
+            .expect("still signed");
+        assert_eq!(
+            tokens.consume(verified).err(),
+            Some(DownloadTokenError::AlreadyUsed)
+        );
+    }
+
+    #[test]
+    fn rejects_tampered_foreign_and_expired_tokens() {
+        let tokens = DownloadTokens::new(b"secret".to_vec());
+        let (token, _) = tokens.mint("abc123", 60, 1_000);
+
+        assert_eq!(
+            tokens.verify(&token, "other", 1_010).err(),
+            Some(DownloadTokenError::WrongPaste)
+        );
+        assert_eq!(
+            tokens.verify(&token, "abc123", 1_060).err(),
+            Some(DownloadTokenError::Expired)
+        );
+
+        let other = DownloadTokens::new(b"different".to_vec());
+        assert_eq!(
+            other.verify(&token, "abc123", 1_010).err(),
+            Some(DownloadTokenError::BadSignature)
+        );
+        assert_eq!(
+            tokens.verify("not-a-token", "abc123", 1_010).err(),
+            Some(DownloadTokenError::Malformed)
+        );
+    }
+}
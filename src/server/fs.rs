@@ -0,0 +1,229 @@
+use std::env;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use rand::{distributions::Alphanumeric, Rng};
+use tokio::fs;
+use urlencoding::encode;
+
+use crate::{PersistenceAdapter, PersistenceError, StoredPaste};
+
+const DEFAULT_DIR: &str = "./data";
+
+/// Stores each paste as `{dir}/{id}.json` on local disk.
+///
+/// Writes go to a hidden temp file in the same directory and are renamed
+/// into place, so a crash mid-write never leaves a truncated paste behind.
+/// Ids are percent-encoded into file names, so a slug can't escape `dir`.
+#[derive(Clone)]
+pub struct FilePersistenceAdapter {
+    dir: PathBuf,
+}
+
+impl FilePersistenceAdapter {
+    /// Use `dir`, creating it if it doesn't exist yet.
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self, String> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)
+            .map_err(|error| format!("cannot create {}: {error}", dir.display()))?;
+        Ok(Self { dir })
+    }
+
+    pub fn from_env() -> Result<Arc<dyn PersistenceAdapter>, String> {
+        let dir = env::var("COPYPASTE_FS_DIR")
+            .ok()
+            .filter(|dir| !dir.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_DIR.to_string());
+        Ok(Arc::new(Self::new(dir)?))
+    }
+
+    fn path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", encode(id)))
+    }
+
+    fn temp_path(&self, id: &str) -> PathBuf {
+        let suffix: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(8)
+            .map(char::from)
+            .collect();
+        self.dir.join(format!(".{}.json.tmp-{suffix}", encode(id)))
+    }
+}
+
+async fn write_atomically(tmp: &Path, target: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    fs::write(tmp, bytes).await?;
+    if let Err(error) = fs::rename(tmp, target).await {
+        let _ = fs::remove_file(tmp).await;
+        return Err(error);
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl PersistenceAdapter for FilePersistenceAdapter {
+    async fn save(&self, id: &str, paste: &StoredPaste) -> Result<(), PersistenceError> {
+        let serialized = serde_json::to_vec(paste)
+            .map_err(|error| PersistenceError::Save(id.to_string(), error.to_string()))?;
+        write_atomically(&self.temp_path(id), &self.path(id), &serialized)
+            .await
+            .map_err(|error| PersistenceError::Save(id.to_string(), error.to_string()))
+    }
+
+    async fn load(&self, id: &str) -> Result<Option<StoredPaste>, PersistenceError> {
+        let bytes = match fs::read(self.path(id)).await {
+            Ok(bytes) => bytes,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(PersistenceError::Load(id.to_string(), error.to_string())),
+        };
+        let paste: StoredPaste = serde_json::from_slice(&bytes)
+            .map_err(|error| PersistenceError::Load(id.to_string(), error.to_string()))?;
+        Ok(Some(paste))
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), PersistenceError> {
+        match fs::remove_file(self.path(id)).await {
+            Ok(()) => Ok(()),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(()),
+            Err(error) => Err(PersistenceError::Delete(id.to_string(), error.to_string())),
+        }
+    }
+
+    async fn health_check(&self) -> Result<(), PersistenceError> {
+        match fs::metadata(&self.dir).await {
+            Ok(meta) if meta.is_dir() => Ok(()),
+            Ok(_) => Err(PersistenceError::Unavailable(format!(
+                "{} is not a directory",
+                self.dir.display()
+            ))),
+            Err(error) => Err(PersistenceError::Unavailable(format!(
+                "{}: {error}",
+                self.dir.display()
+            ))),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "file"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PasteFormat, PasteMetadata, StoredContent};
+    use tempfile::TempDir;
+
+    fn sample_paste(text: &str) -> StoredPaste {
+        StoredPaste {
+            content: StoredContent::Plain { text: text.into() },
+            format: PasteFormat::PlainText,
+            created_at: 1_700_000_000,
+            expires_at: None,
+            burn_after_reading: false,
+            max_reads: None,
+            metadata: PasteMetadata::default(),
+            bundle: None,
+            bundle_parent: None,
+            bundle_label: None,
+            not_before: None,
+            not_after: None,
+            persistence: None,
+            webhook: None,
+            is_live: false,
+            owner_token_hash: None,
+        }
+    }
+
+    fn text_of(paste: &StoredPaste) -> &str {
+        match &paste.content {
+            StoredContent::Plain { text } => text,
+            _ => panic!("expected plain content"),
+        }
+    }
+
+    #[tokio::test]
+    async fn save_load_delete_roundtrip() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path().join("not-yet-created");
+        let adapter = FilePersistenceAdapter::new(&dir).expect("creates missing dir");
+
+        adapter
+            .save("abc123", &sample_paste("hello"))
+            .await
+            .expect("save");
+        assert!(dir.join("abc123.json").is_file());
+
+        let loaded = adapter
+            .load("abc123")
+            .await
+            .expect("load")
+            .expect("present");
+        assert_eq!(text_of(&loaded), "hello");
+
+        adapter.delete("abc123").await.expect("delete");
+        assert!(adapter.load("abc123").await.expect("load").is_none());
+        adapter
+            .delete("abc123")
+            .await
+            .expect("deleting a missing file is fine");
+    }
+
+    #[tokio::test]
+    async fn save_replaces_via_rename_without_leftover_temp_files() {
+        let tmp = TempDir::new().unwrap();
+        let adapter = FilePersistenceAdapter::new(tmp.path()).unwrap();
+
+        adapter.save("id", &sample_paste("first")).await.unwrap();
+        adapter.save("id", &sample_paste("second")).await.unwrap();
+
+        let loaded = adapter.load("id").await.unwrap().unwrap();
+        assert_eq!(text_of(&loaded), "second");
+
+        let names: Vec<String> = std::fs::read_dir(tmp.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, vec!["id.json".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn failed_rename_removes_temp_file() {
+        let tmp = TempDir::new().unwrap();
+        let adapter = FilePersistenceAdapter::new(tmp.path()).unwrap();
+        // A directory at the target path makes the rename fail.
+        std::fs::create_dir(tmp.path().join("blocked.json")).unwrap();
+
+        let err = adapter
+            .save("blocked", &sample_paste("x"))
+            .await
+            .expect_err("rename onto a directory fails");
+        assert!(matches!(err, PersistenceError::Save(id, _) if id == "blocked"));
+
+        let leftovers = std::fs::read_dir(tmp.path())
+            .unwrap()
+            .filter(|entry| {
+                entry
+                    .as_ref()
+                    .unwrap()
+                    .file_name()
+                    .to_string_lossy()
+                    .contains(".tmp-")
+            })
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+
+    #[tokio::test]
+    async fn slugs_cannot_escape_the_directory() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path().join("store");
+        let adapter = FilePersistenceAdapter::new(&dir).unwrap();
+
+        adapter.save("../escape", &sample_paste("x")).await.unwrap();
+        assert!(!tmp.path().join("escape.json").exists());
+        assert!(adapter.load("../escape").await.unwrap().is_some());
+    }
+}
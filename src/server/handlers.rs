@@ -1,67 +1,110 @@
 use std::path::PathBuf;
+use std::time::Instant;
 
 use base64::{engine::general_purpose::STANDARD as BASE64_STANDARD, Engine};
+use once_cell::sync::OnceCell;
 use rocket::{
+    catchers,
     data::{Limits, ToByteUnit},
     delete,
     fs::FileServer,
     get,
-    http::Status,
+    http::{Accept, ContentType, Header, Status},
     patch, post, put,
     request::{FromRequest, Outcome},
     response::content,
     routes,
     serde::json::Json,
-    Build, Request, Rocket, State,
+    Build, Request, Responder, Rocket, State,
 };
 use subtle::ConstantTimeEq;
 
 use crate::{
-    create_paste_store, AttestationRequirement, EncryptionAlgorithm, PasteError, PasteFormat,
-    PasteMetadata, PersistenceLocator, SharedPasteStore, StoredContent, StoredPaste, WebhookConfig,
+    content_digest, create_paste_store, recorded_digest, AttestationRequirement, ContentEncoding,
+    CreatePasteError, EncryptionAlgorithm, IdTaken, PasteError, PasteFormat, PasteMetadata,
+    PersistenceError, PersistenceLocator, SharedPasteStore, StoredContent, StoredPaste,
+    WebhookConfig,
 };
 use sha2::{Digest, Sha256};
 
 use ed25519_dalek::{Signature, Verifier, VerifyingKey};
 use rand::Rng;
 
+use super::access_log::AccessLog;
 use super::api_keys::{
-    RateLimiter, RequireAdminAuth, SharedApiKeyStore, SharedRateLimiter, SqliteApiKeyStore,
+    OptionalApiKeyAuth, RateLimiter, RequireAdminAuth, SharedApiKeyStore, SharedRateLimiter,
+    SqliteApiKeyStore,
 };
-use super::attestation::{self, AttestationVerdict};
+use super::attestation::{self, AttestationVerdict, PromptTokens};
 use super::blockchain::{
     default_anchor_relayer, infer_attestation_ref, infer_retention_class, manifest_hash,
-    AnchorManifest, AnchorPayload, SharedAnchorRelayer,
+    AnchorContentMode, AnchorManifest, AnchorPayload, SharedAnchorRelayer,
 };
-use super::bundles::build_bundle_overview;
+use super::bundles::{build_bundle_overview, BundleArchive};
+use super::cache::{CachePolicy, Cached, IfNoneMatch};
+use super::config::{parse_size_bytes, Config};
 use super::cors::{api_preflight, Cors};
-use super::crypto::{decrypt_content, encrypt_content, DecryptError};
+use super::crypto::{decrypt_content, encrypt_content, DecryptError, PasteBinding};
+use super::detect::detect_language;
+use super::download_tokens::{DownloadTokens, DEFAULT_TOKEN_TTL_SECS, MAX_TOKEN_TTL_SECS};
+use super::headers::SecurityHeaders;
+use super::highlight;
+use super::hsts::Hsts;
+use super::maintenance::{maintenance_unavailable, MaintenanceMode, WritesAllowed};
+use super::metrics::{metrics_endpoint, Metrics, RequestMetrics};
 use super::models::{
-    AnchorRequest, AnchorResponse, ApiError, ApiKeyInfo, AuthChallengeResponse, AuthLoginRequest,
-    AuthLoginResponse, AuthLogoutResponse, CreateApiKeyRequest, CreateApiKeyResponse,
-    CreatePasteRequest, CreatePasteResponse, FinalizePasteRequest, FinalizePasteResponse,
-    ListApiKeysResponse, PasteAttestationInfo, PasteEncryptionInfo, PastePersistenceInfo,
-    PasteStegoInfo, PasteTimeLockInfo, PasteViewQuery, PasteViewResponse, PasteWebhookInfo,
-    PersistenceRequest, RevokeApiKeyResponse, StatsSummaryResponse, StegoRequest, TimeLockRequest,
-    UpdatePasteRequest, UpdatePasteResponse, UserPasteCountResponse, UserPasteListItem,
-    UserPasteListResponse, WebhookRequest, WorkspacePasteItem, WorkspacePasteListResponse,
+    AnchorRequest, AnchorResponse, AnchorVerifyResponse, ApiError, ApiKeyInfo,
+    AuthChallengeResponse, AuthLoginRequest, AuthLoginResponse, AuthLogoutResponse,
+    CreateApiKeyRequest, CreateApiKeyResponse, CreatePasteRequest, CreatePasteResponse,
+    CreatedBundle, CreatedBundleChild, DownloadTokenResponse, FinalizePasteRequest,
+    FinalizePasteResponse, FormatUsageResponse, ImportLineResult, ImportPasteRecord,
+    ListApiKeysResponse, MaintenanceModeRequest, MaintenanceModeResponse, PasskeyChallengeResponse,
+    PasskeyGrantResponse, PasskeyVerifyRequest, PasteAttestationInfo, PasteEncryptionInfo,
+    PasteExistsResponse, PastePersistenceInfo, PasteStegoInfo, PasteTimeLockInfo, PasteViewQuery,
+    PasteViewResponse, PasteWebhookInfo, PersistenceRequest, RawPasteQuery, RevokeApiKeyResponse,
+    StatsSummaryResponse, StegoRequest, TimeLockRequest, UpdatePasteRequest, UpdatePasteResponse,
+    UserPasteCountResponse, UserPasteListItem, UserPasteListResponse, WebhookRequest,
+    WorkspacePasteItem, WorkspacePasteListResponse,
+};
+use super::rate_limit::{
+    too_many_requests, CreateQuota, CreateRateLimit, DailyPasteQuota, PasteRateLimiter,
+    ReadRateLimit,
 };
-use super::rate_limit::{CreateRateLimit, PasteRateLimiter, ReadRateLimit};
 use super::render::{
     render_attestation_prompt, render_expired, render_invalid_key, render_key_prompt,
-    render_paste_view, render_time_locked, StoredPasteView,
+    render_paste_view, render_time_locked, StoredPasteView, Theme,
+};
+use super::robots::{robots_txt, Indexing};
+use super::sessions::{
+    BearerToken, OptionalUserSession, RequireUserSession, SessionStore, SharedSessionStore,
+};
+use super::shutdown::ShutdownFlush;
+use super::slugs::{validate_custom_id, PastePath, ReservedSlugs};
+use super::stego::{embed_payload, parse_data_uri, CarrierFormat, StegoCarrierSource, StegoError};
+use super::sweeper::Sweeper;
+use super::time::{
+    current_timestamp, evaluate_time_lock, format_timestamp, parse_duration, parse_timestamp,
+    TimeLockState,
 };
-use super::sessions::{BearerToken, RequireUserSession, SessionStore, SharedSessionStore};
-use super::stego::{embed_payload, parse_data_uri, StegoCarrierSource};
-use super::time::{current_timestamp, evaluate_time_lock, parse_timestamp, TimeLockState};
 use super::tor::{OnionAccess, TorConfig};
-use super::webhook::{trigger_webhook, validate_webhook_url, WebhookClient, WebhookEvent};
+use super::webauthn::{PasskeyError, PasskeyProof, Passkeys};
+use super::webhook::{
+    trigger_webhook, validate_webhook_url, WebhookClient, WebhookEvent, WebhookTargets,
+};
 use serde::{Deserialize, Serialize};
 use utoipa::{OpenApi, ToSchema};
 use utoipa_scalar::{Scalar, Servable};
 
 pub fn build_rocket(store: SharedPasteStore) -> Rocket<Build> {
-    let tor_config = TorConfig::from_env();
+    build_rocket_with_config(store, &Config::from_env())
+}
+
+/// Build the server from a resolved [`Config`]. CORS and Tor settings come
+/// from `config`; knobs not yet threaded through are read from the env
+/// vars [`Config::bridge_to_env`] exports.
+pub fn build_rocket_with_config(store: SharedPasteStore, config: &Config) -> Rocket<Build> {
+    STARTED_AT.get_or_init(Instant::now);
+    let tor_config = TorConfig::from(&config.tor);
     let api_key_store: SharedApiKeyStore = std::sync::Arc::new(
         SqliteApiKeyStore::in_memory().expect("failed to initialise API key store"),
     );
@@ -69,13 +112,30 @@ pub fn build_rocket(store: SharedPasteStore) -> Rocket<Build> {
     let webhook_client = WebhookClient::new();
     let session_store: SharedSessionStore = std::sync::Arc::new(SessionStore::new());
     let paste_rate_limiter = PasteRateLimiter::from_env();
+    let maintenance = MaintenanceMode::from_env();
+    let daily_quota = DailyPasteQuota::from_env();
+    let download_tokens = DownloadTokens::from_env();
+    let prompt_tokens = PromptTokens::from_env();
+    let passkeys = Passkeys::from_env();
+    let indexing = Indexing::from_env();
+    if highlight::prewarm_from_env() {
+        highlight::prewarm();
+    }
+
+    // Reject oversized bodies while streaming them in rather than after the
+    // whole request is buffered.
+    let body_limit = (max_paste_size() as u64).saturating_add(BODY_LIMIT_OVERHEAD);
 
     // Merge onto Rocket's standard figment so ROCKET_ADDRESS / ROCKET_PORT /
     // Rocket.toml still apply — `.configure(Config { ..Default::default() })`
     // would silently discard them (Default binds 127.0.0.1, which broke Fly).
     rocket::custom(
-        rocket::Config::figment()
-            .merge(("limits", Limits::default().limit("json", 11u64.mebibytes()))),
+        rocket::Config::figment().merge((
+            "limits",
+            Limits::default()
+                .limit("json", body_limit.bytes())
+                .limit("bytes", body_limit.bytes()),
+        )),
     )
     .manage(store)
     .manage(default_anchor_relayer())
@@ -85,22 +145,48 @@ pub fn build_rocket(store: SharedPasteStore) -> Rocket<Build> {
     .manage(webhook_client)
     .manage(session_store)
     .manage(paste_rate_limiter)
-    .attach(Cors)
+    .manage(maintenance)
+    .manage(daily_quota)
+    .manage(download_tokens)
+    .manage(prompt_tokens)
+    .manage(passkeys)
+    .manage(indexing)
+    .manage(Metrics::from_env())
+    .attach(Cors::new(&config.cors.origins))
+    .attach(Hsts::from_env())
+    .attach(SecurityHeaders::from_env())
+    .attach(indexing)
+    .attach(Sweeper::from_env())
+    .attach(ShutdownFlush)
+    .attach(RequestMetrics)
+    .attach(AccessLog::from_config(&config.logging))
+    .register("/", catchers![maintenance_unavailable, too_many_requests])
     .mount(
         "/",
         routes![
             api_preflight,
             index,
             about,
+            robots_txt,
             create,
             create_api,
+            create_raw_api,
             update_api,
             finalize_api,
+            delete_api,
             anchor_api,
+            anchor_verify_api,
             show_api,
+            paste_meta_api,
+            paste_exists_api,
+            webauthn_challenge_api,
+            webauthn_verify_api,
             show,
             show_raw,
+            bundle_zip_api,
+            create_download_token_api,
             stats_summary_api,
+            stats_formats_api,
             auth_challenge_api,
             auth_login_api,
             auth_logout_api,
@@ -112,6 +198,10 @@ pub fn build_rocket(store: SharedPasteStore) -> Rocket<Build> {
             admin_create_key_api,
             admin_list_keys_api,
             admin_delete_key_api,
+            admin_maintenance_status_api,
+            admin_maintenance_update_api,
+            import_api,
+            metrics_endpoint,
             openapi_json,
             spa_fallback
         ],
@@ -120,9 +210,9 @@ pub fn build_rocket(store: SharedPasteStore) -> Rocket<Build> {
     .mount("/static", FileServer::from("static"))
 }
 
-pub async fn launch() -> Result<(), Box<dyn std::error::Error>> {
+pub async fn launch(config: Config) -> Result<(), Box<dyn std::error::Error>> {
     let store = create_paste_store();
-    build_rocket(store).launch().await?;
+    build_rocket_with_config(store, &config).launch().await?;
     Ok(())
 }
 
@@ -138,12 +228,22 @@ pub async fn launch() -> Result<(), Box<dyn std::error::Error>> {
     paths(
         create,
         create_api,
+        create_raw_api,
         update_api,
         finalize_api,
+        delete_api,
         show_api,
+        paste_meta_api,
+        paste_exists_api,
+        webauthn_challenge_api,
+        webauthn_verify_api,
         show,
+        bundle_zip_api,
+        create_download_token_api,
         anchor_api,
+        anchor_verify_api,
         stats_summary_api,
+        stats_formats_api,
         auth_challenge_api,
         auth_login_api,
         auth_logout_api,
@@ -151,15 +251,25 @@ pub async fn launch() -> Result<(), Box<dyn std::error::Error>> {
         user_paste_list_api,
         workspace_pastes_api,
         health_detailed_api,
+        import_api,
     ),
     components(schemas(
         CreatePasteRequest,
         CreatePasteResponse,
+        CreatedBundle,
+        CreatedBundleChild,
+        DownloadTokenResponse,
+        ImportPasteRecord,
+        ImportLineResult,
         UpdatePasteRequest,
         UpdatePasteResponse,
         FinalizePasteRequest,
         FinalizePasteResponse,
         PasteViewResponse,
+        PasteExistsResponse,
+        PasskeyChallengeResponse,
+        PasskeyVerifyRequest,
+        PasskeyGrantResponse,
         PasteEncryptionInfo,
         PasteTimeLockInfo,
         PasteAttestationInfo,
@@ -168,6 +278,7 @@ pub async fn launch() -> Result<(), Box<dyn std::error::Error>> {
         PasteStegoInfo,
         AnchorRequest,
         AnchorResponse,
+        AnchorVerifyResponse,
         StatsSummaryResponse,
         AuthChallengeResponse,
         AuthLoginRequest,
@@ -197,10 +308,11 @@ pub async fn launch() -> Result<(), Box<dyn std::error::Error>> {
         crate::AttestationRequirement,
         crate::PersistenceLocator,
         crate::WebhookConfig,
-        super::models::FormatUsageResponse,
+        FormatUsageResponse,
         super::models::EncryptionUsageResponse,
         super::models::DailyCountResponse,
         super::blockchain::AnchorManifest,
+        super::blockchain::AnchorContentMode,
         super::blockchain::AnchorReceipt,
     ))
 )]
@@ -223,6 +335,9 @@ struct HealthResponse {
     version: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     commit: Option<String>,
+    uptime_seconds: u64,
+    /// `memory`, `redis`, `vault`, `s3` or `file`.
+    persistence_backend: String,
 }
 
 #[derive(Serialize, Deserialize, ToSchema)]
@@ -234,6 +349,8 @@ struct DetailedHealthResponse {
     commit: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     commit_message: Option<String>,
+    uptime_seconds: u64,
+    persistence_backend: String,
     services: ServiceHealth,
 }
 
@@ -251,13 +368,22 @@ struct ServiceStatus {
     message: Option<String>,
 }
 
+/// When the server was built, so health probes can spot restarts.
+static STARTED_AT: OnceCell<Instant> = OnceCell::new();
+
+fn uptime_seconds() -> u64 {
+    STARTED_AT.get_or_init(Instant::now).elapsed().as_secs()
+}
+
 #[get("/health")]
-async fn health_api() -> Json<HealthResponse> {
+async fn health_api(store: &State<SharedPasteStore>) -> Json<HealthResponse> {
     Json(HealthResponse {
         status: "ok".to_string(),
         timestamp: current_timestamp(),
         version: env!("CARGO_PKG_VERSION").to_string(),
         commit: option_env!("GIT_COMMIT").map(String::from),
+        uptime_seconds: uptime_seconds(),
+        persistence_backend: store.persistence_backend().to_string(),
     })
 }
 
@@ -268,11 +394,24 @@ async fn health_api() -> Json<HealthResponse> {
 )]
 #[get("/api/health")]
 async fn health_detailed_api(store: &State<SharedPasteStore>) -> Json<DetailedHealthResponse> {
-    // Check storage
+    // Check storage: the in-memory cache plus a ping of the persistence
+    // backend. An unreachable backend only degrades us, since cached pastes
+    // are still served.
     let stats = store.stats().await;
-    let storage_status = ServiceStatus {
-        status: "ok".to_string(),
-        message: Some(format!("Total pastes: {}", stats.total_pastes)),
+    let probe = tokio::time::timeout(std::time::Duration::from_secs(2), store.health_check()).await;
+    let storage_status = match probe {
+        Ok(Ok(())) => ServiceStatus {
+            status: "ok".to_string(),
+            message: Some(format!("Total pastes: {}", stats.total_pastes)),
+        },
+        Ok(Err(e)) => ServiceStatus {
+            status: "degraded".to_string(),
+            message: Some(e.to_string()),
+        },
+        Err(_) => ServiceStatus {
+            status: "degraded".to_string(),
+            message: Some("Persistence backend timed out".to_string()),
+        },
     };
 
     // Check crypto verifier
@@ -319,6 +458,8 @@ async fn health_detailed_api(store: &State<SharedPasteStore>) -> Json<DetailedHe
             .unwrap_or_else(|| "unknown".to_string()),
         commit: option_env!("GIT_COMMIT").map(String::from),
         commit_message: option_env!("GIT_COMMIT_MESSAGE").map(String::from),
+        uptime_seconds: uptime_seconds(),
+        persistence_backend: store.persistence_backend().to_string(),
         services: ServiceHealth {
             backend: ServiceStatus {
                 status: "ok".to_string(),
@@ -347,6 +488,32 @@ async fn stats_summary_api(
     Json(stats.into())
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/stats/formats",
+    responses((status = 200, description = "Paste counts per format, most used first", body = [FormatUsageResponse]))
+)]
+#[get("/api/stats/formats")]
+async fn stats_formats_api(
+    store: &State<SharedPasteStore>,
+    onion: OnionAccess,
+) -> Json<Vec<FormatUsageResponse>> {
+    if onion.suppress_logs() {
+        rocket::info!("stats_formats accessed via onion host");
+    }
+    let stats = store.stats().await;
+    Json(
+        stats
+            .formats
+            .into_iter()
+            .map(|usage| FormatUsageResponse {
+                format: usage.format,
+                count: usage.count,
+            })
+            .collect(),
+    )
+}
+
 #[utoipa::path(
     get,
     path = "/api/auth/challenge",
@@ -483,12 +650,13 @@ async fn user_paste_count_api(
     }
     check_pubkey_hash_param(&session, pubkey_hash.as_deref())?;
 
-    // Count pastes owned by the authenticated user only.
+    // Count pastes owned by the authenticated user only. `peek_paste` keeps
+    // enumeration from touching burn-after-reading or expiry state.
     let all_pastes = store.get_all_paste_ids().await;
     let mut count = 0;
 
     for id in all_pastes {
-        if let Ok(paste) = store.get_paste(&id).await {
+        if let Ok(paste) = store.peek_paste(&id).await {
             if paste.metadata.owner_pubkey_hash.as_deref() == Some(session.pubkey_hash.as_str()) {
                 count += 1;
             }
@@ -498,34 +666,57 @@ async fn user_paste_count_api(
     Ok(Json(UserPasteCountResponse { paste_count: count }))
 }
 
+/// Page size of `GET /api/user/pastes` when `limit` is omitted.
+const DEFAULT_USER_PASTE_PAGE: usize = 50;
+/// Largest page `GET /api/user/pastes` returns; bigger `limit`s are clamped.
+const MAX_USER_PASTE_PAGE: usize = 200;
+
 #[utoipa::path(
     get,
     path = "/api/user/pastes",
-    params(("pubkey_hash" = Option<String>, Query, description = "Optional; must match the session's pubkey hash")),
+    params(
+        ("pubkey_hash" = Option<String>, Query, description = "Optional; must match the session's pubkey hash"),
+        ("limit" = Option<usize>, Query, description = "Page size (default 50, at most 200)"),
+        ("offset" = Option<usize>, Query, description = "Pastes to skip; use `next` from the previous page"),
+    ),
     responses(
         (status = 200, description = "User paste list response", body = UserPasteListResponse),
+        (status = 400, description = "limit is zero", body = ApiError),
         (status = 401, description = "Missing or invalid session token"),
         (status = 403, description = "pubkey_hash does not match the session", body = ApiError),
     )
 )]
-#[get("/api/user/pastes?<pubkey_hash>")]
+#[get("/api/user/pastes?<pubkey_hash>&<limit>&<offset>")]
 async fn user_paste_list_api(
     store: &State<SharedPasteStore>,
     session: RequireUserSession,
     pubkey_hash: Option<String>,
+    limit: Option<usize>,
+    offset: Option<usize>,
     onion: OnionAccess,
 ) -> Result<Json<UserPasteListResponse>, (Status, Json<ApiError>)> {
     if onion.suppress_logs() {
         rocket::info!("user paste list accessed via onion host");
     }
     check_pubkey_hash_param(&session, pubkey_hash.as_deref())?;
+    let limit = match limit {
+        Some(0) => {
+            return Err(to_api_err(
+                Status::BadRequest,
+                "limit must be at least 1".to_string(),
+            ))
+        }
+        Some(n) => n.min(MAX_USER_PASTE_PAGE),
+        None => DEFAULT_USER_PASTE_PAGE,
+    };
+    let offset = offset.unwrap_or(0);
 
     // List pastes owned by the authenticated user only.
     let all_pastes = store.get_all_paste_ids().await;
     let mut user_pastes = Vec::new();
 
     for id in all_pastes {
-        if let Ok(paste) = store.get_paste(&id).await {
+        if let Ok(paste) = store.peek_paste(&id).await {
             if paste.metadata.owner_pubkey_hash.as_deref() == Some(session.pubkey_hash.as_str()) {
                 let retention_minutes = paste.expires_at.map(|exp| {
                     let now = current_timestamp();
@@ -551,11 +742,22 @@ async fn user_paste_list_api(
         }
     }
 
-    // Sort by created_at descending (newest first)
-    user_pastes.sort_by_key(|p| std::cmp::Reverse(p.created_at));
+    // Newest first; the id tie-break keeps pages stable for pastes created
+    // in the same second.
+    user_pastes.sort_by(|a, b| {
+        b.created_at
+            .cmp(&a.created_at)
+            .then_with(|| a.id.cmp(&b.id))
+    });
+
+    let total = user_pastes.len();
+    let pastes: Vec<_> = user_pastes.into_iter().skip(offset).take(limit).collect();
+    let next = (offset.saturating_add(pastes.len()) < total).then(|| offset + pastes.len());
 
     Ok(Json(UserPasteListResponse {
-        pastes: user_pastes,
+        pastes,
+        total,
+        next,
     }))
 }
 
@@ -579,7 +781,7 @@ async fn workspace_pastes_api(
     let mut pastes = Vec::new();
 
     for id in all_pastes {
-        if let Ok(paste) = store.get_paste(&id).await {
+        if let Ok(paste) = store.peek_paste(&id).await {
             if paste.metadata.workspace.as_deref() == Some(name.as_str())
                 && paste.metadata.owner_pubkey_hash.as_deref() == Some(session.pubkey_hash.as_str())
             {
@@ -608,6 +810,7 @@ async fn workspace_pastes_api(
         (status = 400, description = "Invalid request"),
         (status = 404, description = "Paste not found"),
         (status = 410, description = "Paste expired"),
+        (status = 503, description = "Read-only maintenance mode", body = ApiError),
     )
 )]
 #[post("/api/pastes/<id>/anchor", data = "<body>")]
@@ -617,6 +820,7 @@ async fn anchor_api(
     id: String,
     body: Option<Json<AnchorRequest>>,
     onion: OnionAccess,
+    _writes: WritesAllowed,
 ) -> Result<Json<AnchorResponse>, (Status, String)> {
     let request = body.map(|json| json.into_inner()).unwrap_or_default();
 
@@ -633,7 +837,8 @@ async fn anchor_api(
         ));
     }
 
-    let manifest = AnchorManifest::from_paste(id.clone(), &paste);
+    let content_mode = request.content_mode;
+    let manifest = AnchorManifest::from_paste_with_mode(id.clone(), &paste, content_mode);
     let hash = manifest_hash(&manifest).map_err(|error| {
         (
             Status::InternalServerError,
@@ -661,11 +866,16 @@ async fn anchor_api(
         .await
         .map_err(|error| (Status::BadGateway, format!("Relayer error: {error}")))?;
 
+    if let Err(err) = store.set_anchored_hash(&id, &hash).await {
+        log::warn!("Failed to record anchor hash for {id}: {err}");
+    }
+
     let response = AnchorResponse {
         paste_id: id,
         hash,
         retention_class,
         attestation_ref,
+        content_mode,
         manifest,
         receipt,
     };
@@ -673,6 +883,101 @@ async fn anchor_api(
     Ok(Json(response))
 }
 
+/// Check whether a paste still matches an anchored manifest hash.
+///
+/// Recomputes the manifest hash of the paste as stored now and compares it
+/// with `?hash=`, or with the hash recorded by the last successful
+/// `POST /api/pastes/{id}/anchor` when no hash is given. Both content modes
+/// are tried, so `digest_only` anchors verify too.
+#[utoipa::path(
+    get,
+    path = "/api/pastes/{id}/anchor/verify",
+    params(
+        ("id" = String, Path, description = "Paste identifier"),
+        ("hash" = Option<String>, Query, description = "Expected manifest hash (hex)"),
+    ),
+    responses(
+        (status = 200, description = "Comparison result", body = AnchorVerifyResponse),
+        (status = 400, description = "No hash given and the paste was never anchored"),
+        (status = 404, description = "Paste not found"),
+        (status = 410, description = "Paste expired"),
+    )
+)]
+#[get("/api/pastes/<id>/anchor/verify?<hash>")]
+async fn anchor_verify_api(
+    store: &State<SharedPasteStore>,
+    id: String,
+    hash: Option<String>,
+    onion: OnionAccess,
+) -> Result<Json<AnchorVerifyResponse>, (Status, String)> {
+    // `peek_paste` so verifying never counts as a read.
+    let paste = match store.peek_paste(&id).await {
+        Ok(paste) => paste,
+        Err(PasteError::NotFound(_)) => return Err((Status::NotFound, "Paste not found".into())),
+        Err(PasteError::Expired(_)) => return Err((Status::Gone, "Paste expired".into())),
+    };
+
+    if paste.metadata.tor_access_only && !onion.is_onion() {
+        return Err((
+            Status::Forbidden,
+            "This paste can only be accessed via the Tor hidden service".into(),
+        ));
+    }
+
+    let expected_hash = hash
+        .map(|hash| hash.trim().to_ascii_lowercase())
+        .filter(|hash| !hash.is_empty())
+        .or_else(|| paste.metadata.anchored_hash.clone())
+        .ok_or((
+            Status::BadRequest,
+            "Pass ?hash= or anchor the paste first".to_string(),
+        ))?;
+
+    let mut current = None;
+    for content_mode in [AnchorContentMode::Full, AnchorContentMode::DigestOnly] {
+        let manifest = AnchorManifest::from_paste_with_mode(id.clone(), &paste, content_mode);
+        let hash = manifest_hash(&manifest).map_err(|error| {
+            (
+                Status::InternalServerError,
+                format!("Failed to hash manifest: {error}"),
+            )
+        })?;
+        let matches = hash == expected_hash;
+        if matches || current.is_none() {
+            current = Some((content_mode, hash));
+        }
+        if matches {
+            break;
+        }
+    }
+    let (content_mode, current_hash) = current.expect("at least one content mode");
+
+    Ok(Json(AnchorVerifyResponse {
+        matches: current_hash == expected_hash,
+        content_mode,
+        current_hash,
+        expected_hash,
+    }))
+}
+
+/// Paste content cap when `COPYPASTE_MAX_PASTE_SIZE` is unset or invalid.
+const DEFAULT_MAX_PASTE_SIZE: usize = 10 << 20;
+
+/// Headroom on Rocket's body limits over the content cap, for JSON escaping
+/// and the non-content request fields.
+const BODY_LIMIT_OVERHEAD: u64 = 1 << 20;
+
+/// Maximum paste content length in bytes, from `COPYPASTE_MAX_PASTE_SIZE`
+/// (`"512kb"`, `"10mb"` or a plain byte count).
+fn max_paste_size() -> usize {
+    std::env::var("COPYPASTE_MAX_PASTE_SIZE")
+        .ok()
+        .and_then(|v| parse_size_bytes(&v))
+        .and_then(|n| usize::try_from(n).ok())
+        .filter(|n| *n > 0)
+        .unwrap_or(DEFAULT_MAX_PASTE_SIZE)
+}
+
 /// Convert a status code to a machine-readable error code string.
 fn status_to_code(status: Status) -> &'static str {
     match status.code {
@@ -727,18 +1032,25 @@ impl<'r> FromRequest<'r> for PasteKeyHeader {
     params(
         ("id" = String, Path, description = "Paste identifier"),
         ("X-Paste-Key" = Option<String>, Header, description = "Decryption key (takes precedence over ?key=)"),
+        ("encoding" = Option<String>, Query, description = "`utf8` (default) returns `content`; `base64` returns `content_b64` instead"),
+        ("metadata_only" = Option<bool>, Query, description = "Return only metadata (no key needed, never burns)"),
     ),
     responses(
         (status = 200, description = "Paste content", body = PasteViewResponse),
+        (status = 400, description = "Unsupported encoding", body = ApiError),
         (status = 401, description = "Key required", body = ApiError),
-        (status = 403, description = "Invalid key", body = ApiError),
+        (status = 403, description = "Invalid key, or Tor-only paste requested off the onion host", body = ApiError),
         (status = 404, description = "Paste not found", body = ApiError),
+        (status = 410, description = "Time-lock window has closed", body = ApiError),
+        (status = 423, description = "Time-locked until `not_before`", body = ApiError),
     )
 )]
+#[allow(clippy::too_many_arguments)] // Rocket request guards
 #[get("/api/pastes/<id>?<query..>", rank = 1)]
 async fn show_api(
     store: &State<SharedPasteStore>,
     http: &State<WebhookClient>,
+    passkeys: &State<Passkeys>,
     id: String,
     query: PasteViewQuery,
     key_header: PasteKeyHeader,
@@ -749,6 +1061,35 @@ async fn show_api(
 
     // Header key wins over the query-string key (see handler docs above).
     let key = key_header.0.or_else(|| query.key.clone());
+    json_paste_view(store, http, passkeys, id, &query, key.as_deref(), &onion).await
+}
+
+/// The JSON API read shared by `show_api` and a `show` request that
+/// accepts `application/json`.
+async fn json_paste_view(
+    store: &State<SharedPasteStore>,
+    http: &State<WebhookClient>,
+    passkeys: &Passkeys,
+    id: String,
+    query: &PasteViewQuery,
+    key: Option<&str>,
+    onion: &OnionAccess,
+) -> Result<Json<PasteViewResponse>, (Status, Json<ApiError>)> {
+    // Validate before touching the paste so a typo can't burn it.
+    let base64_content = match query.encoding.as_deref().map(str::to_ascii_lowercase) {
+        None => false,
+        Some(enc) if enc == "utf8" || enc == "utf-8" => false,
+        Some(enc) if enc == "base64" => true,
+        Some(enc) => {
+            return Err((
+                Status::BadRequest,
+                Json(ApiError::new(
+                    "unsupported_encoding",
+                    format!("Unsupported encoding '{enc}'; expected 'utf8' or 'base64'"),
+                )),
+            ));
+        }
+    };
 
     let paste = match store.get_paste(&id).await {
         Ok(paste) => paste,
@@ -778,19 +1119,334 @@ async fn show_api(
         ));
     }
 
+    // Metadata-only peek: report format/encryption/expiry/gating without
+    // decrypting, enforcing gates or consuming a burn-after-reading paste.
+    if query.metadata_only.unwrap_or(false) {
+        return Ok(Json(metadata_view(id, paste)));
+    }
+
+    let passkey = passkeys.proof(query.webauthn.as_deref(), &id, current_timestamp());
+    let text = unlock_for_api(&id, &paste, query, passkey, key).await?;
+
+    // A successful API read counts against the read limit exactly like the
+    // HTML route. Readers that decrypted but lost the race for the last
+    // allowed read get a 404. The response reports the access count as
+    // loaded, i.e. before this read.
+    let Some(receipt) = store.consume_read(&id).await else {
+        return Err((
+            Status::NotFound,
+            Json(ApiError::new(
+                "paste_not_found",
+                format!("Paste '{}' not found", id),
+            )),
+        ));
+    };
+    if receipt.consumed() {
+        fire_burn_webhooks(http, &paste, &id);
+    }
+
+    let size_bytes = served_size(&paste, &text);
+    let digest = content_digest(&text);
+    let tampered = paste_digest_mismatch(&id, &paste, &digest);
+    let encrypted = !matches!(paste.content, StoredContent::Plain { .. });
+    let (content, content_b64, encoding) = if paste.metadata.content_encoding.is_some() {
+        // Binary pastes are already stored as base64.
+        (None, Some(text), "base64")
+    } else if base64_content {
+        (
+            None,
+            Some(BASE64_STANDARD.encode(text.as_bytes())),
+            "base64",
+        )
+    } else {
+        (Some(text), None, "utf8")
+    };
+
+    let mut response = paste_view_response(
+        id,
+        paste,
+        content,
+        content_b64,
+        encoding,
+        receipt.reads_remaining,
+        Some(size_bytes),
+    );
+    response.tampered = tampered;
+    if encrypted {
+        // The reader holds the key, so the digest of what they decrypted
+        // gives nothing away.
+        response.content_sha256 = Some(digest);
+    }
+    Ok(Json(response))
+}
+
+/// Fetch a paste's metadata without its content.
+///
+/// Reads through a non-consuming peek: no key is needed, nothing is
+/// decrypted, the read is not counted and a burn-after-reading paste
+/// survives. A time-locked paste reports its window in `timeLock` rather
+/// than failing.
+#[utoipa::path(
+    get,
+    path = "/api/pastes/{id}/meta",
+    params(("id" = String, Path, description = "Paste identifier")),
+    responses(
+        (status = 200, description = "Paste metadata", body = PasteViewResponse),
+        (status = 403, description = "Tor-only paste requested off the onion host", body = ApiError),
+        (status = 404, description = "Paste not found", body = ApiError),
+    )
+)]
+#[get("/api/pastes/<id>/meta")]
+async fn paste_meta_api(
+    store: &State<SharedPasteStore>,
+    id: String,
+    onion: OnionAccess,
+    _rate: ReadRateLimit,
+) -> Result<Json<PasteViewResponse>, (Status, Json<ApiError>)> {
+    let paste = store.peek_paste(&id).await.map_err(|_| {
+        (
+            Status::NotFound,
+            Json(ApiError::new(
+                "paste_not_found",
+                format!("Paste '{id}' not found"),
+            )),
+        )
+    })?;
+    if paste.metadata.tor_access_only && !onion.is_onion() {
+        return Err((
+            Status::Forbidden,
+            Json(ApiError::new(
+                "tor_only",
+                "This paste is only accessible via its Tor onion address",
+            )),
+        ));
+    }
+    Ok(Json(metadata_view(id, paste)))
+}
+
+/// Report whether a paste can still be read, without reading it.
+///
+/// Lets a sender poll a burn-after-reading paste: the check goes through a
+/// non-consuming peek, so nothing is decrypted, counted or burned, and no
+/// content or metadata is returned.
+#[utoipa::path(
+    get,
+    path = "/api/pastes/{id}/exists",
+    params(("id" = String, Path, description = "Paste identifier")),
+    responses(
+        (status = 200, description = "Paste state", body = PasteExistsResponse),
+        (status = 403, description = "Tor-only paste requested off the onion host", body = ApiError),
+    )
+)]
+#[get("/api/pastes/<id>/exists")]
+async fn paste_exists_api(
+    store: &State<SharedPasteStore>,
+    id: String,
+    onion: OnionAccess,
+    _rate: ReadRateLimit,
+) -> Result<Json<PasteExistsResponse>, (Status, Json<ApiError>)> {
+    let state = |exists, expired, consumed| {
+        Json(PasteExistsResponse {
+            exists,
+            expired,
+            consumed,
+        })
+    };
+    match store.peek_paste(&id).await {
+        Ok(paste) if paste.metadata.tor_access_only && !onion.is_onion() => Err((
+            Status::Forbidden,
+            Json(ApiError::new(
+                "tor_only",
+                "This paste is only accessible via its Tor onion address",
+            )),
+        )),
+        Ok(_) => Ok(state(true, false, false)),
+        Err(PasteError::Expired(_)) => Ok(state(false, true, false)),
+        Err(PasteError::NotFound(_)) => Ok(state(false, false, store.was_consumed(&id).await)),
+    }
+}
+
+/// Start a passkey ceremony for a paste gated by WebAuthn attestation.
+///
+/// Pass `options` to `navigator.credentials.get()` and send the result to
+/// `/verify` with `challengeId` within five minutes.
+#[utoipa::path(
+    post,
+    path = "/api/pastes/{id}/webauthn/challenge",
+    params(("id" = String, Path, description = "Paste identifier")),
+    responses(
+        (status = 200, description = "Challenge issued", body = PasskeyChallengeResponse),
+        (status = 400, description = "Paste is not gated by a passkey", body = ApiError),
+        (status = 403, description = "Tor-only paste requested off the onion host", body = ApiError),
+        (status = 404, description = "Paste not found", body = ApiError),
+    )
+)]
+#[post("/api/pastes/<id>/webauthn/challenge")]
+async fn webauthn_challenge_api(
+    store: &State<SharedPasteStore>,
+    passkeys: &State<Passkeys>,
+    id: String,
+    onion: OnionAccess,
+    _rate: ReadRateLimit,
+) -> Result<Json<PasskeyChallengeResponse>, (Status, Json<ApiError>)> {
+    let paste = store.peek_paste(&id).await.map_err(|_| {
+        (
+            Status::NotFound,
+            Json(ApiError::new(
+                "paste_not_found",
+                format!("Paste '{id}' not found"),
+            )),
+        )
+    })?;
+    if paste.metadata.tor_access_only && !onion.is_onion() {
+        return Err((
+            Status::Forbidden,
+            Json(ApiError::new(
+                "tor_only",
+                "This paste is only accessible via its Tor onion address",
+            )),
+        ));
+    }
+    let gate = paste.metadata.attestation.as_ref().and_then(|requirement| {
+        requirement
+            .factors()
+            .iter()
+            .find_map(|factor| match factor {
+                AttestationRequirement::Webauthn {
+                    rp_id, passkeys, ..
+                } => Some((rp_id, passkeys)),
+                _ => None,
+            })
+    });
+    let Some((rp_id, allowed)) = gate else {
+        return Err((
+            Status::BadRequest,
+            Json(ApiError::new(
+                "passkey_not_required",
+                "This paste is not gated by a passkey",
+            )),
+        ));
+    };
+
+    let (challenge_id, options) = passkeys
+        .challenge(&id, rp_id, allowed, current_timestamp())
+        .map_err(|error| {
+            rocket::error!("Failed to start passkey ceremony for {}: {}", id, error);
+            (
+                Status::InternalServerError,
+                Json(ApiError::new(
+                    "passkey_setup_failed",
+                    "The passkey challenge could not be created",
+                )),
+            )
+        })?;
+    Ok(Json(PasskeyChallengeResponse {
+        challenge_id,
+        options,
+    }))
+}
+
+/// Answer a passkey challenge and receive a grant token for the paste.
+///
+/// Each challenge can be answered once. The token unlocks the paste's
+/// passkey requirement for ten minutes when sent as `?webauthn=<token>`.
+#[utoipa::path(
+    post,
+    path = "/api/pastes/{id}/webauthn/verify",
+    params(("id" = String, Path, description = "Paste identifier")),
+    request_body = PasskeyVerifyRequest,
+    responses(
+        (status = 200, description = "Assertion verified", body = PasskeyGrantResponse),
+        (status = 403, description = "Assertion rejected", body = ApiError),
+        (status = 404, description = "Unknown, expired or already answered challenge", body = ApiError),
+    )
+)]
+#[post("/api/pastes/<id>/webauthn/verify", data = "<body>")]
+async fn webauthn_verify_api(
+    passkeys: &State<Passkeys>,
+    id: String,
+    body: Json<PasskeyVerifyRequest>,
+    _rate: ReadRateLimit,
+) -> Result<Json<PasskeyGrantResponse>, (Status, Json<ApiError>)> {
+    let (token, expires_at) = passkeys
+        .verify(
+            &id,
+            &body.challenge_id,
+            &body.credential,
+            current_timestamp(),
+        )
+        .map_err(|error| match error {
+            PasskeyError::UnknownChallenge => (
+                Status::NotFound,
+                Json(ApiError::new(
+                    "passkey_challenge_unknown",
+                    error.to_string(),
+                )),
+            ),
+            PasskeyError::Rejected(_) | PasskeyError::Setup(_) => {
+                rocket::warn!("Passkey assertion for {} rejected: {}", id, error);
+                (
+                    Status::Forbidden,
+                    Json(ApiError::new(
+                        "passkey_rejected",
+                        "The passkey assertion could not be verified",
+                    )),
+                )
+            }
+        })?;
+    Ok(Json(PasskeyGrantResponse { token, expires_at }))
+}
+
+/// A content-free view of `paste`. Only plaintext has a size we can report
+/// without the key.
+fn metadata_view(id: String, paste: StoredPaste) -> PasteViewResponse {
+    let reads_remaining = paste.reads_remaining();
+    let size_bytes = match &paste.content {
+        StoredContent::Plain { text } => Some(served_size(&paste, text)),
+        _ => None,
+    };
+    paste_view_response(id, paste, None, None, "none", reads_remaining, size_bytes)
+}
+
+/// Size of what `paste` serves: the decoded bytes of a binary paste, else
+/// its text.
+fn served_size(paste: &StoredPaste, text: &str) -> usize {
+    match paste.metadata.content_encoding {
+        Some(ContentEncoding::Base64) => BASE64_STANDARD
+            .decode(text)
+            .map_or(text.len(), |bytes| bytes.len()),
+        None => text.len(),
+    }
+}
+
+/// Enforce a paste's time lock and attestation for a JSON API read, then
+/// decrypt it with `key`.
+async fn unlock_for_api(
+    id: &str,
+    paste: &StoredPaste,
+    query: &PasteViewQuery,
+    passkey: PasskeyProof,
+    key: Option<&str>,
+) -> Result<String, (Status, Json<ApiError>)> {
     let now = current_timestamp();
     if let Some(lock_state) = evaluate_time_lock(&paste.metadata, now) {
-        let (code, message) = match lock_state {
-            TimeLockState::TooEarly(_) => ("time_locked", "This paste is not yet available"),
-            TimeLockState::TooLate(_) => {
-                ("time_lock_elapsed", "This paste's access window has closed")
-            }
+        let (status, code, message) = match lock_state {
+            TimeLockState::TooEarly(_) => (
+                Status::Locked,
+                "time_locked",
+                "This paste is not yet available",
+            ),
+            TimeLockState::TooLate(_) => (
+                Status::Gone,
+                "time_lock_elapsed",
+                "This paste's access window has closed",
+            ),
         };
-        return Err((Status::Locked, Json(ApiError::new(code, message))));
+        return Err((status, Json(ApiError::new(code, message))));
     }
 
     if let Some(requirement) = paste.metadata.attestation.as_ref() {
-        match attestation::verify_attestation(requirement, &query, now) {
+        match attestation::verify_attestation(requirement, query, passkey, now) {
             AttestationVerdict::Granted => {}
             AttestationVerdict::Prompt { invalid } => {
                 let (code, message) = if invalid {
@@ -809,7 +1465,8 @@ async fn show_api(
         }
     }
 
-    let text = match decrypt_content(&paste.content, key.as_deref()) {
+    let text = match decrypt_content(&paste.content, key, PasteBinding::new(id, paste.format)).await
+    {
         Ok(text) => {
             rocket::info!(
                 "Decryption successful for id: {}, content length: {}",
@@ -828,8 +1485,8 @@ async fn show_api(
                 )),
             ));
         }
-        Err(DecryptError::InvalidKey) => {
-            rocket::error!("Invalid key for paste: {}", id);
+        Err(error) => {
+            log_undecryptable(id, &error);
             return Err((
                 Status::Forbidden,
                 Json(ApiError::new(
@@ -839,32 +1496,48 @@ async fn show_api(
             ));
         }
     };
+    Ok(text)
+}
 
-    // Burn-after-reading: a successful API read is a consumption, exactly like
-    // the HTML route. Fire Viewed first, then Consumed only if the delete won
-    // (avoids false Consumed events when concurrent reads race).
-    if paste.burn_after_reading {
-        let webhook_config = paste.metadata.webhook.clone();
-        let mut events_to_fire = Vec::new();
-        if let Some(config) = webhook_config.clone() {
-            events_to_fire.push((config, WebhookEvent::Viewed));
-        }
-        if store.delete_paste(&id).await {
-            if let Some(config) = webhook_config {
-                events_to_fire.push((config, WebhookEvent::Consumed));
-            }
-        }
-        for (config, event) in events_to_fire {
+/// Log why a paste with a key supplied didn't decrypt. Every read route
+/// answers a corrupted paste exactly like a wrong key, so the response never
+/// tells a guesser whether the key got past authentication.
+fn log_undecryptable(id: &str, error: &DecryptError) {
+    match error {
+        DecryptError::Corrupted => rocket::error!("Corrupted content for paste: {}", id),
+        _ => rocket::error!("Invalid key for paste: {}", id),
+    }
+}
+
+/// Fire `Viewed` then `Consumed` for a paste whose last allowed read this
+/// reader's [`PasteStore::consume_read`] took.
+fn fire_burn_webhooks(http: &State<WebhookClient>, paste: &StoredPaste, id: &str) {
+    if let Some(config) = paste.metadata.webhook.clone() {
+        for event in [WebhookEvent::Viewed, WebhookEvent::Consumed] {
             trigger_webhook(
                 http.inner().0.clone(),
-                config,
+                config.clone(),
                 event,
-                &id,
+                id,
                 paste.metadata.bundle_label.clone(),
             );
         }
     }
+}
 
+/// Build the JSON view of `paste` around already-decoded content. Metadata
+/// only depends on the stored record, never on the decryption key.
+/// `reads_remaining` is passed in because a read has already been counted
+/// by the time the content is served.
+fn paste_view_response(
+    id: String,
+    paste: StoredPaste,
+    content: Option<String>,
+    content_b64: Option<String>,
+    encoding: &str,
+    reads_remaining: Option<u32>,
+    size_bytes: Option<usize>,
+) -> PasteViewResponse {
     let encryption = match &paste.content {
         StoredContent::Plain { .. } => PasteEncryptionInfo {
             algorithm: EncryptionAlgorithm::None,
@@ -900,15 +1573,28 @@ async fn show_api(
         }),
     };
 
-    let attestation = paste.metadata.attestation.as_ref().map(|req| match req {
-        AttestationRequirement::Totp { issuer, .. } => PasteAttestationInfo {
-            kind: "totp".to_string(),
-            issuer: issuer.clone(),
-        },
-        AttestationRequirement::SharedSecret { .. } => PasteAttestationInfo {
-            kind: "shared_secret".to_string(),
-            issuer: None,
-        },
+    let attestation = paste.metadata.attestation.as_ref().map(|req| {
+        let kind = |factor: &AttestationRequirement| match factor {
+            AttestationRequirement::Totp { .. } => "totp",
+            AttestationRequirement::SharedSecret { .. } => "shared_secret",
+            AttestationRequirement::Webauthn { .. } => "webauthn",
+            AttestationRequirement::All { .. } => "all",
+        };
+        let issuer = req.factors().iter().find_map(|factor| match factor {
+            AttestationRequirement::Totp { issuer, .. } => issuer.clone(),
+            _ => None,
+        });
+        let factors = match req {
+            AttestationRequirement::All { requirements } => {
+                requirements.iter().map(|f| kind(f).to_string()).collect()
+            }
+            _ => Vec::new(),
+        };
+        PasteAttestationInfo {
+            kind: kind(req).to_string(),
+            issuer,
+            factors,
+        }
     });
 
     let persistence = paste.metadata.persistence.as_ref().map(|loc| match loc {
@@ -929,14 +1615,24 @@ async fn show_api(
     let webhook = paste.metadata.webhook.as_ref().map(|w| PasteWebhookInfo {
         provider: w.provider.clone(),
     });
+    // Records written before encrypted pastes stopped getting a digest
+    // still carry one; it must not reach a reader without the key.
+    let content_sha256 = match paste.content {
+        StoredContent::Plain { .. } => paste.metadata.content_sha256,
+        _ => None,
+    };
 
-    Ok(Json(PasteViewResponse {
+    PasteViewResponse {
         id,
         format: paste.format,
-        content: text,
+        content,
+        content_b64,
+        encoding: encoding.to_string(),
         created_at: paste.created_at,
         expires_at: paste.expires_at,
         burn_after_reading: paste.burn_after_reading,
+        reads_remaining,
+        size_bytes,
         // `paste` is owned here; move the bundle instead of cloning it.
         bundle: paste.bundle,
         encryption,
@@ -949,30 +1645,77 @@ async fn show_api(
         webhook,
         stego,
         workspace: paste.metadata.workspace,
-    }))
+        content_sha256,
+        tampered: false,
+    }
+}
+
+/// Whether `digest`, taken of the content as served, no longer matches the
+/// digest recorded when `paste` was stored. Pastes without a recorded digest
+/// are never reported.
+fn paste_digest_mismatch(id: &str, paste: &StoredPaste, digest: &str) -> bool {
+    let Some(expected) = paste.metadata.content_sha256.as_deref() else {
+        return false;
+    };
+    let tampered = digest != expected;
+    if tampered {
+        rocket::warn!("Content digest mismatch for paste: {}", id);
+    }
+    tampered
 }
 
+/// Create a paste and answer with its path as plain text, or with the
+/// same JSON body as `POST /api/pastes` (errors included) when the client
+/// prefers `application/json`.
 #[utoipa::path(
     post,
     path = "/",
     request_body = CreatePasteRequest,
     responses(
-        (status = 200, description = "Paste created", body = String),
+        (status = 200, description = "Paste created: its path, or the JSON body when `Accept: application/json`", content(
+            ("text/plain" = String),
+            ("application/json" = CreatePasteResponse),
+        )),
         (status = 400, description = "Invalid paste request"),
         (status = 403, description = "Forbidden"),
         (status = 500, description = "Internal server error"),
+        (status = 503, description = "Read-only maintenance mode", body = ApiError),
     )
 )]
+#[allow(clippy::too_many_arguments)] // Rocket request guards
 #[post("/", data = "<body>")]
 async fn create(
     store: &State<SharedPasteStore>,
     body: Json<CreatePasteRequest>,
+    representation: PasteRepresentation,
     onion: OnionAccess,
+    _writes: WritesAllowed,
     _rate: CreateRateLimit,
-) -> Result<String, (Status, String)> {
+    quota: CreateQuota<'_>,
+    session: OptionalUserSession,
+) -> Result<CreatedPaste, CreateFailure> {
     let body = body.into_inner();
-    let created = create_paste_internal(store.inner(), body, &onion).await?;
-    Ok(created.path)
+    let json = representation == PasteRepresentation::Json;
+    match create_paste_internal(store.inner(), body, &onion, &quota, session.0.as_deref()).await {
+        Ok(created) if json => Ok(CreatedPaste::Json(Json(created))),
+        Ok(created) => Ok(CreatedPaste::Path(created.path)),
+        Err((status, message)) if json => Err(CreateFailure::Json(to_api_err(status, message))),
+        Err(error) => Err(CreateFailure::Text(error)),
+    }
+}
+
+/// `create`'s answer: the bare path older clients parse, or the JSON API's
+/// body.
+#[derive(Responder)]
+enum CreatedPaste {
+    Path(String),
+    Json(Json<CreatePasteResponse>),
+}
+
+#[derive(Responder)]
+enum CreateFailure {
+    Text((Status, String)),
+    Json((Status, Json<ApiError>)),
 }
 
 #[utoipa::path(
@@ -985,6 +1728,7 @@ async fn create(
         (status = 401, description = "Authentication required", body = ApiError),
         (status = 403, description = "Forbidden", body = ApiError),
         (status = 500, description = "Internal server error", body = ApiError),
+        (status = 503, description = "Read-only maintenance mode", body = ApiError),
     )
 )]
 #[post("/api/pastes", data = "<body>")]
@@ -992,13 +1736,22 @@ async fn create_api(
     store: &State<SharedPasteStore>,
     body: Result<Json<CreatePasteRequest>, rocket::serde::json::Error<'_>>,
     onion: OnionAccess,
+    _writes: WritesAllowed,
     _rate: CreateRateLimit,
+    quota: CreateQuota<'_>,
+    session: OptionalUserSession,
 ) -> Result<Json<CreatePasteResponse>, (Status, Json<ApiError>)> {
     let body = match body {
         Ok(json) => {
             rocket::info!("Successfully deserialized JSON request");
             json
         }
+        Err(rocket::serde::json::Error::Io(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+            return Err(to_api_err(
+                Status::PayloadTooLarge,
+                "Request body exceeds maximum paste size".to_string(),
+            ));
+        }
         Err(e) => {
             rocket::error!("JSON deserialization failed: {:?}", e);
             return Err((
@@ -1023,32 +1776,232 @@ async fn create_api(
             .map(|e| format!("{:?}", e.algorithm))
     );
 
-    let created = create_paste_internal(store.inner(), body, &onion)
+    let created = create_paste_internal(store.inner(), body, &onion, &quota, session.0.as_deref())
         .await
         .map_err(|(s, msg)| to_api_err(s, msg))?;
     Ok(Json(created))
 }
 
+/// Decide how raw uploaded bytes become paste text.
+///
+/// Every `PasteFormat` is a text format stored as `StoredContent::Plain`, so
+/// invalid UTF-8 is rejected rather than silently mangled; `lossy` opts into
+/// `String::from_utf8_lossy` replacement.
+fn decode_text_upload(bytes: Vec<u8>, lossy: bool) -> Result<String, (Status, String)> {
+    match String::from_utf8(bytes) {
+        Ok(text) => Ok(text),
+        Err(err) if lossy => Ok(String::from_utf8_lossy(err.as_bytes()).into_owned()),
+        Err(err) => Err((
+            Status::BadRequest,
+            format!(
+                "Content is not valid UTF-8 (invalid byte at offset {}); retry with ?lossy=true to replace invalid sequences",
+                err.utf8_error().valid_up_to()
+            ),
+        )),
+    }
+}
+
+/// Create a paste from a raw request body (e.g. `curl --data-binary @file`).
+///
+/// Options that need structure (encryption, attestation, bundles, ...) are
+/// only available through the JSON endpoint.
+#[utoipa::path(
+    post,
+    path = "/api/pastes/raw",
+    request_body(content = String, content_type = "text/plain"),
+    params(
+        ("format" = Option<String>, Query, description = "Paste format, e.g. `plain_text`"),
+        ("retention_minutes" = Option<u64>, Query, description = "Retention in minutes"),
+        ("burn_after_reading" = Option<bool>, Query, description = "Delete after first view"),
+        ("lossy" = Option<bool>, Query, description = "Replace invalid UTF-8 instead of rejecting"),
+    ),
+    responses(
+        (status = 200, description = "Paste created", body = CreatePasteResponse),
+        (status = 400, description = "Invalid UTF-8 or parameters", body = ApiError),
+        (status = 413, description = "Content too large", body = ApiError),
+        (status = 503, description = "Read-only maintenance mode", body = ApiError),
+    )
+)]
+#[post("/api/pastes/raw?<query..>", data = "<body>")]
+#[allow(clippy::too_many_arguments)] // Rocket request guards
+async fn create_raw_api(
+    store: &State<SharedPasteStore>,
+    body: Vec<u8>,
+    query: RawPasteQuery,
+    onion: OnionAccess,
+    _writes: WritesAllowed,
+    _rate: CreateRateLimit,
+    quota: CreateQuota<'_>,
+    session: OptionalUserSession,
+) -> Result<Json<CreatePasteResponse>, (Status, Json<ApiError>)> {
+    let format = query
+        .format
+        .as_deref()
+        .map(|raw| {
+            serde_json::from_value::<PasteFormat>(serde_json::Value::String(raw.to_string()))
+                .map_err(|_| to_api_err(Status::BadRequest, format!("Unknown format '{raw}'")))
+        })
+        .transpose()?;
+    let content = decode_text_upload(body, query.lossy.unwrap_or(false))
+        .map_err(|(s, m)| to_api_err(s, m))?;
+
+    let request = CreatePasteRequest {
+        content,
+        format,
+        retention_minutes: query.retention_minutes,
+        burn_after_reading: query.burn_after_reading.unwrap_or(false),
+        ..Default::default()
+    };
+    let created =
+        create_paste_internal(store.inner(), request, &onion, &quota, session.0.as_deref())
+            .await
+            .map_err(|(s, m)| to_api_err(s, m))?;
+    Ok(Json(created))
+}
+
+/// Which representation of a paste `show` serves, picked from the most
+/// preferred type in the `Accept` header. Anything other than plain text or
+/// JSON, including `*/*` and a missing header, gets the HTML page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PasteRepresentation {
+    Html,
+    Text,
+    Json,
+}
+
+impl PasteRepresentation {
+    fn from_accept(accept: Option<&Accept>) -> Self {
+        let Some(accept) = accept else {
+            return PasteRepresentation::Html;
+        };
+        let preferred = accept.preferred().media_type();
+        if preferred.is_plain() {
+            PasteRepresentation::Text
+        } else if preferred.is_json() {
+            PasteRepresentation::Json
+        } else {
+            PasteRepresentation::Html
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for PasteRepresentation {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(PasteRepresentation::from_accept(req.accept()))
+    }
+}
+
+/// `show` response in the negotiated representation. Each one is exactly
+/// what `/raw/<id>` or `/api/pastes/<id>` would have answered, plus
+/// `Vary: Accept` so caches keep them apart.
+enum NegotiatedPaste {
+    Html(Result<Cached<content::RawHtml<String>>, Status>),
+    Text(Result<Cached<RawPaste>, Status>),
+    Json(Box<JsonPasteView>),
+}
+
+type JsonPasteView = Result<Json<PasteViewResponse>, (Status, Json<ApiError>)>;
+
+impl<'r, 'o: 'r> rocket::response::Responder<'r, 'o> for NegotiatedPaste {
+    fn respond_to(self, request: &'r Request<'_>) -> rocket::response::Result<'o> {
+        let mut response = match self {
+            NegotiatedPaste::Html(view) => view.respond_to(request)?,
+            NegotiatedPaste::Text(view) => view.respond_to(request)?,
+            NegotiatedPaste::Json(view) => view.respond_to(request)?,
+        };
+        response.adjoin_header(Header::new("Vary", "Accept"));
+        Ok(response)
+    }
+}
+
+/// Show a paste, negotiated on `Accept`: the rendered page for `text/html`,
+/// the raw text for `text/plain` (as `/raw/<id>`) and the JSON view for
+/// `application/json` (as `/api/pastes/<id>`). Burn-after-reading, time
+/// locks and attestation apply the same way to all three.
 #[utoipa::path(
     get,
     path = "/{id}",
     params(("id" = String, description = "Paste identifier")),
     responses(
-        (status = 200, description = "Paste rendered as HTML", content_type = "text/html"),
+        (status = 200, description = "Paste rendered as HTML, raw text or JSON depending on `Accept`", content(
+            ("text/html" = String),
+            ("text/plain" = String),
+            ("application/json" = PasteViewResponse),
+        )),
         (status = 401, description = "Key required"),
         (status = 403, description = "Forbidden"),
         (status = 404, description = "Paste not found"),
     )
 )]
-#[get("/<id>?<query..>")]
+#[allow(clippy::too_many_arguments)] // Rocket request guards
+#[get("/<id..>?<query..>")]
 async fn show(
     store: &State<SharedPasteStore>,
     http: &State<WebhookClient>,
-    id: String,
+    tokens: &State<DownloadTokens>,
+    prompts: &State<PromptTokens>,
+    passkeys: &State<Passkeys>,
+    id: PastePath,
     query: PasteViewQuery,
+    representation: PasteRepresentation,
+    key_header: PasteKeyHeader,
     onion: OnionAccess,
+    if_none_match: IfNoneMatch,
     _rate: ReadRateLimit,
-) -> Result<content::RawHtml<String>, Status> {
+) -> NegotiatedPaste {
+    let PastePath(id) = id;
+    match representation {
+        PasteRepresentation::Html => NegotiatedPaste::Html(
+            html_paste_view(
+                store,
+                http,
+                prompts,
+                passkeys,
+                id,
+                &query,
+                &onion,
+                &if_none_match,
+            )
+            .await,
+        ),
+        PasteRepresentation::Text => NegotiatedPaste::Text(
+            raw_paste_view(
+                store,
+                http,
+                tokens,
+                passkeys,
+                id,
+                &query,
+                &onion,
+                &if_none_match,
+            )
+            .await,
+        ),
+        PasteRepresentation::Json => {
+            let key = key_header.0.or_else(|| query.key.clone());
+            NegotiatedPaste::Json(Box::new(
+                json_paste_view(store, http, passkeys, id, &query, key.as_deref(), &onion).await,
+            ))
+        }
+    }
+}
+
+/// The rendered page `show` serves to browsers.
+#[allow(clippy::too_many_arguments)]
+async fn html_paste_view(
+    store: &State<SharedPasteStore>,
+    http: &State<WebhookClient>,
+    prompts: &State<PromptTokens>,
+    passkeys: &Passkeys,
+    id: String,
+    query: &PasteViewQuery,
+    onion: &OnionAccess,
+    if_none_match: &IfNoneMatch,
+) -> Result<Cached<content::RawHtml<String>>, Status> {
+    let theme = Theme::from_query(query.theme.as_deref());
     match store.get_paste(&id).await {
         Ok(paste) => {
             if paste.metadata.tor_access_only && !onion.is_onion() {
@@ -1056,62 +2009,81 @@ async fn show(
             }
 
             let now = current_timestamp();
+            // Too early renders a countdown (423); a closed window never
+            // reopens, so it is 410 like an expired paste.
             if let Some(lock_state) = evaluate_time_lock(&paste.metadata, now) {
-                return Ok(content::RawHtml(render_time_locked(lock_state)));
+                let status = match lock_state {
+                    TimeLockState::TooEarly(_) => Status::Locked,
+                    TimeLockState::TooLate(_) => Status::Gone,
+                };
+                return Ok(Cached::no_store(content::RawHtml(render_time_locked(
+                    lock_state, theme,
+                )))
+                .with_status(status));
             }
 
             if let Some(requirement) = paste.metadata.attestation.as_ref() {
-                match attestation::verify_attestation(requirement, &query, now) {
+                // A submitted form must be one we rendered recently; a
+                // direct link carrying the credentials has no state.
+                let verdict = match query.state.as_deref() {
+                    Some(state) if !prompts.verify(state, &id, now) => {
+                        AttestationVerdict::Prompt { invalid: true }
+                    }
+                    _ => attestation::verify_attestation(
+                        requirement,
+                        query,
+                        passkeys.proof(query.webauthn.as_deref(), &id, now),
+                        now,
+                    ),
+                };
+                match verdict {
                     AttestationVerdict::Granted => {}
                     AttestationVerdict::Prompt { invalid } => {
                         let needs_key_field =
-                            matches!(paste.content, StoredContent::Encrypted { .. })
-                                && query.key.is_none();
-                        return Ok(content::RawHtml(render_attestation_prompt(
-                            &id,
-                            needs_key_field,
-                            query.key.as_deref(),
-                            requirement,
-                            invalid,
+                            matches!(paste.content, StoredContent::Encrypted { .. });
+                        return Ok(Cached::no_store(content::RawHtml(
+                            render_attestation_prompt(
+                                &id,
+                                needs_key_field,
+                                &prompts.mint(&id, now),
+                                requirement,
+                                invalid,
+                                theme,
+                            ),
                         )));
                     }
                 }
             }
 
-            match decrypt_content(&paste.content, query.key.as_deref()) {
+            match decrypt_content(
+                &paste.content,
+                query.key.as_deref(),
+                PasteBinding::new(&id, paste.format),
+            )
+            .await
+            {
                 Ok(text) => {
+                    let digest = content_digest(&text);
+                    paste_digest_mismatch(&id, &paste, &digest);
+                    // Immutable pastes have no read limit, so answering a
+                    // revalidation without counting a read is safe.
+                    let sort_keys = query.wants_sorted_keys();
+                    let representation = if sort_keys { "html-sorted" } else { "html" };
+                    let policy = CachePolicy::for_paste(&id, &paste, &digest, representation);
+                    if if_none_match.matches(&policy) {
+                        return Ok(Cached::not_modified(policy));
+                    }
+
                     let bundle_html = if let Some(bundle) = paste.metadata.bundle.clone() {
-                        build_bundle_overview(store.inner().clone(), &bundle, &query).await
+                        build_bundle_overview(store.inner().clone(), &bundle, query).await
                     } else {
                         None
                     };
 
-                    let webhook_config = paste.metadata.webhook.clone();
-                    let mut events_to_fire = Vec::new();
-
-                    if paste.burn_after_reading {
-                        if let Some(config) = webhook_config.clone() {
-                            events_to_fire.push((config.clone(), WebhookEvent::Viewed));
-                        }
-                    }
-
-                    if paste.burn_after_reading {
-                        let deleted = store.delete_paste(&id).await;
-                        if deleted {
-                            if let Some(config) = webhook_config.clone() {
-                                events_to_fire.push((config, WebhookEvent::Consumed));
-                            }
-                        }
-                    }
-
-                    for (config, event) in events_to_fire {
-                        trigger_webhook(
-                            http.inner().0.clone(),
-                            config,
-                            event,
-                            &id,
-                            paste.metadata.bundle_label.clone(),
-                        );
+                    // Only readers within the read limit see the paste.
+                    let receipt = store.consume_read(&id).await.ok_or(Status::NotFound)?;
+                    if receipt.consumed() {
+                        fire_burn_webhooks(http, &paste, &id);
                     }
 
                     let view = StoredPasteView {
@@ -1120,34 +2092,154 @@ async fn show(
                         created_at: paste.created_at,
                         expires_at: paste.expires_at,
                         burn_after_reading: paste.burn_after_reading,
+                        reads_remaining: receipt.reads_remaining,
                         metadata: &paste.metadata,
                     };
 
-                    Ok(content::RawHtml(render_paste_view(
-                        &id,
-                        &view,
-                        &text,
-                        bundle_html,
-                    )))
+                    Ok(Cached::new(
+                        content::RawHtml(render_paste_view(
+                            &id,
+                            &view,
+                            &text,
+                            bundle_html,
+                            sort_keys,
+                            theme,
+                        )),
+                        policy,
+                    ))
+                }
+                Err(DecryptError::MissingKey) => Ok(Cached::no_store(content::RawHtml(
+                    render_key_prompt(&id, theme),
+                ))),
+                Err(error) => {
+                    log_undecryptable(&id, &error);
+                    Ok(Cached::no_store(content::RawHtml(render_invalid_key(
+                        &id, theme,
+                    ))))
                 }
-                Err(DecryptError::MissingKey) => Ok(content::RawHtml(render_key_prompt(&id))),
-                Err(DecryptError::InvalidKey) => Ok(content::RawHtml(render_invalid_key(&id))),
             }
         }
         Err(PasteError::NotFound(_)) => Err(Status::NotFound),
-        Err(PasteError::Expired(_)) => Ok(content::RawHtml(render_expired(&id))),
+        Err(PasteError::Expired(_)) => Ok(Cached::no_store(content::RawHtml(render_expired(
+            &id, theme,
+        )))),
+    }
+}
+
+/// `/raw/<id>` body: inline text by default, or a named attachment. Binary
+/// pastes are always sent as `application/octet-stream`.
+#[derive(Responder)]
+enum RawPaste {
+    Inline(content::RawText<String>),
+    Attachment(Box<RawAttachment>),
+    Binary(Box<RawBinary>),
+}
+
+#[derive(Responder)]
+struct RawBinary {
+    body: Vec<u8>,
+    content_type: ContentType,
+    disposition: Header<'static>,
+}
+
+#[derive(Responder)]
+struct RawAttachment {
+    body: String,
+    content_type: ContentType,
+    disposition: Header<'static>,
+}
+
+impl RawPaste {
+    fn new(id: &str, format: PasteFormat, text: String, download: bool) -> Self {
+        if !download {
+            return RawPaste::Inline(content::RawText(text));
+        }
+        let (top, sub) = format.media_type();
+        let content_type = ContentType::new(top, sub).with_params(("charset", "utf-8"));
+        let disposition = Header::new(
+            "Content-Disposition",
+            format!(
+                "attachment; filename=\"{}.{}\"",
+                download_file_stem(id),
+                format.file_extension()
+            ),
+        );
+        RawPaste::Attachment(Box::new(RawAttachment {
+            body: text,
+            content_type,
+            disposition,
+        }))
+    }
+
+    /// The decoded bytes of a binary paste, or `None` if its stored base64
+    /// no longer decodes.
+    fn binary(id: &str, text: &str) -> Option<Self> {
+        let body = BASE64_STANDARD.decode(text).ok()?;
+        let disposition = Header::new(
+            "Content-Disposition",
+            format!("attachment; filename=\"{}.bin\"", download_file_stem(id)),
+        );
+        Some(RawPaste::Binary(Box::new(RawBinary {
+            body,
+            content_type: ContentType::Binary,
+            disposition,
+        })))
     }
 }
 
-#[get("/raw/<id>?<query..>")]
+/// `id` reduced to characters that are safe inside a quoted filename.
+fn download_file_stem(id: &str) -> String {
+    id.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[allow(clippy::too_many_arguments)] // Rocket request guards
+#[get("/raw/<id..>?<query..>")]
 async fn show_raw(
     store: &State<SharedPasteStore>,
     http: &State<WebhookClient>,
-    id: String,
+    tokens: &State<DownloadTokens>,
+    passkeys: &State<Passkeys>,
+    id: PastePath,
     query: PasteViewQuery,
     onion: OnionAccess,
+    if_none_match: IfNoneMatch,
     _rate: ReadRateLimit,
-) -> Result<content::RawText<String>, Status> {
+) -> Result<Cached<RawPaste>, Status> {
+    let PastePath(id) = id;
+    raw_paste_view(
+        store,
+        http,
+        tokens,
+        passkeys,
+        id,
+        &query,
+        &onion,
+        &if_none_match,
+    )
+    .await
+}
+
+/// The plain-text read shared by `show_raw` and a `show` request that
+/// accepts `text/plain`.
+#[allow(clippy::too_many_arguments)]
+async fn raw_paste_view(
+    store: &State<SharedPasteStore>,
+    http: &State<WebhookClient>,
+    tokens: &State<DownloadTokens>,
+    passkeys: &Passkeys,
+    id: String,
+    query: &PasteViewQuery,
+    onion: &OnionAccess,
+    if_none_match: &IfNoneMatch,
+) -> Result<Cached<RawPaste>, Status> {
     match store.get_paste(&id).await {
         Ok(paste) => {
             if paste.metadata.tor_access_only && !onion.is_onion() {
@@ -1161,8 +2253,22 @@ async fn show_raw(
                 None => {}
             }
 
-            if let Some(requirement) = paste.metadata.attestation.as_ref() {
-                match attestation::verify_attestation(requirement, &query, now) {
+            // A valid download token stands in for the attestation code; it
+            // is only consumed once the content has actually been decrypted.
+            let download_token = match query.token.as_deref() {
+                Some(token) => Some(
+                    tokens
+                        .verify(token, &id, now)
+                        .map_err(|_| Status::Forbidden)?,
+                ),
+                None => None,
+            };
+
+            if let (None, Some(requirement)) =
+                (download_token.as_ref(), paste.metadata.attestation.as_ref())
+            {
+                let passkey = passkeys.proof(query.webauthn.as_deref(), &id, now);
+                match attestation::verify_attestation(requirement, query, passkey, now) {
                     AttestationVerdict::Granted => {}
                     AttestationVerdict::Prompt { invalid: false } => {
                         return Err(Status::Unauthorized);
@@ -1173,37 +2279,49 @@ async fn show_raw(
                 }
             }
 
-            match decrypt_content(&paste.content, query.key.as_deref()) {
+            match decrypt_content(
+                &paste.content,
+                query.key.as_deref(),
+                PasteBinding::new(&id, paste.format),
+            )
+            .await
+            {
                 Ok(text) => {
-                    if paste.burn_after_reading {
-                        let webhook_config = paste.metadata.webhook.clone();
-                        if let Some(config) = webhook_config.clone() {
-                            trigger_webhook(
-                                http.inner().0.clone(),
-                                config,
-                                WebhookEvent::Viewed,
-                                &id,
-                                paste.metadata.bundle_label.clone(),
-                            );
-                        }
-                        let deleted = store.delete_paste(&id).await;
-                        if deleted {
-                            if let Some(config) = webhook_config {
-                                trigger_webhook(
-                                    http.inner().0.clone(),
-                                    config,
-                                    WebhookEvent::Consumed,
-                                    &id,
-                                    paste.metadata.bundle_label.clone(),
-                                );
-                            }
+                    let digest = content_digest(&text);
+                    paste_digest_mismatch(&id, &paste, &digest);
+                    let download = query.wants_download();
+                    let raw = match paste.metadata.content_encoding {
+                        Some(ContentEncoding::Base64) => {
+                            RawPaste::binary(&id, &text).ok_or(Status::UnprocessableEntity)?
                         }
+                        None => RawPaste::new(&id, paste.format, text, download),
+                    };
+                    // A download token is single-use, so its response is
+                    // never cached and the token is always consumed.
+                    let policy = if download_token.is_some() {
+                        CachePolicy::NoStore
+                    } else {
+                        let representation = if download { "download" } else { "raw" };
+                        CachePolicy::for_paste(&id, &paste, &digest, representation)
+                    };
+                    if if_none_match.matches(&policy) {
+                        return Ok(Cached::not_modified(policy));
+                    }
+                    if let Some(token) = download_token {
+                        tokens.consume(token).map_err(|_| Status::Forbidden)?;
+                    }
+                    let receipt = store.consume_read(&id).await.ok_or(Status::NotFound)?;
+                    if receipt.consumed() {
+                        fire_burn_webhooks(http, &paste, &id);
                     }
 
-                    Ok(content::RawText(text))
+                    Ok(Cached::new(raw, policy))
                 }
                 Err(DecryptError::MissingKey) => Err(Status::Unauthorized),
-                Err(DecryptError::InvalidKey) => Err(Status::Forbidden),
+                Err(error) => {
+                    log_undecryptable(&id, &error);
+                    Err(Status::Forbidden)
+                }
             }
         }
         Err(PasteError::NotFound(_)) => Err(Status::NotFound),
@@ -1211,6 +2329,197 @@ async fn show_raw(
     }
 }
 
+/// `GET /api/bundles/<id>/zip` body.
+#[derive(Responder)]
+struct BundleZip {
+    body: Vec<u8>,
+    content_type: ContentType,
+    disposition: Header<'static>,
+}
+
+/// Download a bundle as a ZIP archive.
+///
+/// The parent and every child share still available are decrypted with the
+/// shared key (`X-Paste-Key` header or `?key=`) and packed one file per
+/// paste, named by child label or position. Consumed, expired or
+/// undecryptable shares are skipped and noted in `MANIFEST.txt`. Packing a
+/// share counts as its read, so burn-after-reading children are consumed.
+#[utoipa::path(
+    get,
+    path = "/api/bundles/{id}/zip",
+    params(
+        ("id" = String, Path, description = "Bundle parent paste identifier"),
+        ("key" = Option<String>, Query, description = "Shared bundle key"),
+    ),
+    responses(
+        (status = 200, description = "ZIP archive", content_type = "application/zip"),
+        (status = 401, description = "Key or attestation required", body = ApiError),
+        (status = 403, description = "Invalid key or Tor-only paste", body = ApiError),
+        (status = 404, description = "Bundle not found", body = ApiError),
+        (status = 410, description = "Time-lock window has closed", body = ApiError),
+        (status = 423, description = "Time-locked", body = ApiError),
+    )
+)]
+#[get("/api/bundles/<id>/zip?<query..>")]
+#[allow(clippy::too_many_arguments)] // Rocket request guards
+async fn bundle_zip_api(
+    store: &State<SharedPasteStore>,
+    http: &State<WebhookClient>,
+    passkeys: &State<Passkeys>,
+    id: String,
+    query: PasteViewQuery,
+    key_header: PasteKeyHeader,
+    onion: OnionAccess,
+    _rate: ReadRateLimit,
+) -> Result<BundleZip, (Status, Json<ApiError>)> {
+    let not_found = || {
+        (
+            Status::NotFound,
+            Json(ApiError::new(
+                "bundle_not_found",
+                format!("Bundle '{id}' not found"),
+            )),
+        )
+    };
+
+    let key = key_header.0.or_else(|| query.key.clone());
+    let paste = store.get_paste(&id).await.map_err(|_| not_found())?;
+    let Some(bundle) = paste.metadata.bundle.clone() else {
+        return Err(not_found());
+    };
+    if paste.metadata.tor_access_only && !onion.is_onion() {
+        return Err((
+            Status::Forbidden,
+            Json(ApiError::new(
+                "tor_only",
+                "This paste is only accessible via its Tor onion address",
+            )),
+        ));
+    }
+
+    let passkey = passkeys.proof(query.webauthn.as_deref(), &id, current_timestamp());
+    let text = unlock_for_api(&id, &paste, &query, passkey, key.as_deref()).await?;
+    let receipt = store.consume_read(&id).await.ok_or_else(not_found)?;
+    if receipt.consumed() {
+        fire_burn_webhooks(http, &paste, &id);
+    }
+
+    let mut archive = BundleArchive::new(&id);
+    archive.add("Bundle", &id, paste.format, text);
+    for (idx, pointer) in bundle.children.iter().enumerate() {
+        let label = pointer
+            .label
+            .clone()
+            .filter(|label| !label.trim().is_empty())
+            .unwrap_or_else(|| format!("share-{}", idx + 1));
+        let child = match store.get_paste(&pointer.id).await {
+            Ok(child) => child,
+            Err(PasteError::Expired(_)) => {
+                archive.skip(&label, "expired");
+                continue;
+            }
+            Err(PasteError::NotFound(_)) => {
+                archive.skip(&label, "already consumed");
+                continue;
+            }
+        };
+        let Ok(child_text) = decrypt_content(
+            &child.content,
+            key.as_deref(),
+            PasteBinding::new(&pointer.id, child.format),
+        )
+        .await
+        else {
+            archive.skip(&label, "not readable with this key");
+            continue;
+        };
+        let Some(receipt) = store.consume_read(&pointer.id).await else {
+            archive.skip(&label, "already consumed");
+            continue;
+        };
+        if receipt.consumed() {
+            fire_burn_webhooks(http, &child, &pointer.id);
+        }
+        archive.add(&label, &label, child.format, child_text);
+    }
+
+    let body = archive.finish().map_err(|e| {
+        rocket::error!("Failed to build bundle archive for {}: {}", id, e);
+        (
+            Status::InternalServerError,
+            Json(ApiError::new(
+                "archive_failed",
+                "The bundle archive could not be built",
+            )),
+        )
+    })?;
+    Ok(BundleZip {
+        body,
+        content_type: ContentType::ZIP,
+        disposition: Header::new(
+            "Content-Disposition",
+            format!("attachment; filename=\"{}.zip\"", download_file_stem(&id)),
+        ),
+    })
+}
+
+/// Mint a one-time download token for a paste owned by the session user.
+///
+/// The token authorises a single `GET /raw/{id}?token=...` in place of an
+/// attestation code. Encrypted pastes still need `?key=`, since the server
+/// never holds paste keys.
+#[utoipa::path(
+    post,
+    path = "/api/pastes/{id}/token",
+    params(
+        ("id" = String, Path, description = "Paste identifier"),
+        ("ttl_secs" = Option<u64>, Query, description = "Token lifetime in seconds (default 3600, max 86400)"),
+    ),
+    responses(
+        (status = 200, description = "Token minted", body = DownloadTokenResponse),
+        (status = 401, description = "Missing or invalid session token"),
+        (status = 403, description = "Paste is not owned by the session user", body = ApiError),
+        (status = 404, description = "Paste not found", body = ApiError),
+    )
+)]
+#[post("/api/pastes/<id>/token?<ttl_secs>")]
+async fn create_download_token_api(
+    store: &State<SharedPasteStore>,
+    tokens: &State<DownloadTokens>,
+    id: String,
+    ttl_secs: Option<u64>,
+    session: RequireUserSession,
+) -> Result<Json<DownloadTokenResponse>, (Status, Json<ApiError>)> {
+    let paste = store.get_paste(&id).await.map_err(|_| {
+        (
+            Status::NotFound,
+            Json(ApiError::new(
+                "paste_not_found",
+                format!("Paste '{}' not found", id),
+            )),
+        )
+    })?;
+    if paste.metadata.owner_pubkey_hash.as_deref() != Some(session.pubkey_hash.as_str()) {
+        return Err((
+            Status::Forbidden,
+            Json(ApiError::new(
+                "forbidden",
+                "Only the paste owner can mint download tokens",
+            )),
+        ));
+    }
+
+    let ttl = ttl_secs
+        .unwrap_or(DEFAULT_TOKEN_TTL_SECS)
+        .clamp(1, MAX_TOKEN_TTL_SECS);
+    let (token, expires_at) = tokens.mint(&id, ttl, current_timestamp());
+    Ok(Json(DownloadTokenResponse {
+        url: format!("/raw/{}?token={}", id, urlencoding::encode(&token)),
+        token,
+        expires_at,
+    }))
+}
+
 fn apply_time_lock(
     lock: &TimeLockRequest,
     metadata: &mut PasteMetadata,
@@ -1267,7 +2576,8 @@ fn webhook_config_from_request(
     if request.url.trim().is_empty() {
         return Err((Status::BadRequest, "Webhook url cannot be empty".into()));
     }
-    // SSRF guard: only public http(s) endpoints may be registered as webhooks.
+    // SSRF guard: only public https endpoints may be registered as webhooks
+    // (see `WebhookTargets` for the env overrides).
     validate_webhook_url(&request.url).map_err(|e| (Status::BadRequest, e))?;
     const MAX_TEMPLATE_LEN: usize = 4096;
     if let Some(ref t) = request.view_template {
@@ -1286,25 +2596,39 @@ fn webhook_config_from_request(
             ));
         }
     }
+    const MAX_SECRET_LEN: usize = 256;
+    let secret = match request.secret.as_deref() {
+        None => None,
+        Some(s) if s.is_empty() || s.len() > MAX_SECRET_LEN => {
+            return Err((
+                Status::BadRequest,
+                "Webhook secret must be between 1 and 256 characters".into(),
+            ));
+        }
+        Some(s) => Some(s.to_string()),
+    };
     Ok(WebhookConfig {
         url: request.url.clone(),
         provider: request.provider.clone(),
         view_template: request.view_template.clone(),
         burn_template: request.burn_template.clone(),
+        secret,
     })
 }
 
-/// Resolve stored content from plaintext, encrypting when requested.
+/// Resolve stored content from plaintext, encrypting when requested. The
+/// ciphertext is bound to `binding`, so it only decrypts as that paste.
 ///
 /// Takes ownership of `text` so the plain-text path stores the buffer without
 /// copying it (paste content can be up to 10 MiB).
 async fn resolve_content(
     text: String,
     encryption: Option<&super::models::EncryptionRequest>,
+    binding: PasteBinding<'_>,
 ) -> Result<StoredContent, (Status, String)> {
     match encryption {
         Some(enc) if enc.algorithm != EncryptionAlgorithm::None => {
-            encrypt_content(&text, &enc.key, enc.algorithm)
+            encrypt_content(&text, &enc.key, enc.algorithm, binding)
                 .await
                 .map_err(|e| (Status::BadRequest, e))
         }
@@ -1317,25 +2641,131 @@ fn env_minutes(name: &str) -> Option<u64> {
     std::env::var(name).ok().and_then(|v| v.parse::<u64>().ok())
 }
 
-async fn create_paste_internal(
-    store: &SharedPasteStore,
-    mut body: CreatePasteRequest,
-    _onion: &OnionAccess,
-) -> Result<CreatePasteResponse, (Status, String)> {
-    // Validate content
-    if body.content.trim().is_empty() {
-        return Err((Status::BadRequest, "Content cannot be empty".into()));
-    }
-    let max_paste_size = std::env::var("COPYPASTE_MAX_PASTE_SIZE")
-        .ok()
-        .and_then(|v| v.parse::<usize>().ok())
-        .unwrap_or(10_485_760); // 10 MB
-    if body.content.len() > max_paste_size {
-        return Err((
-            Status::PayloadTooLarge,
-            "Content exceeds maximum paste size".into(),
-        ));
-    }
+/// What to do with a requested retention above the configured maximum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum RetentionOverflow {
+    #[default]
+    Reject,
+    Clamp,
+}
+
+impl RetentionOverflow {
+    /// `COPYPASTE_RETENTION_OVERFLOW=clamp|reject`, defaulting to reject.
+    fn from_env() -> Self {
+        match std::env::var("COPYPASTE_RETENTION_OVERFLOW")
+            .map(|v| v.trim().to_ascii_lowercase())
+            .as_deref()
+        {
+            Ok("clamp") => Self::Clamp,
+            _ => Self::Reject,
+        }
+    }
+}
+
+/// Retention for a new paste: the request's value, else the configured
+/// default, else the maximum, so a configured maximum always bounds the
+/// paste's lifetime. Requests above the maximum are clamped or rejected
+/// per `overflow`.
+fn resolve_retention(
+    requested: Option<u64>,
+    default: Option<u64>,
+    max: Option<u64>,
+    overflow: RetentionOverflow,
+) -> Result<Option<u64>, String> {
+    let Some(max) = max else {
+        return Ok(requested.or(default));
+    };
+    match requested.or(default) {
+        None => Ok(Some(max)),
+        Some(minutes) if minutes <= max => Ok(Some(minutes)),
+        Some(minutes) => match overflow {
+            RetentionOverflow::Clamp => Ok(Some(max)),
+            RetentionOverflow::Reject => Err(format!(
+                "retention_minutes {minutes} exceeds the configured maximum of {max}"
+            )),
+        },
+    }
+}
+
+/// Read limit for a new paste. `burn_after_reading` means a single read and
+/// cannot be combined with a different `max_reads`.
+fn resolve_max_reads(
+    burn_after_reading: bool,
+    max_reads: Option<u32>,
+) -> Result<Option<u32>, String> {
+    match (burn_after_reading, max_reads) {
+        (_, Some(0)) => Err("max_reads must be at least 1".to_string()),
+        (true, Some(n)) if n != 1 => Err(format!(
+            "burn_after_reading allows a single read and conflicts with max_reads {n}"
+        )),
+        (true, _) => Ok(Some(1)),
+        (false, limit) => Ok(limit),
+    }
+}
+
+/// Expiry for a request carrying an absolute `expires_at` (UNIX seconds or
+/// RFC3339). It must lie after `now`; past the configured maximum retention
+/// it is clamped or rejected per `overflow`, like a relative retention.
+fn resolve_absolute_expiry(
+    raw: &str,
+    now: i64,
+    max_minutes: Option<u64>,
+    overflow: RetentionOverflow,
+) -> Result<i64, String> {
+    let expires_at = parse_timestamp(raw.trim()).map_err(|e| format!("invalid expires_at: {e}"))?;
+    if expires_at <= now {
+        return Err("expires_at must be in the future".to_string());
+    }
+    let Some(max) = max_minutes else {
+        return Ok(expires_at);
+    };
+    let limit = now.saturating_add((max as i64).saturating_mul(60));
+    if expires_at <= limit {
+        return Ok(expires_at);
+    }
+    match overflow {
+        RetentionOverflow::Clamp => Ok(limit),
+        RetentionOverflow::Reject => Err(format!(
+            "expires_at is beyond the configured maximum retention of {max} minutes"
+        )),
+    }
+}
+
+async fn create_paste_internal(
+    store: &SharedPasteStore,
+    mut body: CreatePasteRequest,
+    _onion: &OnionAccess,
+    quota: &CreateQuota<'_>,
+    session_owner: Option<&str>,
+) -> Result<CreatePasteResponse, (Status, String)> {
+    // Validate content
+    if body.content.trim().is_empty() {
+        return Err((Status::BadRequest, "Content cannot be empty".into()));
+    }
+    if body.content.len() > max_paste_size() {
+        return Err((
+            Status::PayloadTooLarge,
+            "Content exceeds maximum paste size".into(),
+        ));
+    }
+
+    // Binary content is stored as canonical base64 text, which every store
+    // and cipher already handles; it is decoded only when served.
+    if body.content_encoding == Some(ContentEncoding::Base64) {
+        if body.live {
+            return Err((
+                Status::BadRequest,
+                "Live pastes cannot carry binary content".into(),
+            ));
+        }
+        let bytes = BASE64_STANDARD.decode(body.content.trim()).map_err(|_| {
+            (
+                Status::BadRequest,
+                "content is not valid base64 for content_encoding \"base64\"".to_string(),
+            )
+        })?;
+        body.content = BASE64_STANDARD.encode(bytes);
+    }
 
     // Validate workspace
     if let Some(ref ws) = body.workspace {
@@ -1347,13 +2777,56 @@ async fn create_paste_internal(
         }
     }
 
+    // Validate the vanity id up front so a taken one fails before any bundle
+    // children are stored; the insert below re-checks atomically.
+    if let Some(custom_id) = body.custom_id.as_deref() {
+        validate_custom_id(custom_id).map_err(|e| (Status::BadRequest, e))?;
+        ReservedSlugs::from_env()
+            .check(custom_id)
+            .map_err(|e| (Status::BadRequest, e))?;
+        if store.peek_paste(custom_id).await.is_ok() {
+            return Err((Status::Conflict, custom_id_taken(custom_id)));
+        }
+    }
+
+    // Pick a syntax for untyped pastes. Skipped for encrypted pastes, whose
+    // format is stored in the clear and would hint at the content.
+    let mut detected_format = false;
+    if matches!(body.format, None | Some(PasteFormat::Code))
+        && body.encryption.is_none()
+        && body.content_encoding.is_none()
+    {
+        if let Some(format) = detect_language(&body.content) {
+            body.format = Some(format);
+            detected_format = true;
+        }
+    }
+
+    // The id and format are bound into the ciphertext, so settle both before
+    // encrypting.
+    let format = body.format.unwrap_or(PasteFormat::PlainText);
+    let id = match body.custom_id.clone() {
+        Some(custom_id) => custom_id,
+        None => store.next_paste_id().await,
+    };
+
     // Resolve content (handle encryption). Move the content buffer out of the
     // request so the plain-text path avoids cloning up to 10 MiB.
     let content_text = std::mem::take(&mut body.content);
-    let content = resolve_content(content_text, body.encryption.as_ref()).await?;
+    let content_sha256 = content_digest(&content_text);
+    let content = resolve_content(
+        content_text,
+        body.encryption.as_ref(),
+        PasteBinding::new(&id, format),
+    )
+    .await?;
 
     // Build metadata
-    let mut metadata = PasteMetadata::default();
+    let mut metadata = PasteMetadata {
+        content_sha256: recorded_digest(&content, content_sha256),
+        content_encoding: body.content_encoding,
+        ..Default::default()
+    };
 
     // Handle attestation
     if let Some(attestation_req) = &body.attestation {
@@ -1374,18 +2847,35 @@ async fn create_paste_internal(
 
     // Handle webhook
     if let Some(ref webhook_req) = body.webhook {
-        metadata.webhook = Some(webhook_config_from_request(webhook_req)?);
+        let webhook = webhook_config_from_request(webhook_req)?;
+        // The syntactic check above cannot see where a host name points.
+        WebhookTargets::from_env()
+            .check_url_resolved(&webhook.url)
+            .await
+            .map_err(|e| (Status::BadRequest, e))?;
+        metadata.webhook = Some(webhook);
     }
 
     // Handle stego — embed encrypted ciphertext into carrier image
     let content = if let Some(ref stego_req) = body.stego {
-        let (algorithm, ciphertext_b64, nonce, salt) = match content {
+        let (algorithm, ciphertext_b64, nonce, salt, kdf, bound, envelope_version) = match content {
             StoredContent::Encrypted {
                 algorithm,
                 ciphertext,
                 nonce,
                 salt,
-            } => (algorithm, ciphertext, nonce, salt),
+                kdf,
+                bound,
+                envelope_version,
+            } => (
+                algorithm,
+                ciphertext,
+                nonce,
+                salt,
+                kdf,
+                bound,
+                envelope_version,
+            ),
             _ => {
                 return Err((
                     Status::BadRequest,
@@ -1410,12 +2900,8 @@ async fn create_paste_internal(
                 }
                 let (mime, data) = parse_data_uri(data_uri)
                     .map_err(|e| (Status::BadRequest, format!("Invalid data URI: {}", e)))?;
-                if !matches!(mime.as_str(), "image/png" | "image/bmp" | "image/jpeg") {
-                    return Err((
-                        Status::BadRequest,
-                        "Carrier image must be PNG, BMP, or JPEG".into(),
-                    ));
-                }
+                CarrierFormat::from_mime(&mime)
+                    .map_err(|error| (Status::BadRequest, carrier_rejected(error)))?;
                 if data.len() > 1_048_576 {
                     return Err((
                         Status::PayloadTooLarge,
@@ -1445,7 +2931,10 @@ async fn create_paste_internal(
             ciphertext: ciphertext_b64,
             nonce,
             salt,
-            carrier_mime: "image/png".to_string(),
+            kdf,
+            bound,
+            envelope_version,
+            carrier_mime: result.mime,
             carrier_image: BASE64_STANDARD.encode(&result.image_data),
             payload_digest,
         }
@@ -1469,42 +2958,55 @@ async fn create_paste_internal(
             ));
         }
 
-        // Create bundle metadata
-        metadata.bundle = Some(crate::BundleMetadata {
-            children: bundle_req
-                .children
-                .iter()
-                .map(|child| crate::BundlePointer {
-                    id: "".to_string(), // Will be set when child pastes are created
-                    label: child.label.clone(),
-                })
-                .collect(),
-        });
+        if bundle_req
+            .children
+            .iter()
+            .any(|child| child.content.trim().is_empty())
+        {
+            return Err((
+                Status::BadRequest,
+                "Bundle child content cannot be empty".into(),
+            ));
+        }
     }
 
     // Set tor access only
     metadata.tor_access_only = body.tor_access_only;
-    metadata.owner_pubkey_hash = body.owner_pubkey_hash;
+    metadata.owner_pubkey_hash = resolve_owner(session_owner, body.owner_pubkey_hash.as_deref())?;
     metadata.workspace = body.workspace;
 
-    // Calculate expiration, honouring the bridged retention config knobs:
-    // apply the configured default when the request omits retention, and
-    // reject retentions above the configured maximum.
-    let retention_minutes = body
-        .retention_minutes
-        .or_else(|| env_minutes("COPYPASTE_RETENTION_DEFAULT_MINUTES"));
-    if let (Some(requested), Some(max)) = (
-        retention_minutes,
-        env_minutes("COPYPASTE_RETENTION_MAX_MINUTES"),
-    ) {
-        if requested > max {
-            return Err((
-                Status::BadRequest,
-                format!("retention_minutes {requested} exceeds the configured maximum of {max}"),
-            ));
-        }
-    }
-    let expires_at = retention_minutes.map(|minutes| current_timestamp() + (minutes as i64 * 60));
+    // Calculate expiration, honouring the bridged retention config knobs. An
+    // explicit timestamp wins over any relative retention.
+    let now = current_timestamp();
+    let expires_at = if let Some(raw) = body.expires_at.as_deref() {
+        Some(
+            resolve_absolute_expiry(
+                raw,
+                now,
+                env_minutes("COPYPASTE_RETENTION_MAX_MINUTES"),
+                RetentionOverflow::from_env(),
+            )
+            .map_err(|message| (Status::BadRequest, message))?,
+        )
+    } else {
+        let requested_minutes = match body.retention.as_deref() {
+            Some(raw) => {
+                Some(parse_duration(raw).map_err(|message| (Status::BadRequest, message))? as u64)
+            }
+            None => body.retention_minutes,
+        };
+        let retention_minutes = resolve_retention(
+            requested_minutes,
+            env_minutes("COPYPASTE_RETENTION_DEFAULT_MINUTES"),
+            env_minutes("COPYPASTE_RETENTION_MAX_MINUTES"),
+            RetentionOverflow::from_env(),
+        )
+        .map_err(|message| (Status::BadRequest, message))?;
+        retention_minutes.map(|minutes| now + (minutes as i64 * 60))
+    };
+
+    let max_reads = resolve_max_reads(body.burn_after_reading, body.max_reads)
+        .map_err(|message| (Status::BadRequest, message))?;
 
     // Handle live paste ownership token
     let (is_live, owner_token_hash, plaintext_token) = if body.live {
@@ -1521,13 +3023,105 @@ async fn create_paste_internal(
         (false, None, None)
     };
 
+    // Charge the daily quota once validation has passed, so rejected requests
+    // don't count against it; the charge is refunded unless the paste is
+    // stored below.
+    let charge = quota.charge(current_timestamp()).map_err(|reset_at| {
+        (
+            Status::TooManyRequests,
+            format!(
+                "Daily paste quota exceeded; it resets at {}",
+                format_timestamp(reset_at)
+            ),
+        )
+    })?;
+
+    // Store each bundle child as its own burn-after-reading paste, encrypted
+    // with the parent's key, and point the parent at the generated ids.
+    let created_bundle = match body.bundle.take() {
+        Some(bundle_req) => {
+            let mut pointers = Vec::with_capacity(bundle_req.children.len());
+            let mut created = Vec::with_capacity(bundle_req.children.len());
+            for child in bundle_req.children {
+                let child_encryption = match (child.key, body.encryption.as_ref()) {
+                    (Some(key), Some(parent)) => Some(super::models::EncryptionRequest {
+                        algorithm: parent.algorithm,
+                        key,
+                    }),
+                    (_, parent) => parent.cloned(),
+                };
+                let child_format = child.format.unwrap_or(PasteFormat::PlainText);
+                // The parent isn't stored yet, so keep clear of its id.
+                let mut child_id = store.next_paste_id().await;
+                while child_id == id {
+                    child_id = store.next_paste_id().await;
+                }
+                let child_sha256 = content_digest(&child.content);
+                let child_content = resolve_content(
+                    child.content,
+                    child_encryption.as_ref(),
+                    PasteBinding::new(&child_id, child_format),
+                )
+                .await?;
+                let child_sha256 = recorded_digest(&child_content, child_sha256);
+                let stored = store
+                    .create_paste_with_id(
+                        &child_id,
+                        StoredPaste {
+                            content: child_content,
+                            format: child_format,
+                            created_at: current_timestamp(),
+                            expires_at,
+                            burn_after_reading: true,
+                            max_reads: None,
+                            bundle: None,
+                            bundle_parent: None,
+                            bundle_label: child.label.clone(),
+                            not_before: None,
+                            not_after: None,
+                            persistence: None,
+                            webhook: None,
+                            metadata: PasteMetadata {
+                                bundle_label: child.label.clone(),
+                                tor_access_only: metadata.tor_access_only,
+                                owner_pubkey_hash: metadata.owner_pubkey_hash.clone(),
+                                workspace: metadata.workspace.clone(),
+                                content_sha256: child_sha256,
+                                ..Default::default()
+                            },
+                            is_live: false,
+                            owner_token_hash: None,
+                        },
+                    )
+                    .await;
+                if let Err(error) = stored {
+                    discard_children(store, &created).await;
+                    return Err(create_failed(error, false));
+                }
+                pointers.push(crate::BundlePointer {
+                    id: child_id.clone(),
+                    label: child.label.clone(),
+                });
+                created.push(CreatedBundleChild {
+                    url: format!("/{child_id}"),
+                    id: child_id,
+                    label: child.label,
+                });
+            }
+            metadata.bundle = Some(crate::BundleMetadata { children: pointers });
+            Some(CreatedBundle { children: created })
+        }
+        None => None,
+    };
+
     // Create the paste
     let paste = StoredPaste {
         content,
-        format: body.format.unwrap_or(PasteFormat::PlainText),
+        format,
         created_at: current_timestamp(),
         expires_at,
-        burn_after_reading: body.burn_after_reading,
+        burn_after_reading: max_reads == Some(1),
+        max_reads,
         bundle: metadata.bundle.clone(),
         bundle_parent: metadata.bundle_parent.clone(),
         bundle_label: metadata.bundle_label.clone(),
@@ -1541,18 +3135,81 @@ async fn create_paste_internal(
     };
 
     // Store the paste
-    let id = store.create_paste(paste).await;
+    if let Err(error) = store.create_paste_with_id(&id, paste).await {
+        // Don't leave the children orphaned.
+        let children = created_bundle
+            .as_ref()
+            .map(|bundle| bundle.children.as_slice())
+            .unwrap_or_default();
+        discard_children(store, children).await;
+        return Err(create_failed(error, body.custom_id.is_some()));
+    }
+    charge.keep();
     let path = format!("/{}", id);
 
+    // Children are stored first so the parent never points at missing ids;
+    // link them back now that the parent id exists.
+    if let Some(bundle) = &created_bundle {
+        for child in &bundle.children {
+            if let Err(err) = store.set_bundle_parent(&child.id, &id).await {
+                log::warn!("Failed to link bundle child {} to {id}: {err}", child.id);
+            }
+        }
+    }
+
     Ok(CreatePasteResponse {
         id: id.clone(),
         path: path.clone(),
         shareable_url: path,
         token: plaintext_token,
         is_live,
+        bundle: created_bundle,
+        detected_format,
     })
 }
 
+/// Why an uploaded carrier's mime type can't hold a payload.
+fn carrier_rejected(error: StegoError) -> String {
+    match error {
+        StegoError::LossyCarrierUnsupported { .. } => format!("Carrier image rejected: {error}"),
+        _ => "Carrier image must be PNG, BMP, or WebP".into(),
+    }
+}
+
+fn custom_id_taken(id: &str) -> String {
+    format!("Paste id '{id}' is already taken")
+}
+
+/// Map a failed insert to a response. A generated id can only be taken by a
+/// concurrent create that picked the same one, which is worth a retry.
+fn create_failed(error: CreatePasteError, custom_id: bool) -> (Status, String) {
+    match error {
+        CreatePasteError::IdTaken(IdTaken(id)) if custom_id => {
+            (Status::Conflict, custom_id_taken(&id))
+        }
+        CreatePasteError::IdTaken(_) => (
+            Status::ServiceUnavailable,
+            "Paste id collided with a concurrent create; please retry".to_string(),
+        ),
+        CreatePasteError::Persistence(error) => not_durable(error),
+    }
+}
+
+/// Strict persistence rejected a save; nothing from this create is kept.
+fn not_durable(error: PersistenceError) -> (Status, String) {
+    log::warn!("Refusing create, paste would not be durable: {error}");
+    (
+        Status::ServiceUnavailable,
+        "Paste storage is unavailable; nothing was saved".to_string(),
+    )
+}
+
+async fn discard_children(store: &SharedPasteStore, children: &[CreatedBundleChild]) {
+    for child in children {
+        store.delete_paste(&child.id).await;
+    }
+}
+
 /// Verify the live-paste ownership token supplied as `Authorization: Bearer`.
 ///
 /// The stored hash is SHA-256(token); comparison is constant-time.
@@ -1603,6 +3260,7 @@ async fn get_paste_for_mutation(
         (status = 404, description = "Paste not found", body = ApiError),
         (status = 409, description = "Paste is not live", body = ApiError),
         (status = 410, description = "Paste expired", body = ApiError),
+        (status = 503, description = "Read-only maintenance mode", body = ApiError),
     )
 )]
 #[put("/api/pastes/<id>", data = "<body>")]
@@ -1611,6 +3269,7 @@ async fn update_api(
     id: String,
     body: Json<UpdatePasteRequest>,
     token: BearerToken,
+    _writes: WritesAllowed,
 ) -> Result<Json<UpdatePasteResponse>, (Status, Json<ApiError>)> {
     let body = body.into_inner();
 
@@ -1633,23 +3292,25 @@ async fn update_api(
             "Content cannot be empty".to_string(),
         ));
     }
-    let max_paste_size = std::env::var("COPYPASTE_MAX_PASTE_SIZE")
-        .ok()
-        .and_then(|v| v.parse::<usize>().ok())
-        .unwrap_or(10_485_760);
-    if body.content.len() > max_paste_size {
+    if body.content.len() > max_paste_size() {
         return Err(to_api_err(
             Status::PayloadTooLarge,
             "Content exceeds maximum paste size".to_string(),
         ));
     }
 
-    let content = resolve_content(body.content, body.encryption.as_ref())
-        .await
-        .map_err(|(s, m)| to_api_err(s, m))?;
+    let content_sha256 = content_digest(&body.content);
+    let content = resolve_content(
+        body.content,
+        body.encryption.as_ref(),
+        PasteBinding::new(&id, paste.format),
+    )
+    .await
+    .map_err(|(s, m)| to_api_err(s, m))?;
 
+    let content_sha256 = recorded_digest(&content, content_sha256);
     store
-        .update_paste(&id, content)
+        .update_paste(&id, content, content_sha256)
         .await
         .map_err(|e| match e {
             PasteError::NotFound(_) => {
@@ -1677,6 +3338,7 @@ async fn update_api(
         (status = 403, description = "Invalid ownership token", body = ApiError),
         (status = 404, description = "Paste not found", body = ApiError),
         (status = 410, description = "Paste expired", body = ApiError),
+        (status = 503, description = "Read-only maintenance mode", body = ApiError),
     )
 )]
 #[patch("/api/pastes/<id>/finalize", data = "<body>")]
@@ -1685,6 +3347,7 @@ async fn finalize_api(
     id: String,
     body: Option<Json<FinalizePasteRequest>>,
     token: BearerToken,
+    _writes: WritesAllowed,
 ) -> Result<Json<FinalizePasteResponse>, (Status, Json<ApiError>)> {
     if let Some(ref body) = body {
         if body.live {
@@ -1713,6 +3376,96 @@ async fn finalize_api(
     Ok(Json(FinalizePasteResponse { id, is_live: false }))
 }
 
+/// Pick the owner to stamp on a new paste. Ownership comes only from the
+/// authenticated session; a body `owner_pubkey_hash` naming anyone else is
+/// rejected, and anonymous callers cannot claim an owner at all.
+fn resolve_owner(
+    session_owner: Option<&str>,
+    requested: Option<&str>,
+) -> Result<Option<String>, (Status, String)> {
+    match (session_owner, requested) {
+        (Some(owner), Some(requested)) if requested != owner => Err((
+            Status::Forbidden,
+            "owner_pubkey_hash does not match the authenticated session".to_string(),
+        )),
+        (owner, _) => Ok(owner.map(str::to_owned)),
+    }
+}
+
+/// Decide whether the holder of a session for `session_owner` may delete
+/// `paste`. Owned pastes need the owner's session; unowned ones are only
+/// deletable when `allow_anonymous` is set.
+fn authorize_delete(
+    paste: &StoredPaste,
+    session_owner: Option<&str>,
+    allow_anonymous: bool,
+) -> Result<(), (Status, String)> {
+    match (paste.metadata.owner_pubkey_hash.as_deref(), session_owner) {
+        (Some(owner), Some(caller)) if owner == caller => Ok(()),
+        (Some(_), Some(_)) => Err((
+            Status::Forbidden,
+            "Only the paste owner can delete it".to_string(),
+        )),
+        (Some(_), None) => Err((
+            Status::Unauthorized,
+            "Owner session required (Authorization: Bearer <session token>)".to_string(),
+        )),
+        (None, _) if allow_anonymous => Ok(()),
+        (None, _) => Err((
+            Status::Forbidden,
+            "Pastes without an owner cannot be deleted".to_string(),
+        )),
+    }
+}
+
+/// Delete a paste before it expires.
+///
+/// Owned pastes require the owner's session via
+/// `Authorization: Bearer <session token>`. Pastes without an owner can only
+/// be deleted when `COPYPASTE_ALLOW_ANONYMOUS_DELETE` is set.
+#[utoipa::path(
+    delete,
+    path = "/api/pastes/{id}",
+    params(("id" = String, Path, description = "Paste identifier")),
+    responses(
+        (status = 204, description = "Paste deleted"),
+        (status = 401, description = "Owner session required", body = ApiError),
+        (status = 403, description = "Caller is not the paste owner", body = ApiError),
+        (status = 404, description = "Paste not found", body = ApiError),
+        (status = 410, description = "Paste expired", body = ApiError),
+        (status = 503, description = "Read-only maintenance mode", body = ApiError),
+    )
+)]
+#[delete("/api/pastes/<id>")]
+async fn delete_api(
+    store: &State<SharedPasteStore>,
+    sessions: &State<SharedSessionStore>,
+    id: String,
+    token: BearerToken,
+    _writes: WritesAllowed,
+) -> Result<Status, (Status, Json<ApiError>)> {
+    let paste = get_paste_for_mutation(store.inner(), &id)
+        .await
+        .map_err(|(s, m)| to_api_err(s, m))?;
+
+    // A stale or unknown token counts as no credential.
+    let session_owner = token.0.as_deref().and_then(|t| sessions.validate(t));
+    let allow_anonymous = std::env::var("COPYPASTE_ALLOW_ANONYMOUS_DELETE")
+        .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "on"))
+        .unwrap_or(false);
+    authorize_delete(&paste, session_owner.as_deref(), allow_anonymous)
+        .map_err(|(s, m)| to_api_err(s, m))?;
+
+    if store.delete_paste(&id).await {
+        Ok(Status::NoContent)
+    } else {
+        Err(to_api_err(
+            Status::NotFound,
+            format!("Paste '{id}' not found"),
+        ))
+    }
+}
+
 #[post("/api/admin/keys", data = "<body>")]
 async fn admin_create_key_api(
     key_store: &State<SharedApiKeyStore>,
@@ -1778,6 +3531,151 @@ async fn admin_delete_key_api(
     Ok(Json(RevokeApiKeyResponse { revoked }))
 }
 
+#[get("/api/admin/maintenance")]
+async fn admin_maintenance_status_api(
+    maintenance: &State<MaintenanceMode>,
+    _auth: RequireAdminAuth,
+) -> Json<MaintenanceModeResponse> {
+    Json(MaintenanceModeResponse {
+        read_only: maintenance.is_read_only(),
+    })
+}
+
+/// Toggle read-only maintenance mode at runtime. Not gated by
+/// `WritesAllowed` so an operator can always switch writes back on.
+#[put("/api/admin/maintenance", data = "<body>")]
+async fn admin_maintenance_update_api(
+    maintenance: &State<MaintenanceMode>,
+    body: Json<MaintenanceModeRequest>,
+    auth: RequireAdminAuth,
+) -> Json<MaintenanceModeResponse> {
+    maintenance.set_read_only(body.read_only);
+    rocket::info!(
+        "Maintenance mode {} by admin key '{}'",
+        if body.read_only {
+            "enabled"
+        } else {
+            "disabled"
+        },
+        auth.0.name
+    );
+    Json(MaintenanceModeResponse {
+        read_only: body.read_only,
+    })
+}
+
+/// Default cap on records per `POST /api/import` request.
+const DEFAULT_IMPORT_MAX_LINES: usize = 1000;
+
+/// Bulk-import pastes from NDJSON, one create-shaped record per line.
+///
+/// Requires an API key with write (or admin) scope. Each non-empty line is
+/// created through the normal create path; the response is NDJSON with one
+/// `{line, id, url}` or `{line, error}` per input record. Requests with more
+/// than `COPYPASTE_IMPORT_MAX_LINES` (default 1000) records are rejected.
+#[utoipa::path(
+    post,
+    path = "/api/import",
+    request_body(content = String, description = "NDJSON of ImportPasteRecord", content_type = "application/x-ndjson"),
+    responses(
+        (status = 200, description = "NDJSON of ImportLineResult, one per record", body = String, content_type = "application/x-ndjson"),
+        (status = 400, description = "Body is not UTF-8", body = ApiError),
+        (status = 401, description = "Missing or invalid API key"),
+        (status = 403, description = "API key lacks write scope", body = ApiError),
+        (status = 413, description = "Too many records", body = ApiError),
+        (status = 503, description = "Read-only maintenance mode", body = ApiError),
+    )
+)]
+#[post("/api/import", data = "<body>")]
+async fn import_api(
+    store: &State<SharedPasteStore>,
+    body: Vec<u8>,
+    auth: OptionalApiKeyAuth,
+    onion: OnionAccess,
+    _writes: WritesAllowed,
+) -> Result<(ContentType, String), (Status, Json<ApiError>)> {
+    let key = auth.0.ok_or((
+        Status::Unauthorized,
+        Json(ApiError::new("unauthorized", "An API key is required")),
+    ))?;
+    if !key.scope.can_write() {
+        return Err((
+            Status::Forbidden,
+            Json(ApiError::new(
+                "forbidden",
+                "Importing requires a write-scoped API key",
+            )),
+        ));
+    }
+
+    let body = String::from_utf8(body).map_err(|_| {
+        (
+            Status::BadRequest,
+            Json(ApiError::new("invalid_body", "Import body must be UTF-8")),
+        )
+    })?;
+    let max_lines = std::env::var("COPYPASTE_IMPORT_MAX_LINES")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_IMPORT_MAX_LINES);
+    let records: Vec<(usize, &str)> = body
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| (idx + 1, line))
+        .collect();
+    if records.len() > max_lines {
+        return Err((
+            Status::PayloadTooLarge,
+            Json(ApiError::new(
+                "too_many_records",
+                format!("Import is limited to {max_lines} records per request"),
+            )),
+        ));
+    }
+
+    rocket::info!("API key '{}' importing {} records", key.name, records.len());
+    let quota = CreateQuota::exempt();
+    let mut output = String::new();
+    for (line, raw) in records {
+        let result = match serde_json::from_str::<ImportPasteRecord>(raw) {
+            Ok(record) => {
+                let request = CreatePasteRequest {
+                    content: record.content,
+                    format: record.format,
+                    retention_minutes: record.retention_minutes,
+                    encryption: record.encryption,
+                    ..Default::default()
+                };
+                match create_paste_internal(store.inner(), request, &onion, &quota, None).await {
+                    Ok(created) => ImportLineResult {
+                        line,
+                        id: Some(created.id),
+                        url: Some(created.shareable_url),
+                        error: None,
+                    },
+                    Err((_, message)) => ImportLineResult {
+                        line,
+                        id: None,
+                        url: None,
+                        error: Some(message),
+                    },
+                }
+            }
+            Err(e) => ImportLineResult {
+                line,
+                id: None,
+                url: None,
+                error: Some(format!("Invalid record: {e}")),
+            },
+        };
+        output.push_str(&serde_json::to_string(&result).expect("result serializes"));
+        output.push('\n');
+    }
+
+    Ok((ContentType::new("application", "x-ndjson"), output))
+}
+
 #[get("/")]
 async fn index() -> content::RawHtml<String> {
     content::RawHtml(include_str!("../../static/index.html").to_string())
@@ -1961,47 +3859,326 @@ mod tests {
     }
 
     #[test]
-    fn show_api_does_not_burn_when_key_is_missing_or_wrong() {
+    fn max_reads_serves_exactly_that_many_reads() {
         let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
-        let rocket = build_rocket(store);
-        let client = Client::tracked(rocket).expect("client");
-
-        let payload = json!({
-            "content": "secret",
-            "format": "plain_text",
-            "burn_after_reading": true,
-            "encryption": { "algorithm": "aes256_gcm", "key": "correct-horse" }
-        });
+        let client = Client::tracked(build_rocket(store)).expect("client");
 
         let response = client
             .post("/api/pastes")
             .header(ContentType::JSON)
-            .body(payload.to_string())
+            .body(json!({ "content": "shared credential", "max_reads": 2 }).to_string())
             .dispatch();
         assert_eq!(response.status(), Status::Ok);
         let parsed: CreatePasteResponse =
             serde_json::from_str(&response.into_string().unwrap()).expect("parse");
+
         let api_path = format!("/api/pastes/{}", parsed.id);
+        let first = client.get(&api_path).dispatch();
+        assert_eq!(first.status(), Status::Ok);
+        let view: serde_json::Value =
+            serde_json::from_str(&first.into_string().unwrap()).expect("view json");
+        assert_eq!(view["content"], "shared credential");
+        assert_eq!(view["readsRemaining"], 1);
 
-        // Missing key and wrong key must NOT consume the paste.
-        let missing = client.get(&api_path).dispatch();
-        assert_eq!(missing.status(), Status::Unauthorized);
-        let wrong = client.get(format!("{api_path}?key=nope")).dispatch();
-        assert_eq!(wrong.status(), Status::Forbidden);
+        let second = client.get(format!("/{}", parsed.id)).dispatch();
+        assert_eq!(second.status(), Status::Ok);
+        let html = second.into_string().unwrap();
+        assert!(html.contains("<strong>Reads remaining:</strong> 0"));
+        assert!(html.contains("last allowed read"));
 
-        // Correct key reads and consumes it.
-        let good = client
-            .get(format!("{api_path}?key=correct-horse"))
-            .dispatch();
-        assert_eq!(good.status(), Status::Ok);
-        let gone = client
-            .get(format!("{api_path}?key=correct-horse"))
-            .dispatch();
-        assert_eq!(gone.status(), Status::NotFound);
+        let third = client.get(&api_path).dispatch();
+        assert_eq!(third.status(), Status::NotFound);
     }
 
     #[test]
-    fn show_api_enforces_time_lock_and_attestation() {
+    fn resolve_max_reads_maps_burn_to_a_single_read() {
+        assert_eq!(resolve_max_reads(false, None), Ok(None));
+        assert_eq!(resolve_max_reads(true, None), Ok(Some(1)));
+        assert_eq!(resolve_max_reads(true, Some(1)), Ok(Some(1)));
+        assert_eq!(resolve_max_reads(false, Some(3)), Ok(Some(3)));
+        assert!(resolve_max_reads(false, Some(0)).is_err());
+        assert!(resolve_max_reads(true, Some(3)).is_err());
+    }
+
+    #[test]
+    fn show_api_metadata_only_skips_key_and_burn() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+
+        let response = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(
+                json!({
+                    "content": "metadata secret",
+                    "format": "markdown",
+                    "retention_minutes": 60,
+                    "burn_after_reading": true,
+                    "encryption": { "algorithm": "aes256_gcm", "key": "meta-key" }
+                })
+                .to_string(),
+            )
+            .dispatch();
+        let created: CreatePasteResponse =
+            serde_json::from_str(&response.into_string().unwrap()).unwrap();
+        let path = format!("/api/pastes/{}", created.id);
+
+        assert_eq!(client.get(&path).dispatch().status(), Status::Unauthorized);
+
+        let meta = client.get(format!("{path}?metadata_only=true")).dispatch();
+        assert_eq!(meta.status(), Status::Ok);
+        let view: serde_json::Value = serde_json::from_str(&meta.into_string().unwrap()).unwrap();
+        assert_eq!(view["encryption"]["requiresKey"], true);
+        assert_eq!(view["encryption"]["algorithm"], "aes256_gcm");
+        assert_eq!(view["format"], "markdown");
+        assert!(view["expiresAt"].is_i64());
+        assert_eq!(view["burnAfterReading"], true);
+        assert!(view.get("content").is_none());
+        assert!(view.get("content_b64").is_none());
+
+        // The peek did not consume the burn-after-reading paste.
+        let full = client.get(format!("{path}?key=meta-key")).dispatch();
+        assert_eq!(full.status(), Status::Ok);
+    }
+
+    #[test]
+    fn meta_api_peeks_without_burning_or_unlocking() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+
+        let response = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(
+                json!({
+                    "content": "burn me",
+                    "format": "markdown",
+                    "burn_after_reading": true,
+                    "encryption": { "algorithm": "chacha20_poly1305", "key": "meta-key" },
+                })
+                .to_string(),
+            )
+            .dispatch();
+        let burn: CreatePasteResponse =
+            serde_json::from_str(&response.into_string().unwrap()).unwrap();
+
+        for _ in 0..2 {
+            let meta = client
+                .get(format!("/api/pastes/{}/meta", burn.id))
+                .dispatch();
+            assert_eq!(meta.status(), Status::Ok);
+            let view: serde_json::Value =
+                serde_json::from_str(&meta.into_string().unwrap()).unwrap();
+            assert_eq!(view["encryption"]["algorithm"], "chacha20_poly1305");
+            assert_eq!(view["burnAfterReading"], true);
+            assert_eq!(view["accessCount"], 0);
+            assert!(view.get("content").is_none());
+        }
+        let full = client
+            .get(format!("/api/pastes/{}?key=meta-key", burn.id))
+            .dispatch();
+        assert_eq!(full.status(), Status::Ok);
+        assert_eq!(
+            client
+                .get(format!("/api/pastes/{}/meta", burn.id))
+                .dispatch()
+                .status(),
+            Status::NotFound
+        );
+
+        let not_before = current_timestamp() + 3600;
+        let response = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(
+                json!({
+                    "content": "later",
+                    "time_lock": { "not_before": not_before.to_string() },
+                })
+                .to_string(),
+            )
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let locked: CreatePasteResponse =
+            serde_json::from_str(&response.into_string().unwrap()).unwrap();
+        assert_eq!(
+            client
+                .get(format!("/api/pastes/{}", locked.id))
+                .dispatch()
+                .status(),
+            Status::Locked
+        );
+        let meta = client
+            .get(format!("/api/pastes/{}/meta", locked.id))
+            .dispatch();
+        assert_eq!(meta.status(), Status::Ok);
+        let view: serde_json::Value = serde_json::from_str(&meta.into_string().unwrap()).unwrap();
+        assert_eq!(view["timeLock"]["notBefore"], not_before);
+        assert_eq!(view["sizeBytes"], 5);
+    }
+
+    #[test]
+    fn exists_api_reports_live_consumed_and_expired_pastes() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store.clone())).expect("client");
+        let exists = |id: &str| -> serde_json::Value {
+            let response = client.get(format!("/api/pastes/{id}/exists")).dispatch();
+            assert_eq!(response.status(), Status::Ok);
+            serde_json::from_str(&response.into_string().unwrap()).unwrap()
+        };
+
+        let response = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(
+                json!({
+                    "content": "burn me",
+                    "burn_after_reading": true,
+                    "encryption": { "algorithm": "aes256_gcm", "key": "exists-key" },
+                })
+                .to_string(),
+            )
+            .dispatch();
+        let burn: CreatePasteResponse =
+            serde_json::from_str(&response.into_string().unwrap()).unwrap();
+
+        for _ in 0..2 {
+            let state = exists(&burn.id);
+            assert_eq!(
+                state,
+                json!({ "exists": true, "expired": false, "consumed": false })
+            );
+        }
+        assert_eq!(
+            client
+                .get(format!("/api/pastes/{}?key=exists-key", burn.id))
+                .dispatch()
+                .status(),
+            Status::Ok,
+            "polling must not burn the paste"
+        );
+        assert_eq!(
+            exists(&burn.id),
+            json!({ "exists": false, "expired": false, "consumed": true })
+        );
+
+        let now = current_timestamp();
+        let expired = StoredPaste {
+            content: StoredContent::Plain {
+                text: "stale".into(),
+            },
+            format: PasteFormat::PlainText,
+            created_at: now - 120,
+            expires_at: Some(now - 60),
+            burn_after_reading: false,
+            max_reads: None,
+            bundle: None,
+            bundle_parent: None,
+            bundle_label: None,
+            not_before: None,
+            not_after: None,
+            persistence: None,
+            webhook: None,
+            metadata: PasteMetadata::default(),
+            is_live: false,
+            owner_token_hash: None,
+        };
+        let expired_id = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(store.create_paste(expired))
+            .unwrap();
+        assert_eq!(
+            exists(&expired_id),
+            json!({ "exists": false, "expired": true, "consumed": false })
+        );
+        assert_eq!(
+            exists("never-created"),
+            json!({ "exists": false, "expired": false, "consumed": false })
+        );
+    }
+
+    #[test]
+    fn show_api_base64_encoding_round_trips_content() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+        let original = "héllo\n\tworld ✓";
+
+        let response = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(json!({ "content": original, "format": "plain_text" }).to_string())
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let parsed: CreatePasteResponse =
+            serde_json::from_str(&response.into_string().unwrap()).unwrap();
+
+        let resp = client
+            .get(format!("/api/pastes/{}?encoding=base64", parsed.id))
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+        let view: serde_json::Value = serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+        assert_eq!(view["encoding"], "base64");
+        assert!(view.get("content").is_none());
+        let decoded = BASE64_STANDARD
+            .decode(view["content_b64"].as_str().expect("content_b64"))
+            .expect("valid base64");
+        assert_eq!(decoded, original.as_bytes());
+
+        // Default stays a UTF-8 string.
+        let resp = client.get(format!("/api/pastes/{}", parsed.id)).dispatch();
+        let view: serde_json::Value = serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+        assert_eq!(view["encoding"], "utf8");
+        assert_eq!(view["content"], original);
+        assert!(view.get("content_b64").is_none());
+
+        let resp = client
+            .get(format!("/api/pastes/{}?encoding=hex", parsed.id))
+            .dispatch();
+        assert_eq!(resp.status(), Status::BadRequest);
+    }
+
+    #[test]
+    fn show_api_does_not_burn_when_key_is_missing_or_wrong() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let rocket = build_rocket(store);
+        let client = Client::tracked(rocket).expect("client");
+
+        let payload = json!({
+            "content": "secret",
+            "format": "plain_text",
+            "burn_after_reading": true,
+            "encryption": { "algorithm": "aes256_gcm", "key": "correct-horse" }
+        });
+
+        let response = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(payload.to_string())
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let parsed: CreatePasteResponse =
+            serde_json::from_str(&response.into_string().unwrap()).expect("parse");
+        let api_path = format!("/api/pastes/{}", parsed.id);
+
+        // Missing key and wrong key must NOT consume the paste.
+        let missing = client.get(&api_path).dispatch();
+        assert_eq!(missing.status(), Status::Unauthorized);
+        let wrong = client.get(format!("{api_path}?key=nope")).dispatch();
+        assert_eq!(wrong.status(), Status::Forbidden);
+
+        // Correct key reads and consumes it.
+        let good = client
+            .get(format!("{api_path}?key=correct-horse"))
+            .dispatch();
+        assert_eq!(good.status(), Status::Ok);
+        let gone = client
+            .get(format!("{api_path}?key=correct-horse"))
+            .dispatch();
+        assert_eq!(gone.status(), Status::NotFound);
+    }
+
+    #[test]
+    fn show_api_enforces_time_lock_and_attestation() {
         let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
         let rocket = build_rocket(store);
         let client = Client::tracked(rocket).expect("client");
@@ -2045,6 +4222,198 @@ mod tests {
         assert_eq!(with_code.status(), Status::Ok);
     }
 
+    #[test]
+    fn attestation_prompt_carries_signed_state_instead_of_the_key() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+        let key = "plaintext-key-Zq81";
+        let response = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(
+                json!({
+                    "content": "gated and sealed",
+                    "encryption": { "algorithm": "aes256_gcm", "key": key },
+                    "attestation": { "kind": "shared_secret", "secret": "open-sesame" }
+                })
+                .to_string(),
+            )
+            .dispatch();
+        let created: CreatePasteResponse =
+            serde_json::from_str(&response.into_string().unwrap()).unwrap();
+        let id = created.id;
+
+        let prompt = client
+            .get(format!("/{id}?key={key}"))
+            .dispatch()
+            .into_string()
+            .unwrap();
+        assert!(prompt.contains("Additional verification required"));
+        assert!(!prompt.contains(key), "key leaked into the prompt");
+        assert!(prompt.contains(r#"<input type="password" name="key" id="key" required />"#));
+        let state = prompt
+            .split(r#"name="state" value=""#)
+            .nth(1)
+            .and_then(|rest| rest.split('"').next())
+            .expect("state token")
+            .to_string();
+
+        let wrong = client
+            .get(format!("/{id}?key={key}&attest=nope&state={state}"))
+            .dispatch()
+            .into_string()
+            .unwrap();
+        assert!(wrong.contains("Verification failed"));
+        assert!(!wrong.contains(key));
+
+        let forged = client
+            .get(format!("/{id}?key={key}&attest=open-sesame&state=1.AAAA"))
+            .dispatch()
+            .into_string()
+            .unwrap();
+        assert!(forged.contains("Verification failed"));
+
+        let granted = client
+            .get(format!("/{id}?key={key}&attest=open-sesame&state={state}"))
+            .dispatch();
+        assert_eq!(granted.status(), Status::Ok);
+        assert!(granted.into_string().unwrap().contains("gated and sealed"));
+    }
+
+    #[test]
+    fn webauthn_ceremony_issues_challenges_and_rejects_forged_assertions() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+        let passkey: serde_json::Value =
+            serde_json::from_str(crate::server::webauthn::TEST_PASSKEY).unwrap();
+        let create = |attestation: serde_json::Value| -> String {
+            let response = client
+                .post("/api/pastes")
+                .header(ContentType::JSON)
+                .body(json!({ "content": "passkey only", "attestation": attestation }).to_string())
+                .dispatch();
+            assert_eq!(response.status(), Status::Ok);
+            serde_json::from_str::<CreatePasteResponse>(&response.into_string().unwrap())
+                .unwrap()
+                .id
+        };
+        let id = create(json!({
+            "kind": "webauthn",
+            "rp_id": "copypaste.fyi",
+            "passkeys": [passkey],
+        }));
+        let plain = create(json!({ "kind": "shared_secret", "secret": "s" }));
+
+        let meta: serde_json::Value = serde_json::from_str(
+            &client
+                .get(format!("/api/pastes/{id}/meta"))
+                .dispatch()
+                .into_string()
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(meta["attestation"]["kind"], "webauthn");
+
+        let response = client
+            .post(format!("/api/pastes/{id}/webauthn/challenge"))
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let challenge: PasskeyChallengeResponse =
+            serde_json::from_str(&response.into_string().unwrap()).unwrap();
+        let options = serde_json::to_value(&challenge.options).unwrap();
+        assert_eq!(options["publicKey"]["rpId"], "copypaste.fyi");
+        assert_eq!(
+            client
+                .post(format!("/api/pastes/{plain}/webauthn/challenge"))
+                .dispatch()
+                .status(),
+            Status::BadRequest
+        );
+
+        let forged = json!({
+            "challengeId": challenge.challenge_id,
+            "credential": {
+                "id": "uZcVDBVS68E_MtAgeQpElJxldF_6cY9sSvbWqx_qRh8wiu42lyRBRmh5yFeD_r9k130dMbFHBHI9RTFgdJQIzQ",
+                "rawId": "uZcVDBVS68E_MtAgeQpElJxldF_6cY9sSvbWqx_qRh8wiu42lyRBRmh5yFeD_r9k130dMbFHBHI9RTFgdJQIzQ",
+                "response": {
+                    "authenticatorData": "AAAA",
+                    "clientDataJSON": "e30",
+                    "signature": "AAAA",
+                    "userHandle": null
+                },
+                "type": "public-key"
+            }
+        });
+        for expected in [Status::Forbidden, Status::NotFound] {
+            let response = client
+                .post(format!("/api/pastes/{id}/webauthn/verify"))
+                .header(ContentType::JSON)
+                .body(forged.to_string())
+                .dispatch();
+            assert_eq!(response.status(), expected);
+        }
+
+        let response = client.get(format!("/api/pastes/{id}")).dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+        let response = client
+            .get(format!("/api/pastes/{id}?webauthn=4102444800.AAAA"))
+            .dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+        assert!(response
+            .into_string()
+            .unwrap()
+            .contains("attestation_invalid"));
+    }
+
+    #[test]
+    fn root_create_negotiates_path_text_or_json_from_accept() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+        let payload = json!({ "content": "negotiated", "format": "plain_text" }).to_string();
+
+        for accept in [None, Some("*/*"), Some("text/plain")] {
+            let mut request = client
+                .post("/")
+                .header(ContentType::JSON)
+                .body(payload.clone());
+            if let Some(accept) = accept {
+                request = request.header(Header::new("Accept", accept));
+            }
+            let response = request.dispatch();
+            assert_eq!(response.status(), Status::Ok);
+            let path = response.into_string().unwrap();
+            assert!(
+                path.starts_with('/') && !path.contains('{'),
+                "{accept:?}: {path}"
+            );
+            assert_eq!(client.get(&path).dispatch().status(), Status::Ok);
+        }
+
+        let response = client
+            .post("/")
+            .header(ContentType::JSON)
+            .header(Accept::JSON)
+            .body(payload.clone())
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.content_type(), Some(ContentType::JSON));
+        let created: CreatePasteResponse =
+            serde_json::from_str(&response.into_string().unwrap()).unwrap();
+        assert_eq!(created.path, format!("/{}", created.id));
+        assert_eq!(client.get(&created.path).dispatch().status(), Status::Ok);
+
+        let response = client
+            .post("/")
+            .header(ContentType::JSON)
+            .header(Accept::JSON)
+            .body(json!({ "content": "" }).to_string())
+            .dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+        let error: serde_json::Value =
+            serde_json::from_str(&response.into_string().unwrap()).unwrap();
+        assert!(error["message"].is_string());
+    }
+
     #[test]
     fn create_api_returns_json_and_persists_paste() {
         let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
@@ -2108,6 +4477,66 @@ mod tests {
         assert!(!stats.encryption_usage.is_empty());
     }
 
+    #[test]
+    fn stats_formats_endpoint_returns_sorted_histogram() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(Arc::clone(&store))).expect("client");
+
+        for format in ["rust", "json", "rust", "markdown", "json", "rust"] {
+            let response = client
+                .post("/api/pastes")
+                .header(ContentType::JSON)
+                .body(json!({ "content": "x", "format": format }).to_string())
+                .dispatch();
+            assert_eq!(response.status(), Status::Ok);
+        }
+
+        let response = client.get("/api/stats/formats").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let formats: Vec<FormatUsageResponse> =
+            serde_json::from_str(&response.into_string().expect("body")).expect("histogram");
+        let histogram: Vec<(PasteFormat, usize)> =
+            formats.iter().map(|f| (f.format, f.count)).collect();
+        assert_eq!(
+            histogram,
+            vec![
+                (PasteFormat::Rust, 3),
+                (PasteFormat::Json, 2),
+                (PasteFormat::Markdown, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn metrics_record_route_latency_and_request_counts() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+
+        let created = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(json!({ "content": "measured", "format": "plain_text" }).to_string())
+            .dispatch();
+        assert_eq!(created.status(), Status::Ok);
+        let created: CreatePasteResponse =
+            serde_json::from_str(&created.into_string().expect("body")).expect("response");
+        assert_eq!(client.get(&created.path).dispatch().status(), Status::Ok);
+
+        let response = client.get("/metrics").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let text = response.into_string().expect("metrics");
+        assert!(text.contains(
+            "copypaste_http_requests_total{method=\"POST\",route=\"/api/pastes\",status=\"2xx\"} 1"
+        ));
+        assert!(text.contains(
+            "copypaste_http_request_duration_seconds_count{method=\"GET\",route=\"/<id..>\",status=\"2xx\"} 1"
+        ));
+        assert!(text.contains(
+            "copypaste_http_request_duration_seconds_bucket{method=\"GET\",route=\"/<id..>\",status=\"2xx\",le=\"+Inf\"} 1"
+        ));
+        assert!(!text.contains(&created.id));
+    }
+
     #[test]
     fn health_endpoint_returns_ok_status() {
         let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
@@ -2118,10 +4547,13 @@ mod tests {
         assert_eq!(response.status(), Status::Ok);
 
         let body = response.into_string().expect("body");
+        let raw: serde_json::Value = serde_json::from_str(&body).expect("parse health");
+        assert!(raw["uptime_seconds"].is_u64(), "{body}");
         let health: HealthResponse = serde_json::from_str(&body).expect("parse health");
 
         assert_eq!(health.status, "ok");
         assert!(health.timestamp > 0);
+        assert_eq!(health.persistence_backend, "memory");
     }
 
     #[test]
@@ -2139,12 +4571,51 @@ mod tests {
 
         assert!(!health.status.is_empty());
         assert!(health.timestamp > 0);
+        assert!(health.uptime_seconds < 24 * 3600);
+        assert_eq!(health.persistence_backend, "memory");
         assert_eq!(health.services.backend.status, "ok");
         assert_eq!(health.services.storage.status, "ok");
         // crypto_verifier status depends on whether service is running
         assert!(!health.services.crypto_verifier.status.is_empty());
     }
 
+    #[test]
+    fn detailed_health_degrades_when_persistence_is_unreachable() {
+        use crate::server::redis::RedisPersistenceAdapter;
+        use httpmock::prelude::*;
+
+        let detailed_health = |server: &MockServer| {
+            let adapter = RedisPersistenceAdapter::new(server.base_url(), "token", "paste:");
+            let store: SharedPasteStore =
+                Arc::new(MemoryPasteStore::with_persistence(Arc::new(adapter)));
+            let client = Client::tracked(build_rocket(store)).expect("client");
+            let response = client.get("/api/health").dispatch();
+            assert_eq!(response.status(), Status::Ok);
+            serde_json::from_str::<DetailedHealthResponse>(&response.into_string().unwrap())
+                .expect("parse detailed health")
+        };
+
+        let healthy = MockServer::start();
+        healthy.mock(|when, then| {
+            when.method(GET).path("/ping");
+            then.status(200)
+                .json_body(json!({"result": "PONG", "error": null}));
+        });
+        assert_eq!(detailed_health(&healthy).services.storage.status, "ok");
+
+        let failing = MockServer::start();
+        let ping = failing.mock(|when, then| {
+            when.method(GET).path("/ping");
+            then.status(503).body("redis down");
+        });
+        let health = detailed_health(&failing);
+        ping.assert();
+        assert_eq!(health.persistence_backend, "redis");
+        assert_eq!(health.services.storage.status, "degraded");
+        assert!(health.services.storage.message.unwrap().contains("503"));
+        assert_ne!(health.status, "ok");
+    }
+
     #[test]
     fn status_to_code_maps_known_codes() {
         assert_eq!(status_to_code(Status::BadRequest), "bad_request");
@@ -2219,7 +4690,7 @@ mod tests {
             .dispatch();
         assert_eq!(ok.status(), Status::Ok);
         let view: PasteViewResponse = serde_json::from_str(&ok.into_string().unwrap()).unwrap();
-        assert_eq!(view.content, "secret content");
+        assert_eq!(view.content.as_deref(), Some("secret content"));
         assert!(view.encryption.requires_key);
     }
 
@@ -2241,20 +4712,341 @@ mod tests {
         let get = client.get(format!("/api/pastes/{}", created.id)).dispatch();
         assert_eq!(get.status(), Status::Ok);
         let view: PasteViewResponse = serde_json::from_str(&get.into_string().unwrap()).unwrap();
-        assert_eq!(view.content, "hello");
+        assert_eq!(view.content.as_deref(), Some("hello"));
         assert!(!view.encryption.requires_key);
         assert!(!view.burn_after_reading);
     }
 
     #[test]
-    fn auth_challenge_returns_nonempty_string() {
+    fn anchor_verify_detects_drift_from_the_anchored_manifest() {
         let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
-        let rocket = build_rocket(store);
-        let client = Client::tracked(rocket).expect("client");
+        let client = Client::tracked(build_rocket(store.clone())).expect("client");
 
-        let resp = client.get("/api/auth/challenge").dispatch();
-        assert_eq!(resp.status(), Status::Ok);
-        let body = resp.into_string().unwrap();
+        let create = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(json!({"content": "audited", "format": "plain_text"}).to_string())
+            .dispatch();
+        let created: CreatePasteResponse =
+            serde_json::from_str(&create.into_string().unwrap()).unwrap();
+        let verify_path = format!("/api/pastes/{}/anchor/verify", created.id);
+
+        let unanchored = client.get(verify_path.clone()).dispatch();
+        assert_eq!(unanchored.status(), Status::BadRequest);
+
+        let anchor = client
+            .post(format!("/api/pastes/{}/anchor", created.id))
+            .dispatch();
+        assert_eq!(anchor.status(), Status::Ok);
+        let anchored: AnchorResponse =
+            serde_json::from_str(&anchor.into_string().unwrap()).unwrap();
+
+        // Reads are not drift.
+        let view = client.get(format!("/api/pastes/{}", created.id)).dispatch();
+        assert_eq!(view.status(), Status::Ok);
+
+        let verify = |path: String| -> AnchorVerifyResponse {
+            let resp = client.get(path).dispatch();
+            assert_eq!(resp.status(), Status::Ok);
+            serde_json::from_str(&resp.into_string().unwrap()).unwrap()
+        };
+        let stored = verify(verify_path.clone());
+        assert!(stored.matches);
+        assert_eq!(stored.expected_hash, anchored.hash);
+        assert_eq!(stored.current_hash, anchored.hash);
+        let supplied = verify(format!("{verify_path}?hash={}", anchored.hash));
+        assert!(supplied.matches);
+        assert_eq!(anchored.content_mode, AnchorContentMode::Full);
+
+        let private = client
+            .post(format!("/api/pastes/{}/anchor", created.id))
+            .header(ContentType::JSON)
+            .body(json!({"contentMode": "digest_only"}).to_string())
+            .dispatch();
+        assert_eq!(private.status(), Status::Ok);
+        let private: AnchorResponse =
+            serde_json::from_str(&private.into_string().unwrap()).unwrap();
+        assert_eq!(private.content_mode, AnchorContentMode::DigestOnly);
+        assert_ne!(private.hash, anchored.hash);
+        assert!(private.manifest.content.is_none());
+        let digest_verified = verify(verify_path.clone());
+        assert!(digest_verified.matches);
+        assert_eq!(digest_verified.content_mode, AnchorContentMode::DigestOnly);
+        let full_verified = verify(format!("{verify_path}?hash={}", anchored.hash));
+        assert!(full_verified.matches);
+        assert_eq!(full_verified.content_mode, AnchorContentMode::Full);
+
+        tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(store.set_bundle_parent(&created.id, "some-bundle"))
+            .expect("metadata change");
+
+        let drifted = verify(format!("{verify_path}?hash={}", anchored.hash));
+        assert!(!drifted.matches);
+        assert_eq!(drifted.expected_hash, anchored.hash);
+        assert_ne!(drifted.current_hash, anchored.hash);
+    }
+
+    #[test]
+    fn create_detects_language_only_for_untyped_pastes() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store.clone())).expect("client");
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        let create = |body: serde_json::Value| {
+            let resp = client
+                .post("/api/pastes")
+                .header(ContentType::JSON)
+                .body(body.to_string())
+                .dispatch();
+            assert_eq!(resp.status(), Status::Ok);
+            let raw: serde_json::Value =
+                serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+            let id = raw["id"].as_str().unwrap().to_string();
+            let format = rt.block_on(store.peek_paste(&id)).unwrap().format;
+            (format, raw.get("detectedFormat").cloned())
+        };
+
+        let rust = "fn main() {\n    println!(\"hi\");\n}\n";
+        assert_eq!(
+            create(json!({ "content": rust, "format": "code" })),
+            (PasteFormat::Rust, Some(json!(true)))
+        );
+        assert_eq!(
+            create(json!({ "content": "{\"a\": [1, 2]}" })),
+            (PasteFormat::Json, Some(json!(true)))
+        );
+        // An explicit format always wins.
+        assert_eq!(
+            create(json!({ "content": rust, "format": "plain_text" })),
+            (PasteFormat::PlainText, None)
+        );
+        assert_eq!(
+            create(json!({ "content": "just some notes" })),
+            (PasteFormat::PlainText, None)
+        );
+        // Encrypted pastes keep their format private.
+        assert_eq!(
+            create(json!({
+                "content": rust,
+                "format": "code",
+                "encryption": { "algorithm": "aes256_gcm", "key": "k" }
+            })),
+            (PasteFormat::Code, None)
+        );
+    }
+
+    #[test]
+    fn immutable_paste_views_revalidate_with_etag() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+
+        let create = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(json!({"content": "hello", "format": "plain_text"}).to_string())
+            .dispatch();
+        let created: CreatePasteResponse =
+            serde_json::from_str(&create.into_string().unwrap()).unwrap();
+
+        for path in [format!("/{}", created.id), format!("/raw/{}", created.id)] {
+            let first = client.get(path.clone()).dispatch();
+            assert_eq!(first.status(), Status::Ok);
+            let etag = first.headers().get_one("ETag").expect("etag").to_string();
+            assert_eq!(
+                first.headers().get_one("Cache-Control"),
+                Some("private, no-cache")
+            );
+
+            let second = client
+                .get(path.clone())
+                .header(Header::new("If-None-Match", etag.clone()))
+                .dispatch();
+            assert_eq!(second.status(), Status::NotModified);
+            assert_eq!(second.headers().get_one("ETag"), Some(etag.as_str()));
+            assert!(second.into_string().unwrap_or_default().is_empty());
+
+            let stale = client
+                .get(path)
+                .header(Header::new("If-None-Match", "\"stale\""))
+                .dispatch();
+            assert_eq!(stale.status(), Status::Ok);
+        }
+
+        let html = client.get(format!("/{}", created.id)).dispatch();
+        let raw = client.get(format!("/raw/{}", created.id)).dispatch();
+        assert_ne!(
+            html.headers().get_one("ETag"),
+            raw.headers().get_one("ETag")
+        );
+    }
+
+    #[test]
+    fn show_route_sort_keys_reorders_json_with_its_own_etag() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+
+        let create = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(json!({"content": r#"{"zeta":1,"alpha":2}"#, "format": "json"}).to_string())
+            .dispatch();
+        let created: CreatePasteResponse =
+            serde_json::from_str(&create.into_string().unwrap()).unwrap();
+
+        let written = client.get(format!("/{}", created.id)).dispatch();
+        let written_etag = written.headers().get_one("ETag").map(str::to_string);
+        let written = written.into_string().unwrap();
+        assert!(written.find("zeta") < written.find("alpha"));
+
+        let sorted = client
+            .get(format!("/{}?sort_keys=1", created.id))
+            .dispatch();
+        assert_ne!(
+            sorted.headers().get_one("ETag").map(str::to_string),
+            written_etag
+        );
+        let sorted = sorted.into_string().unwrap();
+        assert!(sorted.find("alpha") < sorted.find("zeta"));
+        assert!(sorted.contains("language-json"));
+    }
+
+    #[test]
+    fn burn_after_reading_views_are_never_cacheable() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+
+        for path_prefix in ["/", "/raw/"] {
+            let create = client
+                .post("/api/pastes")
+                .header(ContentType::JSON)
+                .body(
+                    json!({"content": "secret", "format": "plain_text", "burn_after_reading": true})
+                        .to_string(),
+                )
+                .dispatch();
+            let created: CreatePasteResponse =
+                serde_json::from_str(&create.into_string().unwrap()).unwrap();
+
+            let view = client
+                .get(format!("{path_prefix}{}", created.id))
+                .header(Header::new("If-None-Match", "*"))
+                .dispatch();
+            assert_eq!(view.status(), Status::Ok);
+            assert!(view.headers().get_one("ETag").is_none());
+            assert_eq!(view.headers().get_one("Cache-Control"), Some("no-store"));
+        }
+    }
+
+    #[test]
+    fn show_api_reports_content_digest_and_flags_tampering() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store.clone())).expect("client");
+
+        let create = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(json!({"content": "hello", "format": "plain_text"}).to_string())
+            .dispatch();
+        let created: CreatePasteResponse =
+            serde_json::from_str(&create.into_string().unwrap()).unwrap();
+
+        let get = client.get(format!("/api/pastes/{}", created.id)).dispatch();
+        let body: serde_json::Value = serde_json::from_str(&get.into_string().unwrap()).unwrap();
+        assert_eq!(
+            body["contentSha256"],
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        assert!(body.get("tampered").is_none());
+
+        // Swap the stored content underneath the recorded digest.
+        let original = body["contentSha256"].as_str().map(str::to_string);
+        tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(store.update_paste(
+                &created.id,
+                StoredContent::Plain {
+                    text: "hellO".into(),
+                },
+                original,
+            ))
+            .expect("update");
+
+        let get = client.get(format!("/api/pastes/{}", created.id)).dispatch();
+        assert_eq!(get.status(), Status::Ok);
+        let view: PasteViewResponse = serde_json::from_str(&get.into_string().unwrap()).unwrap();
+        assert_eq!(view.content.as_deref(), Some("hellO"));
+        assert!(view.tampered);
+    }
+
+    #[test]
+    fn encrypted_paste_digest_is_only_shown_to_key_holders() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store.clone())).expect("client");
+        let key = "correct horse battery staple";
+
+        let create = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(
+                json!({
+                    "content": "hunter2",
+                    "encryption": { "algorithm": "aes256_gcm", "key": key }
+                })
+                .to_string(),
+            )
+            .dispatch();
+        let created: CreatePasteResponse =
+            serde_json::from_str(&create.into_string().unwrap()).unwrap();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let stored = runtime.block_on(store.peek_paste(&created.id)).unwrap();
+        assert!(stored.metadata.content_sha256.is_none());
+
+        // Records from before digests were withheld must not leak theirs.
+        runtime
+            .block_on(store.update_paste(
+                &created.id,
+                stored.content,
+                Some(content_digest("hunter2")),
+            ))
+            .expect("update");
+        for path in [
+            format!("/api/pastes/{}?metadata_only=true", created.id),
+            format!("/api/pastes/{}/meta", created.id),
+        ] {
+            let response = client.get(&path).dispatch();
+            assert_eq!(response.status(), Status::Ok, "{path}");
+            let body: serde_json::Value =
+                serde_json::from_str(&response.into_string().unwrap()).unwrap();
+            assert!(body.get("contentSha256").is_none(), "{path}: {body}");
+        }
+
+        let response = client
+            .get(format!(
+                "/api/pastes/{}?key={}",
+                created.id,
+                urlencoding::encode(key)
+            ))
+            .dispatch();
+        let view: PasteViewResponse =
+            serde_json::from_str(&response.into_string().unwrap()).unwrap();
+        assert_eq!(view.content.as_deref(), Some("hunter2"));
+        assert_eq!(
+            view.content_sha256.as_deref(),
+            Some(content_digest("hunter2").as_str())
+        );
+        assert!(!view.tampered);
+    }
+
+    #[test]
+    fn auth_challenge_returns_nonempty_string() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let rocket = build_rocket(store);
+        let client = Client::tracked(rocket).expect("client");
+
+        let resp = client.get("/api/auth/challenge").dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+        let body = resp.into_string().unwrap();
         let parsed: serde_json::Value = serde_json::from_str(&body).unwrap();
         assert!(!parsed["challenge"].as_str().unwrap().is_empty());
     }
@@ -2301,59 +5093,431 @@ mod tests {
             .dispatch();
         assert_eq!(resp.status(), Status::Unauthorized);
 
-        // With a valid session → own count (zero pastes yet).
-        let (token, _) = login(&client);
+        // With a valid session → own count (zero pastes yet).
+        let (token, _) = login(&client);
+        let resp = client
+            .get("/api/user/paste-count")
+            .header(bearer(&token))
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+        let parsed: serde_json::Value = serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+        assert_eq!(parsed["pasteCount"], 0);
+    }
+
+    #[test]
+    fn user_paste_list_requires_session_and_returns_own_pastes() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let rocket = build_rocket(store);
+        let client = Client::tracked(rocket).expect("client");
+
+        // Without a session token → 401.
+        let resp = client
+            .get("/api/user/pastes?pubkey_hash=nonexistent")
+            .dispatch();
+        assert_eq!(resp.status(), Status::Unauthorized);
+
+        // With a garbage token → 401.
+        let resp = client
+            .get("/api/user/pastes")
+            .header(bearer("not-a-real-session"))
+            .dispatch();
+        assert_eq!(resp.status(), Status::Unauthorized);
+
+        // With a valid session, only own pastes are listed.
+        let (token, _) = login(&client);
+        let create_resp = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .header(bearer(&token))
+            .body(json!({ "content": "mine", "format": "plain_text" }).to_string())
+            .dispatch();
+        assert_eq!(create_resp.status(), Status::Ok);
+
+        let resp = client
+            .get("/api/user/pastes")
+            .header(bearer(&token))
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+        let parsed: serde_json::Value = serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+        assert_eq!(parsed["pastes"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn custom_id_is_used_verbatim_and_served_across_segments() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+
+        let response = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(json!({ "content": "runbook", "custom_id": "team/deploy-runbook" }).to_string())
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let created: CreatePasteResponse =
+            serde_json::from_str(&response.into_string().unwrap()).unwrap();
+        assert_eq!(created.id, "team/deploy-runbook");
+        assert_eq!(created.path, "/team/deploy-runbook");
+
+        let view = client.get("/team/deploy-runbook").dispatch();
+        assert_eq!(view.status(), Status::Ok);
+        assert!(view.into_string().unwrap().contains("runbook"));
+        let raw = client.get("/raw/team/deploy-runbook").dispatch();
+        assert_eq!(raw.into_string().as_deref(), Some("runbook"));
+        let api = client.get("/api/pastes/team%2Fdeploy-runbook").dispatch();
+        assert_eq!(api.status(), Status::Ok);
+
+        // Reserved first segments still reach their own routes.
+        let spa = client.get("/p/team").dispatch();
+        assert_eq!(spa.status(), Status::Ok);
+        assert_eq!(spa.content_type(), Some(ContentType::HTML));
+    }
+
+    #[test]
+    fn taken_custom_id_is_a_conflict() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+        let create = |content: &str| {
+            client
+                .post("/api/pastes")
+                .header(ContentType::JSON)
+                .body(json!({ "content": content, "custom_id": "release-notes" }).to_string())
+                .dispatch()
+        };
+
+        assert_eq!(create("first").status(), Status::Ok);
+        let second = create("second");
+        assert_eq!(second.status(), Status::Conflict);
+        let err: ApiError = serde_json::from_str(&second.into_string().unwrap()).unwrap();
+        assert_eq!(err.code, "conflict");
+
+        let raw = client.get("/raw/release-notes").dispatch();
+        assert_eq!(raw.into_string().as_deref(), Some("first"));
+    }
+
+    #[test]
+    fn reserved_or_malformed_custom_ids_are_rejected() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+
+        for custom_id in ["raw", "api/keys", "health", "static/app", "Upper-Case", "a"] {
+            let response = client
+                .post("/api/pastes")
+                .header(ContentType::JSON)
+                .body(json!({ "content": "x", "custom_id": custom_id }).to_string())
+                .dispatch();
+            assert_eq!(response.status(), Status::BadRequest, "{custom_id}");
+        }
+    }
+
+    #[test]
+    fn create_takes_owner_from_session_not_body() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store.clone())).expect("client");
+        let (token, pubkey_hash) = login(&client);
+        let owner_of = |id: &str| {
+            tokio::runtime::Runtime::new()
+                .unwrap()
+                .block_on(store.peek_paste(id))
+                .unwrap()
+                .metadata
+                .owner_pubkey_hash
+        };
+        let create = |body: serde_json::Value, token: Option<&str>| {
+            let mut request = client
+                .post("/api/pastes")
+                .header(ContentType::JSON)
+                .body(body.to_string());
+            if let Some(token) = token {
+                request = request.header(bearer(token));
+            }
+            request.dispatch()
+        };
+
+        // Authenticated: the session's hash is stamped.
+        let resp = create(json!({ "content": "mine" }), Some(&token));
+        assert_eq!(resp.status(), Status::Ok);
+        let created: CreatePasteResponse =
+            serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+        assert_eq!(owner_of(&created.id), Some(pubkey_hash.clone()));
+
+        // Authenticated but claiming someone else: rejected.
+        let resp = create(
+            json!({ "content": "spoof", "owner_pubkey_hash": "victim" }),
+            Some(&token),
+        );
+        assert_eq!(resp.status(), Status::Forbidden);
+
+        // Anonymous: the body's claim is ignored.
+        let resp = create(
+            json!({ "content": "anon", "owner_pubkey_hash": pubkey_hash }),
+            None,
+        );
+        assert_eq!(resp.status(), Status::Ok);
+        let created: CreatePasteResponse =
+            serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+        assert_eq!(owner_of(&created.id), None);
+    }
+
+    #[test]
+    fn user_paste_list_pages_cover_every_paste_once() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+        let (token, _) = login(&client);
+        for i in 0..7 {
+            let resp = client
+                .post("/api/pastes")
+                .header(ContentType::JSON)
+                .header(bearer(&token))
+                .body(json!({ "content": format!("paste {i}") }).to_string())
+                .dispatch();
+            assert_eq!(resp.status(), Status::Ok);
+        }
+
+        let page = |query: &str| -> serde_json::Value {
+            let resp = client
+                .get(format!("/api/user/pastes{query}"))
+                .header(bearer(&token))
+                .dispatch();
+            assert_eq!(resp.status(), Status::Ok);
+            serde_json::from_str(&resp.into_string().unwrap()).unwrap()
+        };
+
+        let mut seen = Vec::new();
+        let mut offset = 0;
+        let mut sizes = Vec::new();
+        loop {
+            let body = page(&format!("?limit=3&offset={offset}"));
+            assert_eq!(body["total"], 7);
+            let ids: Vec<String> = body["pastes"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|p| p["id"].as_str().unwrap().to_string())
+                .collect();
+            sizes.push(ids.len());
+            seen.extend(ids);
+            match body["next"].as_u64() {
+                Some(next) => offset = next as usize,
+                None => break,
+            }
+        }
+        assert_eq!(sizes, vec![3, 3, 1]);
+
+        let full = page("");
+        let all: Vec<String> = full["pastes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["id"].as_str().unwrap().to_string())
+            .collect();
+        assert!(full["next"].is_null());
+        assert_eq!(seen, all, "pages concatenate to the full listing in order");
+        let mut unique = seen.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), 7);
+
+        let past_end = page("?offset=10");
+        assert!(past_end["pastes"].as_array().unwrap().is_empty());
+        assert!(past_end["next"].is_null());
+
+        let resp = client
+            .get("/api/user/pastes?limit=0")
+            .header(bearer(&token))
+            .dispatch();
+        assert_eq!(resp.status(), Status::BadRequest);
+    }
+
+    #[test]
+    fn user_paste_count_does_not_consume_burn_after_reading_pastes() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store.clone())).expect("client");
+        let (token, pubkey_hash) = login(&client);
+
+        let resp = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .header(bearer(&token))
+            .body(
+                json!({
+                    "content": "read me once",
+                    "burn_after_reading": true,
+                    "owner_pubkey_hash": pubkey_hash
+                })
+                .to_string(),
+            )
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+        let created: CreatePasteResponse =
+            serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+
+        for _ in 0..2 {
+            let resp = client
+                .get("/api/user/paste-count")
+                .header(bearer(&token))
+                .dispatch();
+            let parsed: serde_json::Value =
+                serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+            assert_eq!(parsed["pasteCount"], 1);
+        }
+
+        let resp = client
+            .get("/api/user/pastes")
+            .header(bearer(&token))
+            .dispatch();
+        let parsed: serde_json::Value = serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+        assert_eq!(parsed["pastes"][0]["burnAfterReading"], true);
+
+        let paste = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(store.peek_paste(&created.id))
+            .expect("paste should still exist");
+        assert_eq!(paste.metadata.access_count, 0);
+    }
+
+    #[test]
+    fn delete_api_lets_owner_revoke_and_404s_afterwards() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+        let (token, _) = login(&client);
+
+        let resp = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .header(bearer(&token))
+            .body(json!({ "content": "secret" }).to_string())
+            .dispatch();
+        let created: CreatePasteResponse =
+            serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+
+        let resp = client
+            .delete(format!("/api/pastes/{}", created.id))
+            .header(bearer(&token))
+            .dispatch();
+        assert_eq!(resp.status(), Status::NoContent);
+
+        let resp = client.get(format!("/api/pastes/{}", created.id)).dispatch();
+        assert_eq!(resp.status(), Status::NotFound);
+
         let resp = client
-            .get("/api/user/paste-count")
+            .delete(format!("/api/pastes/{}", created.id))
             .header(bearer(&token))
             .dispatch();
-        assert_eq!(resp.status(), Status::Ok);
-        let parsed: serde_json::Value = serde_json::from_str(&resp.into_string().unwrap()).unwrap();
-        assert_eq!(parsed["pasteCount"], 0);
+        assert_eq!(resp.status(), Status::NotFound);
     }
 
     #[test]
-    fn user_paste_list_requires_session_and_returns_own_pastes() {
+    fn delete_api_rejects_other_users_and_missing_credentials() {
         let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
-        let rocket = build_rocket(store);
-        let client = Client::tracked(rocket).expect("client");
+        let client = Client::tracked(build_rocket(store)).expect("client");
+        let sessions = client.rocket().state::<SharedSessionStore>().unwrap();
+        sessions.insert("owner-token", "owner-hash");
+        sessions.insert("intruder-token", "someone-else");
 
-        // Without a session token → 401.
         let resp = client
-            .get("/api/user/pastes?pubkey_hash=nonexistent")
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .header(bearer("owner-token"))
+            .body(json!({ "content": "secret" }).to_string())
             .dispatch();
-        assert_eq!(resp.status(), Status::Unauthorized);
+        let created: CreatePasteResponse =
+            serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+        let path = format!("/api/pastes/{}", created.id);
 
-        // With a garbage token → 401.
         let resp = client
-            .get("/api/user/pastes")
-            .header(bearer("not-a-real-session"))
+            .delete(&path)
+            .header(bearer("intruder-token"))
             .dispatch();
+        assert_eq!(resp.status(), Status::Forbidden);
+
+        let resp = client.delete(&path).dispatch();
         assert_eq!(resp.status(), Status::Unauthorized);
 
-        // With a valid session, only own pastes are listed.
-        let (token, pubkey_hash) = login(&client);
-        let create_resp = client
+        let resp = client.get(&path).dispatch();
+        assert_eq!(resp.status(), Status::Ok, "paste must survive");
+    }
+
+    #[test]
+    fn authorize_delete_gates_unowned_pastes_on_flag() {
+        let mut paste = StoredPaste {
+            content: StoredContent::Plain { text: "x".into() },
+            format: PasteFormat::PlainText,
+            created_at: current_timestamp(),
+            expires_at: None,
+            burn_after_reading: false,
+            max_reads: None,
+            bundle: None,
+            bundle_parent: None,
+            bundle_label: None,
+            not_before: None,
+            not_after: None,
+            persistence: None,
+            webhook: None,
+            metadata: PasteMetadata::default(),
+            is_live: false,
+            owner_token_hash: None,
+        };
+        assert_eq!(
+            authorize_delete(&paste, None, false).unwrap_err().0,
+            Status::Forbidden
+        );
+        assert_eq!(
+            authorize_delete(&paste, Some("anyone"), false)
+                .unwrap_err()
+                .0,
+            Status::Forbidden
+        );
+        assert!(authorize_delete(&paste, None, true).is_ok());
+
+        paste.metadata.owner_pubkey_hash = Some("owner".into());
+        assert!(authorize_delete(&paste, Some("owner"), false).is_ok());
+        assert_eq!(
+            authorize_delete(&paste, None, true).unwrap_err().0,
+            Status::Unauthorized
+        );
+    }
+
+    #[test]
+    fn successful_reads_increment_access_count() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store.clone())).expect("client");
+        let resp = client
             .post("/api/pastes")
             .header(ContentType::JSON)
-            .body(
-                json!({
-                    "content": "mine",
-                    "format": "plain_text",
-                    "owner_pubkey_hash": pubkey_hash
-                })
-                .to_string(),
-            )
+            .body(json!({ "content": "counted", "encryption": { "algorithm": "aes256_gcm", "key": "k" } }).to_string())
             .dispatch();
-        assert_eq!(create_resp.status(), Status::Ok);
+        let created: CreatePasteResponse =
+            serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let stored_count = || {
+            rt.block_on(store.peek_paste(&created.id))
+                .unwrap()
+                .metadata
+                .access_count
+        };
 
+        // A failed decrypt is not an access.
         let resp = client
-            .get("/api/user/pastes")
-            .header(bearer(&token))
+            .get(format!("/api/pastes/{}?key=wrong", created.id))
             .dispatch();
+        assert_eq!(resp.status(), Status::Forbidden);
+        assert_eq!(stored_count(), 0);
+
+        for (expected_shown, expected_stored) in [(0, 1), (1, 2)] {
+            let resp = client
+                .get(format!("/api/pastes/{}?key=k", created.id))
+                .dispatch();
+            let view: PasteViewResponse =
+                serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+            assert_eq!(view.access_count, expected_shown);
+            assert_eq!(stored_count(), expected_stored);
+        }
+
+        let resp = client.get(format!("/raw/{}?key=k", created.id)).dispatch();
         assert_eq!(resp.status(), Status::Ok);
-        let parsed: serde_json::Value = serde_json::from_str(&resp.into_string().unwrap()).unwrap();
-        assert_eq!(parsed["pastes"].as_array().unwrap().len(), 1);
+        let resp = client.get(format!("/{}?key=k", created.id)).dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+        assert_eq!(stored_count(), 4);
     }
 
     #[test]
@@ -2503,6 +5667,52 @@ mod tests {
         assert_eq!(second.status(), Status::NotFound);
     }
 
+    #[test]
+    fn raw_download_names_file_after_format() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+
+        for (format, extension, content_type) in [
+            ("rust", "rs", "text/plain; charset=utf-8"),
+            ("python", "py", "text/plain; charset=utf-8"),
+            ("json", "json", "application/json; charset=utf-8"),
+            ("markdown", "md", "text/markdown; charset=utf-8"),
+            ("plain_text", "txt", "text/plain; charset=utf-8"),
+        ] {
+            let response = client
+                .post("/api/pastes")
+                .header(ContentType::JSON)
+                .body(json!({ "content": "{}", "format": format }).to_string())
+                .dispatch();
+            let created: CreatePasteResponse =
+                serde_json::from_str(&response.into_string().unwrap()).unwrap();
+
+            let download = client
+                .get(format!("/raw/{}?download=1", created.id))
+                .dispatch();
+            assert_eq!(download.status(), Status::Ok);
+            assert_eq!(
+                download.headers().get_one("Content-Disposition"),
+                Some(format!("attachment; filename=\"{}.{extension}\"", created.id).as_str())
+            );
+            assert_eq!(
+                download.headers().get_one("Content-Type"),
+                Some(content_type)
+            );
+            assert_eq!(download.into_string().as_deref(), Some("{}"));
+
+            let inline = client.get(format!("/raw/{}", created.id)).dispatch();
+            assert!(inline.headers().get_one("Content-Disposition").is_none());
+            assert_eq!(inline.content_type(), Some(ContentType::Plain));
+        }
+    }
+
+    #[test]
+    fn download_file_stem_strips_path_and_quote_characters() {
+        assert_eq!(download_file_stem("team/notes\"x"), "team_notes_x");
+        assert_eq!(download_file_stem("stellar-otter_42"), "stellar-otter_42");
+    }
+
     #[test]
     fn stego_builtin_carrier_embeds_and_returns_carrier_image() {
         let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
@@ -2537,7 +5747,7 @@ mod tests {
             .dispatch();
         assert_eq!(get.status(), Status::Ok);
         let view: PasteViewResponse = serde_json::from_str(&get.into_string().unwrap()).unwrap();
-        assert_eq!(view.content, "hidden message");
+        assert_eq!(view.content.as_deref(), Some("hidden message"));
         assert!(view.encryption.requires_key);
         let stego = view.stego.expect("stego info should be present");
         assert_eq!(stego.carrier_mime, "image/png");
@@ -2609,6 +5819,64 @@ mod tests {
         assert_eq!(create.status(), Status::PayloadTooLarge);
     }
 
+    #[test]
+    fn stego_jpeg_carrier_is_rejected_as_lossy() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+
+        let payload = json!({
+            "content": "hidden",
+            "format": "plain_text",
+            "encryption": {
+                "algorithm": "aes256_gcm",
+                "key": "key"
+            },
+            "stego": {
+                "mode": "uploaded",
+                "data_uri": format!(
+                    "data:image/jpeg;base64,{}",
+                    BASE64_STANDARD.encode(b"\xFF\xD8\xFF\xE0\x00\x10JFIF")
+                )
+            }
+        });
+
+        let create = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(payload.to_string())
+            .dispatch();
+        assert_eq!(create.status(), Status::BadRequest);
+        let message = create.into_string().unwrap_or_default();
+        assert!(message.contains("Carrier image rejected"));
+        assert!(message.contains("lossy"));
+    }
+
+    #[test]
+    fn stego_accepts_carrier_mime_aliases() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+        let mut bmp = Vec::new();
+        image::RgbImage::new(64, 64)
+            .write_to(&mut std::io::Cursor::new(&mut bmp), image::ImageFormat::Bmp)
+            .unwrap();
+
+        let payload = json!({
+            "content": "hidden",
+            "format": "plain_text",
+            "encryption": { "algorithm": "aes256_gcm", "key": "key" },
+            "stego": {
+                "mode": "uploaded",
+                "data_uri": format!("data:image/x-ms-bmp;base64,{}", BASE64_STANDARD.encode(&bmp))
+            }
+        });
+        let create = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(payload.to_string())
+            .dispatch();
+        assert_eq!(create.status(), Status::Ok);
+    }
+
     #[test]
     fn stego_payload_digest_matches_ciphertext_sha256() {
         use sha2::{Digest, Sha256};
@@ -2830,30 +6098,355 @@ mod tests {
         assert_eq!(resp.status(), Status::Forbidden);
     }
 
-    // ── Admin auth with missing env var ────────────────────────────────────────
+    #[test]
+    fn tor_only_paste_gated_on_json_api() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let rocket = build_rocket(store);
+        let client = Client::tracked(rocket).expect("client");
+
+        let create_resp = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(
+                json!({
+                    "content": "secret tor api paste",
+                    "format": "plain_text",
+                    "tor_access_only": true
+                })
+                .to_string(),
+            )
+            .dispatch();
+        assert_eq!(create_resp.status(), Status::Ok);
+        let created: CreatePasteResponse =
+            serde_json::from_str(&create_resp.into_string().unwrap()).unwrap();
+        let path = format!("/api/pastes/{}", created.id);
+
+        let clearnet = client
+            .get(path.clone())
+            .header(rocket::http::Header::new("Host", "copypaste.fyi"))
+            .dispatch();
+        assert_eq!(clearnet.status(), Status::Forbidden);
+        let error: ApiError = serde_json::from_str(&clearnet.into_string().unwrap()).unwrap();
+        assert_eq!(error.code, "tor_only");
+
+        let onion = client
+            .get(path)
+            .header(rocket::http::Header::new("Host", "copypastexyz.onion"))
+            .dispatch();
+        assert_eq!(onion.status(), Status::Ok);
+        let view: PasteViewResponse = serde_json::from_str(&onion.into_string().unwrap()).unwrap();
+        assert_eq!(view.content.as_deref(), Some("secret tor api paste"));
+    }
+
+    // ── Admin auth with missing env var ────────────────────────────────────────
+
+    #[test]
+    fn admin_auth_with_no_env_var_rejects_arbitrary_bearer_token() {
+        std::env::remove_var("COPYPASTE_ADMIN_TOKEN");
+
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let rocket = build_rocket(store);
+        let client = Client::tracked(rocket).expect("client");
+
+        // Send a non-empty bearer token; no env var and no key in DB → rejected
+        let resp = client
+            .get("/api/admin/keys")
+            .header(rocket::http::Header::new(
+                "Authorization",
+                "Bearer notarealtoken",
+            ))
+            .dispatch();
+        // No admin key in DB and env var is unset → Unauthorized (no matching key)
+        assert_eq!(resp.status(), Status::Unauthorized);
+    }
+
+    // ── Time lock HTTP enforcement ─────────────────────────────────────────────
+
+    #[test]
+    fn show_route_applies_theme_query_to_every_page() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+
+        let create = |body: serde_json::Value| -> CreatePasteResponse {
+            let resp = client
+                .post("/api/pastes")
+                .header(ContentType::JSON)
+                .body(body.to_string())
+                .dispatch();
+            serde_json::from_str(&resp.into_string().unwrap()).unwrap()
+        };
+        let plain = create(json!({ "content": "themed" }));
+        let encrypted = create(json!({
+            "content": "themed",
+            "encryption": { "algorithm": "aes256_gcm", "key": "k" }
+        }));
+
+        let body = |path: String| client.get(path).dispatch().into_string().unwrap();
+        let dark = r#"data-theme="dark""#;
+        assert!(body(format!("/{}?theme=dark", plain.id)).contains(dark));
+        assert!(body(format!("/{}?theme=dark", encrypted.id)).contains(dark));
+        assert!(!body(format!("/{}", plain.id)).contains("data-theme"));
+    }
+
+    #[test]
+    fn show_route_negotiates_representation_from_accept() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+
+        let create = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(json!({ "content": "negotiated <b>text</b>" }).to_string())
+            .dispatch();
+        let created: CreatePasteResponse =
+            serde_json::from_str(&create.into_string().unwrap()).unwrap();
+        let get = |accept: &str| {
+            client
+                .get(format!("/{}", created.id))
+                .header(Header::new("Accept", accept.to_string()))
+                .dispatch()
+        };
+
+        let html = get("text/html,application/xhtml+xml,*/*;q=0.8");
+        assert_eq!(html.status(), Status::Ok);
+        assert_eq!(html.content_type(), Some(ContentType::HTML));
+        assert_eq!(html.headers().get_one("Vary"), Some("Accept"));
+        let body = html.into_string().unwrap();
+        assert!(body.starts_with("<!DOCTYPE html>"));
+        assert!(body.contains("&lt;b&gt;") && !body.contains("<b>text"));
+
+        let text = get("text/plain");
+        assert_eq!(text.status(), Status::Ok);
+        assert_eq!(text.content_type(), Some(ContentType::Plain));
+        assert_eq!(text.headers().get_one("Vary"), Some("Accept"));
+        assert_eq!(text.into_string().unwrap(), "negotiated <b>text</b>");
+
+        let json = get("application/json");
+        assert_eq!(json.status(), Status::Ok);
+        assert_eq!(json.content_type(), Some(ContentType::JSON));
+        let view: serde_json::Value = serde_json::from_str(&json.into_string().unwrap()).unwrap();
+        assert_eq!(view["id"], created.id.as_str());
+        assert_eq!(view["content"], "negotiated <b>text</b>");
+
+        let default = client.get(format!("/{}", created.id)).dispatch();
+        assert_eq!(default.content_type(), Some(ContentType::HTML));
+        let wildcard = get("*/*");
+        assert_eq!(wildcard.content_type(), Some(ContentType::HTML));
+    }
+
+    #[test]
+    fn show_route_negotiated_reads_share_burn_lock_and_attestation() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+
+        let create = |body: serde_json::Value| -> CreatePasteResponse {
+            let resp = client
+                .post("/api/pastes")
+                .header(ContentType::JSON)
+                .body(body.to_string())
+                .dispatch();
+            serde_json::from_str(&resp.into_string().unwrap()).unwrap()
+        };
+        let get = |path: String, accept: &str| {
+            client
+                .get(path)
+                .header(Header::new("Accept", accept.to_string()))
+                .dispatch()
+        };
+        let accepts = ["text/html", "text/plain", "application/json"];
+
+        // Whichever representation reads a burn paste first consumes it.
+        for first in accepts {
+            let burn = create(json!({ "content": "once", "burn_after_reading": true }));
+            assert_eq!(get(format!("/{}", burn.id), first).status(), Status::Ok);
+            for accept in accepts {
+                let again = get(format!("/{}", burn.id), accept);
+                assert_eq!(again.status(), Status::NotFound, "{first} then {accept}");
+            }
+        }
+
+        let locked = create(json!({
+            "content": "later",
+            "time_lock": { "not_before": "9999-01-01T00:00:00Z" }
+        }));
+        for accept in accepts {
+            let resp = get(format!("/{}", locked.id), accept);
+            assert_eq!(resp.status(), Status::Locked, "{accept}");
+        }
+        let json = get(format!("/{}", locked.id), "application/json");
+        assert!(json.into_string().unwrap().contains("time_locked"));
+
+        let gated = create(json!({
+            "content": "gated",
+            "attestation": { "kind": "shared_secret", "secret": "open-sesame" }
+        }));
+        let text = get(format!("/{}", gated.id), "text/plain");
+        assert_eq!(text.status(), Status::Unauthorized);
+        let json = get(format!("/{}", gated.id), "application/json");
+        assert_eq!(json.status(), Status::Unauthorized);
+        assert!(json.into_string().unwrap().contains("attestation_required"));
+        let html = get(format!("/{}", gated.id), "text/html");
+        assert!(html.into_string().unwrap().contains("attest"));
+        for accept in accepts {
+            let resp = get(format!("/{}?attest=open-sesame", gated.id), accept);
+            assert_eq!(resp.status(), Status::Ok, "{accept}");
+        }
+    }
+
+    #[test]
+    fn undecryptable_pastes_answer_exactly_like_a_wrong_key() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(Arc::clone(&store))).expect("client");
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        let create = || {
+            let resp = client
+                .post("/api/pastes")
+                .header(ContentType::JSON)
+                .body(
+                    json!({
+                        "content": "guarded",
+                        "encryption": { "algorithm": "aes256_gcm", "key": "right" }
+                    })
+                    .to_string(),
+                )
+                .dispatch();
+            serde_json::from_str::<CreatePasteResponse>(&resp.into_string().unwrap())
+                .unwrap()
+                .id
+        };
+        let status = |path: String| client.get(path).dispatch().status();
+        let api_code = |path: String| {
+            let body = client.get(path).dispatch().into_string().unwrap();
+            serde_json::from_str::<serde_json::Value>(&body).unwrap()["code"].clone()
+        };
+
+        let intact = create();
+        assert_eq!(status(format!("/raw/{intact}")), Status::Unauthorized);
+        assert_eq!(
+            status(format!("/raw/{intact}?key=wrong")),
+            Status::Forbidden
+        );
+        assert_eq!(api_code(format!("/api/pastes/{intact}")), "key_required");
+        assert_eq!(
+            api_code(format!("/api/pastes/{intact}?key=wrong")),
+            "invalid_key"
+        );
+        let wrong_key_page = client
+            .get(format!("/{intact}?key=wrong"))
+            .dispatch()
+            .into_string()
+            .unwrap()
+            .replace(&intact, "ID");
+
+        // Storage that no longer decodes, or an envelope this build can't
+        // read, fails the right key the same way a wrong key fails.
+        let corruptions: [fn(&mut String, &mut u8); 2] = [
+            |ciphertext, _| *ciphertext = "%%% not base64 %%%".to_string(),
+            |_, version| *version = 99,
+        ];
+        for corrupt in corruptions {
+            let id = create();
+            let mut content = rt.block_on(store.peek_paste(&id)).unwrap().content;
+            let StoredContent::Encrypted {
+                ciphertext,
+                envelope_version,
+                ..
+            } = &mut content
+            else {
+                panic!("expected encrypted content");
+            };
+            corrupt(ciphertext, envelope_version);
+            rt.block_on(store.update_paste(&id, content, None))
+                .expect("update");
+
+            assert_eq!(status(format!("/raw/{id}")), Status::Unauthorized);
+            assert_eq!(status(format!("/raw/{id}?key=right")), Status::Forbidden);
+            assert_eq!(
+                status(format!("/api/pastes/{id}?key=right")),
+                Status::Forbidden
+            );
+            assert_eq!(
+                api_code(format!("/api/pastes/{id}?key=right")),
+                "invalid_key"
+            );
+            let page = client
+                .get(format!("/{id}?key=right"))
+                .dispatch()
+                .into_string()
+                .unwrap();
+            assert_eq!(page.replace(&id, "ID"), wrong_key_page);
+        }
+    }
+
+    #[test]
+    fn binary_paste_round_trips_bytes_through_raw_download() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+        // A gzip header: not valid UTF-8.
+        let bytes: &[u8] = &[0x1f, 0x8b, 0x08, 0x00, 0xff, 0xfe, 0x80, 0x00, b'\n'];
+
+        let create = |extra: serde_json::Value| {
+            let mut body = json!({
+                "content": BASE64_STANDARD.encode(bytes),
+                "content_encoding": "base64"
+            });
+            body.as_object_mut()
+                .unwrap()
+                .extend(extra.as_object().unwrap().clone());
+            let resp = client
+                .post("/api/pastes")
+                .header(ContentType::JSON)
+                .body(body.to_string())
+                .dispatch();
+            assert_eq!(resp.status(), Status::Ok);
+            serde_json::from_str::<CreatePasteResponse>(&resp.into_string().unwrap()).unwrap()
+        };
+
+        let plain = create(json!({}));
+        let raw = client.get(format!("/raw/{}", plain.id)).dispatch();
+        assert_eq!(raw.status(), Status::Ok);
+        assert_eq!(raw.content_type(), Some(ContentType::Binary));
+        assert_eq!(
+            raw.headers().get_one("Content-Disposition"),
+            Some(format!("attachment; filename=\"{}.bin\"", plain.id).as_str())
+        );
+        assert_eq!(raw.into_bytes().unwrap(), bytes);
+
+        let api = client.get(format!("/api/pastes/{}", plain.id)).dispatch();
+        let view: serde_json::Value = serde_json::from_str(&api.into_string().unwrap()).unwrap();
+        assert_eq!(view["encoding"], "base64");
+        assert_eq!(view["content_b64"], BASE64_STANDARD.encode(bytes));
+        assert!(view["content"].is_null());
+
+        let html = client.get(format!("/{}", plain.id)).dispatch();
+        let html = html.into_string().unwrap();
+        assert!(html.contains("Binary content (9 B)"));
+        assert!(html.contains(">download</a>"));
+
+        let encrypted = create(json!({
+            "encryption": { "algorithm": "aes256_gcm", "key": "binary-key" }
+        }));
+        let raw = client
+            .get(format!("/raw/{}?key=binary-key", encrypted.id))
+            .dispatch();
+        assert_eq!(raw.content_type(), Some(ContentType::Binary));
+        assert_eq!(raw.into_bytes().unwrap(), bytes);
+    }
 
     #[test]
-    fn admin_auth_with_no_env_var_rejects_arbitrary_bearer_token() {
-        std::env::remove_var("COPYPASTE_ADMIN_TOKEN");
-
+    fn binary_paste_rejects_invalid_base64() {
         let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
-        let rocket = build_rocket(store);
-        let client = Client::tracked(rocket).expect("client");
+        let client = Client::tracked(build_rocket(store)).expect("client");
 
-        // Send a non-empty bearer token; no env var and no key in DB → rejected
         let resp = client
-            .get("/api/admin/keys")
-            .header(rocket::http::Header::new(
-                "Authorization",
-                "Bearer notarealtoken",
-            ))
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(json!({ "content": "not base64!", "content_encoding": "base64" }).to_string())
             .dispatch();
-        // No admin key in DB and env var is unset → Unauthorized (no matching key)
-        assert_eq!(resp.status(), Status::Unauthorized);
+        assert_eq!(resp.status(), Status::BadRequest);
     }
 
-    // ── Time lock HTTP enforcement ─────────────────────────────────────────────
-
     #[test]
     fn show_route_time_lock_before_not_before_renders_locked_page() {
         let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
@@ -2877,9 +6470,12 @@ mod tests {
             serde_json::from_str(&create_resp.into_string().unwrap()).unwrap();
 
         let resp = client.get(format!("/{}", created.id)).dispatch();
-        assert_eq!(resp.status(), Status::Ok);
+        assert_eq!(resp.status(), Status::Locked);
         let body = resp.into_string().unwrap();
         assert!(body.contains("Time-locked paste") || body.contains("unlocks after"));
+
+        let raw = client.get(format!("/raw/{}", created.id)).dispatch();
+        assert_eq!(raw.status(), Status::Locked);
     }
 
     #[test]
@@ -2905,10 +6501,15 @@ mod tests {
             serde_json::from_str(&create_resp.into_string().unwrap()).unwrap();
 
         let resp = client.get(format!("/{}", created.id)).dispatch();
-        // After not_after, the access window has closed
-        assert_eq!(resp.status(), Status::Ok);
+        // After not_after, the access window has closed for good
+        assert_eq!(resp.status(), Status::Gone);
         let body = resp.into_string().unwrap();
         assert!(body.contains("Time window elapsed") || body.contains("Access window closed"));
+
+        let api = client.get(format!("/api/pastes/{}", created.id)).dispatch();
+        assert_eq!(api.status(), Status::Gone);
+        let error: ApiError = serde_json::from_str(&api.into_string().unwrap()).unwrap();
+        assert_eq!(error.code, "time_lock_elapsed");
     }
 
     #[test]
@@ -3048,15 +6649,20 @@ mod tests {
         let rocket = build_rocket(store);
         let client = Client::tracked(rocket).expect("client");
 
-        // Create a paste with a victim's owner hash.
+        // Create a paste owned by a victim's session.
+        client
+            .rocket()
+            .state::<SharedSessionStore>()
+            .unwrap()
+            .insert("victim-token", "victim_hash_abc123");
         let create_resp = client
             .post("/api/pastes")
             .header(ContentType::JSON)
+            .header(bearer("victim-token"))
             .body(
                 json!({
                     "content": "owner-only paste",
-                    "format": "plain_text",
-                    "owner_pubkey_hash": "victim_hash_abc123"
+                    "format": "plain_text"
                 })
                 .to_string(),
             )
@@ -3154,6 +6760,25 @@ mod tests {
         assert_eq!(err.0, Status::BadRequest);
     }
 
+    #[test]
+    fn webhook_config_keeps_secret_and_rejects_empty_one() {
+        let cfg = webhook_config_from_request(&WebhookRequest {
+            url: "https://example.com".into(),
+            secret: Some("whsec".into()),
+            ..Default::default()
+        })
+        .expect("secret accepted");
+        assert_eq!(cfg.secret.as_deref(), Some("whsec"));
+
+        let err = webhook_config_from_request(&WebhookRequest {
+            url: "https://example.com".into(),
+            secret: Some(String::new()),
+            ..Default::default()
+        })
+        .expect_err("empty secret rejected");
+        assert_eq!(err.0, Status::BadRequest);
+    }
+
     #[test]
     fn webhook_config_accepts_templates_at_limit() {
         let template = "x".repeat(4096);
@@ -3191,6 +6816,323 @@ mod tests {
         assert_eq!(resp.status(), Status::BadRequest);
     }
 
+    #[test]
+    fn create_api_bundle_response_lists_created_children() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+
+        let payload = json!({
+            "content": "bundle parent",
+            "format": "plain_text",
+            "encryption": { "algorithm": "aes256_gcm", "key": "bundlekey" },
+            "bundle": { "children": [
+                { "content": "share for alice", "label": "Alice" },
+                { "content": "share for bob" }
+            ] }
+        });
+        let resp = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(payload.to_string())
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+        let created: CreatePasteResponse =
+            serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+        let children = created.bundle.expect("bundle children").children;
+        assert_eq!(children.len(), 2);
+        assert_eq!(children[0].label.as_deref(), Some("Alice"));
+        assert_eq!(children[1].label, None);
+
+        for (child, expected) in children.iter().zip(["share for alice", "share for bob"]) {
+            assert!(!child.id.is_empty());
+            assert_eq!(child.url, format!("/{}", child.id));
+            let view = client
+                .get(format!("/api/pastes/{}?key=bundlekey", child.id))
+                .dispatch();
+            assert_eq!(view.status(), Status::Ok);
+            let view: PasteViewResponse =
+                serde_json::from_str(&view.into_string().unwrap()).unwrap();
+            assert_eq!(view.content.as_deref(), Some(expected));
+            assert!(view.burn_after_reading);
+        }
+
+        // The parent records the real child ids.
+        let parent = client
+            .get(format!("/api/pastes/{}?key=bundlekey", created.id))
+            .dispatch();
+        let parent: PasteViewResponse =
+            serde_json::from_str(&parent.into_string().unwrap()).unwrap();
+        let ids: Vec<_> = parent
+            .bundle
+            .expect("parent bundle")
+            .children
+            .into_iter()
+            .map(|c| c.id)
+            .collect();
+        assert_eq!(ids, vec![children[0].id.clone(), children[1].id.clone()]);
+    }
+
+    /// Saves every paste except bundle parents, to fail a create after its
+    /// children were stored.
+    struct ParentRejectingAdapter;
+
+    #[rocket::async_trait]
+    impl crate::PersistenceAdapter for ParentRejectingAdapter {
+        async fn save(&self, id: &str, paste: &StoredPaste) -> Result<(), PersistenceError> {
+            match paste.bundle {
+                Some(_) => Err(PersistenceError::Save(id.to_string(), "rejected".into())),
+                None => Ok(()),
+            }
+        }
+
+        async fn load(&self, _id: &str) -> Result<Option<StoredPaste>, PersistenceError> {
+            Ok(None)
+        }
+
+        async fn delete(&self, _id: &str) -> Result<(), PersistenceError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn failed_bundle_create_discards_its_children() {
+        let store: SharedPasteStore = Arc::new(
+            MemoryPasteStore::with_persistence(Arc::new(ParentRejectingAdapter))
+                .with_strict_persistence(true),
+        );
+        let client = Client::tracked(build_rocket(store.clone())).expect("client");
+
+        let resp = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(
+                json!({
+                    "content": "bundle parent",
+                    "encryption": { "algorithm": "aes256_gcm", "key": "bundlekey" },
+                    "bundle": { "children": [{ "content": "one" }, { "content": "two" }] }
+                })
+                .to_string(),
+            )
+            .dispatch();
+        assert_eq!(resp.status(), Status::ServiceUnavailable);
+        let ids = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(store.get_all_paste_ids());
+        assert!(ids.is_empty(), "orphaned children: {ids:?}");
+    }
+
+    #[test]
+    fn bundle_zip_packs_each_available_child() {
+        use std::io::Read;
+
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+
+        let payload = json!({
+            "content": "bundle parent",
+            "format": "plain_text",
+            "encryption": { "algorithm": "aes256_gcm", "key": "bundlekey" },
+            "bundle": { "children": [
+                { "content": "# alice", "label": "Alice", "format": "markdown" },
+                { "content": "share two" },
+                { "content": "carol's share", "label": "Carol" }
+            ] }
+        });
+        let resp = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(payload.to_string())
+            .dispatch();
+        let created: CreatePasteResponse =
+            serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+        let children = created.bundle.expect("bundle children").children;
+
+        // Carol already opened her share.
+        let carol = client
+            .get(format!("/api/pastes/{}?key=bundlekey", children[2].id))
+            .dispatch();
+        assert_eq!(carol.status(), Status::Ok);
+
+        let missing_key = client
+            .get(format!("/api/bundles/{}/zip", created.id))
+            .dispatch();
+        assert_eq!(missing_key.status(), Status::Unauthorized);
+
+        let resp = client
+            .get(format!("/api/bundles/{}/zip?key=bundlekey", created.id))
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+        assert_eq!(resp.content_type(), Some(ContentType::ZIP));
+        let body = resp.into_bytes().unwrap();
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(body)).expect("zip");
+
+        let mut read_entry = |name: &str| {
+            let mut text = String::new();
+            archive
+                .by_name(name)
+                .unwrap_or_else(|_| panic!("missing {name}"))
+                .read_to_string(&mut text)
+                .unwrap();
+            text
+        };
+        assert_eq!(read_entry(&format!("{}.txt", created.id)), "bundle parent");
+        assert_eq!(read_entry("Alice.md"), "# alice");
+        assert_eq!(read_entry("share-2.txt"), "share two");
+        let manifest = read_entry("MANIFEST.txt");
+        assert!(manifest.contains("Carol: skipped (already consumed)"));
+        assert_eq!(archive.len(), 4);
+
+        // Packed shares were consumed like any other read.
+        let alice = client
+            .get(format!("/api/pastes/{}?key=bundlekey", children[0].id))
+            .dispatch();
+        assert_eq!(alice.status(), Status::NotFound);
+    }
+
+    #[test]
+    fn bundle_overview_does_not_cache_persisted_children() {
+        use crate::{PasteStore, PersistenceAdapter};
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let adapter = Arc::new(crate::server::fs::FilePersistenceAdapter::new(dir.path()).unwrap());
+        let store = Arc::new(MemoryPasteStore::with_persistence(adapter.clone()));
+        let client = Client::tracked(build_rocket(store.clone())).expect("client");
+        let paste = |text: &str, bundle: Option<crate::BundleMetadata>| StoredPaste {
+            content: StoredContent::Plain { text: text.into() },
+            format: PasteFormat::PlainText,
+            created_at: current_timestamp(),
+            expires_at: None,
+            burn_after_reading: true,
+            max_reads: None,
+            bundle: bundle.clone(),
+            bundle_parent: None,
+            bundle_label: None,
+            not_before: None,
+            not_after: None,
+            persistence: None,
+            webhook: None,
+            metadata: PasteMetadata {
+                bundle,
+                ..PasteMetadata::default()
+            },
+            is_live: false,
+            owner_token_hash: None,
+        };
+        // Only the backend holds the child, as after a restart.
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime
+            .block_on(adapter.save("share", &paste("a share", None)))
+            .unwrap();
+        let bundle = crate::BundleMetadata {
+            children: vec![crate::BundlePointer {
+                id: "share".into(),
+                label: Some("Share".into()),
+            }],
+        };
+        let mut parent = paste("parent", Some(bundle));
+        parent.burn_after_reading = false;
+        let parent_id = runtime.block_on(store.create_paste(parent)).unwrap();
+
+        let html = client
+            .get(format!("/{parent_id}"))
+            .dispatch()
+            .into_string()
+            .unwrap();
+        assert!(html.contains("Available"));
+        assert!(!runtime.block_on(store.entries.read()).contains_key("share"));
+    }
+
+    #[test]
+    fn bundle_zip_rejects_pastes_without_a_bundle() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+
+        let resp = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(json!({"content": "solo", "format": "plain_text"}).to_string())
+            .dispatch();
+        let created: CreatePasteResponse =
+            serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+
+        let resp = client
+            .get(format!("/api/bundles/{}/zip", created.id))
+            .dispatch();
+        assert_eq!(resp.status(), Status::NotFound);
+        let err: ApiError = serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+        assert_eq!(err.code, "bundle_not_found");
+    }
+
+    #[test]
+    fn download_token_allows_exactly_one_raw_download() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+        let sessions = client.rocket().state::<SharedSessionStore>().unwrap();
+        sessions.insert("owner-session", "owner-hash");
+        sessions.insert("other-session", "other-hash");
+
+        let resp = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .header(bearer("owner-session"))
+            .body(
+                json!({
+                    "content": "token gated",
+                    "attestation": { "kind": "shared_secret", "secret": "open-sesame" }
+                })
+                .to_string(),
+            )
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+        let created: CreatePasteResponse =
+            serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+        let mint_path = format!("/api/pastes/{}/token", created.id);
+
+        assert_eq!(
+            client.post(&mint_path).dispatch().status(),
+            Status::Unauthorized
+        );
+        let resp = client
+            .post(&mint_path)
+            .header(rocket::http::Header::new(
+                "Authorization",
+                "Bearer other-session",
+            ))
+            .dispatch();
+        assert_eq!(resp.status(), Status::Forbidden);
+
+        let resp = client
+            .post(&mint_path)
+            .header(rocket::http::Header::new(
+                "Authorization",
+                "Bearer owner-session",
+            ))
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+        let minted: DownloadTokenResponse =
+            serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+        assert!(minted.expires_at > current_timestamp());
+
+        // Without the token the attestation code is still required.
+        let raw_path = format!("/raw/{}", created.id);
+        assert_eq!(
+            client.get(&raw_path).dispatch().status(),
+            Status::Unauthorized
+        );
+
+        let first = client.get(&minted.url).dispatch();
+        assert_eq!(first.status(), Status::Ok);
+        assert_eq!(first.into_string().unwrap(), "token gated");
+
+        let reuse = client.get(&minted.url).dispatch();
+        assert_eq!(reuse.status(), Status::Forbidden);
+
+        let forged = client
+            .get(format!("{raw_path}?token={}x", minted.token))
+            .dispatch();
+        assert_eq!(forged.status(), Status::Forbidden);
+    }
+
     #[test]
     fn stego_uploaded_rejects_invalid_mime_type() {
         let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
@@ -3214,6 +7156,10 @@ mod tests {
             .body(payload.to_string())
             .dispatch();
         assert_eq!(resp.status(), Status::BadRequest);
+        assert!(resp
+            .into_string()
+            .unwrap_or_default()
+            .contains("Carrier image must be PNG, BMP, or WebP"));
     }
 
     #[test]
@@ -3318,7 +7264,7 @@ mod tests {
             .dispatch();
         assert_eq!(ok.status(), Status::Ok);
         let view: PasteViewResponse = serde_json::from_str(&ok.into_string().unwrap()).unwrap();
-        assert_eq!(view.content, "header secret");
+        assert_eq!(view.content.as_deref(), Some("header secret"));
 
         // Wrong header + correct query param → header takes precedence → 403.
         let forbidden = client
@@ -3348,6 +7294,10 @@ mod tests {
             "http://localhost/hook",
             "http://10.1.2.3/hook",
             "file:///etc/passwd",
+            "https://169.254.169.254/latest/meta-data",
+            "https://localhost/hook",
+            "https://10.1.2.3/hook",
+            "http://hooks.example.com/plain-http",
         ] {
             let payload = json!({
                 "content": "payload",
@@ -3421,19 +7371,166 @@ mod tests {
         assert_eq!(resp.status(), Status::Ok);
         let created: CreatePasteResponse =
             serde_json::from_str(&resp.into_string().unwrap()).unwrap();
-
+
+        let stored = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(store.get_paste(&created.id))
+            .expect("paste should exist");
+        let expires_at = stored.expires_at.expect("default retention must apply");
+        let expected = current_timestamp() + 30 * 60;
+        assert!(
+            (expires_at - expected).abs() <= 5,
+            "expires_at should be ~30 minutes out"
+        );
+
+        std::env::remove_var("COPYPASTE_RETENTION_DEFAULT_MINUTES");
+    }
+
+    #[test]
+    fn create_api_parses_human_retention_ahead_of_minutes() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let rocket = build_rocket(Arc::clone(&store));
+        let client = Client::tracked(rocket).expect("client");
+
+        let resp = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(json!({ "content": "x", "retention": "45m", "retention_minutes": 5 }).to_string())
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+        let created: CreatePasteResponse =
+            serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+        let stored = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(store.peek_paste(&created.id))
+            .expect("paste should exist");
+        let expires_at = stored.expires_at.expect("retention must apply");
+        let expected = current_timestamp() + 45 * 60;
+        assert!(
+            (expires_at - expected).abs() <= 5,
+            "`retention` should win over `retention_minutes`"
+        );
+
+        let malformed = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(json!({ "content": "x", "retention": "banana" }).to_string())
+            .dispatch();
+        assert_eq!(malformed.status(), Status::BadRequest);
+    }
+
+    #[test]
+    fn resolve_absolute_expiry_validates_and_bounds_timestamps() {
+        let now = 1_700_000_000;
+        assert_eq!(
+            resolve_absolute_expiry("1700003600", now, None, RetentionOverflow::Reject),
+            Ok(1_700_003_600)
+        );
+        assert_eq!(
+            resolve_absolute_expiry("2023-11-14T23:13:20Z", now, None, RetentionOverflow::Reject),
+            Ok(now + 3600)
+        );
+        for past in ["1700000000", "1699999999", "2020-01-01T00:00:00Z"] {
+            let err = resolve_absolute_expiry(past, now, None, RetentionOverflow::Reject)
+                .expect_err("non-future timestamps are rejected");
+            assert!(err.contains("future"), "{past}: {err}");
+        }
+        assert!(
+            resolve_absolute_expiry("tomorrow", now, None, RetentionOverflow::Reject)
+                .unwrap_err()
+                .contains("invalid expires_at")
+        );
+
+        // The configured maximum retention bounds absolute expiries too.
+        assert_eq!(
+            resolve_absolute_expiry("1700086400", now, Some(60), RetentionOverflow::Clamp),
+            Ok(now + 3600)
+        );
+        assert!(
+            resolve_absolute_expiry("1700086400", now, Some(60), RetentionOverflow::Reject)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn create_api_prefers_absolute_expires_at_over_retention() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let rocket = build_rocket(Arc::clone(&store));
+        let client = Client::tracked(rocket).expect("client");
+
+        let target = current_timestamp() + 20 * 60;
+        let rfc3339 = chrono::DateTime::from_timestamp(target, 0)
+            .unwrap()
+            .to_rfc3339();
+        let resp = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(
+                json!({
+                    "content": "x",
+                    "expires_at": rfc3339,
+                    "retention_minutes": 5,
+                    "retention": "1m",
+                })
+                .to_string(),
+            )
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+        let created: CreatePasteResponse =
+            serde_json::from_str(&resp.into_string().unwrap()).unwrap();
         let stored = tokio::runtime::Runtime::new()
             .unwrap()
-            .block_on(store.get_paste(&created.id))
+            .block_on(store.peek_paste(&created.id))
             .expect("paste should exist");
-        let expires_at = stored.expires_at.expect("default retention must apply");
-        let expected = current_timestamp() + 30 * 60;
-        assert!(
-            (expires_at - expected).abs() <= 5,
-            "expires_at should be ~30 minutes out"
+        assert_eq!(stored.expires_at, Some(target));
+
+        let past = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(json!({ "content": "x", "expires_at": "2020-01-01T00:00:00Z" }).to_string())
+            .dispatch();
+        assert_eq!(past.status(), Status::BadRequest);
+    }
+
+    #[test]
+    fn resolve_retention_clamps_or_rejects_above_max() {
+        assert_eq!(
+            resolve_retention(Some(525_600_000), None, Some(60), RetentionOverflow::Clamp),
+            Ok(Some(60))
+        );
+        let err = resolve_retention(Some(61), None, Some(60), RetentionOverflow::Reject)
+            .expect_err("over the max is rejected");
+        assert!(err.contains("maximum of 60"));
+        assert_eq!(
+            resolve_retention(Some(60), None, Some(60), RetentionOverflow::Reject),
+            Ok(Some(60))
+        );
+        // An out-of-range configured default is subject to the same policy.
+        assert_eq!(
+            resolve_retention(None, Some(90), Some(60), RetentionOverflow::Clamp),
+            Ok(Some(60))
         );
+    }
 
-        std::env::remove_var("COPYPASTE_RETENTION_DEFAULT_MINUTES");
+    #[test]
+    fn resolve_retention_defaults_to_max_when_nothing_requested() {
+        assert_eq!(
+            resolve_retention(None, None, Some(60), RetentionOverflow::Reject),
+            Ok(Some(60))
+        );
+        assert_eq!(
+            resolve_retention(None, Some(30), Some(60), RetentionOverflow::Reject),
+            Ok(Some(30))
+        );
+        // Without a maximum, omitting retention still means no expiry.
+        assert_eq!(
+            resolve_retention(None, None, None, RetentionOverflow::Reject),
+            Ok(None)
+        );
+        assert_eq!(
+            resolve_retention(Some(525_600_000), None, None, RetentionOverflow::Reject),
+            Ok(Some(525_600_000))
+        );
     }
 
     // ── Per-IP rate limiting (config knobs wired up) ───────────────────────────
@@ -3514,19 +7611,24 @@ mod tests {
         let resp = client.get("/api/workspaces/team-alpha/pastes").dispatch();
         assert_eq!(resp.status(), Status::Unauthorized);
 
-        let (token, pubkey_hash) = login(&client);
+        let (token, _) = login(&client);
 
         // One paste owned by the session in the workspace, one owned by someone else.
-        for (owner, content) in [(pubkey_hash.as_str(), "mine"), ("someone_else", "theirs")] {
+        client
+            .rocket()
+            .state::<SharedSessionStore>()
+            .unwrap()
+            .insert("other-token", "someone_else");
+        for (session, content) in [(token.as_str(), "mine"), ("other-token", "theirs")] {
             let resp = client
                 .post("/api/pastes")
                 .header(ContentType::JSON)
+                .header(bearer(session))
                 .body(
                     json!({
                         "content": content,
                         "format": "plain_text",
-                        "workspace": "team-alpha",
-                        "owner_pubkey_hash": owner
+                        "workspace": "team-alpha"
                     })
                     .to_string(),
                 )
@@ -3604,7 +7706,7 @@ mod tests {
         let view = client.get(format!("/api/pastes/{id}")).dispatch();
         assert_eq!(view.status(), Status::Ok);
         let view: PasteViewResponse = serde_json::from_str(&view.into_string().unwrap()).unwrap();
-        assert_eq!(view.content, "live v2");
+        assert_eq!(view.content.as_deref(), Some("live v2"));
         assert!(view.is_live);
     }
 
@@ -3705,6 +7807,417 @@ mod tests {
         assert_eq!(resp.status(), Status::Ok);
     }
 
+    // ── Raw uploads ────────────────────────────────────────────────────────────
+
+    #[test]
+    fn decode_text_upload_rejects_invalid_utf8_unless_lossy() {
+        let bytes = vec![b'o', b'k', 0xff, b'!'];
+        let err = decode_text_upload(bytes.clone(), false).expect_err("invalid utf-8");
+        assert_eq!(err.0, Status::BadRequest);
+        assert!(err.1.contains("offset 2"));
+
+        let text = decode_text_upload(bytes, true).expect("lossy decode");
+        assert_eq!(text, "ok\u{FFFD}!");
+    }
+
+    #[test]
+    fn create_raw_api_validates_utf8_and_supports_lossy_opt_in() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let rocket = build_rocket(store);
+        let client = Client::tracked(rocket).expect("client");
+
+        let invalid = vec![b'h', b'i', 0xc3, 0x28];
+        let resp = client
+            .post("/api/pastes/raw")
+            .header(ContentType::Plain)
+            .body(invalid.clone())
+            .dispatch();
+        assert_eq!(resp.status(), Status::BadRequest);
+        let err: ApiError = serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+        assert!(err.message.contains("UTF-8"));
+
+        let resp = client
+            .post("/api/pastes/raw?lossy=true&format=markdown")
+            .header(ContentType::Plain)
+            .body(invalid)
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+        let created: CreatePasteResponse =
+            serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+
+        let resp = client.get(format!("/raw/{}", created.id)).dispatch();
+        assert_eq!(resp.into_string().unwrap(), "hi\u{FFFD}(");
+        let resp = client.get(format!("/api/pastes/{}", created.id)).dispatch();
+        let view: serde_json::Value = serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+        assert_eq!(view["format"], "markdown");
+
+        let resp = client
+            .post("/api/pastes/raw?format=klingon")
+            .header(ContentType::Plain)
+            .body("valid text")
+            .dispatch();
+        assert_eq!(resp.status(), Status::BadRequest);
+    }
+
+    // ── Daily paste quota ──────────────────────────────────────────────────────
+
+    #[test]
+    fn daily_quota_rejects_creates_with_reset_time() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+        client
+            .rocket()
+            .state::<DailyPasteQuota>()
+            .expect("quota state")
+            .set_limits(Some(2), None);
+
+        // Invalid requests are rejected before the quota is charged.
+        let resp = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(json!({ "content": "   " }).to_string())
+            .dispatch();
+        assert_eq!(resp.status(), Status::BadRequest);
+
+        for i in 0..2 {
+            let resp = client
+                .post("/api/pastes")
+                .header(ContentType::JSON)
+                .body(json!({ "content": format!("paste {i}") }).to_string())
+                .dispatch();
+            assert_eq!(resp.status(), Status::Ok);
+        }
+
+        let resp = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(json!({ "content": "one too many" }).to_string())
+            .dispatch();
+        assert_eq!(resp.status(), Status::TooManyRequests);
+        let err: ApiError = serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+        assert!(err.message.contains("Daily paste quota exceeded"));
+        assert!(err.message.contains("UTC"), "reset time should be surfaced");
+
+        let resp = client
+            .post("/")
+            .header(ContentType::JSON)
+            .body(json!({ "content": "root endpoint too" }).to_string())
+            .dispatch();
+        assert_eq!(resp.status(), Status::TooManyRequests);
+    }
+
+    #[test]
+    fn failed_create_does_not_use_daily_quota() {
+        let store: SharedPasteStore = Arc::new(
+            MemoryPasteStore::with_persistence(Arc::new(ParentRejectingAdapter))
+                .with_strict_persistence(true),
+        );
+        let client = Client::tracked(build_rocket(store)).expect("client");
+        client
+            .rocket()
+            .state::<DailyPasteQuota>()
+            .expect("quota state")
+            .set_limits(Some(1), None);
+
+        // The parent's save is refused after the quota was charged.
+        let resp = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(
+                json!({
+                    "content": "bundle parent",
+                    "encryption": { "algorithm": "aes256_gcm", "key": "bundlekey" },
+                    "bundle": { "children": [{ "content": "one" }] }
+                })
+                .to_string(),
+            )
+            .dispatch();
+        assert_eq!(resp.status(), Status::ServiceUnavailable);
+
+        let resp = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(json!({ "content": "still within quota" }).to_string())
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+        let resp = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(json!({ "content": "one too many" }).to_string())
+            .dispatch();
+        assert_eq!(resp.status(), Status::TooManyRequests);
+    }
+
+    // ── NDJSON import ──────────────────────────────────────────────────────────
+
+    #[test]
+    fn import_api_creates_records_and_reports_bad_lines() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+        let (_, writer_key) = client
+            .rocket()
+            .state::<SharedApiKeyStore>()
+            .unwrap()
+            .create_key("migrator", crate::server::api_keys::ApiScope::Write, None)
+            .expect("write key");
+
+        let body = [
+            json!({ "content": "first import", "format": "plain_text" }).to_string(),
+            json!({ "content": "second import", "retention_minutes": 60 }).to_string(),
+            "{ not json".to_string(),
+            String::new(),
+            json!({
+                "content": "third import",
+                "encryption": { "algorithm": "aes256_gcm", "key": "imported-key" }
+            })
+            .to_string(),
+        ]
+        .join("\n");
+
+        let unauth = client.post("/api/import").body(body.clone()).dispatch();
+        assert_eq!(unauth.status(), Status::Unauthorized);
+
+        let resp = client
+            .post("/api/import")
+            .header(rocket::http::Header::new(
+                "Authorization",
+                format!("Bearer {writer_key}"),
+            ))
+            .body(body)
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+        let results: Vec<ImportLineResult> = resp
+            .into_string()
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).expect("ndjson line"))
+            .collect();
+        assert_eq!(results.len(), 4);
+
+        let created: Vec<_> = results.iter().filter(|r| r.id.is_some()).collect();
+        assert_eq!(created.len(), 3);
+        assert_eq!(
+            created.iter().map(|r| r.line).collect::<Vec<_>>(),
+            vec![1, 2, 5]
+        );
+        let failed: Vec<_> = results.iter().filter(|r| r.error.is_some()).collect();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].line, 3);
+
+        let first = client.get(created[0].url.as_deref().unwrap()).dispatch();
+        assert_eq!(first.status(), Status::Ok);
+        let raw = client
+            .get(format!(
+                "/raw/{}?key=imported-key",
+                created[2].id.as_deref().unwrap()
+            ))
+            .dispatch();
+        assert_eq!(raw.into_string().unwrap(), "third import");
+    }
+
+    // ── Paste size limit ───────────────────────────────────────────────────────
+
+    #[test]
+    fn paste_size_limit_is_inclusive_and_reported() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+        let create = |len: usize| {
+            client
+                .post("/api/pastes")
+                .header(ContentType::JSON)
+                .body(json!({ "content": "a".repeat(len) }).to_string())
+                .dispatch()
+        };
+
+        let resp = create(DEFAULT_MAX_PASTE_SIZE);
+        assert_eq!(resp.status(), Status::Ok);
+        let created: CreatePasteResponse =
+            serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+
+        let resp = create(DEFAULT_MAX_PASTE_SIZE + 1);
+        assert_eq!(resp.status(), Status::PayloadTooLarge);
+        let err: ApiError = serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+        assert_eq!(err.code, "payload_too_large");
+
+        // Past Rocket's body limit the request is cut off while streaming.
+        let resp = create(DEFAULT_MAX_PASTE_SIZE + 2 * BODY_LIMIT_OVERHEAD as usize);
+        assert_eq!(resp.status(), Status::PayloadTooLarge);
+
+        let resp = client.get(format!("/api/pastes/{}", created.id)).dispatch();
+        let view: serde_json::Value = serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+        assert_eq!(view["sizeBytes"], DEFAULT_MAX_PASTE_SIZE);
+    }
+
+    // ── Rate limiting ──────────────────────────────────────────────────────────
+
+    #[test]
+    fn create_past_rate_limit_returns_429_with_retry_after() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+        client
+            .rocket()
+            .state::<PasteRateLimiter>()
+            .expect("rate limiter state")
+            .set_limits(Some(2), None);
+
+        for _ in 0..2 {
+            let resp = client
+                .post("/api/pastes")
+                .header(ContentType::JSON)
+                .body(json!({ "content": "within limit" }).to_string())
+                .dispatch();
+            assert_eq!(resp.status(), Status::Ok);
+        }
+
+        let resp = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(json!({ "content": "over limit" }).to_string())
+            .dispatch();
+        assert_eq!(resp.status(), Status::TooManyRequests);
+        let retry_after: u64 = resp
+            .headers()
+            .get_one("Retry-After")
+            .expect("Retry-After header")
+            .parse()
+            .unwrap();
+        assert!((1..=30).contains(&retry_after));
+        let err: ApiError = serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+        assert_eq!(err.code, "too_many_requests");
+    }
+
+    // ── Read-only maintenance mode ─────────────────────────────────────────────
+
+    #[test]
+    fn read_only_mode_rejects_writes_but_serves_reads() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let rocket = build_rocket(store);
+        let client = Client::tracked(rocket).expect("client");
+
+        let resp = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(json!({ "content": "before maintenance", "live": true }).to_string())
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+        let created: CreatePasteResponse =
+            serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+        let token = created.token.clone().expect("live paste token");
+
+        client
+            .rocket()
+            .state::<MaintenanceMode>()
+            .expect("maintenance state")
+            .set_read_only(true);
+
+        let resp = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(json!({ "content": "during maintenance" }).to_string())
+            .dispatch();
+        assert_eq!(resp.status(), Status::ServiceUnavailable);
+        assert!(resp.headers().get_one("Retry-After").is_some());
+        let err: ApiError = serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+        assert_eq!(err.code, "maintenance");
+        assert!(err.message.contains("maintenance"));
+
+        let resp = client
+            .post("/")
+            .header(ContentType::JSON)
+            .body(json!({ "content": "during maintenance" }).to_string())
+            .dispatch();
+        assert_eq!(resp.status(), Status::ServiceUnavailable);
+
+        let resp = client
+            .put(format!("/api/pastes/{}", created.id))
+            .header(ContentType::JSON)
+            .header(bearer(&token))
+            .body(json!({ "content": "edited" }).to_string())
+            .dispatch();
+        assert_eq!(resp.status(), Status::ServiceUnavailable);
+
+        let resp = client
+            .patch(format!("/api/pastes/{}/finalize", created.id))
+            .header(bearer(&token))
+            .dispatch();
+        assert_eq!(resp.status(), Status::ServiceUnavailable);
+
+        let resp = client
+            .post(format!("/api/pastes/{}/anchor", created.id))
+            .header(ContentType::JSON)
+            .body("{}")
+            .dispatch();
+        assert_eq!(resp.status(), Status::ServiceUnavailable);
+
+        // Reads keep working across all three views.
+        let resp = client.get(format!("/api/pastes/{}", created.id)).dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+        let resp = client.get(format!("/{}", created.id)).dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+        let resp = client.get(format!("/raw/{}", created.id)).dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+        assert_eq!(resp.into_string().unwrap(), "before maintenance");
+    }
+
+    #[test]
+    fn admin_can_toggle_maintenance_mode() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let rocket = build_rocket(store);
+        let client = Client::tracked(rocket).expect("client");
+
+        let (_, admin_key) = client
+            .rocket()
+            .state::<SharedApiKeyStore>()
+            .expect("key store")
+            .create_key("ops", crate::server::api_keys::ApiScope::Admin, None)
+            .expect("admin key");
+
+        let resp = client
+            .put("/api/admin/maintenance")
+            .header(ContentType::JSON)
+            .body(json!({ "readOnly": true }).to_string())
+            .dispatch();
+        assert_eq!(resp.status(), Status::Unauthorized);
+
+        let resp = client
+            .put("/api/admin/maintenance")
+            .header(ContentType::JSON)
+            .header(bearer(&admin_key))
+            .body(json!({ "readOnly": true }).to_string())
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+
+        let resp = client
+            .get("/api/admin/maintenance")
+            .header(bearer(&admin_key))
+            .dispatch();
+        let status: MaintenanceModeResponse =
+            serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+        assert!(status.read_only);
+
+        let resp = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(json!({ "content": "blocked" }).to_string())
+            .dispatch();
+        assert_eq!(resp.status(), Status::ServiceUnavailable);
+
+        let resp = client
+            .put("/api/admin/maintenance")
+            .header(ContentType::JSON)
+            .header(bearer(&admin_key))
+            .body(json!({ "readOnly": false }).to_string())
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+
+        let resp = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(json!({ "content": "allowed again" }).to_string())
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+    }
+
     // ── OpenAPI docs ───────────────────────────────────────────────────────────
 
     #[test]
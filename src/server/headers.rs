@@ -0,0 +1,214 @@
+//! Browser hardening headers.
+//!
+//! Paste views are HTML built from user content on our own origin, so even
+//! after markdown sanitisation the [`SecurityHeaders`] fairing adds a second
+//! line of defence. HTML responses get a `Content-Security-Policy` that only
+//! allows same-origin scripts and `X-Frame-Options: DENY`; every response gets
+//! `X-Content-Type-Options: nosniff` (raw views must never be sniffed into
+//! HTML) and `Referrer-Policy: no-referrer` (share links carry keys).
+//!
+//! The policy can be replaced wholesale with `COPYPASTE_CSP`. The Scalar API
+//! reference at `/api/docs` is our own static page but loads its renderer
+//! from jsDelivr, so it always gets [`docs_csp`] instead.
+
+use std::sync::OnceLock;
+
+use base64::Engine;
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::{ContentType, Header};
+use rocket::{Request, Response};
+use sha2::{Digest, Sha256};
+
+/// The single-page shell served at `/`, which carries one inline script.
+const SHELL_HTML: &str = include_str!("../../static/index.html");
+
+#[derive(Clone)]
+pub struct SecurityHeaders {
+    csp: String,
+}
+
+impl SecurityHeaders {
+    pub fn new(csp: impl Into<String>) -> Self {
+        Self { csp: csp.into() }
+    }
+
+    pub fn from_env() -> Self {
+        std::env::var("COPYPASTE_CSP")
+            .ok()
+            .map(|csp| csp.trim().to_string())
+            .filter(|csp| !csp.is_empty())
+            .map(Self::new)
+            .unwrap_or_default()
+    }
+}
+
+impl Default for SecurityHeaders {
+    fn default() -> Self {
+        Self::new(default_csp())
+    }
+}
+
+/// `default-src 'self'` with no inline scripts beyond the shell's own, which
+/// is pinned by hash. Inline styles stay allowed for syntax highlighting, and
+/// the share QR code is loaded from api.qrserver.com.
+pub fn default_csp() -> &'static str {
+    static CSP: OnceLock<String> = OnceLock::new();
+    CSP.get_or_init(|| {
+        let script_src = match shell_script_hash() {
+            Some(hash) => format!("script-src 'self' 'sha256-{hash}'"),
+            None => "script-src 'self'".to_string(),
+        };
+        format!(
+            "default-src 'self'; {script_src}; style-src 'self' 'unsafe-inline'; \
+             img-src 'self' data: https://api.qrserver.com; object-src 'none'; \
+             base-uri 'none'; form-action 'self'; frame-ancestors 'none'"
+        )
+    })
+}
+
+/// Path of the Scalar API reference (`handlers::build_rocket`).
+const DOCS_PATH: &str = "/api/docs";
+
+/// Policy for the API reference page: the Scalar bundle and the fonts it
+/// pulls in come from their CDNs; the embedded spec is a non-executable
+/// `application/json` script, so no inline scripts are allowed.
+pub fn docs_csp() -> &'static str {
+    "default-src 'self'; script-src 'self' https://cdn.jsdelivr.net; \
+     style-src 'self' 'unsafe-inline' https://cdn.jsdelivr.net; \
+     font-src 'self' data: https://cdn.jsdelivr.net https://fonts.scalar.com; \
+     img-src 'self' data: https:; connect-src 'self'; object-src 'none'; \
+     base-uri 'none'; form-action 'self'; frame-ancestors 'none'"
+}
+
+/// Base64 SHA-256 of the shell's inline `<script>` body, as CSP expects it.
+fn shell_script_hash() -> Option<String> {
+    let start = SHELL_HTML.find("<script>")? + "<script>".len();
+    let end = start + SHELL_HTML[start..].find("</script>")?;
+    let digest = Sha256::digest(&SHELL_HTML.as_bytes()[start..end]);
+    Some(base64::engine::general_purpose::STANDARD.encode(digest))
+}
+
+#[rocket::async_trait]
+impl Fairing for SecurityHeaders {
+    fn info(&self) -> Info {
+        Info {
+            name: "Security headers",
+            kind: Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        response.set_header(Header::new("X-Content-Type-Options", "nosniff"));
+        response.set_header(Header::new("Referrer-Policy", "no-referrer"));
+
+        if response.content_type() == Some(ContentType::HTML) {
+            let csp = if request.uri().path() == DOCS_PATH {
+                docs_csp().to_string()
+            } else {
+                self.csp.clone()
+            };
+            response.set_header(Header::new("Content-Security-Policy", csp));
+            response.set_header(Header::new("X-Frame-Options", "DENY"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::handlers::build_rocket;
+    use crate::{MemoryPasteStore, SharedPasteStore};
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    #[test]
+    fn rendered_paste_carries_csp_and_nosniff() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+
+        let created = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(json!({ "content": "<b>hi</b>", "format": "markdown" }).to_string())
+            .dispatch();
+        assert_eq!(created.status(), Status::Ok);
+        let body: serde_json::Value =
+            serde_json::from_str(&created.into_string().unwrap()).unwrap();
+        let id = body["id"].as_str().unwrap();
+
+        let view = client.get(format!("/{id}")).dispatch();
+        assert_eq!(view.status(), Status::Ok);
+        let headers = view.headers();
+        let csp = headers.get_one("Content-Security-Policy").expect("csp");
+        assert!(csp.contains("default-src 'self'"));
+        assert!(!csp.contains("script-src 'self' 'unsafe-inline'"));
+        assert_eq!(headers.get_one("X-Content-Type-Options"), Some("nosniff"));
+        assert_eq!(headers.get_one("Referrer-Policy"), Some("no-referrer"));
+        assert_eq!(headers.get_one("X-Frame-Options"), Some("DENY"));
+
+        let raw = client.get(format!("/raw/{id}")).dispatch();
+        assert_eq!(
+            raw.headers().get_one("X-Content-Type-Options"),
+            Some("nosniff")
+        );
+        assert!(raw.headers().get_one("Content-Security-Policy").is_none());
+    }
+
+    #[test]
+    fn api_docs_may_load_the_scalar_bundle() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+
+        let docs = client.get(DOCS_PATH).dispatch();
+        assert_eq!(docs.status(), Status::Ok);
+        let csp = docs
+            .headers()
+            .get_one("Content-Security-Policy")
+            .expect("csp")
+            .to_string();
+        let body = docs.into_string().unwrap();
+        assert!(body.contains("https://cdn.jsdelivr.net/npm/@scalar/api-reference"));
+        let script_src = csp
+            .split(';')
+            .map(str::trim)
+            .find(|directive| directive.starts_with("script-src"))
+            .expect("script-src");
+        assert!(script_src.contains("https://cdn.jsdelivr.net"), "{csp}");
+        assert!(!script_src.contains("'unsafe-inline'"), "{csp}");
+        assert!(csp.contains("frame-ancestors 'none'"));
+
+        // Other pages keep the strict default.
+        let landing = client.get("/about").dispatch();
+        let landing_csp = landing
+            .headers()
+            .get_one("Content-Security-Policy")
+            .unwrap();
+        assert!(!landing_csp.contains("cdn.jsdelivr.net"), "{landing_csp}");
+    }
+
+    #[test]
+    fn shell_inline_script_is_pinned_by_hash() {
+        let hash = shell_script_hash().expect("shell has an inline script");
+        assert!(default_csp().contains(&format!("'sha256-{hash}'")));
+    }
+
+    #[rocket::get("/page")]
+    fn page() -> rocket::response::content::RawHtml<&'static str> {
+        rocket::response::content::RawHtml("<p>ok</p>")
+    }
+
+    #[test]
+    fn custom_policy_replaces_the_default() {
+        let rocket = rocket::build()
+            .attach(SecurityHeaders::new("default-src 'none'"))
+            .mount("/", rocket::routes![page]);
+        let client = Client::untracked(rocket).expect("client");
+        let response = client.get("/page").dispatch();
+        assert_eq!(
+            response.headers().get_one("Content-Security-Policy"),
+            Some("default-src 'none'")
+        );
+    }
+}
@@ -0,0 +1,214 @@
+//! Shared syntax-highlighting assets for code pastes.
+//!
+//! The assets (syntect's bundled syntax set and theme) are built once into a
+//! process-wide [`OnceCell`] on first use. Loading them lazily means the
+//! first code-paste view pays the initialisation cost, so `build_rocket`
+//! calls [`prewarm`] at boot when `COPYPASTE_PREWARM_HIGHLIGHT` is set.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use html_escape::encode_safe;
+use once_cell::sync::OnceCell;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::html::{styled_line_to_highlighted_html, IncludeBackground};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+use syntect::util::LinesWithEndings;
+
+use crate::PasteFormat;
+
+use super::render::{format_code, number_lines, source_lines};
+
+static ASSETS: OnceCell<HighlightAssets> = OnceCell::new();
+
+/// Number of times the assets have been built; stays at most 1.
+static INIT_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Bundled theme used for the inline span styles.
+const THEME: &str = "InspiredGitHub";
+
+/// Larger pastes are shown unhighlighted; highlighting cost grows with
+/// input size and runs on every view.
+pub const MAX_HIGHLIGHT_BYTES: usize = 128 * 1024;
+
+/// Syntax definitions and theme for the code formats.
+pub struct HighlightAssets {
+    /// Language name (used for the `language-*` class) and the syntect
+    /// token (file extension) that selects its grammar, keyed by format.
+    syntaxes: HashMap<PasteFormat, (&'static str, &'static str)>,
+    syntax_set: SyntaxSet,
+    theme: Theme,
+}
+
+impl HighlightAssets {
+    fn load() -> Self {
+        INIT_COUNT.fetch_add(1, Ordering::Relaxed);
+        let syntaxes = [
+            (PasteFormat::Javascript, ("javascript", "js")),
+            (PasteFormat::Typescript, ("typescript", "ts")),
+            (PasteFormat::Python, ("python", "py")),
+            (PasteFormat::Rust, ("rust", "rs")),
+            (PasteFormat::Go, ("go", "go")),
+            (PasteFormat::Cpp, ("cpp", "cpp")),
+            (PasteFormat::Kotlin, ("kotlin", "kt")),
+            (PasteFormat::Java, ("java", "java")),
+            (PasteFormat::Csharp, ("csharp", "cs")),
+            (PasteFormat::Php, ("php", "php")),
+            (PasteFormat::Ruby, ("ruby", "rb")),
+            (PasteFormat::Bash, ("bash", "sh")),
+            (PasteFormat::Yaml, ("yaml", "yaml")),
+            (PasteFormat::Sql, ("sql", "sql")),
+            (PasteFormat::Swift, ("swift", "swift")),
+            (PasteFormat::Html, ("html", "html")),
+            (PasteFormat::Css, ("css", "css")),
+            (PasteFormat::Json, ("json", "json")),
+        ]
+        .into_iter()
+        .collect();
+        let theme = ThemeSet::load_defaults()
+            .themes
+            .remove(THEME)
+            .expect("bundled syntect theme");
+        Self {
+            syntaxes,
+            syntax_set: SyntaxSet::load_defaults_newlines(),
+            theme,
+        }
+    }
+
+    /// Syntax name for `format`, if it is a known code language.
+    pub fn syntax_for(&self, format: PasteFormat) -> Option<&'static str> {
+        self.syntaxes.get(&format).map(|(name, _)| *name)
+    }
+
+    /// Grammar for `format`, if syntect bundles one (TypeScript, Kotlin and
+    /// Swift aren't in the default set).
+    fn grammar_for(&self, format: PasteFormat) -> Option<&SyntaxReference> {
+        let (_, token) = self.syntaxes.get(&format)?;
+        self.syntax_set.find_syntax_by_token(token)
+    }
+
+    /// Highlight `text` as numbered lines of inline-styled spans, or `None`
+    /// if the grammar fails on this input.
+    fn highlight(&self, text: &str, syntax: &SyntaxReference) -> Option<String> {
+        let mut lines = HighlightLines::new(syntax, &self.theme);
+        let mut html = Vec::new();
+        for line in LinesWithEndings::from(text) {
+            // The grammar needs the newline, but it belongs between the
+            // numbered line spans rather than inside the last token.
+            let regions: Vec<_> = lines
+                .highlight_line(line, &self.syntax_set)
+                .ok()?
+                .into_iter()
+                .map(|(style, token)| (style, token.strip_suffix('\n').unwrap_or(token)))
+                .collect();
+            html.push(styled_line_to_highlighted_html(&regions, IncludeBackground::No).ok()?);
+        }
+        Some(number_lines(html, text.ends_with('\n')))
+    }
+}
+
+/// Shared assets, loading them on first call.
+pub fn assets() -> &'static HighlightAssets {
+    ASSETS.get_or_init(HighlightAssets::load)
+}
+
+/// Load the assets now so the first code-paste view doesn't have to.
+pub fn prewarm() {
+    assets();
+}
+
+/// Whether the assets have already been loaded.
+pub fn is_warm() -> bool {
+    ASSETS.get().is_some()
+}
+
+pub fn init_count() -> usize {
+    INIT_COUNT.load(Ordering::Relaxed)
+}
+
+/// Whether `COPYPASTE_PREWARM_HIGHLIGHT` asks for boot-time loading.
+pub fn prewarm_from_env() -> bool {
+    std::env::var("COPYPASTE_PREWARM_HIGHLIGHT")
+        .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "on"))
+        .unwrap_or(false)
+}
+
+/// Render a code paste, highlighted with inline styles when syntect has a
+/// grammar for its language (and the paste is under
+/// [`MAX_HIGHLIGHT_BYTES`]), tagged with just the language class when it
+/// isn't, and as the plain `<pre><code>` block for untyped code.
+pub fn highlight_code(text: &str, format: PasteFormat) -> String {
+    let assets = assets();
+    let Some(name) = assets.syntax_for(format) else {
+        return format_code(text);
+    };
+    let body = assets
+        .grammar_for(format)
+        .filter(|_| text.len() <= MAX_HIGHLIGHT_BYTES)
+        .and_then(|syntax| assets.highlight(text, syntax))
+        .unwrap_or_else(|| {
+            let (lines, trailing_newline) = source_lines(text);
+            number_lines(lines.map(encode_safe), trailing_newline)
+        });
+    format!("<pre><code class=\"language-{name}\">{body}</code></pre>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prewarm_loads_assets_once_before_first_render() {
+        prewarm();
+        assert!(is_warm());
+        assert_eq!(init_count(), 1);
+
+        let html = highlight_code("fn main() {}", PasteFormat::Rust);
+        assert!(html.contains("language-rust"));
+        assert_eq!(init_count(), 1, "render must reuse the pre-warmed assets");
+
+        prewarm();
+        assert_eq!(init_count(), 1);
+    }
+
+    #[test]
+    fn rust_paste_is_highlighted_and_round_trips() {
+        let source = "fn main() {\n    let s = \"<b>&'\";\n}\n";
+        let html = highlight_code(source, PasteFormat::Rust);
+        assert!(html.starts_with("<pre><code class=\"language-rust\">"));
+        assert!(html.contains("<span style=\""));
+        assert!(html.contains(r#"id="L3""#) && !html.contains(r#"id="L4""#));
+
+        let without_tags = regex::Regex::new("<[^>]*>").unwrap().replace_all(&html, "");
+        assert_eq!(html_escape::decode_html_entities(&without_tags), source);
+        assert!(!html.contains("<b>"));
+    }
+
+    #[test]
+    fn language_without_bundled_grammar_is_escaped_and_tagged() {
+        assert_eq!(
+            highlight_code("let a = b < c", PasteFormat::Kotlin),
+            "<pre><code class=\"language-kotlin\"><span class=\"line\" id=\"L1\">\
+             <a class=\"ln\" href=\"#L1\" data-line=\"1\"></a>let a = b &lt; c</span></code></pre>"
+        );
+    }
+
+    #[test]
+    fn oversized_paste_is_not_highlighted() {
+        let source = "x".repeat(MAX_HIGHLIGHT_BYTES + 1);
+        let html = highlight_code(&source, PasteFormat::Rust);
+        assert!(!html.contains("<span style="));
+        assert!(html.contains(&source));
+    }
+
+    #[test]
+    fn unknown_language_falls_back_to_plain_block() {
+        assert_eq!(
+            highlight_code("a < b", PasteFormat::Code),
+            "<pre><code><span class=\"line\" id=\"L1\">\
+             <a class=\"ln\" href=\"#L1\" data-line=\"1\"></a>a &lt; b</span></code></pre>"
+        );
+    }
+}
@@ -0,0 +1,224 @@
+//! HSTS and secure-cookie hardening for HTTPS deployments.
+//!
+//! The [`Hsts`] fairing only acts on requests that arrived over HTTPS —
+//! either Rocket's own TLS listener or, when trusted, a reverse proxy's
+//! `X-Forwarded-Proto: https`. For those it emits `Strict-Transport-Security`
+//! and upgrades every `Set-Cookie` to carry `Secure`, `HttpOnly` and
+//! `SameSite=Lax`. Plain-HTTP requests pass through untouched so local
+//! development keeps working.
+//!
+//! Configured from env at rocket build time:
+//! `COPYPASTE_HSTS_MAX_AGE` (seconds, default one year, `0` disables the
+//! header), `COPYPASTE_HSTS_INCLUDE_SUBDOMAINS` and
+//! `COPYPASTE_TRUST_FORWARDED_PROTO` (default `true`).
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::Header;
+use rocket::{Request, Response};
+
+const DEFAULT_MAX_AGE_SECS: u64 = 365 * 24 * 60 * 60;
+
+#[derive(Clone, Copy)]
+pub struct Hsts {
+    max_age_secs: u64,
+    include_subdomains: bool,
+    trust_forwarded_proto: bool,
+}
+
+impl Hsts {
+    pub fn new(max_age_secs: u64, include_subdomains: bool, trust_forwarded_proto: bool) -> Self {
+        Self {
+            max_age_secs,
+            include_subdomains,
+            trust_forwarded_proto,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let max_age_secs = std::env::var("COPYPASTE_HSTS_MAX_AGE")
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .unwrap_or(DEFAULT_MAX_AGE_SECS);
+        Self::new(
+            max_age_secs,
+            env_flag("COPYPASTE_HSTS_INCLUDE_SUBDOMAINS").unwrap_or(false),
+            env_flag("COPYPASTE_TRUST_FORWARDED_PROTO").unwrap_or(true),
+        )
+    }
+
+    fn is_https(&self, request: &Request<'_>) -> bool {
+        if request.rocket().config().tls_enabled() {
+            return true;
+        }
+        self.trust_forwarded_proto
+            && request
+                .headers()
+                .get_one("X-Forwarded-Proto")
+                .and_then(|proto| proto.split(',').next())
+                .is_some_and(|proto| proto.trim().eq_ignore_ascii_case("https"))
+    }
+
+    fn header_value(&self) -> String {
+        if self.include_subdomains {
+            format!("max-age={}; includeSubDomains", self.max_age_secs)
+        } else {
+            format!("max-age={}", self.max_age_secs)
+        }
+    }
+}
+
+impl Default for Hsts {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_AGE_SECS, false, true)
+    }
+}
+
+fn env_flag(name: &str) -> Option<bool> {
+    std::env::var(name)
+        .ok()
+        .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "on"))
+}
+
+/// Append any of `Secure`, `HttpOnly`, `SameSite=Lax` the cookie lacks. An
+/// explicit `SameSite` policy is left as is.
+fn harden_cookie(cookie: &str) -> String {
+    let attributes: Vec<String> = cookie
+        .split(';')
+        .skip(1)
+        .map(|attr| attr.trim().to_ascii_lowercase())
+        .collect();
+    let has = |name: &str| {
+        attributes
+            .iter()
+            .any(|attr| attr == name || attr.starts_with(&format!("{name}=")))
+    };
+
+    let mut hardened = cookie.trim_end().trim_end_matches(';').to_string();
+    if !has("secure") {
+        hardened.push_str("; Secure");
+    }
+    if !has("httponly") {
+        hardened.push_str("; HttpOnly");
+    }
+    if !has("samesite") {
+        hardened.push_str("; SameSite=Lax");
+    }
+    hardened
+}
+
+#[rocket::async_trait]
+impl Fairing for Hsts {
+    fn info(&self) -> Info {
+        Info {
+            name: "HSTS and secure cookies",
+            kind: Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        if !self.is_https(request) {
+            return;
+        }
+
+        if self.max_age_secs > 0 {
+            response.set_header(Header::new(
+                "Strict-Transport-Security",
+                self.header_value(),
+            ));
+        }
+
+        let cookies: Vec<String> = response
+            .headers()
+            .get("Set-Cookie")
+            .map(harden_cookie)
+            .collect();
+        if !cookies.is_empty() {
+            response.remove_header("Set-Cookie");
+            for cookie in cookies {
+                response.adjoin_header(Header::new("Set-Cookie", cookie));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::handlers::build_rocket;
+    use crate::{MemoryPasteStore, SharedPasteStore};
+    use rocket::http::{Cookie, CookieJar, Status};
+    use rocket::local::blocking::Client;
+    use std::sync::Arc;
+
+    #[test]
+    fn hsts_only_sent_for_forwarded_https() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+
+        let https = client
+            .get("/health")
+            .header(Header::new("X-Forwarded-Proto", "https"))
+            .dispatch();
+        assert_eq!(https.status(), Status::Ok);
+        assert_eq!(
+            https.headers().get_one("Strict-Transport-Security"),
+            Some("max-age=31536000")
+        );
+
+        let http = client.get("/health").dispatch();
+        assert!(http
+            .headers()
+            .get_one("Strict-Transport-Security")
+            .is_none());
+
+        let spoofed = client
+            .get("/health")
+            .header(Header::new("X-Forwarded-Proto", "http"))
+            .dispatch();
+        assert!(spoofed
+            .headers()
+            .get_one("Strict-Transport-Security")
+            .is_none());
+    }
+
+    #[rocket::get("/set-theme")]
+    fn set_theme(cookies: &CookieJar<'_>) -> &'static str {
+        cookies.add(Cookie::new("theme", "dark"));
+        "ok"
+    }
+
+    #[test]
+    fn cookies_hardened_over_https_only() {
+        let rocket = rocket::build()
+            .attach(Hsts::new(600, true, true))
+            .mount("/", rocket::routes![set_theme]);
+        let client = Client::untracked(rocket).expect("client");
+
+        let https = client
+            .get("/set-theme")
+            .header(Header::new("X-Forwarded-Proto", "https"))
+            .dispatch();
+        assert_eq!(
+            https.headers().get_one("Strict-Transport-Security"),
+            Some("max-age=600; includeSubDomains")
+        );
+        let cookie = https.headers().get_one("Set-Cookie").expect("cookie");
+        assert!(cookie.starts_with("theme=dark"));
+        assert!(cookie.contains("Secure"));
+        assert!(cookie.contains("HttpOnly"));
+        // Rocket defaults to SameSite=Strict; an existing policy is kept.
+        assert!(cookie.contains("SameSite="));
+
+        let http = client.get("/set-theme").dispatch();
+        let cookie = http.headers().get_one("Set-Cookie").expect("cookie");
+        assert!(!cookie.contains("Secure"));
+    }
+
+    #[test]
+    fn harden_cookie_keeps_existing_attributes() {
+        assert_eq!(
+            harden_cookie("a=b; Path=/; SameSite=Strict; Secure"),
+            "a=b; Path=/; SameSite=Strict; Secure; HttpOnly"
+        );
+    }
+}
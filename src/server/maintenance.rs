@@ -0,0 +1,125 @@
+//! Global read-only / maintenance mode.
+//!
+//! During migrations or incidents operators can stop accepting writes while
+//! existing pastes keep being served. The flag is seeded from
+//! `COPYPASTE_READ_ONLY` at rocket build time and can be flipped at runtime via
+//! `PUT /api/admin/maintenance`. Write routes take the [`WritesAllowed`] guard,
+//! which fails with 503; the [`maintenance_unavailable`] catcher turns that into
+//! a JSON error carrying a `Retry-After` header.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use rocket::{
+    catch,
+    http::{Header, Status},
+    request::{FromRequest, Outcome},
+    serde::json::Json,
+    Request, Responder,
+};
+
+use super::models::ApiError;
+
+/// `Retry-After` value (seconds) used when `COPYPASTE_READ_ONLY_RETRY_AFTER` is unset.
+const DEFAULT_RETRY_AFTER_SECS: u64 = 300;
+
+/// Read-only flag kept on Rocket managed state.
+pub struct MaintenanceMode {
+    read_only: AtomicBool,
+    retry_after_secs: u64,
+}
+
+impl MaintenanceMode {
+    pub fn new(read_only: bool, retry_after_secs: u64) -> Self {
+        Self {
+            read_only: AtomicBool::new(read_only),
+            retry_after_secs,
+        }
+    }
+
+    /// Build from `COPYPASTE_READ_ONLY` (`true`/`1`/`on`) and
+    /// `COPYPASTE_READ_ONLY_RETRY_AFTER` (seconds, default 300).
+    pub fn from_env() -> Self {
+        let read_only = std::env::var("COPYPASTE_READ_ONLY")
+            .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "on"))
+            .unwrap_or(false);
+        let retry_after_secs = std::env::var("COPYPASTE_READ_ONLY_RETRY_AFTER")
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .unwrap_or(DEFAULT_RETRY_AFTER_SECS);
+        Self::new(read_only, retry_after_secs)
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.load(Ordering::Relaxed)
+    }
+
+    pub fn set_read_only(&self, read_only: bool) {
+        self.read_only.store(read_only, Ordering::Relaxed);
+    }
+
+    pub fn retry_after_secs(&self) -> u64 {
+        self.retry_after_secs
+    }
+}
+
+impl Default for MaintenanceMode {
+    fn default() -> Self {
+        Self::new(false, DEFAULT_RETRY_AFTER_SECS)
+    }
+}
+
+/// Request guard for write routes; fails with 503 while read-only mode is on.
+pub struct WritesAllowed;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for WritesAllowed {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match req.rocket().state::<MaintenanceMode>() {
+            Some(mode) if mode.is_read_only() => Outcome::Error((Status::ServiceUnavailable, ())),
+            _ => Outcome::Success(WritesAllowed),
+        }
+    }
+}
+
+/// JSON 503 body plus `Retry-After`.
+#[derive(Responder)]
+#[response(status = 503)]
+pub struct MaintenanceResponse {
+    body: Json<ApiError>,
+    retry_after: Header<'static>,
+}
+
+#[catch(503)]
+pub fn maintenance_unavailable(req: &Request<'_>) -> MaintenanceResponse {
+    let (message, retry_after) = match req.rocket().state::<MaintenanceMode>() {
+        Some(mode) if mode.is_read_only() => (
+            "The service is in read-only maintenance mode; new writes are temporarily disabled",
+            mode.retry_after_secs(),
+        ),
+        _ => (
+            "The service is temporarily unavailable",
+            DEFAULT_RETRY_AFTER_SECS,
+        ),
+    };
+    MaintenanceResponse {
+        body: Json(ApiError::new("maintenance", message)),
+        retry_after: Header::new("Retry-After", retry_after.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flag_toggles_at_runtime() {
+        let mode = MaintenanceMode::default();
+        assert!(!mode.is_read_only());
+        mode.set_read_only(true);
+        assert!(mode.is_read_only());
+        mode.set_read_only(false);
+        assert!(!mode.is_read_only());
+    }
+}
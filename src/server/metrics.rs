@@ -0,0 +1,560 @@
+//! Per-route request metrics in the Prometheus text exposition format.
+//!
+//! The [`RequestMetrics`] fairing times every request and records, per
+//! route template and status class, a request counter, an error counter,
+//! a latency histogram over the standard Prometheus buckets and a
+//! response-size summary. Routes are labelled by their mount template
+//! (`/<id>`, not `/abc123`) so paste ids never become label values, and
+//! onion requests are only counted in aggregate — the host is never a label.
+//! Everything is rendered by `GET /metrics`, followed by store gauges taken
+//! from [`PasteStore::stats`] at scrape time. The gauges live in a
+//! `prometheus` registry ([`StoreGauges`]) and are encoded by its
+//! `TextEncoder`.
+//!
+//! When `COPYPASTE_METRICS_TOKEN` is set, scrapes must send it as
+//! `Authorization: Bearer <token>`.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use prometheus::{Encoder, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::{ContentType, Status};
+use rocket::{get, Data, Request, Response, State};
+use serde::Serialize;
+use subtle::ConstantTimeEq;
+
+use super::sessions::BearerToken;
+use super::tor::OnionAccess;
+use crate::{SharedPasteStore, StoreStats};
+
+/// Default Prometheus client histogram buckets, in seconds.
+pub const LATENCY_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+#[derive(Default)]
+struct RouteSeries {
+    requests: u64,
+    errors: u64,
+    /// Cumulative counts per entry of [`LATENCY_BUCKETS`].
+    latency_buckets: [u64; LATENCY_BUCKETS.len()],
+    latency_sum: f64,
+    response_bytes_sum: u64,
+}
+
+/// Collected series, keyed by `(method, route, status class)`.
+#[derive(Default)]
+pub struct Metrics {
+    series: Mutex<BTreeMap<(String, String, &'static str), RouteSeries>>,
+    onion_requests: AtomicU64,
+    /// Locked for a whole scrape so concurrent ones don't interleave their
+    /// updates.
+    store: Mutex<StoreGauges>,
+    /// Bearer token required to scrape; `None` leaves `/metrics` open.
+    token: Option<String>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build with the scrape token from `COPYPASTE_METRICS_TOKEN`.
+    pub fn from_env() -> Self {
+        Self::new().with_token(std::env::var("COPYPASTE_METRICS_TOKEN").ok())
+    }
+
+    /// Require `token` (ignored when empty) on scrapes.
+    pub fn with_token(mut self, token: Option<String>) -> Self {
+        self.token = token.filter(|t| !t.trim().is_empty());
+        self
+    }
+
+    fn authorized(&self, presented: Option<&str>) -> bool {
+        match (&self.token, presented) {
+            (None, _) => true,
+            (Some(expected), Some(presented)) => {
+                bool::from(expected.as_bytes().ct_eq(presented.as_bytes()))
+            }
+            (Some(_), None) => false,
+        }
+    }
+
+    pub fn record(
+        &self,
+        method: &str,
+        route: &str,
+        status: u16,
+        latency_secs: f64,
+        response_bytes: u64,
+    ) {
+        let class = status_class(status);
+        let mut series = self.series.lock().unwrap();
+        let entry = series
+            .entry((method.to_string(), route.to_string(), class))
+            .or_default();
+        entry.requests += 1;
+        if status >= 500 {
+            entry.errors += 1;
+        }
+        for (bucket, bound) in entry.latency_buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if latency_secs <= bound {
+                *bucket += 1;
+            }
+        }
+        entry.latency_sum += latency_secs;
+        entry.response_bytes_sum += response_bytes;
+    }
+
+    pub fn record_onion(&self) {
+        self.onion_requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Render every series in the Prometheus text format.
+    pub fn render(&self) -> String {
+        let series = self.series.lock().unwrap();
+        let mut out = String::new();
+
+        out.push_str("# HELP copypaste_http_requests_total HTTP requests handled.\n");
+        out.push_str("# TYPE copypaste_http_requests_total counter\n");
+        for ((method, route, class), s) in series.iter() {
+            let _ = writeln!(
+                out,
+                "copypaste_http_requests_total{{{}}} {}",
+                labels(method, route, class),
+                s.requests
+            );
+        }
+
+        out.push_str(
+            "# HELP copypaste_http_errors_total HTTP requests answered with a 5xx status.\n",
+        );
+        out.push_str("# TYPE copypaste_http_errors_total counter\n");
+        for ((method, route, class), s) in series.iter() {
+            let _ = writeln!(
+                out,
+                "copypaste_http_errors_total{{{}}} {}",
+                labels(method, route, class),
+                s.errors
+            );
+        }
+
+        out.push_str(
+            "# HELP copypaste_http_request_duration_seconds Time from request to response.\n",
+        );
+        out.push_str("# TYPE copypaste_http_request_duration_seconds histogram\n");
+        for ((method, route, class), s) in series.iter() {
+            let labels = labels(method, route, class);
+            for (bound, count) in LATENCY_BUCKETS.iter().zip(s.latency_buckets) {
+                let _ = writeln!(
+                    out,
+                    "copypaste_http_request_duration_seconds_bucket{{{labels},le=\"{bound}\"}} {count}"
+                );
+            }
+            let _ = writeln!(
+                out,
+                "copypaste_http_request_duration_seconds_bucket{{{labels},le=\"+Inf\"}} {}",
+                s.requests
+            );
+            let _ = writeln!(
+                out,
+                "copypaste_http_request_duration_seconds_sum{{{labels}}} {}",
+                s.latency_sum
+            );
+            let _ = writeln!(
+                out,
+                "copypaste_http_request_duration_seconds_count{{{labels}}} {}",
+                s.requests
+            );
+        }
+
+        out.push_str("# HELP copypaste_http_response_size_bytes Response body size.\n");
+        out.push_str("# TYPE copypaste_http_response_size_bytes summary\n");
+        for ((method, route, class), s) in series.iter() {
+            let labels = labels(method, route, class);
+            let _ = writeln!(
+                out,
+                "copypaste_http_response_size_bytes_sum{{{labels}}} {}",
+                s.response_bytes_sum
+            );
+            let _ = writeln!(
+                out,
+                "copypaste_http_response_size_bytes_count{{{labels}}} {}",
+                s.requests
+            );
+        }
+
+        out.push_str(
+            "# HELP copypaste_onion_requests_total Requests received via the onion host.\n",
+        );
+        out.push_str("# TYPE copypaste_onion_requests_total counter\n");
+        let _ = writeln!(
+            out,
+            "copypaste_onion_requests_total {}",
+            self.onion_requests.load(Ordering::Relaxed)
+        );
+        out
+    }
+}
+
+/// Paste-store gauges, registered in their own `prometheus` registry.
+pub struct StoreGauges {
+    registry: Registry,
+    total: IntGauge,
+    active: IntGauge,
+    expired: IntGauge,
+    burn_after_reading: IntGauge,
+    time_locked: IntGauge,
+    by_format: IntGaugeVec,
+    by_encryption: IntGaugeVec,
+}
+
+impl Default for StoreGauges {
+    fn default() -> Self {
+        let registry = Registry::new();
+        let gauge = |name: &str, help: &str| {
+            let gauge = IntGauge::new(name, help).expect("valid gauge");
+            registry
+                .register(Box::new(gauge.clone()))
+                .expect("gauge registered once");
+            gauge
+        };
+        let gauge_vec = |name: &str, help: &str, label: &str| {
+            let gauges = IntGaugeVec::new(Opts::new(name, help), &[label]).expect("valid gauge");
+            registry
+                .register(Box::new(gauges.clone()))
+                .expect("gauge registered once");
+            gauges
+        };
+        Self {
+            total: gauge("copypaste_pastes_total", "Pastes currently stored."),
+            active: gauge(
+                "copypaste_pastes_active",
+                "Stored pastes that have not expired.",
+            ),
+            expired: gauge(
+                "copypaste_pastes_expired",
+                "Stored pastes past their expiry, awaiting eviction.",
+            ),
+            burn_after_reading: gauge(
+                "copypaste_burn_after_reading_total",
+                "Stored pastes with a read limit of one.",
+            ),
+            time_locked: gauge(
+                "copypaste_time_locked_total",
+                "Stored pastes with a time lock.",
+            ),
+            by_format: gauge_vec(
+                "copypaste_pastes_by_format",
+                "Stored pastes per format.",
+                "format",
+            ),
+            by_encryption: gauge_vec(
+                "copypaste_pastes_by_encryption",
+                "Stored pastes per encryption algorithm.",
+                "algorithm",
+            ),
+            registry,
+        }
+    }
+}
+
+impl StoreGauges {
+    /// Set every gauge from `stats` and encode the registry in the
+    /// Prometheus text format.
+    pub fn render(&self, stats: &StoreStats) -> String {
+        self.total.set(stats.total_pastes as i64);
+        self.active.set(stats.active_pastes as i64);
+        self.expired.set(stats.expired_pastes as i64);
+        self.burn_after_reading
+            .set(stats.burn_after_reading_count as i64);
+        self.time_locked.set(stats.time_locked_count as i64);
+        // Formats no longer stored must drop out rather than keep their
+        // last count.
+        self.by_format.reset();
+        for usage in &stats.formats {
+            self.by_format
+                .with_label_values(&[label_value(&usage.format)])
+                .set(usage.count as i64);
+        }
+        self.by_encryption.reset();
+        for usage in &stats.encryption_usage {
+            self.by_encryption
+                .with_label_values(&[label_value(&usage.algorithm)])
+                .set(usage.count as i64);
+        }
+
+        let mut out = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut out)
+            .expect("registered gauges encode");
+        String::from_utf8(out).expect("the text format is UTF-8")
+    }
+}
+
+/// The serde name of a unit enum variant, e.g. `plain_text`.
+fn label_value<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => "unknown".to_string(),
+    }
+}
+
+fn status_class(status: u16) -> &'static str {
+    match status {
+        100..=199 => "1xx",
+        200..=299 => "2xx",
+        300..=399 => "3xx",
+        400..=499 => "4xx",
+        _ => "5xx",
+    }
+}
+
+fn labels(method: &str, route: &str, class: &str) -> String {
+    format!(
+        "method=\"{method}\",route=\"{}\",status=\"{class}\"",
+        route.replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
+
+/// Request start time, stashed in the request-local cache.
+struct RequestStart(Instant);
+
+#[derive(Clone, Copy)]
+pub struct RequestMetrics;
+
+#[rocket::async_trait]
+impl Fairing for RequestMetrics {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request metrics",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        request.local_cache(|| RequestStart(Instant::now()));
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let Some(metrics) = request.rocket().state::<Metrics>() else {
+            return;
+        };
+        let started = request.local_cache(|| RequestStart(Instant::now())).0;
+        let route = request
+            .route()
+            .map(|route| route.uri.path().to_string())
+            .unwrap_or_else(|| "unmatched".to_string());
+        let size = response.body().preset_size().unwrap_or(0) as u64;
+        metrics.record(
+            request.method().as_str(),
+            &route,
+            response.status().code,
+            started.elapsed().as_secs_f64(),
+            size,
+        );
+        if request
+            .guard::<OnionAccess>()
+            .await
+            .succeeded()
+            .is_some_and(|access| access.is_onion())
+        {
+            metrics.record_onion();
+        }
+    }
+}
+
+#[get("/metrics")]
+pub async fn metrics_endpoint(
+    metrics: &State<Metrics>,
+    store: &State<SharedPasteStore>,
+    token: BearerToken,
+) -> Result<(ContentType, String), Status> {
+    if !metrics.authorized(token.0.as_deref()) {
+        return Err(Status::Unauthorized);
+    }
+    let stats = store.stats().await;
+    let mut body = metrics.render();
+    body.push_str(&metrics.store.lock().unwrap().render(&stats));
+    Ok((
+        ContentType::new("text", "plain").with_params(("version", "0.0.4")),
+        body,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::MemoryPasteStore;
+    use rocket::http::Header;
+    use rocket::local::blocking::Client;
+    use std::sync::Arc;
+
+    /// Parse `text` with a Prometheus exposition-format parser, requiring
+    /// every non-comment line to come back in a sample. The parser skips
+    /// lines it cannot read, and folds histogram buckets into one sample.
+    fn parse_exposition(text: &str) -> prometheus_parse::Scrape {
+        use prometheus_parse::Value;
+
+        let scrape = prometheus_parse::Scrape::parse(text.lines().map(|l| Ok(l.to_string())))
+            .expect("valid exposition format");
+        let parsed_lines: usize = scrape
+            .samples
+            .iter()
+            .map(|sample| match &sample.value {
+                Value::Histogram(buckets) => buckets.len(),
+                Value::Summary(quantiles) => quantiles.len(),
+                _ => 1,
+            })
+            .sum();
+        let sample_lines = text
+            .lines()
+            .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+            .count();
+        assert_eq!(parsed_lines, sample_lines, "unparsed lines in:\n{text}");
+        scrape
+    }
+
+    fn sample<'a>(
+        scrape: &'a prometheus_parse::Scrape,
+        metric: &str,
+        label: Option<(&str, &str)>,
+    ) -> Option<&'a prometheus_parse::Value> {
+        scrape
+            .samples
+            .iter()
+            .find(|sample| {
+                sample.metric == metric
+                    && label.is_none_or(|(name, value)| sample.labels.get(name) == Some(value))
+            })
+            .map(|sample| &sample.value)
+    }
+
+    fn client(metrics: Metrics) -> Client {
+        metrics.record("GET", "/<id>", 200, 0.02, 10);
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let rocket = rocket::build()
+            .manage(store)
+            .manage(metrics)
+            .mount("/", rocket::routes![metrics_endpoint]);
+        Client::untracked(rocket).expect("client")
+    }
+
+    #[test]
+    fn scrape_includes_store_gauges_and_parses() {
+        let client = client(Metrics::new());
+        let response = client.get("/metrics").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        let scrape = parse_exposition(&response.into_string().unwrap());
+        for expected in [
+            "copypaste_onion_requests_total",
+            "copypaste_pastes_total",
+            "copypaste_pastes_active",
+            "copypaste_pastes_expired",
+            "copypaste_burn_after_reading_total",
+            "copypaste_time_locked_total",
+        ] {
+            assert!(
+                sample(&scrape, expected, None).is_some(),
+                "missing {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn store_gauges_label_formats_and_algorithms() {
+        let stats = StoreStats {
+            total_pastes: 3,
+            active_pastes: 2,
+            expired_pastes: 1,
+            burn_after_reading_count: 1,
+            time_locked_count: 0,
+            formats: vec![crate::FormatUsage {
+                format: crate::PasteFormat::PlainText,
+                count: 3,
+            }],
+            encryption_usage: vec![crate::EncryptionUsage {
+                algorithm: crate::EncryptionAlgorithm::Aes256Gcm,
+                count: 1,
+            }],
+            created_by_day: vec![],
+        };
+        let gauges = StoreGauges::default();
+        let scrape = parse_exposition(&gauges.render(&stats));
+        assert!(matches!(
+            sample(&scrape, "copypaste_pastes_total", None),
+            Some(prometheus_parse::Value::Gauge(v)) if *v == 3.0
+        ));
+        assert!(matches!(
+            sample(&scrape, "copypaste_pastes_by_format", Some(("format", "plain_text"))),
+            Some(prometheus_parse::Value::Gauge(v)) if *v == 3.0
+        ));
+        assert!(matches!(
+            sample(
+                &scrape,
+                "copypaste_pastes_by_encryption",
+                Some(("algorithm", "aes256_gcm"))
+            ),
+            Some(prometheus_parse::Value::Gauge(v)) if *v == 1.0
+        ));
+
+        // A format that is no longer stored drops out on the next scrape.
+        let scrape = parse_exposition(&gauges.render(&StoreStats {
+            formats: vec![],
+            ..stats
+        }));
+        assert!(sample(&scrape, "copypaste_pastes_by_format", None).is_none());
+    }
+
+    #[test]
+    fn token_gates_scrapes() {
+        let client = client(Metrics::new().with_token(Some("scrape-me".into())));
+        assert_eq!(
+            client.get("/metrics").dispatch().status(),
+            Status::Unauthorized
+        );
+        let wrong = client
+            .get("/metrics")
+            .header(Header::new("Authorization", "Bearer nope"))
+            .dispatch();
+        assert_eq!(wrong.status(), Status::Unauthorized);
+        let ok = client
+            .get("/metrics")
+            .header(Header::new("Authorization", "Bearer scrape-me"))
+            .dispatch();
+        assert_eq!(ok.status(), Status::Ok);
+    }
+
+    #[test]
+    fn histogram_buckets_are_cumulative() {
+        let metrics = Metrics::new();
+        metrics.record("GET", "/<id>", 200, 0.02, 10);
+        metrics.record("GET", "/<id>", 200, 3.0, 30);
+        metrics.record("GET", "/<id>", 503, 0.001, 0);
+
+        let text = metrics.render();
+        parse_exposition(&text);
+        let ok = "method=\"GET\",route=\"/<id>\",status=\"2xx\"";
+        assert!(text.contains(&format!("copypaste_http_requests_total{{{ok}}} 2")));
+        assert!(text.contains(&format!(
+            "copypaste_http_request_duration_seconds_bucket{{{ok},le=\"0.01\"}} 0"
+        )));
+        assert!(text.contains(&format!(
+            "copypaste_http_request_duration_seconds_bucket{{{ok},le=\"0.025\"}} 1"
+        )));
+        assert!(text.contains(&format!(
+            "copypaste_http_request_duration_seconds_bucket{{{ok},le=\"5\"}} 2"
+        )));
+        assert!(text.contains(&format!(
+            "copypaste_http_response_size_bytes_sum{{{ok}}} 40"
+        )));
+        assert!(text.contains(
+            "copypaste_http_errors_total{method=\"GET\",route=\"/<id>\",status=\"5xx\"} 1"
+        ));
+    }
+}
@@ -1,17 +1,33 @@
+pub mod access_log;
 pub mod api_keys;
 pub mod attestation;
 pub mod blockchain;
 pub mod bundles;
+pub mod cache;
 pub mod config;
 pub mod cors;
 pub mod crypto;
+pub mod detect;
+pub mod download_tokens;
+pub mod fs;
 pub mod handlers;
+pub mod headers;
+pub mod highlight;
+pub mod hsts;
+pub mod maintenance;
+pub mod metrics;
 pub mod models;
 pub mod rate_limit;
 pub mod redis;
 pub mod render;
+pub mod robots;
+pub mod s3;
 pub mod sessions;
+pub mod shutdown;
+pub mod slugs;
 pub mod stego;
+pub mod sweeper;
 pub mod time;
 pub mod tor;
+pub mod webauthn;
 pub mod webhook;
@@ -1,14 +1,14 @@
 use crate::server::api_keys::ApiScope;
 use crate::{
-    BundleMetadata, DailyCount, EncryptionAlgorithm, EncryptionUsage, FormatUsage, PasteFormat,
-    StoreStats, WebhookProvider,
+    BundleMetadata, ContentEncoding, DailyCount, EncryptionAlgorithm, EncryptionUsage, FormatUsage,
+    PasteFormat, StoreStats, WebhookProvider,
 };
 use rocket::form::FromForm;
 use rocket::serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
 use crate::server::attestation::AttestationRequest;
-use crate::server::blockchain::{AnchorManifest, AnchorReceipt};
+use crate::server::blockchain::{AnchorContentMode, AnchorManifest, AnchorReceipt};
 
 #[derive(Serialize, Deserialize, Clone, ToSchema)]
 #[serde(rename_all = "snake_case")]
@@ -29,6 +29,80 @@ pub struct CreatePasteResponse {
     pub token: Option<String>,
     #[serde(default)]
     pub is_live: bool,
+    /// Child shares created for a bundle request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bundle: Option<CreatedBundle>,
+    /// Set when the format was omitted or `code` and the server picked one
+    /// from the content.
+    #[serde(default, skip_serializing_if = "crate::bool_is_false")]
+    pub detected_format: bool,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct CreatedBundle {
+    pub children: Vec<CreatedBundleChild>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct CreatedBundleChild {
+    pub id: String,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// Response for `POST /api/pastes/{id}/token`.
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadTokenResponse {
+    pub token: String,
+    /// Relative one-time download URL (`/raw/{id}?token=...`).
+    pub url: String,
+    pub expires_at: i64,
+}
+
+/// Response for `POST /api/pastes/{id}/webauthn/challenge`.
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PasskeyChallengeResponse {
+    /// Echo back in the verify request.
+    pub challenge_id: String,
+    /// Options to pass to `navigator.credentials.get()`.
+    #[schema(value_type = Object)]
+    pub options: webauthn_rs::prelude::RequestChallengeResponse,
+}
+
+/// Body of `POST /api/pastes/{id}/webauthn/verify`.
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PasskeyVerifyRequest {
+    pub challenge_id: String,
+    /// The `PublicKeyCredential` from `navigator.credentials.get()`, with
+    /// binary fields base64url-encoded.
+    #[schema(value_type = Object)]
+    pub credential: webauthn_rs::prelude::PublicKeyCredential,
+}
+
+/// Response for `POST /api/pastes/{id}/webauthn/verify`.
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PasskeyGrantResponse {
+    /// Present as `?webauthn=<token>` when reading the paste.
+    pub token: String,
+    pub expires_at: i64,
+}
+
+/// Response for `GET /api/pastes/{id}/exists`.
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PasteExistsResponse {
+    /// The paste can still be read.
+    pub exists: bool,
+    /// The paste is past its retention or time-lock window.
+    pub expired: bool,
+    /// The paste was deleted by its final permitted read (burn after
+    /// reading or `max_reads`).
+    pub consumed: bool,
 }
 
 #[derive(Serialize, Deserialize, Default, ToSchema)]
@@ -38,6 +112,23 @@ pub struct AnchorRequest {
     pub retention_class: Option<u8>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub attestation_ref: Option<String>,
+    /// `digest_only` anchors a SHA-256 of the stored content instead of the
+    /// content itself.
+    pub content_mode: AnchorContentMode,
+}
+
+/// Response for `GET /api/pastes/{id}/anchor/verify`.
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AnchorVerifyResponse {
+    pub matches: bool,
+    /// Manifest mode `current_hash` was computed in: the one matching
+    /// `expected_hash`, or `full` when neither does.
+    pub content_mode: AnchorContentMode,
+    /// Manifest hash of the paste as it is stored now.
+    pub current_hash: String,
+    /// `?hash=`, or the hash recorded when the paste was last anchored.
+    pub expected_hash: String,
 }
 
 #[derive(Serialize, Deserialize, ToSchema)]
@@ -49,6 +140,7 @@ pub struct AnchorResponse {
     pub retention_class: Option<u8>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub attestation_ref: Option<String>,
+    pub content_mode: AnchorContentMode,
     pub manifest: AnchorManifest,
     pub receipt: AnchorReceipt,
 }
@@ -58,10 +150,31 @@ pub struct AnchorResponse {
 pub struct PasteViewResponse {
     pub id: String,
     pub format: PasteFormat,
-    pub content: String,
+    /// Decrypted content as a UTF-8 string; omitted when `?encoding=base64`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    /// Base64 of the decrypted bytes; only set when `?encoding=base64`.
+    #[serde(
+        rename = "content_b64",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub content_b64: Option<String>,
+    /// Encoding of the content field (`utf8` or `base64`; `none` for
+    /// `?metadata_only=true`).
+    #[serde(default = "default_content_encoding")]
+    pub encoding: String,
     pub created_at: i64,
     pub expires_at: Option<i64>,
     pub burn_after_reading: bool,
+    /// Reads left before the paste is deleted, after this one; absent for
+    /// pastes without a read limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reads_remaining: Option<u32>,
+    /// Byte length of the (decrypted) content; absent for metadata-only
+    /// views of encrypted pastes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<usize>,
     pub bundle: Option<BundleMetadata>,
     pub encryption: PasteEncryptionInfo,
     #[serde(default)]
@@ -82,6 +195,17 @@ pub struct PasteViewResponse {
     pub stego: Option<PasteStegoInfo>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub workspace: Option<String>,
+    /// Hex SHA-256 of the plaintext recorded at creation; absent for pastes
+    /// stored before digests were recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_sha256: Option<String>,
+    /// Set when the decrypted content no longer matches `contentSha256`.
+    #[serde(default, skip_serializing_if = "crate::bool_is_false")]
+    pub tampered: bool,
+}
+
+fn default_content_encoding() -> String {
+    "utf8".to_string()
 }
 
 #[derive(Serialize, Deserialize, ToSchema)]
@@ -103,9 +227,13 @@ pub struct PasteTimeLockInfo {
 #[derive(Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct PasteAttestationInfo {
+    /// `totp`, `shared_secret`, or `all` when several factors are required.
     pub kind: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub issuer: Option<String>,
+    /// Kind of each required factor; only set when `kind` is `all`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub factors: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, ToSchema)]
@@ -210,6 +338,10 @@ pub struct CreateBundleChildRequest {
     pub format: Option<PasteFormat>,
     #[serde(default)]
     pub label: Option<String>,
+    /// Independent key for this share (same algorithm as the parent);
+    /// defaults to the parent's key.
+    #[serde(default)]
+    pub key: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Default, ToSchema)]
@@ -226,9 +358,21 @@ pub struct CreatePasteRequest {
     #[serde(default)]
     pub format: Option<PasteFormat>,
     pub retention_minutes: Option<u64>,
+    /// Human-readable retention such as `90m`, `24h`, `7d` or `2w` (a bare
+    /// number means minutes). Takes precedence over `retention_minutes`.
+    #[serde(default)]
+    pub retention: Option<String>,
+    /// Absolute expiry as UNIX seconds or RFC3339; must be in the future.
+    /// Takes precedence over `retention` and `retention_minutes`.
+    #[serde(default)]
+    pub expires_at: Option<String>,
     pub encryption: Option<EncryptionRequest>,
     #[serde(default)]
     pub burn_after_reading: bool,
+    /// Delete the paste after this many successful reads;
+    /// `burn_after_reading: true` is shorthand for `max_reads: 1`.
+    #[serde(default)]
+    pub max_reads: Option<u32>,
     #[serde(default)]
     pub bundle: Option<CreateBundleRequest>,
     #[serde(default)]
@@ -243,12 +387,23 @@ pub struct CreatePasteRequest {
     pub stego: Option<StegoRequest>,
     #[serde(default)]
     pub tor_access_only: bool,
+    /// Ignored for anonymous requests; the owner is always the session's
+    /// pubkey hash, and a conflicting value is rejected with 403.
     pub owner_pubkey_hash: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub workspace: Option<String>,
     /// When `true`, paste starts in live mode — content can be updated via `PUT /api/pastes/{id}`.
     #[serde(default)]
     pub live: bool,
+    /// Vanity id such as `team/deploy-runbook` (3-64 chars of `a-z`, `0-9`,
+    /// `-`, `/`). Rejected with 409 if taken; a generated id is used when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_id: Option<String>,
+    /// `base64`: `content` is opaque bytes in standard base64. They are
+    /// stored and served as bytes without UTF-8 validation; the HTML view
+    /// offers a download instead of rendering them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_encoding: Option<ContentEncoding>,
 }
 
 /// Request body for `PUT /api/pastes/{id}` (update live paste content).
@@ -304,6 +459,8 @@ pub struct WebhookRequest {
     pub provider: Option<WebhookProvider>,
     pub view_template: Option<String>,
     pub burn_template: Option<String>,
+    /// Secret used to sign deliveries (`X-Copypaste-Signature`).
+    pub secret: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, ToSchema)]
@@ -352,6 +509,10 @@ pub struct UserPasteListItem {
 #[serde(rename_all = "camelCase")]
 pub struct UserPasteListResponse {
     pub pastes: Vec<UserPasteListItem>,
+    /// Number of pastes the user owns across all pages.
+    pub total: usize,
+    /// `offset` of the next page, or `None` on the last one.
+    pub next: Option<usize>,
 }
 
 #[derive(Serialize, Deserialize, ToSchema)]
@@ -441,6 +602,20 @@ pub struct RevokeApiKeyResponse {
     pub revoked: bool,
 }
 
+// ── Maintenance mode ──────────────────────────────────────────────────────────
+
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceModeRequest {
+    pub read_only: bool,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceModeResponse {
+    pub read_only: bool,
+}
+
 // ── Standardised error shape ──────────────────────────────────────────────────
 
 /// Machine-readable error envelope returned by all API error responses.
@@ -452,8 +627,9 @@ pub struct ApiError {
     /// Human-readable description.
     pub message: String,
     /// Optional structured details (validation errors, upstream messages, etc.).
+    /// Boxed so error results stay small.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub details: Option<serde_json::Value>,
+    pub details: Option<Box<serde_json::Value>>,
 }
 
 impl ApiError {
@@ -473,7 +649,7 @@ impl ApiError {
         Self {
             code: code.into(),
             message: message.into(),
-            details: Some(details),
+            details: Some(Box::new(details)),
         }
     }
 }
@@ -485,4 +661,81 @@ pub struct PasteViewQuery {
     pub key: Option<String>,
     pub code: Option<String>,
     pub attest: Option<String>,
+    /// `utf8` (default) or `base64`; only honoured by the JSON API.
+    pub encoding: Option<String>,
+    /// JSON API only: return metadata without decrypting or burning.
+    pub metadata_only: Option<bool>,
+    /// One-time download token minted via `POST /api/pastes/<id>/token`;
+    /// honoured by `/raw/<id>` in place of an attestation code.
+    pub token: Option<String>,
+    /// `/raw/<id>` only: serve as an attachment (`?download=1`).
+    pub download: Option<String>,
+    /// HTML views only: `dark` or `light`; otherwise `prefers-color-scheme`.
+    pub theme: Option<String>,
+    /// HTML views of JSON pastes only: print object keys sorted
+    /// (`?sort_keys=1`) instead of in the order they were written.
+    pub sort_keys: Option<String>,
+    /// HTML attestation form only: the signed token it was rendered with,
+    /// see [`super::attestation::PromptTokens`].
+    pub state: Option<String>,
+    /// Passkey grant token from `POST /api/pastes/<id>/webauthn/verify`.
+    pub webauthn: Option<String>,
+}
+
+impl PasteViewQuery {
+    /// `?download`, `?download=1`, `?download=true`, ... but not `=0`/`=false`.
+    pub fn wants_download(&self) -> bool {
+        query_flag(self.download.as_deref())
+    }
+
+    /// `?sort_keys`, read like [`PasteViewQuery::wants_download`].
+    pub fn wants_sorted_keys(&self) -> bool {
+        query_flag(self.sort_keys.as_deref())
+    }
+}
+
+/// Whether a boolean query flag is set: present and not `0`/`false`/`no`/`off`.
+fn query_flag(value: Option<&str>) -> bool {
+    value.is_some_and(|value| {
+        !matches!(
+            value.to_ascii_lowercase().as_str(),
+            "0" | "false" | "no" | "off"
+        )
+    })
+}
+
+/// One NDJSON record accepted by `POST /api/import`.
+#[derive(Deserialize, ToSchema)]
+pub struct ImportPasteRecord {
+    pub content: String,
+    #[serde(default)]
+    pub format: Option<PasteFormat>,
+    #[serde(default)]
+    pub retention_minutes: Option<u64>,
+    #[serde(default)]
+    pub encryption: Option<EncryptionRequest>,
+}
+
+/// One NDJSON result line returned by `POST /api/import`: either `id`/`url`
+/// or `error`, keyed by the 1-based input line number.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ImportLineResult {
+    pub line: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Query parameters for `POST /api/pastes/raw`, where the request body is the
+/// paste content itself rather than a JSON envelope.
+#[derive(FromForm, Default)]
+pub struct RawPasteQuery {
+    pub format: Option<String>,
+    pub retention_minutes: Option<u64>,
+    pub burn_after_reading: Option<bool>,
+    /// Replace invalid UTF-8 sequences with U+FFFD instead of rejecting.
+    pub lossy: Option<bool>,
 }
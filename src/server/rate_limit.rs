@@ -1,84 +1,171 @@
-//! Per-IP fixed-window rate limiting for paste creation and reads.
+//! Per-IP token-bucket rate limiting for paste creation and reads.
 //!
 //! Wires up the `[rate_limit]` config knobs (`creates_per_minute`,
 //! `reads_per_minute`) that were previously parsed and validated but never
 //! consumed. `config::Config::bridge_to_env` exports them as
 //! `COPYPASTE_RATE_LIMIT_CREATES` / `COPYPASTE_RATE_LIMIT_READS`; this module
-//! reads those env vars at rocket build time. When a knob is unset (or `0`),
-//! the corresponding limiter is disabled, so embedded/test usage is unaffected.
+//! reads those env vars at rocket build time, with
+//! `COPYPASTE_RATE_LIMIT_PER_MINUTE` as a fallback for creates. When a knob is
+//! unset (or `0`), the corresponding limiter is disabled, so embedded/test
+//! usage is unaffected.
+//!
+//! Each client gets a bucket holding up to `n` tokens that refills at `n` per
+//! minute, so short bursts are absorbed while the sustained rate is capped.
+//! A rejected request fails its guard with 429 and the [`too_many_requests`]
+//! catcher answers with a `Retry-After` of the seconds until the next token.
+//!
+//! Clients are keyed by [`Request::client_ip`]; with
+//! `COPYPASTE_TRUST_FORWARDED_FOR` set, the address the reverse proxy appended
+//! to `X-Forwarded-For` is used instead.
+//!
+//! [`DailyPasteQuota`] adds a longer-horizon cap on top: a per-IP count of
+//! pastes created per calendar day (`COPYPASTE_DAILY_PASTE_QUOTA`), reset at
+//! midnight in a configurable UTC offset.
 
 use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Mutex;
 use std::time::{Duration, Instant};
 
 use rocket::{
-    http::Status,
+    catch,
+    http::{Header, Status},
     request::{FromRequest, Outcome},
-    Request, State,
+    serde::json::Json,
+    Request, Responder, State,
 };
 
-/// Fixed rate-limit window length.
+use super::models::ApiError;
+use super::tor::OnionAccess;
+
+/// Time for an empty bucket to refill completely.
 const WINDOW: Duration = Duration::from_secs(60);
 
-/// Purge stale windows once the map grows beyond this many client entries.
+/// Purge idle buckets once the map grows beyond this many client entries...
 const PURGE_THRESHOLD: usize = 10_000;
 
-/// Per-IP fixed-window counters for paste creates and reads.
+/// ...or at least this often.
+const PURGE_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Tokens available to one client.
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// One client map per limited action.
+struct Buckets {
+    clients: Mutex<(HashMap<String, Bucket>, Instant)>,
+}
+
+impl Buckets {
+    fn new() -> Self {
+        Self {
+            clients: Mutex::new((HashMap::new(), Instant::now())),
+        }
+    }
+
+    /// Take a token for `ip` from a bucket of `capacity` refilling
+    /// `capacity` per [`WINDOW`]. Returns the wait until the next token when
+    /// the bucket is empty.
+    fn take(&self, capacity: u32, ip: &str) -> Result<(), Duration> {
+        let capacity = f64::from(capacity);
+        let per_sec = capacity / WINDOW.as_secs_f64();
+        let now = Instant::now();
+        let mut guard = self.clients.lock().unwrap();
+        let (clients, last_purge) = &mut *guard;
+        if clients.len() > PURGE_THRESHOLD || now.duration_since(*last_purge) > PURGE_INTERVAL {
+            // A bucket idle for a full window is back at capacity, which is
+            // the same as having no entry at all.
+            clients.retain(|_, bucket| now.duration_since(bucket.updated) < WINDOW);
+            *last_purge = now;
+        }
+        let bucket = clients.entry(ip.to_owned()).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * per_sec).min(capacity);
+        bucket.updated = now;
+        if bucket.tokens < 1.0 {
+            return Err(Duration::from_secs_f64((1.0 - bucket.tokens) / per_sec));
+        }
+        bucket.tokens -= 1.0;
+        Ok(())
+    }
+}
+
+/// Per-IP token buckets for paste creates and reads.
 pub struct PasteRateLimiter {
-    creates_per_minute: Option<u32>,
-    reads_per_minute: Option<u32>,
-    creates: Mutex<HashMap<String, (u32, Instant)>>,
-    reads: Mutex<HashMap<String, (u32, Instant)>>,
+    /// `0` means disabled.
+    creates_per_minute: AtomicU32,
+    reads_per_minute: AtomicU32,
+    trust_forwarded_for: bool,
+    creates: Buckets,
+    reads: Buckets,
 }
 
 impl PasteRateLimiter {
     /// `None` (or `Some(0)`) disables the corresponding limiter.
     pub fn new(creates_per_minute: Option<u32>, reads_per_minute: Option<u32>) -> Self {
         Self {
-            creates_per_minute: creates_per_minute.filter(|n| *n > 0),
-            reads_per_minute: reads_per_minute.filter(|n| *n > 0),
-            creates: Mutex::new(HashMap::new()),
-            reads: Mutex::new(HashMap::new()),
+            creates_per_minute: AtomicU32::new(creates_per_minute.unwrap_or(0)),
+            reads_per_minute: AtomicU32::new(reads_per_minute.unwrap_or(0)),
+            trust_forwarded_for: false,
+            creates: Buckets::new(),
+            reads: Buckets::new(),
         }
     }
 
-    /// Build from `COPYPASTE_RATE_LIMIT_CREATES` / `COPYPASTE_RATE_LIMIT_READS`.
-    /// Unset, unparsable, or zero values disable the respective limiter.
+    /// Build from `COPYPASTE_RATE_LIMIT_CREATES` (falling back to
+    /// `COPYPASTE_RATE_LIMIT_PER_MINUTE`), `COPYPASTE_RATE_LIMIT_READS` and
+    /// `COPYPASTE_TRUST_FORWARDED_FOR`. Unset, unparsable, or zero values
+    /// disable the respective limiter.
     pub fn from_env() -> Self {
-        Self::new(
-            limit_from_env("COPYPASTE_RATE_LIMIT_CREATES"),
+        let mut limiter = Self::new(
+            limit_from_env("COPYPASTE_RATE_LIMIT_CREATES")
+                .or_else(|| limit_from_env("COPYPASTE_RATE_LIMIT_PER_MINUTE")),
             limit_from_env("COPYPASTE_RATE_LIMIT_READS"),
-        )
+        );
+        limiter.trust_forwarded_for = std::env::var("COPYPASTE_TRUST_FORWARDED_FOR")
+            .is_ok_and(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "on"));
+        limiter
+    }
+
+    /// Replace the create and read limits at runtime; buckets are kept.
+    pub fn set_limits(&self, creates_per_minute: Option<u32>, reads_per_minute: Option<u32>) {
+        self.creates_per_minute
+            .store(creates_per_minute.unwrap_or(0), Ordering::Relaxed);
+        self.reads_per_minute
+            .store(reads_per_minute.unwrap_or(0), Ordering::Relaxed);
     }
 
     /// Returns `true` when a create request from `ip` is allowed.
     pub fn allow_create(&self, ip: &str) -> bool {
-        Self::allow(&self.creates, self.creates_per_minute, ip)
+        self.check_create(ip).is_ok()
     }
 
     /// Returns `true` when a read request from `ip` is allowed.
     pub fn allow_read(&self, ip: &str) -> bool {
-        Self::allow(&self.reads, self.reads_per_minute, ip)
+        self.check_read(ip).is_ok()
     }
 
-    fn allow(map: &Mutex<HashMap<String, (u32, Instant)>>, limit: Option<u32>, ip: &str) -> bool {
-        let Some(limit) = limit else {
-            return true;
-        };
-        let mut map = map.lock().unwrap();
-        let now = Instant::now();
-        if map.len() > PURGE_THRESHOLD {
-            map.retain(|_, (_, start)| now.duration_since(*start) <= WINDOW);
-        }
-        let entry = map.entry(ip.to_owned()).or_insert((0, now));
-        if now.duration_since(entry.1) > WINDOW {
-            *entry = (0, now);
-        }
-        if entry.0 >= limit {
-            return false;
+    /// Charge one create to `ip`; `Err` carries the wait until one is allowed.
+    pub fn check_create(&self, ip: &str) -> Result<(), Duration> {
+        Self::check(&self.creates, &self.creates_per_minute, ip)
+    }
+
+    /// Charge one read to `ip`; `Err` carries the wait until one is allowed.
+    pub fn check_read(&self, ip: &str) -> Result<(), Duration> {
+        Self::check(&self.reads, &self.reads_per_minute, ip)
+    }
+
+    fn check(buckets: &Buckets, limit: &AtomicU32, ip: &str) -> Result<(), Duration> {
+        match limit.load(Ordering::Relaxed) {
+            0 => Ok(()),
+            limit => buckets.take(limit, ip),
         }
-        entry.0 += 1;
-        true
     }
 }
 
@@ -89,12 +176,43 @@ fn limit_from_env(name: &str) -> Option<u32> {
         .filter(|n| *n > 0)
 }
 
+/// The last `X-Forwarded-For` hop, i.e. the peer our own proxy saw. Earlier
+/// entries are supplied by the client and cannot be trusted.
+fn forwarded_for(req: &Request<'_>) -> Option<IpAddr> {
+    req.headers()
+        .get("X-Forwarded-For")
+        .last()?
+        .rsplit(',')
+        .next()?
+        .trim()
+        .parse()
+        .ok()
+}
+
 fn client_key(req: &Request<'_>) -> String {
-    req.client_ip()
+    let trust_forwarded_for = req
+        .rocket()
+        .state::<PasteRateLimiter>()
+        .is_some_and(|limiter| limiter.trust_forwarded_for);
+    trust_forwarded_for
+        .then(|| forwarded_for(req))
+        .flatten()
+        .or_else(|| req.client_ip())
         .map(|ip| ip.to_string())
         .unwrap_or_else(|| "unknown".to_string())
 }
 
+/// Seconds until a rate-limited client may retry, cached on the request by
+/// the failing guard for [`too_many_requests`].
+#[derive(Clone, Copy, Default)]
+struct RetryAfter(Option<u64>);
+
+fn limited<T>(req: &Request<'_>, wait: Duration) -> Outcome<T, ()> {
+    let secs = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+    req.local_cache(|| RetryAfter(Some(secs.max(1))));
+    Outcome::Error((Status::TooManyRequests, ()))
+}
+
 /// Request guard enforcing the create limit; fails with 429 when exceeded.
 pub struct CreateRateLimit;
 
@@ -107,10 +225,9 @@ impl<'r> FromRequest<'r> for CreateRateLimit {
             Outcome::Success(limiter) => limiter,
             _ => return Outcome::Success(CreateRateLimit),
         };
-        if limiter.allow_create(&client_key(req)) {
-            Outcome::Success(CreateRateLimit)
-        } else {
-            Outcome::Error((Status::TooManyRequests, ()))
+        match limiter.check_create(&client_key(req)) {
+            Ok(()) => Outcome::Success(CreateRateLimit),
+            Err(wait) => limited(req, wait),
         }
     }
 }
@@ -127,14 +244,217 @@ impl<'r> FromRequest<'r> for ReadRateLimit {
             Outcome::Success(limiter) => limiter,
             _ => return Outcome::Success(ReadRateLimit),
         };
-        if limiter.allow_read(&client_key(req)) {
-            Outcome::Success(ReadRateLimit)
+        match limiter.check_read(&client_key(req)) {
+            Ok(()) => Outcome::Success(ReadRateLimit),
+            Err(wait) => limited(req, wait),
+        }
+    }
+}
+
+/// JSON 429 body plus `Retry-After`.
+#[derive(Responder)]
+#[response(status = 429)]
+pub struct RateLimitedResponse {
+    body: Json<ApiError>,
+    retry_after: Header<'static>,
+}
+
+#[catch(429)]
+pub fn too_many_requests(req: &Request<'_>) -> RateLimitedResponse {
+    let retry_after = req
+        .local_cache(RetryAfter::default)
+        .0
+        .unwrap_or(WINDOW.as_secs());
+    RateLimitedResponse {
+        body: Json(ApiError::new(
+            "too_many_requests",
+            "Rate limit exceeded; retry later",
+        )),
+        retry_after: Header::new("Retry-After", retry_after.to_string()),
+    }
+}
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Per-IP count of pastes created per calendar day.
+///
+/// Days roll over at local midnight for `utc_offset_secs`
+/// (`COPYPASTE_DAILY_PASTE_QUOTA_UTC_OFFSET_MINUTES`, default UTC). Onion
+/// requests are counted separately and may use their own limit
+/// (`COPYPASTE_DAILY_PASTE_QUOTA_ONION`), since Tor clients typically all
+/// arrive from the local tor daemon's address.
+pub struct DailyPasteQuota {
+    /// `0` means disabled.
+    limit: AtomicU32,
+    onion_limit: AtomicU32,
+    utc_offset_secs: i64,
+    counts: Mutex<HashMap<String, (u32, i64)>>,
+}
+
+impl DailyPasteQuota {
+    /// `None` (or `Some(0)`) disables the corresponding quota.
+    pub fn new(limit: Option<u32>, onion_limit: Option<u32>, utc_offset_minutes: i32) -> Self {
+        Self {
+            limit: AtomicU32::new(limit.unwrap_or(0)),
+            onion_limit: AtomicU32::new(onion_limit.unwrap_or(0)),
+            utc_offset_secs: i64::from(utc_offset_minutes) * 60,
+            counts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Build from `COPYPASTE_DAILY_PASTE_QUOTA`, `COPYPASTE_DAILY_PASTE_QUOTA_ONION`
+    /// (falls back to the clearnet quota) and
+    /// `COPYPASTE_DAILY_PASTE_QUOTA_UTC_OFFSET_MINUTES`.
+    pub fn from_env() -> Self {
+        let limit = limit_from_env("COPYPASTE_DAILY_PASTE_QUOTA");
+        let onion_limit = limit_from_env("COPYPASTE_DAILY_PASTE_QUOTA_ONION").or(limit);
+        let utc_offset_minutes = std::env::var("COPYPASTE_DAILY_PASTE_QUOTA_UTC_OFFSET_MINUTES")
+            .ok()
+            .and_then(|v| v.trim().parse::<i32>().ok())
+            .filter(|m| m.abs() < 24 * 60)
+            .unwrap_or(0);
+        Self::new(limit, onion_limit, utc_offset_minutes)
+    }
+
+    /// Replace the clearnet and onion limits at runtime; counts are kept.
+    pub fn set_limits(&self, limit: Option<u32>, onion_limit: Option<u32>) {
+        self.limit.store(limit.unwrap_or(0), Ordering::Relaxed);
+        self.onion_limit
+            .store(onion_limit.unwrap_or(0), Ordering::Relaxed);
+    }
+
+    /// Count one paste for `ip` at unix time `now`.
+    ///
+    /// Returns `Err(reset_at)` (unix seconds of the next local midnight) when
+    /// the client has already used up today's quota.
+    pub fn try_consume(&self, ip: &str, onion: bool, now: i64) -> Result<(), i64> {
+        let limit = if onion {
+            &self.onion_limit
+        } else {
+            &self.limit
+        };
+        let limit = limit.load(Ordering::Relaxed);
+        if limit == 0 {
+            return Ok(());
+        }
+        let day = self.day(now);
+        let key = Self::key(ip, onion);
+
+        let mut counts = self.counts.lock().unwrap();
+        if counts.len() > PURGE_THRESHOLD {
+            counts.retain(|_, (_, entry_day)| *entry_day == day);
+        }
+        let entry = counts.entry(key).or_insert((0, day));
+        if entry.1 != day {
+            *entry = (0, day);
+        }
+        if entry.0 >= limit {
+            return Err((day + 1) * SECONDS_PER_DAY - self.utc_offset_secs);
+        }
+        entry.0 += 1;
+        Ok(())
+    }
+
+    /// Give back a paste counted by [`Self::try_consume`] at `now` that was
+    /// never created. A count that has since rolled over to a new day is
+    /// left alone.
+    pub fn refund(&self, ip: &str, onion: bool, now: i64) {
+        let day = self.day(now);
+        let mut counts = self.counts.lock().unwrap();
+        if let Some(entry) = counts.get_mut(&Self::key(ip, onion)) {
+            if entry.1 == day {
+                entry.0 = entry.0.saturating_sub(1);
+            }
+        }
+    }
+
+    fn day(&self, now: i64) -> i64 {
+        (now + self.utc_offset_secs).div_euclid(SECONDS_PER_DAY)
+    }
+
+    fn key(ip: &str, onion: bool) -> String {
+        if onion {
+            format!("onion:{ip}")
         } else {
-            Outcome::Error((Status::TooManyRequests, ()))
+            ip.to_owned()
         }
     }
 }
 
+/// Infallible guard capturing what paste creation needs to charge the daily
+/// quota. The quota is only consumed once a request has passed validation.
+pub struct CreateQuota<'r> {
+    quota: Option<&'r DailyPasteQuota>,
+    client: String,
+    onion: bool,
+}
+
+impl CreateQuota<'_> {
+    /// A quota that never refuses, for authenticated bulk paths such as
+    /// `POST /api/import` that have their own bounds.
+    pub fn exempt() -> CreateQuota<'static> {
+        CreateQuota {
+            quota: None,
+            client: String::new(),
+            onion: false,
+        }
+    }
+
+    /// Count one paste at `now`, see [`DailyPasteQuota::try_consume`]. The
+    /// charge is refunded when dropped unless [`QuotaCharge::keep`] is
+    /// called, so a create that fails after this point costs nothing.
+    pub fn charge(&self, now: i64) -> Result<QuotaCharge<'_>, i64> {
+        if let Some(quota) = self.quota {
+            quota.try_consume(&self.client, self.onion, now)?;
+        }
+        Ok(QuotaCharge {
+            quota: self,
+            now,
+            kept: false,
+        })
+    }
+}
+
+/// One paste counted against the daily quota by [`CreateQuota::charge`].
+#[must_use = "a charge that is dropped is refunded"]
+pub struct QuotaCharge<'a> {
+    quota: &'a CreateQuota<'a>,
+    now: i64,
+    kept: bool,
+}
+
+impl QuotaCharge<'_> {
+    /// The paste was created; keep it counted.
+    pub fn keep(mut self) {
+        self.kept = true;
+    }
+}
+
+impl Drop for QuotaCharge<'_> {
+    fn drop(&mut self) {
+        if let (false, Some(quota)) = (self.kept, self.quota.quota) {
+            quota.refund(&self.quota.client, self.quota.onion, self.now);
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for CreateQuota<'r> {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let onion = match req.guard::<OnionAccess>().await {
+            Outcome::Success(access) => access.is_onion(),
+            _ => false,
+        };
+        Outcome::Success(CreateQuota {
+            quota: req.rocket().state::<DailyPasteQuota>(),
+            client: client_key(req),
+            onion,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -184,9 +504,132 @@ mod tests {
         assert!(limiter.allow_create("10.0.0.2"));
     }
 
+    #[test]
+    fn empty_bucket_reports_wait_for_next_token() {
+        let limiter = PasteRateLimiter::new(Some(2), None);
+        assert!(limiter.check_create("1.2.3.4").is_ok());
+        assert!(limiter.check_create("1.2.3.4").is_ok());
+        let wait = limiter.check_create("1.2.3.4").unwrap_err();
+        // Two tokens per minute refill one every 30 s.
+        assert!(wait > Duration::from_secs(29) && wait <= Duration::from_secs(30));
+    }
+
+    #[rocket::get("/limited")]
+    fn limited_route(_rate: CreateRateLimit) -> &'static str {
+        "ok"
+    }
+
+    /// Statuses for three requests from the same peer claiming to be
+    /// forwarded for `a`, `a` and `b`.
+    fn forwarded_statuses(trust: bool) -> Vec<Status> {
+        use rocket::local::blocking::Client;
+        let mut limiter = PasteRateLimiter::new(Some(1), None);
+        limiter.trust_forwarded_for = trust;
+        let rocket = rocket::build()
+            .manage(limiter)
+            .mount("/", rocket::routes![limited_route]);
+        let client = Client::untracked(rocket).expect("client");
+        ["6.6.6.6, 203.0.113.7", "203.0.113.7", "203.0.113.8"]
+            .into_iter()
+            .map(|forwarded| {
+                client
+                    .get("/limited")
+                    .header(Header::new("X-Forwarded-For", forwarded))
+                    .dispatch()
+                    .status()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn forwarded_for_keys_clients_only_when_trusted() {
+        let limited = Status::TooManyRequests;
+        assert_eq!(
+            forwarded_statuses(true),
+            vec![Status::Ok, limited, Status::Ok]
+        );
+        assert_eq!(
+            forwarded_statuses(false),
+            vec![Status::Ok, limited, limited]
+        );
+    }
+
+    #[test]
+    fn set_limits_toggles_at_runtime() {
+        let limiter = PasteRateLimiter::new(None, None);
+        limiter.set_limits(Some(1), None);
+        assert!(limiter.allow_create("1.2.3.4"));
+        assert!(!limiter.allow_create("1.2.3.4"));
+        limiter.set_limits(None, None);
+        assert!(limiter.allow_create("1.2.3.4"));
+    }
+
+    #[test]
+    fn daily_quota_blocks_until_next_midnight() {
+        let quota = DailyPasteQuota::new(Some(2), None, 0);
+        let noon = 19_000 * SECONDS_PER_DAY + 12 * 3600;
+        assert!(quota.try_consume("1.2.3.4", false, noon).is_ok());
+        assert!(quota.try_consume("1.2.3.4", false, noon + 1).is_ok());
+        let reset_at = quota
+            .try_consume("1.2.3.4", false, noon + 2)
+            .expect_err("third paste exceeds quota");
+        assert_eq!(reset_at, 19_001 * SECONDS_PER_DAY);
+
+        // Other clients are unaffected, and the count resets after midnight.
+        assert!(quota.try_consume("5.6.7.8", false, noon).is_ok());
+        assert!(quota.try_consume("1.2.3.4", false, reset_at - 1).is_err());
+        assert!(quota.try_consume("1.2.3.4", false, reset_at).is_ok());
+    }
+
+    #[test]
+    fn daily_quota_refunds_within_the_same_day() {
+        let quota = DailyPasteQuota::new(Some(1), None, 0);
+        let noon = 19_000 * SECONDS_PER_DAY + 12 * 3600;
+        assert!(quota.try_consume("1.2.3.4", false, noon).is_ok());
+        quota.refund("1.2.3.4", false, noon);
+        assert!(quota.try_consume("1.2.3.4", false, noon + 1).is_ok());
+
+        // A refund for yesterday's paste doesn't free one today.
+        let tomorrow = noon + SECONDS_PER_DAY;
+        assert!(quota.try_consume("1.2.3.4", false, tomorrow).is_ok());
+        quota.refund("1.2.3.4", false, noon);
+        assert!(quota.try_consume("1.2.3.4", false, tomorrow + 1).is_err());
+    }
+
+    #[test]
+    fn daily_quota_honours_utc_offset() {
+        // UTC+02:00: local midnight is 22:00 UTC the previous day.
+        let quota = DailyPasteQuota::new(Some(1), None, 120);
+        let utc_2130 = 19_000 * SECONDS_PER_DAY + 21 * 3600 + 30 * 60;
+        assert!(quota.try_consume("9.9.9.9", false, utc_2130).is_ok());
+        let reset_at = quota
+            .try_consume("9.9.9.9", false, utc_2130 + 60)
+            .expect_err("quota exhausted");
+        assert_eq!(reset_at, 19_000 * SECONDS_PER_DAY + 22 * 3600);
+        assert!(quota.try_consume("9.9.9.9", false, reset_at).is_ok());
+    }
+
+    #[test]
+    fn daily_quota_tracks_onion_separately() {
+        let quota = DailyPasteQuota::new(Some(1), Some(3), 0);
+        let now = 19_000 * SECONDS_PER_DAY;
+        assert!(quota.try_consume("127.0.0.1", false, now).is_ok());
+        assert!(quota.try_consume("127.0.0.1", false, now).is_err());
+        for _ in 0..3 {
+            assert!(quota.try_consume("127.0.0.1", true, now).is_ok());
+        }
+        assert!(quota.try_consume("127.0.0.1", true, now).is_err());
+
+        let disabled = DailyPasteQuota::new(None, None, 0);
+        for _ in 0..100 {
+            assert!(disabled.try_consume("127.0.0.1", false, now).is_ok());
+        }
+    }
+
     #[test]
     fn from_env_disabled_when_unset() {
         std::env::remove_var("COPYPASTE_RATE_LIMIT_CREATES");
+        std::env::remove_var("COPYPASTE_RATE_LIMIT_PER_MINUTE");
         std::env::remove_var("COPYPASTE_RATE_LIMIT_READS");
         let limiter = PasteRateLimiter::from_env();
         for _ in 0..100 {
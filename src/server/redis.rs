@@ -1,16 +1,16 @@
-use std::env;
-use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
-
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::Deserialize;
+use std::env;
+use std::sync::Arc;
 use urlencoding::encode;
 
-use crate::{PersistenceAdapter, PersistenceError, StoredPaste};
+use crate::{remaining_ttl_secs, PersistenceAdapter, PersistenceError, StoredPaste};
 
 const DEFAULT_KEY_PREFIX: &str = "paste:";
 const KEY_PREFIX_ENV: &str = "COPYPASTE_REDIS_KEY_PREFIX";
+/// `COUNT` hint passed to each `SCAN` call in [`RedisPersistenceAdapter::list_ids`].
+const SCAN_BATCH: u32 = 500;
 
 #[derive(Clone)]
 pub struct RedisPersistenceAdapter {
@@ -23,7 +23,10 @@ pub struct RedisPersistenceAdapter {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{EncryptionAlgorithm, PasteFormat, PasteMetadata, StoredContent, StoredPaste};
+    use crate::{
+        EncryptionAlgorithm, KdfKind, PasteFormat, PasteMetadata, StoredContent, StoredPaste,
+        ENVELOPE_V1,
+    };
     use httpmock::prelude::*;
     use regex::Regex;
     use serde_json::json;
@@ -50,11 +53,15 @@ mod tests {
                 ciphertext: "cipher".into(),
                 nonce: "nonce".into(),
                 salt: "salt".into(),
+                kdf: KdfKind::default(),
+                bound: false,
+                envelope_version: ENVELOPE_V1,
             },
             format: PasteFormat::Json,
             created_at: now - 60,
             expires_at: Some(now + 3600),
             burn_after_reading: false,
+            max_reads: None,
             metadata: PasteMetadata::default(),
             bundle: None,
             bundle_parent: None,
@@ -192,6 +199,87 @@ mod tests {
         adapter.delete("roundtrip").await.expect("delete succeeds");
         delete_mock.assert();
     }
+
+    #[tokio::test]
+    async fn list_ids_follows_scan_cursor_until_zero() {
+        let server = MockServer::start();
+        let adapter = test_adapter(&server);
+        let pattern = urlencoding::encode("prefix:*").into_owned();
+
+        let first_path = format!("/scan/0/match/{pattern}/count/{SCAN_BATCH}");
+        let first = server.mock(move |when, then| {
+            when.method(GET)
+                .path(first_path.clone())
+                .header("authorization", "Bearer token");
+            then.status(200).json_body(json!({
+                "result": ["17", ["prefix:alpha", "prefix:beta"]],
+                "error": null
+            }));
+        });
+        let second_path = format!("/scan/17/match/{pattern}/count/{SCAN_BATCH}");
+        let second = server.mock(move |when, then| {
+            when.method(GET)
+                .path(second_path.clone())
+                .header("authorization", "Bearer token");
+            then.status(200).json_body(json!({
+                "result": ["0", ["prefix:gamma", "prefix:beta", "other:delta"]],
+                "error": null
+            }));
+        });
+
+        let ids = adapter.list_ids().await.expect("scan succeeds");
+        assert_eq!(ids, vec!["alpha", "beta", "gamma"]);
+        first.assert();
+        second.assert();
+    }
+
+    #[tokio::test]
+    async fn list_ids_surfaces_scan_errors() {
+        let server = MockServer::start();
+        let adapter = test_adapter(&server);
+        server.mock(|when, then| {
+            when.method(GET).path_contains("/scan/");
+            then.status(500).body("boom");
+        });
+
+        let err = adapter.list_ids().await.expect_err("500 should fail");
+        assert!(err.to_string().contains("SCAN"));
+    }
+
+    #[tokio::test]
+    async fn health_check_pings_redis() {
+        let server = MockServer::start();
+        let adapter = test_adapter(&server);
+        let ping = server.mock(|when, then| {
+            when.method(GET)
+                .path("/ping")
+                .header("authorization", "Bearer token");
+            then.status(200)
+                .json_body(json!({"result": "PONG", "error": null}));
+        });
+
+        adapter.health_check().await.expect("PONG is healthy");
+        ping.assert();
+    }
+
+    #[tokio::test]
+    async fn health_check_fails_when_redis_errors() {
+        let server = MockServer::start();
+        let adapter = test_adapter(&server);
+        server.mock(|when, then| {
+            when.method(GET).path("/ping");
+            then.status(503).body("down");
+        });
+
+        let err = adapter.health_check().await.expect_err("503 is unhealthy");
+        assert!(matches!(err, PersistenceError::Unavailable(_)));
+    }
+
+    #[test]
+    fn escape_glob_quotes_metacharacters() {
+        assert_eq!(escape_glob("paste:"), "paste:");
+        assert_eq!(escape_glob("a*b?[c]"), r"a\*b\?\[c\]");
+    }
 }
 
 #[derive(Deserialize)]
@@ -209,14 +297,21 @@ impl RedisPersistenceAdapter {
         let key_prefix =
             env::var(KEY_PREFIX_ENV).unwrap_or_else(|_| DEFAULT_KEY_PREFIX.to_string());
 
-        let adapter = RedisPersistenceAdapter {
-            client: Client::new(),
-            base_url: base_url.trim_end_matches('/').to_string(),
-            token,
-            key_prefix,
-        };
+        Ok(Arc::new(Self::new(base_url, token, key_prefix)))
+    }
 
-        Ok(Arc::new(adapter))
+    /// Talk to the Upstash REST endpoint at `base_url`.
+    pub fn new(
+        base_url: impl Into<String>,
+        token: impl Into<String>,
+        key_prefix: impl Into<String>,
+    ) -> Self {
+        RedisPersistenceAdapter {
+            client: Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            token: token.into(),
+            key_prefix: key_prefix.into(),
+        }
     }
 
     fn key(&self, id: &str) -> String {
@@ -298,6 +393,67 @@ impl RedisPersistenceAdapter {
     async fn delete_key(&self, key: &str) -> Result<(), PersistenceError> {
         self.post_command("del", key, &[]).await
     }
+
+    /// One `SCAN` step over keys under the prefix; returns the next cursor
+    /// (`"0"` once the iteration is complete) and the keys it yielded.
+    async fn scan_page(&self, cursor: &str) -> Result<(String, Vec<String>), PersistenceError> {
+        let pattern = format!("{}*", escape_glob(&self.key_prefix));
+        let url = format!(
+            "{}/scan/{}/match/{}/count/{}",
+            self.base_url,
+            encode(cursor),
+            encode(&pattern),
+            SCAN_BATCH
+        );
+        let scan_error = |message: String| PersistenceError::Load(pattern.clone(), message);
+
+        let response = self
+            .client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", self.token))
+            .send()
+            .await
+            .map_err(|error| scan_error(error.to_string()))?;
+
+        if !response.status().is_success() {
+            let text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "<empty>".to_string());
+            return Err(scan_error(format!("Redis SCAN failed: {}", text)));
+        }
+
+        let body: RedisResponse<(serde_json::Value, Vec<String>)> = response
+            .json()
+            .await
+            .map_err(|error| scan_error(error.to_string()))?;
+
+        if let Some(error) = body.error {
+            return Err(scan_error(error));
+        }
+
+        let (cursor, keys) = body
+            .result
+            .ok_or_else(|| scan_error("Redis SCAN returned no result".to_string()))?;
+        // Upstash reports the cursor as a string; accept a bare number too.
+        let cursor = match cursor {
+            serde_json::Value::String(value) => value,
+            other => other.to_string(),
+        };
+        Ok((cursor, keys))
+    }
+}
+
+/// Escape Redis glob metacharacters so a key prefix matches literally.
+fn escape_glob(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        if matches!(ch, '*' | '?' | '[' | ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
 }
 
 #[async_trait]
@@ -307,20 +463,7 @@ impl PersistenceAdapter for RedisPersistenceAdapter {
         let serialized = serde_json::to_string(paste)
             .map_err(|error| PersistenceError::Save(id.to_string(), error.to_string()))?;
 
-        let ttl_seconds = paste.expires_at.and_then(|expires_at| {
-            let now = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .map(|d| d.as_secs() as i64)
-                .unwrap_or_default();
-            let remaining = expires_at - now;
-            if remaining > 0 {
-                Some(remaining as u64)
-            } else {
-                None
-            }
-        });
-
-        if let Some(ttl) = ttl_seconds {
+        if let Some(ttl) = remaining_ttl_secs(paste) {
             self.post_command("setex", &key, &[&ttl.to_string(), &serialized])
                 .await
         } else {
@@ -343,4 +486,58 @@ impl PersistenceAdapter for RedisPersistenceAdapter {
         let key = self.key(id);
         self.delete_key(&key).await
     }
+
+    async fn health_check(&self) -> Result<(), PersistenceError> {
+        let response = self
+            .client
+            .get(format!("{}/ping", self.base_url))
+            .header("Authorization", format!("Bearer {}", self.token))
+            .send()
+            .await
+            .map_err(|error| PersistenceError::Unavailable(error.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(PersistenceError::Unavailable(format!(
+                "Redis PING returned {}",
+                response.status()
+            )));
+        }
+
+        let body: RedisResponse<String> = response
+            .json()
+            .await
+            .map_err(|error| PersistenceError::Unavailable(error.to_string()))?;
+        match (body.error, body.result) {
+            (Some(error), _) => Err(PersistenceError::Unavailable(error)),
+            (None, Some(reply)) if reply.eq_ignore_ascii_case("PONG") => Ok(()),
+            (None, other) => Err(PersistenceError::Unavailable(format!(
+                "unexpected Redis PING reply: {other:?}"
+            ))),
+        }
+    }
+
+    async fn list_ids(&self) -> Result<Vec<String>, PersistenceError> {
+        let mut ids = Vec::new();
+        let mut cursor = "0".to_string();
+        loop {
+            let (next, keys) = self.scan_page(&cursor).await?;
+            ids.extend(
+                keys.iter()
+                    .filter_map(|key| key.strip_prefix(&self.key_prefix))
+                    .map(str::to_string),
+            );
+            if next == "0" {
+                break;
+            }
+            cursor = next;
+        }
+        // SCAN may return a key more than once across pages.
+        ids.sort();
+        ids.dedup();
+        Ok(ids)
+    }
+
+    fn name(&self) -> &'static str {
+        "redis"
+    }
 }
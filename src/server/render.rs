@@ -1,21 +1,65 @@
 use crate::{
-    AttestationRequirement, EncryptionAlgorithm, PasteFormat, PasteMetadata, PersistenceLocator,
-    StoredContent, WebhookProvider,
+    content_digest, AttestationRequirement, EncryptionAlgorithm, PasteFormat, PasteMetadata,
+    PersistenceLocator, StoredContent, WebhookProvider,
 };
-use html_escape::encode_safe;
-use pulldown_cmark::{html, Options, Parser};
+use base64::{engine::general_purpose::STANDARD as BASE64_STANDARD, Engine};
+use html_escape::{encode_double_quoted_attribute, encode_safe};
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag};
 
-use super::time::format_timestamp;
+use super::highlight::highlight_code;
+use super::time::{format_iso8601, format_timestamp};
 
-pub fn layout(title: &str, body: String) -> String {
+/// Colour scheme picked with `?theme=dark|light`. `Auto` leaves it to the
+/// browser's `prefers-color-scheme`, falling back to light.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Theme {
+    #[default]
+    Auto,
+    Light,
+    Dark,
+}
+
+impl Theme {
+    /// Parse the `theme` query parameter; anything unrecognised is `Auto`.
+    pub fn from_query(value: Option<&str>) -> Self {
+        match value.map(|v| v.trim().to_ascii_lowercase()).as_deref() {
+            Some("dark") => Theme::Dark,
+            Some("light") => Theme::Light,
+            _ => Theme::Auto,
+        }
+    }
+
+    fn name(self) -> Option<&'static str> {
+        match self {
+            Theme::Auto => None,
+            Theme::Light => Some("light"),
+            Theme::Dark => Some("dark"),
+        }
+    }
+
+    /// Hidden input that carries an explicit theme through prompt forms.
+    fn form_field(self) -> String {
+        self.name()
+            .map(|name| format!("<input type=\"hidden\" name=\"theme\" value=\"{name}\" />"))
+            .unwrap_or_default()
+    }
+}
+
+pub fn layout(title: &str, theme: Theme, body: String) -> String {
+    let (theme_attr, color_scheme) = match theme.name() {
+        Some(name) => (format!(" data-theme=\"{name}\""), name),
+        None => (String::new(), "light dark"),
+    };
     format!(
         r#"<!DOCTYPE html>
-<html lang="en">
+<html lang="en"{theme_attr}>
 <head>
     <meta charset="utf-8" />
     <meta name="viewport" content="width=device-width, initial-scale=1" />
+    <meta name="color-scheme" content="{color_scheme}" />
     <title>{title}</title>
     <link rel="stylesheet" href="/static/view.css" />
+    <script src="/static/view.js" defer></script>
 </head>
 <body>
     <header>
@@ -37,35 +81,23 @@ pub fn render_paste_view(
     paste: &StoredPasteView,
     text: &str,
     bundle_html: Option<String>,
+    sort_keys: bool,
+    theme: Theme,
 ) -> String {
-    let rendered_body = match paste.format {
-        PasteFormat::PlainText => format_plain(text),
-        PasteFormat::Markdown => format_markdown(text),
-        PasteFormat::Json => format_json(text),
-        PasteFormat::Code
-        | PasteFormat::Javascript
-        | PasteFormat::Typescript
-        | PasteFormat::Python
-        | PasteFormat::Rust
-        | PasteFormat::Go
-        | PasteFormat::Cpp
-        | PasteFormat::Kotlin
-        | PasteFormat::Java
-        | PasteFormat::Csharp
-        | PasteFormat::Php
-        | PasteFormat::Ruby
-        | PasteFormat::Bash
-        | PasteFormat::Yaml
-        | PasteFormat::Sql
-        | PasteFormat::Swift
-        | PasteFormat::Html
-        | PasteFormat::Css => format_code(text),
+    let binary = paste
+        .metadata
+        .content_encoding
+        .and_then(|_| BASE64_STANDARD.decode(text).ok());
+    let rendered_body = match &binary {
+        Some(bytes) => format_binary(id, bytes),
+        None => render_text(text, paste.format, sort_keys),
     };
 
-    let created = format_timestamp(paste.created_at);
+    let created = time_element(paste.created_at, "");
+    let size = format_size(binary.as_ref().map_or(text.len(), Vec::len));
     let retention = paste
         .expires_at
-        .map(format_timestamp)
+        .map(|ts| time_element(ts, &format!(r#" data-expires="{ts}""#)))
         .unwrap_or_else(|| "No expiry".to_string());
 
     let encryption = match paste.content {
@@ -77,6 +109,7 @@ pub fn render_paste_view(
             EncryptionAlgorithm::ChaCha20Poly1305 => "ChaCha20-Poly1305".to_string(),
             EncryptionAlgorithm::XChaCha20Poly1305 => "XChaCha20-Poly1305".to_string(),
             EncryptionAlgorithm::KyberHybridAes256Gcm => "Kyber Hybrid AES-256-GCM".to_string(),
+            EncryptionAlgorithm::Age => "age (X25519)".to_string(),
         },
     };
 
@@ -88,10 +121,17 @@ pub fn render_paste_view(
 
     let burn_note = if paste.burn_after_reading {
         r#"<p class="burn-note">This paste was configured to burn after reading. The link is now invalid for future visits.</p>"#.to_string()
+    } else if paste.reads_remaining == Some(0) {
+        r#"<p class="burn-note">This was the last allowed read of this paste. The link is now invalid for future visits.</p>"#.to_string()
     } else {
         String::new()
     };
 
+    let reads_remaining = paste
+        .reads_remaining
+        .map(|remaining| format!("\n    <div><strong>Reads remaining:</strong> {remaining}</div>"))
+        .unwrap_or_default();
+
     let time_lock = match (paste.metadata.not_before, paste.metadata.not_after) {
         (None, None) => "None".to_string(),
         (Some(start), Some(end)) => {
@@ -103,11 +143,20 @@ pub fn render_paste_view(
 
     let attestation = match paste.metadata.attestation {
         None => "None".to_string(),
-        Some(AttestationRequirement::Totp { ref issuer, .. }) => issuer
-            .as_ref()
-            .map(|iss| format!("TOTP ({iss})"))
-            .unwrap_or_else(|| "TOTP".to_string()),
-        Some(AttestationRequirement::SharedSecret { .. }) => "Shared secret".to_string(),
+        Some(ref requirement) => requirement
+            .factors()
+            .iter()
+            .map(|factor| match factor {
+                AttestationRequirement::Totp { issuer, .. } => issuer
+                    .as_ref()
+                    .map(|iss| format!("TOTP ({iss})"))
+                    .unwrap_or_else(|| "TOTP".to_string()),
+                AttestationRequirement::SharedSecret { .. } => "Shared secret".to_string(),
+                AttestationRequirement::Webauthn { rp_id, .. } => format!("Passkey ({rp_id})"),
+                AttestationRequirement::All { .. } => "All factors".to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(" + "),
     };
 
     let persistence = paste
@@ -145,16 +194,30 @@ pub fn render_paste_view(
 
     let bundle_section = bundle_html.unwrap_or_default();
 
+    // Encrypted pastes carry no stored digest; this reader has the key, so
+    // show the digest of what they decrypted.
+    let digest = match paste.content {
+        StoredContent::Plain { .. } => paste
+            .metadata
+            .content_sha256
+            .clone()
+            .unwrap_or_else(|| "Not recorded".to_string()),
+        _ => content_digest(text),
+    };
+
     layout(
         "copypaste.fyi | View paste",
+        theme,
         format!(
             r#"<section class="meta">
     <div><strong>ID:</strong> {id}</div>
     <div><strong>Format:</strong> {format}</div>
     <div><strong>Created:</strong> {created}</div>
+    <div><strong>Size:</strong> {size}</div>
+    <div><strong>SHA-256:</strong> <code>{digest}</code></div>
     <div><strong>Retention:</strong> {retention}</div>
     <div><strong>Encryption:</strong> {encryption}</div>
-    <div><strong>Burn after reading:</strong> {burn}</div>
+    <div><strong>Burn after reading:</strong> {burn}</div>{reads_remaining}
     <div><strong>Time lock:</strong> {time_lock}</div>
     <div><strong>Attestation:</strong> {attestation}</div>
     <div><strong>Persistence:</strong> {persistence}</div>
@@ -169,10 +232,13 @@ pub fn render_paste_view(
 "#,
             id = encode_safe(id),
             format = encode_safe(&format!("{:?}", paste.format)),
-            created = encode_safe(&created),
-            retention = encode_safe(&retention),
+            created = created,
+            size = size,
+            digest = encode_safe(&digest),
+            retention = retention,
             encryption = encryption,
             burn = burn_status,
+            reads_remaining = reads_remaining,
             burn_note = burn_note,
             time_lock = encode_safe(&time_lock),
             attestation = encode_safe(&attestation),
@@ -185,7 +251,17 @@ pub fn render_paste_view(
     )
 }
 
-pub fn render_time_locked(state: super::time::TimeLockState) -> String {
+/// A `<time>` for `ts` showing the UTC string; `view.js` rewrites it in the
+/// viewer's locale and counts down to any `data-expires` epoch.
+fn time_element(ts: i64, attrs: &str) -> String {
+    let text = encode_safe(&format_timestamp(ts)).into_owned();
+    match format_iso8601(ts) {
+        Some(iso) => format!(r#"<time datetime="{iso}"{attrs}>{text}</time>"#),
+        None => text,
+    }
+}
+
+pub fn render_time_locked(state: super::time::TimeLockState, theme: Theme) -> String {
     let (heading, message) = match state {
         super::time::TimeLockState::TooEarly(ts) => (
             "Time-locked paste",
@@ -205,6 +281,7 @@ pub fn render_time_locked(state: super::time::TimeLockState) -> String {
 
     layout(
         "copypaste.fyi | Locked",
+        theme,
         format!(
             r#"<section class="notice">
     <h2>{heading}</h2>
@@ -218,14 +295,12 @@ pub fn render_time_locked(state: super::time::TimeLockState) -> String {
     )
 }
 
-pub fn render_attestation_prompt(
-    id: &str,
-    needs_key_field: bool,
-    existing_key: Option<&str>,
-    requirement: &AttestationRequirement,
-    invalid: bool,
-) -> String {
-    let (prompt_label, field_name, field_type, helper) = match requirement {
+/// Label, query field, input type and helper text prompting for one
+/// attestation factor.
+fn attestation_field(
+    factor: &AttestationRequirement,
+) -> (String, &'static str, &'static str, &'static str) {
+    match factor {
         AttestationRequirement::Totp { issuer, .. } => (
             issuer
                 .as_ref()
@@ -235,15 +310,42 @@ pub fn render_attestation_prompt(
             "text",
             "Enter the current code from your authenticator.",
         ),
-        AttestationRequirement::SharedSecret { .. } => (
+        AttestationRequirement::Webauthn { .. } => (
+            "Passkey grant".to_string(),
+            "webauthn",
+            "password",
+            "Confirm with your passkey via POST /api/pastes/<id>/webauthn/challenge and /verify, then enter the grant token it returns.",
+        ),
+        // `All` never nests, so only a single requirement reaches here.
+        AttestationRequirement::SharedSecret { .. } | AttestationRequirement::All { .. } => (
             "Shared secret".to_string(),
             "attest",
             "password",
             "Provide the shared secret agreed upon with the sender.",
         ),
+    }
+}
+
+pub fn render_attestation_prompt(
+    id: &str,
+    needs_key_field: bool,
+    state: &str,
+    requirement: &AttestationRequirement,
+    invalid: bool,
+    theme: Theme,
+) -> String {
+    let factors = requirement.factors();
+    let helper = match factors {
+        [single] => attestation_field(single).3,
+        _ => "This paste needs every factor below. Fill them all in together.",
     };
 
-    let mut form_inputs = String::new();
+    // The key is never echoed back into the page, even when the viewer
+    // arrived with one: they re-enter it here instead.
+    let mut form_inputs = format!(
+        "        <input type=\"hidden\" name=\"state\" value=\"{}\" />\n",
+        encode_safe(state)
+    );
 
     if needs_key_field {
         form_inputs.push_str(
@@ -251,32 +353,30 @@ pub fn render_attestation_prompt(
         <input type="password" name="key" id="key" required />
 "#,
         );
-    } else if let Some(key) = existing_key {
-        let escaped = encode_safe(key);
+    }
+
+    for factor in factors {
+        let (prompt_label, field_name, field_type, _) = attestation_field(factor);
         form_inputs.push_str(&format!(
-            "        <input type=\"hidden\" name=\"key\" value=\"{escaped}\" />\n"
+            "        <label for=\"{field_name}\">{prompt_label}</label>\n",
+            field_name = field_name,
+            prompt_label = encode_safe(&prompt_label),
         ));
-    }
 
-    form_inputs.push_str(&format!(
-        "        <label for=\"{field_name}\">{prompt_label}</label>\n",
-        field_name = field_name,
-        prompt_label = encode_safe(&prompt_label),
-    ));
+        let mut field_attributes = String::new();
+        if matches!(factor, AttestationRequirement::Totp { .. }) {
+            field_attributes.push_str(" pattern=\"[0-9]{6,10}\"");
+            field_attributes.push_str(" inputmode=\"numeric\"");
+        }
 
-    let mut field_attributes = String::new();
-    if matches!(requirement, AttestationRequirement::Totp { .. }) {
-        field_attributes.push_str(" pattern=\"[0-9]{6,10}\"");
-        field_attributes.push_str(" inputmode=\"numeric\"");
+        form_inputs.push_str(&format!(
+            "        <input type=\"{field_type}\" name=\"{field_name}\" id=\"{field_name}\" required{attrs} />\n",
+            field_type = field_type,
+            field_name = field_name,
+            attrs = field_attributes,
+        ));
     }
 
-    form_inputs.push_str(&format!(
-        "        <input type=\"{field_type}\" name=\"{field_name}\" id=\"{field_name}\" required{attrs} />\n",
-        field_type = field_type,
-        field_name = field_name,
-        attrs = field_attributes,
-    ));
-
     let error = if invalid {
         "<p class=\"error\">Verification failed. Double-check your entry and try again.</p>\n"
             .to_string()
@@ -286,13 +386,15 @@ pub fn render_attestation_prompt(
 
     layout(
         "copypaste.fyi | Verification required",
+        theme,
         format!(
             r#"<section class="notice">
     <h2>Additional verification required</h2>
     <p>{helper}</p>
     {error}
     <form method="get" action="/{id}">
-{inputs}        <button type="submit">Continue</button>
+{inputs}        {theme_field}
+        <button type="submit">Continue</button>
     </form>
 </section>
 "#,
@@ -300,13 +402,15 @@ pub fn render_attestation_prompt(
             error = error,
             inputs = form_inputs,
             id = encode_safe(id),
+            theme_field = theme.form_field(),
         ),
     )
 }
 
-pub fn render_key_prompt(id: &str) -> String {
+pub fn render_key_prompt(id: &str, theme: Theme) -> String {
     layout(
         "copypaste.fyi | Encrypted paste",
+        theme,
         format!(
             r#"<section class="notice">
     <h2>This paste is encrypted</h2>
@@ -314,18 +418,21 @@ pub fn render_key_prompt(id: &str) -> String {
     <form method="get" action="/{id}">
         <label for="key">Encryption key</label>
         <input type="password" name="key" id="key" required />
+        {theme_field}
         <button type="submit">Decrypt</button>
     </form>
 </section>
 "#,
             id = encode_safe(id),
+            theme_field = theme.form_field(),
         ),
     )
 }
 
-pub fn render_invalid_key(id: &str) -> String {
+pub fn render_invalid_key(id: &str, theme: Theme) -> String {
     layout(
         "copypaste.fyi | Invalid key",
+        theme,
         format!(
             r#"<section class="notice error">
     <h2>Invalid encryption key</h2>
@@ -333,18 +440,21 @@ pub fn render_invalid_key(id: &str) -> String {
     <form method="get" action="/{id}">
         <label for="key">Try again</label>
         <input type="password" name="key" id="key" required />
+        {theme_field}
         <button type="submit">Decrypt</button>
     </form>
 </section>
 "#,
             id = encode_safe(id),
+            theme_field = theme.form_field(),
         ),
     )
 }
 
-pub fn render_expired(id: &str) -> String {
+pub fn render_expired(id: &str, theme: Theme) -> String {
     layout(
         "copypaste.fyi | Paste expired",
+        theme,
         format!(
             r#"<section class="notice error">
     <h2>Paste expired</h2>
@@ -362,32 +472,277 @@ pub struct StoredPasteView<'a> {
     pub created_at: i64,
     pub expires_at: Option<i64>,
     pub burn_after_reading: bool,
+    /// Reads left after this view, for pastes with a read limit.
+    pub reads_remaining: Option<u32>,
     pub metadata: &'a PasteMetadata,
 }
 
+/// The body of a text paste in its format's rendering.
+fn render_text(text: &str, format: PasteFormat, sort_keys: bool) -> String {
+    match format {
+        PasteFormat::PlainText => format_plain(text),
+        PasteFormat::Markdown => format_markdown(text),
+        PasteFormat::Json => format_json(text, sort_keys),
+        PasteFormat::Toml => format_toml(text),
+        PasteFormat::Diff => format_diff(text),
+        PasteFormat::Csv => format_csv(text),
+        PasteFormat::Tsv => format_tsv(text),
+        PasteFormat::Code
+        | PasteFormat::Javascript
+        | PasteFormat::Typescript
+        | PasteFormat::Python
+        | PasteFormat::Rust
+        | PasteFormat::Go
+        | PasteFormat::Cpp
+        | PasteFormat::Kotlin
+        | PasteFormat::Java
+        | PasteFormat::Csharp
+        | PasteFormat::Php
+        | PasteFormat::Ruby
+        | PasteFormat::Bash
+        | PasteFormat::Yaml
+        | PasteFormat::Sql
+        | PasteFormat::Swift
+        | PasteFormat::Html
+        | PasteFormat::Css => highlight_code(text, format),
+    }
+}
+
+/// A binary paste isn't rendered; it gets a download link carrying the bytes
+/// as a data URI, which keeps working after a burn-after-reading view.
+fn format_binary(id: &str, bytes: &[u8]) -> String {
+    format!(
+        r#"<p class="binary">Binary content ({}) — <a href="data:application/octet-stream;base64,{}" download="{}.bin">download</a></p>"#,
+        format_size(bytes.len()),
+        BASE64_STANDARD.encode(bytes),
+        encode_double_quoted_attribute(id)
+    )
+}
+
+/// `"512 B"`, `"1.5 KiB"`, `"10.0 MiB"`.
+pub fn format_size(bytes: usize) -> String {
+    const KIB: f64 = 1024.0;
+    let value = bytes as f64;
+    if value < KIB {
+        format!("{bytes} B")
+    } else if value < KIB * KIB {
+        format!("{:.1} KiB", value / KIB)
+    } else {
+        format!("{:.1} MiB", value / (KIB * KIB))
+    }
+}
+
 pub fn format_plain(text: &str) -> String {
-    format!("<pre>{}</pre>", encode_safe(text))
+    let (lines, trailing_newline) = source_lines(text);
+    format!(
+        "<pre>{}</pre>",
+        number_lines(lines.map(encode_safe), trailing_newline)
+    )
+}
+
+/// Split `text` into lines without their `\n`, plus whether it ended with
+/// one. An empty paste has no lines.
+pub fn source_lines(text: &str) -> (impl Iterator<Item = &str>, bool) {
+    let (body, trailing_newline) = match text.strip_suffix('\n') {
+        Some(body) => (body, true),
+        None => (text, false),
+    };
+    let lines = (!text.is_empty())
+        .then(|| body.split('\n'))
+        .into_iter()
+        .flatten();
+    (lines, trailing_newline)
 }
 
+/// Join already-escaped HTML lines, wrapping line `n` in a `#L{n}` anchor
+/// target with a gutter link. The number is drawn by CSS from `data-line`,
+/// so copying the text never picks it up.
+pub fn number_lines<S: AsRef<str>>(
+    lines: impl IntoIterator<Item = S>,
+    trailing_newline: bool,
+) -> String {
+    let mut html = String::new();
+    for (idx, line) in lines.into_iter().enumerate() {
+        let n = idx + 1;
+        if idx > 0 {
+            html.push('\n');
+        }
+        html.push_str(&format!(
+            r##"<span class="line" id="L{n}"><a class="ln" href="#L{n}" data-line="{n}"></a>{}</span>"##,
+            line.as_ref()
+        ));
+    }
+    if trailing_newline {
+        html.push('\n');
+    }
+    html
+}
+
+/// Render markdown as HTML, treating the paste as untrusted.
+///
+/// Raw HTML in the source is emitted as escaped text and script-capable
+/// link/image targets are replaced with `#`, so a markdown paste can't run
+/// script on our origin.
 pub fn format_markdown(text: &str) -> String {
     let mut options = Options::empty();
     options.insert(Options::ENABLE_STRIKETHROUGH);
     options.insert(Options::ENABLE_TABLES);
     options.insert(Options::ENABLE_FOOTNOTES);
-    let parser = Parser::new_ext(text, options);
+    let parser = Parser::new_ext(text, options).map(|event| match event {
+        Event::Html(raw) | Event::InlineHtml(raw) => Event::Text(raw),
+        Event::Start(Tag::Link {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Link {
+            link_type,
+            dest_url: safe_url(dest_url),
+            title,
+            id,
+        }),
+        Event::Start(Tag::Image {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Image {
+            link_type,
+            dest_url: safe_url(dest_url),
+            title,
+            id,
+        }),
+        other => other,
+    });
     let mut html_output = String::new();
     html::push_html(&mut html_output, parser);
     html_output
 }
 
+fn safe_url(url: CowStr<'_>) -> CowStr<'_> {
+    // Browsers ignore embedded whitespace/control chars when parsing schemes.
+    let normalized: String = url
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && !c.is_ascii_control())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    let dangerous = normalized
+        .split_once(':')
+        .is_some_and(|(scheme, _)| matches!(scheme, "javascript" | "vbscript" | "data"));
+    if dangerous {
+        CowStr::Borrowed("#")
+    } else {
+        url
+    }
+}
+
 pub fn format_code(text: &str) -> String {
-    format!("<pre><code>{}</code></pre>", encode_safe(text))
+    let (lines, trailing_newline) = source_lines(text);
+    format!(
+        "<pre><code>{}</code></pre>",
+        number_lines(lines.map(encode_safe), trailing_newline)
+    )
+}
+
+/// Render unified diff output with added, removed, hunk-header and
+/// file-header lines wrapped in classed spans; other lines stay neutral.
+pub fn format_diff(text: &str) -> String {
+    let (lines, trailing_newline) = source_lines(text);
+    let lines = lines.map(|line| {
+        let class = if line.starts_with("+++ ") || line.starts_with("--- ") {
+            Some("diff-file")
+        } else if line.starts_with('+') {
+            Some("diff-add")
+        } else if line.starts_with('-') {
+            Some("diff-del")
+        } else if line.starts_with("@@") {
+            Some("diff-hunk")
+        } else {
+            None
+        };
+        match class {
+            Some(class) => format!(r#"<span class="{class}">{}</span>"#, encode_safe(line)),
+            None => encode_safe(line).into_owned(),
+        }
+    });
+    format!(
+        r#"<pre><code class="language-diff">{}</code></pre>"#,
+        number_lines(lines, trailing_newline)
+    )
+}
+
+pub fn format_csv(text: &str) -> String {
+    format_delimited(text, b',')
+}
+
+pub fn format_tsv(text: &str) -> String {
+    format_delimited(text, b'\t')
+}
+
+/// Render delimited rows as a table with the first row as headers. Input
+/// that doesn't parse, or whose rows differ in width, falls back to
+/// [`format_plain`].
+fn format_delimited(text: &str, delimiter: u8) -> String {
+    let rows: Result<Vec<csv::StringRecord>, _> = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .from_reader(text.as_bytes())
+        .records()
+        .collect();
+    let rows = match rows {
+        Ok(rows) if !rows.is_empty() => rows,
+        _ => return format_plain(text),
+    };
+
+    let cells = |row: &csv::StringRecord, tag: &str| {
+        row.iter()
+            .map(|cell| format!("<{tag}>{}</{tag}>", encode_safe(cell)))
+            .collect::<String>()
+    };
+    let (header, body) = rows.split_first().expect("rows is non-empty");
+    let body: String = body
+        .iter()
+        .map(|row| format!("<tr>{}</tr>\n", cells(row, "td")))
+        .collect();
+    format!(
+        "<div class=\"table-scroll\"><table class=\"delimited\">\n<thead><tr>{}</tr></thead>\n<tbody>\n{body}</tbody>\n</table></div>",
+        cells(header, "th")
+    )
 }
 
-pub fn format_json(text: &str) -> String {
+/// Pretty-print and highlight valid JSON, keeping keys in the order they
+/// were written unless `sort_keys` asks for them sorted at every level.
+/// Invalid input is shown as-is.
+pub fn format_json(text: &str, sort_keys: bool) -> String {
     match serde_json::from_str::<serde_json::Value>(text) {
-        Ok(value) => {
-            format_code(&serde_json::to_string_pretty(&value).unwrap_or_else(|_| text.to_string()))
+        Ok(mut value) => {
+            if sort_keys {
+                sort_json_keys(&mut value);
+            }
+            let pretty = serde_json::to_string_pretty(&value).unwrap_or_else(|_| text.to_string());
+            highlight_code(&pretty, PasteFormat::Json)
+        }
+        Err(_) => format_code(text),
+    }
+}
+
+fn sort_json_keys(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            map.sort_keys();
+            map.values_mut().for_each(sort_json_keys);
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(sort_json_keys),
+        _ => {}
+    }
+}
+
+/// Re-serialize valid TOML in normalized form, like [`format_json`]; invalid
+/// input is shown as-is.
+pub fn format_toml(text: &str) -> String {
+    match text.parse::<toml::Table>() {
+        Ok(table) => {
+            format_code(&toml::to_string_pretty(&table).unwrap_or_else(|_| text.to_string()))
         }
         Err(_) => format_code(text),
     }
@@ -398,7 +753,8 @@ mod tests {
     use super::*;
     use crate::{
         server::time::TimeLockState, AttestationRequirement, BundleMetadata, BundlePointer,
-        EncryptionAlgorithm, PasteMetadata, StoredContent, WebhookConfig, WebhookProvider,
+        EncryptionAlgorithm, KdfKind, PasteMetadata, StoredContent, TotpAlgorithm, WebhookConfig,
+        WebhookProvider, ENVELOPE_V1,
     };
 
     fn sample_metadata() -> PasteMetadata {
@@ -419,6 +775,7 @@ mod tests {
                 step: 30,
                 allowed_drift: 1,
                 issuer: Some("Test Issuer".to_string()),
+                algorithm: TotpAlgorithm::Sha1,
             }),
             persistence: Some(PersistenceLocator::S3 {
                 bucket: "bucket".to_string(),
@@ -429,21 +786,42 @@ mod tests {
                 provider: Some(WebhookProvider::Slack),
                 view_template: None,
                 burn_template: None,
+                secret: None,
             }),
             tor_access_only: true,
             owner_pubkey_hash: Some("owner_hash".to_string()),
             access_count: 3,
             workspace: None,
+            content_sha256: None,
+            anchored_hash: None,
+            content_encoding: None,
         }
     }
 
     #[test]
     fn layout_wraps_title_and_body() {
-        let html = layout("Title", "<p>Content</p>".to_string());
+        let html = layout("Title", Theme::Auto, "<p>Content</p>".to_string());
         assert!(html.contains("<title>Title</title>"));
         assert!(html.contains("<p>Content</p>"));
     }
 
+    #[test]
+    fn layout_applies_requested_theme() {
+        let dark = render_key_prompt("abc", Theme::from_query(Some("dark")));
+        assert!(dark.contains(r#"<html lang="en" data-theme="dark">"#));
+        assert!(dark.contains(r#"name="theme" value="dark""#));
+
+        let light = layout("T", Theme::from_query(Some("LIGHT")), String::new());
+        assert!(light.contains(r#"data-theme="light""#));
+
+        // No choice: light unless the browser prefers dark.
+        let default = render_expired("id", Theme::from_query(None));
+        assert!(default.contains(r#"<html lang="en">"#));
+        assert!(!default.contains("data-theme"));
+        assert!(default.contains(r#"content="light dark""#));
+        assert_eq!(Theme::from_query(Some("neon")), Theme::Auto);
+    }
+
     #[test]
     fn render_paste_view_formats_metadata() {
         let content = StoredContent::Encrypted {
@@ -451,6 +829,9 @@ mod tests {
             ciphertext: "cipher".to_string(),
             nonce: "nonce".to_string(),
             salt: "salt".to_string(),
+            kdf: KdfKind::default(),
+            bound: false,
+            envelope_version: ENVELOPE_V1,
         };
         let metadata = sample_metadata();
         let view = StoredPasteView {
@@ -459,11 +840,19 @@ mod tests {
             created_at: 1,
             expires_at: Some(2),
             burn_after_reading: true,
+            reads_remaining: None,
             metadata: &metadata,
         };
         let bundle_html = Some("<div class=\"bundle\">bundle</div>".to_string());
 
-        let html = render_paste_view("paste-id", &view, "# Heading", bundle_html);
+        let html = render_paste_view(
+            "paste-id",
+            &view,
+            "# Heading",
+            bundle_html,
+            false,
+            Theme::Auto,
+        );
 
         assert!(html.contains("ChaCha20-Poly1305"));
         assert!(html.contains("bundle"));
@@ -472,11 +861,78 @@ mod tests {
         assert!(html.contains("Slack"));
     }
 
+    #[test]
+    fn render_paste_view_offers_binary_content_as_download() {
+        let text = BASE64_STANDARD.encode([0xff, 0x00, 0xfe, b'<']);
+        let content = StoredContent::Plain { text: text.clone() };
+        let metadata = PasteMetadata {
+            content_encoding: Some(crate::ContentEncoding::Base64),
+            ..Default::default()
+        };
+        let view = StoredPasteView {
+            content: &content,
+            format: PasteFormat::PlainText,
+            created_at: 0,
+            expires_at: None,
+            burn_after_reading: false,
+            reads_remaining: None,
+            metadata: &metadata,
+        };
+        let html = render_paste_view("bin-id", &view, &text, None, false, Theme::Auto);
+        assert!(html.contains("Binary content (4 B)"));
+        assert!(html.contains(&format!(
+            r#"href="data:application/octet-stream;base64,{text}" download="bin-id.bin""#
+        )));
+        assert!(html.contains("<strong>Size:</strong> 4 B"));
+        assert!(!html.contains("<pre>"));
+    }
+
+    #[test]
+    fn render_paste_view_shows_reads_remaining() {
+        let content = StoredContent::Plain {
+            text: "hello".to_string(),
+        };
+        let metadata = PasteMetadata {
+            content_sha256: Some("2cf24dba5fb0a30e".to_string()),
+            ..Default::default()
+        };
+        let mut view = StoredPasteView {
+            content: &content,
+            format: PasteFormat::PlainText,
+            created_at: 0,
+            expires_at: None,
+            burn_after_reading: false,
+            reads_remaining: Some(2),
+            metadata: &metadata,
+        };
+        let html = render_paste_view("id", &view, "hello", None, false, Theme::Auto);
+        assert!(html.contains("<strong>Reads remaining:</strong> 2"));
+        assert!(html.contains("<strong>Size:</strong> 5 B"));
+        assert!(html.contains("<strong>SHA-256:</strong> <code>2cf24dba5fb0a30e</code>"));
+        assert!(!html.contains("last allowed read"));
+
+        view.reads_remaining = Some(0);
+        let last = render_paste_view("id", &view, "hello", None, false, Theme::Auto);
+        assert!(last.contains("last allowed read"));
+
+        view.reads_remaining = None;
+        let unlimited = render_paste_view("id", &view, "hello", None, false, Theme::Auto);
+        assert!(!unlimited.contains("Reads remaining"));
+    }
+
+    #[test]
+    fn format_size_picks_a_readable_unit() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(10 << 20), "10.0 MiB");
+    }
+
     #[test]
     fn render_time_locked_variants() {
-        let early = render_time_locked(TimeLockState::TooEarly(1));
+        let early = render_time_locked(TimeLockState::TooEarly(1), Theme::Auto);
         assert!(early.contains("Time-locked paste"));
-        let late = render_time_locked(TimeLockState::TooLate(2));
+        let late = render_time_locked(TimeLockState::TooLate(2), Theme::Auto);
         assert!(late.contains("Time window elapsed"));
     }
 
@@ -485,15 +941,17 @@ mod tests {
         let totp_html = render_attestation_prompt(
             "id",
             true,
-            None,
+            "1000.c2ln",
             &AttestationRequirement::Totp {
                 secret: "secret".into(),
                 digits: 6,
                 step: 30,
                 allowed_drift: 1,
                 issuer: None,
+                algorithm: TotpAlgorithm::Sha1,
             },
             true,
+            Theme::Auto,
         );
         assert!(totp_html.contains("Encryption key"));
         assert!(totp_html.contains("pattern=\"[0-9]{6,10}\""));
@@ -502,28 +960,60 @@ mod tests {
         let secret_html = render_attestation_prompt(
             "id",
             false,
-            Some("existing"),
+            "1000.c2ln",
             &AttestationRequirement::SharedSecret {
                 hash: "hash".into(),
             },
             false,
+            Theme::Auto,
         );
         assert!(secret_html.contains("type=\"password\""));
-        assert!(secret_html.contains("existing"));
+        assert!(secret_html.contains("name=\"state\" value=\"1000.c2ln\""));
+        assert!(!secret_html.contains("name=\"key\""));
+    }
+
+    #[test]
+    fn render_attestation_prompt_collects_every_factor() {
+        let html = render_attestation_prompt(
+            "id",
+            false,
+            "1000.c2ln",
+            &AttestationRequirement::All {
+                requirements: vec![
+                    AttestationRequirement::Totp {
+                        secret: "secret".into(),
+                        digits: 6,
+                        step: 30,
+                        allowed_drift: 1,
+                        issuer: Some("Ops".into()),
+                        algorithm: TotpAlgorithm::Sha1,
+                    },
+                    AttestationRequirement::SharedSecret {
+                        hash: "hash".into(),
+                    },
+                ],
+            },
+            false,
+            Theme::Auto,
+        );
+        assert!(html.contains("every factor"));
+        assert!(html.contains("One-time code (Ops)"));
+        assert!(html.contains("name=\"code\""));
+        assert!(html.contains("name=\"attest\""));
     }
 
     #[test]
     fn render_key_and_error_prompts() {
-        let key_html = render_key_prompt("abc");
+        let key_html = render_key_prompt("abc", Theme::Auto);
         assert!(key_html.contains("Encryption key"));
 
-        let invalid_html = render_invalid_key("def");
+        let invalid_html = render_invalid_key("def", Theme::Auto);
         assert!(invalid_html.contains("Invalid encryption key"));
     }
 
     #[test]
     fn render_expired_contains_message() {
-        let html = render_expired("expired-id");
+        let html = render_expired("expired-id", Theme::Auto);
         assert!(html.contains("Paste expired"));
         assert!(html.contains("expired-id"));
     }
@@ -540,10 +1030,11 @@ mod tests {
             created_at: 0,
             expires_at: None,
             burn_after_reading: false,
+            reads_remaining: None,
             metadata: &metadata,
         };
 
-        let html = render_paste_view("id", &view, "hello", None);
+        let html = render_paste_view("id", &view, "hello", None, false, Theme::Auto);
 
         // Format should appear as escaped Debug output, not raw enum Display
         assert!(html.contains("PlainText"));
@@ -551,6 +1042,101 @@ mod tests {
         assert!(html.contains("No expiry"));
     }
 
+    #[test]
+    fn render_paste_view_emits_machine_readable_times() {
+        let content = StoredContent::Plain {
+            text: "hello".to_string(),
+        };
+        let metadata = PasteMetadata::default();
+        let mut view = StoredPasteView {
+            content: &content,
+            format: PasteFormat::PlainText,
+            created_at: 1_700_000_000,
+            expires_at: Some(1_700_003_600),
+            burn_after_reading: false,
+            reads_remaining: None,
+            metadata: &metadata,
+        };
+
+        let html = render_paste_view("id", &view, "hello", None, false, Theme::Auto);
+        assert!(html.contains(r#"<time datetime="2023-11-14T22:13:20Z">"#));
+        assert!(html.contains(
+            r#"<time datetime="2023-11-14T23:13:20Z" data-expires="1700003600">2023-11-14 23:13:20 UTC</time>"#
+        ));
+        assert!(html.contains(r#"src="/static/view.js""#));
+
+        view.expires_at = None;
+        let html = render_paste_view("id", &view, "hello", None, false, Theme::Auto);
+        assert!(!html.contains("data-expires"));
+        assert!(html.contains("No expiry"));
+    }
+
+    #[test]
+    fn format_csv_renders_a_header_row_and_escaped_cells() {
+        let html = format_csv("name,qty,note\nwidget,3,\"a, <b>\"\ngadget,5,\n");
+        assert_eq!(html.matches("<th>").count(), 3);
+        assert_eq!(html.matches("<tr>").count(), 3);
+        assert_eq!(html.matches("<td>").count(), 6);
+        assert!(html.contains("<td>a, &lt;b&gt;</td>"));
+        assert!(!html.contains("<b>"));
+
+        let tsv = format_tsv("a\tb\n1\t2\n");
+        assert_eq!(tsv.matches("<th>").count(), 2);
+        assert!(tsv.contains("<td>2</td>"));
+    }
+
+    #[test]
+    fn format_csv_falls_back_to_plain_for_malformed_input() {
+        let ragged = "a,b,c\n1,2\n";
+        assert_eq!(format_csv(ragged), format_plain(ragged));
+        assert_eq!(format_csv(""), format_plain(""));
+    }
+
+    #[test]
+    fn format_toml_normalizes_valid_documents() {
+        let html = format_toml("title='demo'\n[server]\nport=8080\n");
+        assert_eq!(
+            html,
+            format_code("title = \"demo\"\n\n[server]\nport = 8080\n")
+        );
+    }
+
+    #[test]
+    fn format_toml_shows_invalid_input_as_escaped_code() {
+        let broken = "[server\nname = <script>\n";
+        let html = format_toml(broken);
+        assert_eq!(html, format_code(broken));
+        assert!(html.contains("&lt;script&gt;"));
+    }
+
+    #[test]
+    fn code_and_plain_lines_get_numbered_anchors() {
+        let text = "first\nif a < b {\n\nlast\n";
+        for html in [format_code(text), format_plain(text)] {
+            for n in 1..=4 {
+                assert!(html.contains(&format!(r#"id="L{n}""#)), "missing L{n}");
+                assert!(html.contains(&format!(r##"href="#L{n}""##)));
+            }
+            assert!(!html.contains(r#"id="L5""#));
+            assert!(html.contains("if a &lt; b {</span>"));
+            assert!(!html.contains("a < b"));
+        }
+        assert_eq!(format_plain(""), "<pre></pre>");
+    }
+
+    #[test]
+    fn diff_lines_are_classed_and_escaped() {
+        let diff =
+            "diff --git a/x b/x\n--- x.orig\n+++ x\n@@ -1,2 +1,2 @@\n context\n-old <b>\n+new <i>\n";
+        let html = format_diff(diff);
+        assert!(html.contains(r#"<span class="diff-add">+new &lt;i&gt;</span>"#));
+        assert!(html.contains(r#"<span class="diff-del">-old &lt;b&gt;</span>"#));
+        assert!(html.contains(r#"<span class="diff-hunk">@@ -1,2 +1,2 @@</span>"#));
+        assert!(html.contains(r#"<span class="diff-file">+++ x</span>"#));
+        assert!(html.contains(r#"data-line="5"></a> context</span>"#));
+        assert!(!html.contains("<b>") && !html.contains("<i>"));
+    }
+
     #[test]
     fn format_helpers_escape_and_render() {
         let plain = format_plain("<script>");
@@ -561,11 +1147,95 @@ mod tests {
 
         let code = format_code("let x = 1;");
         assert!(code.contains("<code>"));
+        assert!(code.contains(r#"id="L1""#));
 
-        let pretty_json = format_json("{\"k\":1}");
+        let pretty_json = format_json("{\"k\":1}", false);
         assert!(pretty_json.contains("\n"));
 
-        let fallback_json = format_json("not-json");
+        let fallback_json = format_json("not-json", false);
         assert!(fallback_json.contains("not-json"));
     }
+
+    /// Text a reader sees in rendered HTML: tags dropped, quotes decoded.
+    fn visible_text(html: &str) -> String {
+        let mut text = String::new();
+        let mut in_tag = false;
+        for c in html.chars() {
+            match c {
+                '<' => in_tag = true,
+                '>' => in_tag = false,
+                _ if !in_tag => text.push(c),
+                _ => {}
+            }
+        }
+        text.replace("&quot;", "\"").replace("&#34;", "\"")
+    }
+
+    #[test]
+    fn format_json_sort_keys_orders_every_object() {
+        let text = r#"{"b":1,"a":{"d":[{"z":1,"y":2}],"c":3}}"#;
+
+        let written = visible_text(&format_json(text, false));
+        assert!(written.find("\"b\"") < written.find("\"a\""));
+        assert!(written.find("\"z\"") < written.find("\"y\""));
+
+        let sorted = visible_text(&format_json(text, true));
+        let positions: Vec<_> = ["\"a\"", "\"c\"", "\"d\"", "\"y\"", "\"z\"", "\"b\""]
+            .iter()
+            .map(|key| sorted.find(key).expect("key rendered"))
+            .collect();
+        assert!(positions.is_sorted(), "{sorted}");
+
+        let reordered = r#"{"a":{"c":3,"d":[{"y":2,"z":1}]},"b":1}"#;
+        assert_eq!(format_json(reordered, true), format_json(text, true));
+    }
+
+    #[test]
+    fn format_json_highlights_valid_json_only() {
+        let html = format_json(r#"{"k":[1,true,null]}"#, true);
+        assert!(html.contains("language-json"));
+        assert!(html.contains("style="));
+
+        for sort_keys in [false, true] {
+            assert_eq!(
+                format_json("{\"b\":1,", sort_keys),
+                format_code("{\"b\":1,")
+            );
+        }
+    }
+
+    #[test]
+    fn markdown_escapes_raw_html() {
+        let block = format_markdown("<script>alert(1)</script>\n\nafter");
+        assert!(!block.contains("<script>"));
+        assert!(block.contains("&lt;script&gt;"));
+
+        let inline = format_markdown("hi <img src=x onerror=alert(1)> there");
+        assert!(!inline.contains("<img"));
+        assert!(inline.contains("&lt;img src=x onerror=alert(1)&gt;"));
+    }
+
+    #[test]
+    fn markdown_neutralises_script_urls() {
+        let html = format_markdown(
+            "[a](javascript:alert(1)) [b](JaVaScRiPt:alert(1)) ![c](data:text/html,x) <javascript:alert(1)>",
+        );
+        assert!(!html.to_ascii_lowercase().contains("=\"javascript:"));
+        assert!(!html.contains("=\"data:"));
+        assert!(html.contains("href=\"#\""));
+
+        let safe = format_markdown("[docs](https://example.com/a?b=c)");
+        assert!(safe.contains("href=\"https://example.com/a?b=c\""));
+    }
+
+    #[test]
+    fn markdown_keeps_supported_extensions() {
+        let html = format_markdown(
+            "**bold** ~~gone~~ [^1]\n\n| a | b |\n|---|---|\n| 1 | 2 |\n\n[^1]: note",
+        );
+        assert!(html.contains("<strong>bold</strong>"));
+        assert!(html.contains("<del>gone</del>"));
+        assert!(html.contains("<table>"));
+        assert!(html.contains("footnote-definition"));
+    }
 }
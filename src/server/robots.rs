@@ -0,0 +1,166 @@
+//! Search-engine indexing policy.
+//!
+//! Paste links are often shared as if they were secret, so by default
+//! crawlers are kept away from them: `/robots.txt` disallows everything but
+//! the landing page, `/about` and static assets, and the [`Indexing`] fairing
+//! adds `X-Robots-Tag: noindex, nofollow` to every response of the paste
+//! views (`show` and `show_raw`) for crawlers that ignore robots.txt or
+//! reach a paste through an outside link.
+//!
+//! `COPYPASTE_ALLOW_INDEXING=true` lifts both restrictions.
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::Header;
+use rocket::response::content;
+use rocket::{get, Request, Response, State};
+
+/// Routes whose responses are paste content.
+const PASTE_ROUTES: &[&str] = &["show", "show_raw"];
+
+const DISALLOW_PASTES: &str = "User-agent: *
+Allow: /$
+Allow: /about$
+Allow: /static/
+Disallow: /raw/
+Disallow: /
+";
+
+const ALLOW_ALL: &str = "User-agent: *
+Allow: /
+";
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Indexing {
+    allowed: bool,
+}
+
+impl Indexing {
+    pub fn new(allowed: bool) -> Self {
+        Self { allowed }
+    }
+
+    pub fn from_env() -> Self {
+        let allowed = std::env::var("COPYPASTE_ALLOW_INDEXING")
+            .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "on"))
+            .unwrap_or(false);
+        Self::new(allowed)
+    }
+
+    fn robots_txt(&self) -> &'static str {
+        if self.allowed {
+            ALLOW_ALL
+        } else {
+            DISALLOW_PASTES
+        }
+    }
+}
+
+#[get("/robots.txt")]
+pub fn robots_txt(indexing: &State<Indexing>) -> content::RawText<&'static str> {
+    content::RawText(indexing.robots_txt())
+}
+
+#[rocket::async_trait]
+impl Fairing for Indexing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Paste indexing policy",
+            kind: Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        if self.allowed {
+            return;
+        }
+        let is_paste = request
+            .route()
+            .and_then(|route| route.name.as_deref())
+            .is_some_and(|name| PASTE_ROUTES.contains(&name));
+        if is_paste {
+            response.set_header(Header::new("X-Robots-Tag", "noindex, nofollow"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::handlers::build_rocket;
+    use crate::{MemoryPasteStore, SharedPasteStore};
+    use rocket::http::{ContentType, Status};
+    use rocket::local::blocking::Client;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    #[test]
+    fn robots_txt_keeps_crawlers_off_pastes_but_not_the_landing_page() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+
+        let response = client.get("/robots.txt").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(response.content_type(), Some(ContentType::Plain));
+        let body = response.into_string().unwrap();
+        let rules: Vec<&str> = body.lines().collect();
+        for rule in [
+            "User-agent: *",
+            "Allow: /$",
+            "Allow: /about$",
+            "Disallow: /raw/",
+            "Disallow: /",
+        ] {
+            assert!(rules.contains(&rule), "missing {rule:?} in {body}");
+        }
+    }
+
+    #[test]
+    fn paste_views_carry_noindex_but_the_landing_page_does_not() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let client = Client::tracked(build_rocket(store)).expect("client");
+
+        let created = client
+            .post("/api/pastes")
+            .header(ContentType::JSON)
+            .body(json!({ "content": "secret link" }).to_string())
+            .dispatch();
+        let body: serde_json::Value =
+            serde_json::from_str(&created.into_string().unwrap()).unwrap();
+        let id = body["id"].as_str().unwrap();
+
+        for path in [
+            format!("/{id}"),
+            format!("/raw/{id}"),
+            "/missing".to_string(),
+        ] {
+            let response = client.get(path.clone()).dispatch();
+            assert_eq!(
+                response.headers().get_one("X-Robots-Tag"),
+                Some("noindex, nofollow"),
+                "{path}"
+            );
+        }
+        for path in ["/", "/about"] {
+            let response = client.get(path).dispatch();
+            assert_eq!(response.status(), Status::Ok);
+            assert!(
+                response.headers().get_one("X-Robots-Tag").is_none(),
+                "{path}"
+            );
+        }
+    }
+
+    #[test]
+    fn allowing_indexing_opens_robots_txt_and_drops_the_header() {
+        let indexing = Indexing::new(true);
+        let rocket = rocket::build()
+            .manage(indexing)
+            .attach(indexing)
+            .mount("/", rocket::routes![robots_txt]);
+        let client = Client::untracked(rocket).expect("client");
+
+        let response = client.get("/robots.txt").dispatch();
+        assert!(response.headers().get_one("X-Robots-Tag").is_none());
+        assert_eq!(response.into_string().unwrap(), "User-agent: *\nAllow: /\n");
+    }
+}
@@ -116,6 +116,27 @@ impl<'r> FromRequest<'r> for RequireUserSession {
     }
 }
 
+/// Infallible guard resolving an optional session: the bearer token's
+/// `pubkey_hash` when it names a live session, `None` for anonymous callers
+/// and for unknown or expired tokens alike.
+pub struct OptionalUserSession(pub Option<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for OptionalUserSession {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let Outcome::Success(BearerToken(Some(token))) = req.guard::<BearerToken>().await else {
+            return Outcome::Success(OptionalUserSession(None));
+        };
+        let owner = req
+            .rocket()
+            .state::<SharedSessionStore>()
+            .and_then(|store| store.validate(&token));
+        Outcome::Success(OptionalUserSession(owner))
+    }
+}
+
 /// Infallible guard that extracts an optional bearer token (used by logout,
 /// which must succeed whether or not a session exists).
 pub struct BearerToken(pub Option<String>);
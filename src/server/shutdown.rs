@@ -0,0 +1,53 @@
+//! Flush cached pastes to persistence when the server stops.
+//!
+//! Rocket already turns Ctrl-C, `SIGTERM` and `SIGHUP` into a graceful
+//! shutdown (its `shutdown.ctrlc`/`shutdown.signals` settings, backed by
+//! `tokio::signal`). The [`ShutdownFlush`] fairing runs once in-flight
+//! requests have drained and hands every live paste to
+//! [`crate::PasteStore::flush`], so writes whose save failed earlier get a
+//! last attempt. A memory-only deployment has nowhere to write; the number
+//! of pastes lost is logged instead.
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::{Orbit, Rocket};
+
+use crate::{FlushReport, SharedPasteStore};
+
+pub struct ShutdownFlush;
+
+/// Flush `store` and log the outcome.
+pub async fn flush_on_shutdown(store: &SharedPasteStore) -> FlushReport {
+    let report = store.flush().await;
+    if report.unpersisted > 0 {
+        log::warn!(
+            "shutting down without persistence: {} paste(s) will be lost",
+            report.unpersisted
+        );
+    }
+    if report.failed > 0 {
+        log::warn!(
+            "shutdown flush saved {} paste(s), {} failed",
+            report.saved,
+            report.failed
+        );
+    } else if report.saved > 0 {
+        log::info!("shutdown flush saved {} paste(s)", report.saved);
+    }
+    report
+}
+
+#[rocket::async_trait]
+impl Fairing for ShutdownFlush {
+    fn info(&self) -> Info {
+        Info {
+            name: "Shutdown persistence flush",
+            kind: Kind::Shutdown,
+        }
+    }
+
+    async fn on_shutdown(&self, rocket: &Rocket<Orbit>) {
+        if let Some(store) = rocket.state::<SharedPasteStore>() {
+            flush_on_shutdown(store).await;
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! Reserved words that user-chosen paste slugs may not claim.
+//!
+//! Paste views live at `/{id}`, so a custom slug equal to a mounted route
+//! prefix (`api`, `raw`, `static`, ...) would be shadowed by — or shadow —
+//! that route. The built-in list covers every static top-level segment that
+//! `build_rocket` mounts; operators can add more via the comma-separated
+//! `COPYPASTE_RESERVED_SLUGS`. Matching is case-insensitive and applies to
+//! the first path segment, so `api/anything` is rejected too.
+//!
+//! Custom ids may contain `/` (`team/deploy-runbook`); [`PastePath`] lets
+//! the view routes match them across segments.
+
+use std::collections::HashSet;
+
+use rocket::http::uri::{fmt::Path, Segments};
+use rocket::request::FromSegments;
+
+/// Top-level route segments mounted by `build_rocket`, plus `metrics` which
+/// is kept free for an exporter endpoint and `p`, the SPA's paste route.
+pub const BUILTIN_RESERVED_SLUGS: &[&str] = &[
+    "about",
+    "api",
+    "health",
+    "metrics",
+    "p",
+    "raw",
+    "robots.txt",
+    "static",
+    "status",
+];
+
+pub const CUSTOM_ID_MIN_LEN: usize = 3;
+pub const CUSTOM_ID_MAX_LEN: usize = 64;
+
+/// Check the shape of a client-chosen paste id: lowercase ASCII letters,
+/// digits, `-` and `/`, with no empty path segments.
+pub fn validate_custom_id(id: &str) -> Result<(), String> {
+    if !(CUSTOM_ID_MIN_LEN..=CUSTOM_ID_MAX_LEN).contains(&id.len()) {
+        return Err(format!(
+            "custom_id must be {CUSTOM_ID_MIN_LEN}-{CUSTOM_ID_MAX_LEN} characters"
+        ));
+    }
+    if !id
+        .bytes()
+        .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-' || b == b'/')
+    {
+        return Err("custom_id may only contain a-z, 0-9, '-' and '/'".to_string());
+    }
+    if id.split('/').any(str::is_empty) {
+        return Err("custom_id must not start or end with '/' or contain '//'".to_string());
+    }
+    Ok(())
+}
+
+pub struct ReservedSlugs {
+    words: HashSet<String>,
+}
+
+impl ReservedSlugs {
+    /// Built-in reserved words plus `extra`.
+    pub fn new<I, S>(extra: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let words = BUILTIN_RESERVED_SLUGS
+            .iter()
+            .map(|w| w.to_string())
+            .chain(
+                extra
+                    .into_iter()
+                    .map(|w| w.as_ref().trim().trim_matches('/').to_ascii_lowercase())
+                    .filter(|w| !w.is_empty()),
+            )
+            .collect();
+        Self { words }
+    }
+
+    /// Built-ins plus the comma-separated `COPYPASTE_RESERVED_SLUGS`.
+    pub fn from_env() -> Self {
+        let extra = std::env::var("COPYPASTE_RESERVED_SLUGS").unwrap_or_default();
+        Self::new(extra.split(','))
+    }
+
+    pub fn is_reserved(&self, slug: &str) -> bool {
+        let first = slug
+            .trim_start_matches('/')
+            .split('/')
+            .next()
+            .unwrap_or_default();
+        self.words.contains(&first.to_ascii_lowercase())
+    }
+
+    /// Reject reserved slugs with a message suitable for a 400 response.
+    pub fn check(&self, slug: &str) -> Result<(), String> {
+        if self.is_reserved(slug) {
+            Err(format!(
+                "'{slug}' is reserved and cannot be used as a paste id"
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Default for ReservedSlugs {
+    fn default() -> Self {
+        Self::new(std::iter::empty::<&str>())
+    }
+}
+
+/// A paste id taken from one or more path segments. Multi-segment paths
+/// must be a valid custom id outside the built-in reserved words; anything
+/// else forwards, so `/static/...` and the SPA fallback keep working.
+pub struct PastePath(pub String);
+
+impl<'r> FromSegments<'r> for PastePath {
+    type Error = ();
+
+    fn from_segments(segments: Segments<'r, Path>) -> Result<Self, ()> {
+        let parts: Vec<&str> = segments.collect();
+        match parts.as_slice() {
+            [] => Err(()),
+            [id] => Ok(PastePath(id.to_string())),
+            _ => {
+                let id = parts.join("/");
+                let valid =
+                    validate_custom_id(&id).is_ok() && !ReservedSlugs::default().is_reserved(&id);
+                valid.then_some(PastePath(id)).ok_or(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::handlers::build_rocket;
+    use crate::{MemoryPasteStore, SharedPasteStore};
+    use std::sync::Arc;
+
+    #[test]
+    fn rejects_route_prefixes_and_allows_ordinary_slugs() {
+        let reserved = ReservedSlugs::default();
+        assert!(reserved.check("api").is_err());
+        assert!(reserved.check("raw").is_err());
+        assert!(reserved.check("API/keys").is_err());
+        assert!(reserved.check("deploy-runbook").is_ok());
+        assert!(reserved.check("team/rawhide").is_ok());
+    }
+
+    #[test]
+    fn custom_id_shape_is_validated() {
+        assert!(validate_custom_id("team/deploy-runbook").is_ok());
+        assert!(validate_custom_id("abc").is_ok());
+        assert!(validate_custom_id("ab").is_err());
+        assert!(validate_custom_id(&"a".repeat(CUSTOM_ID_MAX_LEN + 1)).is_err());
+        assert!(validate_custom_id("Team/Runbook").is_err());
+        assert!(validate_custom_id("has space").is_err());
+        assert!(validate_custom_id("/leading").is_err());
+        assert!(validate_custom_id("trailing/").is_err());
+        assert!(validate_custom_id("double//slash").is_err());
+    }
+
+    #[test]
+    fn extra_words_are_normalised() {
+        let reserved = ReservedSlugs::new([" Admin ", "/login/", ""]);
+        assert!(reserved.is_reserved("admin"));
+        assert!(reserved.is_reserved("login/form"));
+        assert!(!reserved.is_reserved("team"));
+    }
+
+    #[test]
+    fn builtin_list_covers_every_mounted_top_level_segment() {
+        let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+        let rocket = build_rocket(store);
+        let reserved = ReservedSlugs::default();
+        for route in rocket.routes() {
+            let segment = route
+                .uri
+                .path()
+                .trim_start_matches('/')
+                .split('/')
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            if segment.is_empty() || segment.starts_with('<') {
+                continue;
+            }
+            assert!(
+                reserved.is_reserved(&segment),
+                "route {} is not covered by the reserved slug list",
+                route.uri
+            );
+        }
+    }
+}
@@ -1,8 +1,9 @@
 use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
 use base64::Engine;
+use image::codecs::bmp::BmpEncoder;
 use image::codecs::png::PngEncoder;
-use image::load_from_memory;
-use image::{ImageBuffer, ImageEncoder, Rgba, RgbaImage};
+use image::{load_from_memory, load_from_memory_with_format};
+use image::{ImageBuffer, ImageEncoder, ImageFormat, Rgba, RgbaImage};
 use std::{f32::consts::PI, io::Cursor};
 
 #[derive(Debug, thiserror::Error)]
@@ -19,8 +20,17 @@ pub enum StegoError {
     PayloadTooLarge { required: usize, capacity: usize },
     #[error("failed to encode stego image: {0}")]
     EncodeFailure(String),
+    #[error("carrier declares a {declared}-byte payload but can hold at most {capacity} bytes")]
+    InvalidLength { declared: usize, capacity: usize },
+    #[error("{mime} is lossy and would destroy the embedded bits; use PNG, BMP or WebP")]
+    LossyCarrierUnsupported { mime: String },
+    #[error("carrier image is too small ({width}x{height}) to hold the {LENGTH_HEADER_BYTES}-byte length header")]
+    CarrierTooSmall { width: u32, height: u32 },
 }
 
+/// Every embedded payload is preceded by a big-endian `u32` length.
+const LENGTH_HEADER_BYTES: usize = 4;
+
 pub enum StegoCarrierSource {
     BuiltIn(String),
     Uploaded { mime: String, data: Vec<u8> },
@@ -32,36 +42,91 @@ pub struct StegoEmbedResult {
     pub image_data: Vec<u8>,
 }
 
+/// Lossless formats a carrier can be kept in. The embedded bits live in the
+/// pixels' low bits, so the output must be written losslessly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CarrierFormat {
+    Png,
+    Bmp,
+    /// Lossy WebP uploads are accepted too; the output is always lossless.
+    WebP,
+}
+
+impl CarrierFormat {
+    /// The format an uploaded carrier of type `mime` is kept in, or why it
+    /// can't carry a payload.
+    pub fn from_mime(mime: &str) -> Result<Self, StegoError> {
+        match mime {
+            "image/png" => Ok(CarrierFormat::Png),
+            "image/bmp" | "image/x-ms-bmp" => Ok(CarrierFormat::Bmp),
+            "image/webp" => Ok(CarrierFormat::WebP),
+            "image/jpeg" | "image/jpg" | "image/pjpeg" => {
+                Err(StegoError::LossyCarrierUnsupported {
+                    mime: mime.to_string(),
+                })
+            }
+            _ => Err(StegoError::UnsupportedFormat),
+        }
+    }
+
+    fn image_format(self) -> ImageFormat {
+        match self {
+            CarrierFormat::Png => ImageFormat::Png,
+            CarrierFormat::Bmp => ImageFormat::Bmp,
+            CarrierFormat::WebP => ImageFormat::WebP,
+        }
+    }
+
+    fn mime(self) -> &'static str {
+        match self {
+            CarrierFormat::Png => "image/png",
+            CarrierFormat::Bmp => "image/bmp",
+            CarrierFormat::WebP => "image/webp",
+        }
+    }
+
+    fn encode(self, image: &RgbaImage) -> Result<Vec<u8>, StegoError> {
+        let (width, height) = image.dimensions();
+        let mut buffer = Vec::new();
+        let result = match self {
+            CarrierFormat::Png => PngEncoder::new(Cursor::new(&mut buffer))
+                .write_image(image, width, height, image::ColorType::Rgba8)
+                .map_err(|error| error.to_string()),
+            CarrierFormat::Bmp => BmpEncoder::new(&mut buffer)
+                .write_image(image, width, height, image::ColorType::Rgba8)
+                .map_err(|error| error.to_string()),
+            CarrierFormat::WebP => image_webp::WebPEncoder::new(&mut buffer)
+                .encode(image, width, height, image_webp::ColorType::Rgba8)
+                .map_err(|error| error.to_string()),
+        };
+        result.map_err(StegoError::EncodeFailure)?;
+        Ok(buffer)
+    }
+}
+
+/// Hide `payload` in the carrier. Built-in carriers come out as PNG;
+/// uploads keep their (lossless) format, and JPEG is refused because its
+/// compression would wipe out the low bits.
 pub fn embed_payload(
     source: StegoCarrierSource,
     payload: &[u8],
 ) -> Result<StegoEmbedResult, StegoError> {
-    let (mut image, mime) = match source {
-        StegoCarrierSource::BuiltIn(identifier) => generate_builtin(identifier.as_str()),
+    let (mut image, format) = match source {
+        StegoCarrierSource::BuiltIn(identifier) => {
+            (generate_builtin(identifier.as_str()), CarrierFormat::Png)
+        }
         StegoCarrierSource::Uploaded { mime, data } => {
-            let dynamic = load_from_memory(&data)
+            let format = CarrierFormat::from_mime(&mime)?;
+            let dynamic = load_from_memory_with_format(&data, format.image_format())
                 .map_err(|error| StegoError::DecodeCarrier(error.to_string()))?;
-            (dynamic.to_rgba8(), mime)
+            (dynamic.to_rgba8(), format)
         }
     };
 
     embed_message(payload, &mut image)?;
-    let mut buffer = Vec::new();
-    {
-        let encoder = PngEncoder::new(Cursor::new(&mut buffer));
-        if let Err(error) = encoder.write_image(
-            &image,
-            image.width(),
-            image.height(),
-            image::ColorType::Rgba8,
-        ) {
-            return Err(StegoError::EncodeFailure(error.to_string()));
-        }
-    }
-
     Ok(StegoEmbedResult {
-        mime,
-        image_data: buffer,
+        mime: format.mime().to_string(),
+        image_data: format.encode(&image)?,
     })
 }
 
@@ -73,17 +138,80 @@ pub fn parse_data_uri(input: &str) -> Result<(String, Vec<u8>), StegoError> {
     if !meta.ends_with(";base64") {
         return Err(StegoError::InvalidDataUri);
     }
+    // `image/PNG;name=x.png;base64` → `image/png`.
     let mime = meta
         .strip_suffix(";base64")
         .ok_or(StegoError::InvalidDataUri)?
-        .to_string();
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
     let data = BASE64_STANDARD
         .decode(data_part)
         .map_err(|_| StegoError::InvalidDataUri)?;
     Ok((mime, data))
 }
 
+/// Recover a payload written by [`embed_payload`]: a 32-bit big-endian
+/// length header followed by the payload bytes, one bit in the low bit of
+/// each RGB channel.
+pub fn extract_payload(image: &RgbaImage) -> Result<Vec<u8>, StegoError> {
+    let mut bits = image
+        .pixels()
+        .flat_map(|pixel| pixel.0.into_iter().take(3).map(|channel| channel & 1));
+    let mut next_byte = || (0..8).try_fold(0u8, |byte, _| Some((byte << 1) | bits.next()?));
+
+    let capacity = ((image.width() as usize) * (image.height() as usize) * 3 / 8).saturating_sub(4);
+    let mut header = [0u8; 4];
+    for byte in &mut header {
+        *byte = next_byte().ok_or(StegoError::InvalidLength {
+            declared: 0,
+            capacity,
+        })?;
+    }
+    let declared = u32::from_be_bytes(header) as usize;
+    if declared > capacity {
+        return Err(StegoError::InvalidLength { declared, capacity });
+    }
+
+    (0..declared)
+        .map(|_| next_byte())
+        .collect::<Option<Vec<u8>>>()
+        .ok_or(StegoError::InvalidLength { declared, capacity })
+}
+
+/// Decode an encoded carrier image (as produced by [`embed_payload`]) and
+/// recover its payload.
+pub fn extract_from_image_bytes(data: &[u8]) -> Result<Vec<u8>, StegoError> {
+    let image = load_from_memory(data)
+        .map_err(|error| StegoError::DecodeCarrier(error.to_string()))?
+        .to_rgba8();
+    extract_payload(&image)
+}
+
+/// Check that `image` holds the length header plus `payload_len` bytes,
+/// before any bits are written. Degenerate carriers (zero-sized, or too
+/// small for the header alone) get their own error.
+fn ensure_capacity(image: &RgbaImage, payload_len: usize) -> Result<(), StegoError> {
+    let (width, height) = image.dimensions();
+    let capacity_bytes = (width as usize) * (height as usize) * 3 / 8;
+    if capacity_bytes < LENGTH_HEADER_BYTES {
+        return Err(StegoError::CarrierTooSmall { width, height });
+    }
+    let capacity = capacity_bytes - LENGTH_HEADER_BYTES;
+    if payload_len > capacity {
+        return Err(StegoError::PayloadTooLarge {
+            required: payload_len,
+            capacity,
+        });
+    }
+    Ok(())
+}
+
 fn embed_message(payload: &[u8], image: &mut RgbaImage) -> Result<(), StegoError> {
+    ensure_capacity(image, payload.len())?;
+
     let length_bytes = (payload.len() as u32).to_be_bytes();
     let mut bits = Vec::with_capacity((payload.len() + length_bytes.len()) * 8);
     for byte in length_bytes.iter().chain(payload.iter()) {
@@ -92,14 +220,6 @@ fn embed_message(payload: &[u8], image: &mut RgbaImage) -> Result<(), StegoError
         }
     }
 
-    let capacity_bits = (image.width() as usize) * (image.height() as usize) * 3;
-    if bits.len() > capacity_bits {
-        return Err(StegoError::PayloadTooLarge {
-            required: payload.len(),
-            capacity: capacity_bits / 8,
-        });
-    }
-
     let mut bit_index = 0;
     let total_bits = bits.len();
 
@@ -121,7 +241,7 @@ fn clamp_to_byte(value: f32) -> u8 {
     value.round().clamp(0.0, 255.0) as u8
 }
 
-fn generate_builtin(identifier: &str) -> (RgbaImage, String) {
+fn generate_builtin(identifier: &str) -> RgbaImage {
     match identifier {
         "aurora" => generate_gradient(|x, y, width, height| {
             let fx = x as f32 / width as f32;
@@ -227,7 +347,7 @@ fn generate_builtin(identifier: &str) -> (RgbaImage, String) {
     }
 }
 
-fn generate_gradient<F>(mut f: F) -> (RgbaImage, String)
+fn generate_gradient<F>(mut f: F) -> RgbaImage
 where
     F: FnMut(u32, u32, u32, u32) -> Rgba<u8>,
 {
@@ -244,7 +364,7 @@ where
         }
     }
 
-    (buffer, "image/png".to_string())
+    buffer
 }
 
 fn pseudo_random(x: u32, y: u32) -> f32 {
@@ -299,23 +419,166 @@ mod tests {
         assert!(!result.image_data.is_empty());
     }
 
+    fn png_carrier(width: u32, height: u32) -> StegoCarrierSource {
+        let pixels = vec![255u8; (width * height * 4) as usize];
+        let mut buffer = Vec::new();
+        image::codecs::png::PngEncoder::new(&mut buffer)
+            .write_image(&pixels, width, height, image::ColorType::Rgba8)
+            .expect("encode carrier");
+        StegoCarrierSource::Uploaded {
+            mime: "image/png".to_string(),
+            data: buffer,
+        }
+    }
+
     #[test]
     fn embed_payload_rejects_large_payload_for_small_carrier() {
-        let mut buffer = Vec::new();
-        {
-            let encoder = image::codecs::png::PngEncoder::new(&mut buffer);
-            encoder
-                .write_image(&[255, 0, 0, 255], 1, 1, image::ColorType::Rgba8)
-                .expect("encode 1x1 image");
+        // 4x4 carrier: 6 bytes of capacity, 4 of them for the header.
+        let err =
+            embed_payload(png_carrier(4, 4), &[0u8; 16]).expect_err("payload should be too large");
+        assert!(matches!(
+            err,
+            StegoError::PayloadTooLarge {
+                required: 16,
+                capacity: 2
+            }
+        ));
+        assert!(embed_payload(png_carrier(4, 4), &[7u8; 2]).is_ok());
+    }
+
+    #[test]
+    fn embed_payload_rejects_carrier_smaller_than_length_header() {
+        let err = embed_payload(png_carrier(1, 1), b"x").expect_err("1x1 cannot hold a header");
+        assert!(matches!(
+            err,
+            StegoError::CarrierTooSmall {
+                width: 1,
+                height: 1
+            }
+        ));
+        assert!(err.to_string().contains("too small"));
+    }
+
+    #[test]
+    fn embed_message_rejects_zero_dimension_carrier() {
+        for (width, height) in [(0, 0), (0, 64), (64, 0)] {
+            let mut image = RgbaImage::new(width, height);
+            assert!(matches!(
+                embed_message(b"", &mut image),
+                Err(StegoError::CarrierTooSmall { .. })
+            ));
         }
+    }
 
-        let source = StegoCarrierSource::Uploaded {
-            mime: "image/png".to_string(),
-            data: buffer,
-        };
+    #[test]
+    fn extract_payload_round_trips_through_png() {
+        let payload: Vec<u8> = (0..=255u8).cycle().take(1000).collect();
+        let result = embed_payload(StegoCarrierSource::BuiltIn("cinder".to_string()), &payload)
+            .expect("embed");
+
+        let decoded =
+            image::load_from_memory_with_format(&result.image_data, image::ImageFormat::Png)
+                .expect("png decodes")
+                .to_rgba8();
+        assert_eq!(extract_payload(&decoded).expect("extract"), payload);
+        assert_eq!(
+            extract_from_image_bytes(&result.image_data).expect("extract"),
+            payload
+        );
+    }
+
+    #[test]
+    fn extract_payload_handles_empty_payload() {
+        let result =
+            embed_payload(StegoCarrierSource::BuiltIn("aurora".to_string()), b"").expect("embed");
+        assert!(extract_from_image_bytes(&result.image_data)
+            .expect("extract")
+            .is_empty());
+    }
+
+    #[test]
+    fn extract_payload_rejects_length_beyond_capacity() {
+        // All-ones low bits declare a 4 GiB payload in a 4x4 carrier.
+        let image = RgbaImage::from_pixel(4, 4, Rgba([255, 255, 255, 255]));
+        assert!(matches!(
+            extract_payload(&image),
+            Err(StegoError::InvalidLength { capacity: 2, .. })
+        ));
+
+        let tiny = RgbaImage::from_pixel(1, 1, Rgba([0, 0, 0, 255]));
+        assert!(matches!(
+            extract_payload(&tiny),
+            Err(StegoError::InvalidLength { declared: 0, .. })
+        ));
+    }
+
+    fn encoded_carrier(format: CarrierFormat) -> Vec<u8> {
+        let image = RgbaImage::from_fn(64, 64, |x, y| Rgba([x as u8, y as u8, 9, 255]));
+        format.encode(&image).expect("encode carrier")
+    }
+
+    #[test]
+    fn embed_payload_keeps_lossless_upload_formats() {
+        let cases = [
+            (CarrierFormat::Png, "image/png", &b"\x89PNG\r\n\x1a\n"[..]),
+            (CarrierFormat::Bmp, "image/bmp", &b"BM"[..]),
+            (CarrierFormat::WebP, "image/webp", &b"RIFF"[..]),
+        ];
+        for (format, mime, magic) in cases {
+            let result = embed_payload(
+                StegoCarrierSource::Uploaded {
+                    mime: mime.to_string(),
+                    data: encoded_carrier(format),
+                },
+                b"payload",
+            )
+            .expect("embed into lossless carrier");
+            assert_eq!(result.mime, mime);
+            assert!(result.image_data.starts_with(magic), "{mime} re-encoded");
+            assert_eq!(
+                extract_from_image_bytes(&result.image_data).expect("extract"),
+                b"payload",
+                "{mime} lost the payload"
+            );
+        }
+    }
+
+    #[test]
+    fn embed_payload_rejects_jpeg_carriers() {
+        let err = embed_payload(
+            StegoCarrierSource::Uploaded {
+                mime: "image/jpeg".to_string(),
+                data: b"\xFF\xD8\xFF\xE0\x00\x10JFIF".to_vec(),
+            },
+            b"payload",
+        )
+        .expect_err("jpeg is lossy");
+        assert!(matches!(
+            err,
+            StegoError::LossyCarrierUnsupported { ref mime } if mime == "image/jpeg"
+        ));
+    }
+
+    #[test]
+    fn embed_payload_rejects_bytes_that_do_not_match_the_mime() {
+        let err = embed_payload(
+            StegoCarrierSource::Uploaded {
+                mime: "image/webp".to_string(),
+                data: encoded_carrier(CarrierFormat::Png),
+            },
+            b"payload",
+        )
+        .expect_err("png bytes are not webp");
+        assert!(matches!(err, StegoError::DecodeCarrier(_)));
+    }
 
-        let err = embed_payload(source, &[0u8; 16]).expect_err("payload should be too large");
-        assert!(matches!(err, StegoError::PayloadTooLarge { .. }));
+    #[test]
+    fn parse_data_uri_normalizes_mime() {
+        let uri = format!(
+            "data:Image/WebP;name=c.webp;base64,{}",
+            BASE64_STANDARD_TEST.encode(b"x")
+        );
+        assert_eq!(parse_data_uri(&uri).unwrap().0, "image/webp");
     }
 
     #[test]
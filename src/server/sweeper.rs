@@ -0,0 +1,78 @@
+//! Periodic eviction pass over the paste store.
+//!
+//! The [`Sweeper`] fairing spawns [`run_sweeper`] at liftoff, calling
+//! [`crate::PasteStore::sweep_once`] every `COPYPASTE_SWEEP_INTERVAL_SECONDS`
+//! (default 60) until Rocket shuts down, and fires an expiry webhook for
+//! each removed paste that has one, saying whether it was ever read. What
+//! gets removed is the store's policy: expired pastes, plus
+//! burn-after-reading pastes older than `COPYPASTE_BURN_MAX_AGE_SECS`.
+
+use std::time::Duration;
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::{Orbit, Rocket};
+
+use crate::{run_sweeper, SharedPasteStore, StoredPaste};
+
+use super::webhook::{trigger_webhook, WebhookClient, WebhookEvent};
+
+pub const DEFAULT_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+pub struct Sweeper {
+    interval: Duration,
+}
+
+impl Sweeper {
+    pub fn new(interval: Duration) -> Self {
+        Self { interval }
+    }
+
+    pub fn from_env() -> Self {
+        let interval = std::env::var("COPYPASTE_SWEEP_INTERVAL_SECONDS")
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_SWEEP_INTERVAL);
+        Self::new(interval)
+    }
+}
+
+fn notify_expired(http: &reqwest::Client, (id, paste): &(String, StoredPaste)) {
+    if let Some(config) = paste.metadata.webhook.clone() {
+        trigger_webhook(
+            http.clone(),
+            config,
+            WebhookEvent::expired(paste.metadata.access_count),
+            id,
+            paste.metadata.bundle_label.clone(),
+        );
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for Sweeper {
+    fn info(&self) -> Info {
+        Info {
+            name: "Paste sweeper",
+            kind: Kind::Liftoff,
+        }
+    }
+
+    async fn on_liftoff(&self, rocket: &Rocket<Orbit>) {
+        let (Some(store), Some(http)) = (
+            rocket.state::<SharedPasteStore>().cloned(),
+            rocket
+                .state::<WebhookClient>()
+                .map(|client| client.0.clone()),
+        ) else {
+            return;
+        };
+        tokio::spawn(run_sweeper(
+            store,
+            self.interval,
+            rocket.shutdown(),
+            move |removed| notify_expired(&http, removed),
+        ));
+    }
+}
@@ -1,4 +1,4 @@
-use chrono::DateTime;
+use chrono::{DateTime, SecondsFormat};
 
 use crate::PasteMetadata;
 
@@ -24,12 +24,47 @@ pub fn parse_timestamp(input: &str) -> Result<i64, String> {
         .map_err(|_| "expected UNIX seconds or RFC3339 timestamp".to_string())
 }
 
+/// Parse a human-friendly duration into minutes.
+///
+/// Accepts a bare number of minutes (`"90"`) or a number with an
+/// `m`/`h`/`d`/`w` suffix (`"90m"`, `"24h"`, `"7d"`, `"2w"`), case-insensitive.
+pub fn parse_duration(input: &str) -> Result<i64, String> {
+    let invalid = || format!("invalid duration '{input}': expected e.g. 90m, 24h, 7d or 2w");
+    let s = input.trim().to_ascii_lowercase();
+    let (value, multiplier) = if let Some(rest) = s.strip_suffix('m') {
+        (rest, 1i64)
+    } else if let Some(rest) = s.strip_suffix('h') {
+        (rest, 60)
+    } else if let Some(rest) = s.strip_suffix('d') {
+        (rest, 60 * 24)
+    } else if let Some(rest) = s.strip_suffix('w') {
+        (rest, 60 * 24 * 7)
+    } else {
+        (s.as_str(), 1)
+    };
+    let value = value.trim();
+    if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    value
+        .parse::<i64>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .ok_or_else(invalid)
+}
+
 pub fn format_timestamp(ts: i64) -> String {
     DateTime::from_timestamp(ts, 0)
         .map(|dt| dt.format("%Y-%m-%d %H:%M:%S UTC").to_string())
         .unwrap_or_else(|| ts.to_string())
 }
 
+/// RFC 3339 / ISO 8601 form of `ts` for `<time datetime>` attributes, or
+/// `None` when it is out of range.
+pub fn format_iso8601(ts: i64) -> Option<String> {
+    DateTime::from_timestamp(ts, 0).map(|dt| dt.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
 pub fn evaluate_time_lock(metadata: &PasteMetadata, now: i64) -> Option<TimeLockState> {
     if let Some(not_before) = metadata.not_before {
         if now < not_before {
@@ -63,11 +98,51 @@ mod tests {
         assert!(parse_timestamp("not-a-timestamp").is_err());
     }
 
+    #[test]
+    fn parse_duration_handles_each_suffix() {
+        assert_eq!(parse_duration("90m").unwrap(), 90);
+        assert_eq!(parse_duration("24h").unwrap(), 24 * 60);
+        assert_eq!(parse_duration("7d").unwrap(), 7 * 24 * 60);
+        assert_eq!(parse_duration("2w").unwrap(), 2 * 7 * 24 * 60);
+        assert_eq!(parse_duration(" 1H ").unwrap(), 60);
+    }
+
+    #[test]
+    fn parse_duration_treats_bare_numbers_as_minutes() {
+        assert_eq!(parse_duration("45").unwrap(), 45);
+    }
+
+    #[test]
+    fn parse_duration_rejects_invalid_input() {
+        for input in [
+            "banana",
+            "",
+            "m",
+            "5x",
+            "-5m",
+            "+5m",
+            "1.5h",
+            "99999999999999999999w",
+        ] {
+            assert!(
+                parse_duration(input).is_err(),
+                "{input:?} should be rejected"
+            );
+        }
+        assert!(parse_duration("banana").unwrap_err().contains("banana"));
+    }
+
     #[test]
     fn format_timestamp_renders_utc_string() {
         assert_eq!(format_timestamp(0), "1970-01-01 00:00:00 UTC");
     }
 
+    #[test]
+    fn format_iso8601_renders_utc_instant() {
+        assert_eq!(format_iso8601(90).as_deref(), Some("1970-01-01T00:01:30Z"));
+        assert_eq!(format_iso8601(i64::MAX), None);
+    }
+
     #[test]
     fn format_timestamp_falls_back_for_invalid_input() {
         let rendered = format_timestamp(i64::MAX);
@@ -3,6 +3,8 @@ use std::env;
 use rocket::request::{FromRequest, Outcome};
 use rocket::Request;
 
+use super::config::TorSection;
+
 #[derive(Debug, Clone)]
 pub struct TorConfig {
     pub onion_host: Option<String>,
@@ -151,6 +153,20 @@ mod tests {
     }
 }
 
+impl From<&TorSection> for TorConfig {
+    fn from(section: &TorSection) -> Self {
+        Self {
+            onion_host: section
+                .onion_host
+                .as_deref()
+                .map(str::trim)
+                .filter(|host| !host.is_empty())
+                .map(str::to_string),
+            suppress_logs: section.suppress_logs,
+        }
+    }
+}
+
 impl TorConfig {
     pub fn from_env() -> Self {
         let onion_host = env::var("COPYPASTE_ONION_HOST")
@@ -0,0 +1,368 @@
+//! Passkey (WebAuthn) attestation.
+//!
+//! A paste gated by [`AttestationRequirement::Webauthn`] is unlocked in two
+//! steps: `POST /api/pastes/<id>/webauthn/challenge` starts an assertion
+//! ceremony against the paste's passkeys, and `POST .../webauthn/verify`
+//! checks the authenticator's answer. A verified assertion is exchanged for
+//! a short-lived grant token, `expires_at "." base64url(HMAC-SHA256)`, bound
+//! to the paste id; reads present it as `?webauthn=<token>` and
+//! [`super::attestation::verify_attestation`] grants on it.
+//!
+//! Ceremony state is kept in memory and removed on first use, so each
+//! challenge can be answered once. The signing secret comes from
+//! `COPYPASTE_WEBAUTHN_TOKEN_SECRET` (random per process otherwise).
+//! Passkeys are stored with the paste and never updated, so authenticator
+//! signature counters are not tracked.
+//!
+//! [`AttestationRequirement::Webauthn`]: crate::AttestationRequirement::Webauthn
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use rand::{rngs::OsRng, RngCore};
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+use webauthn_rs::prelude::{
+    Passkey, PasskeyAuthentication, PublicKeyCredential, RequestChallengeResponse, Url, Webauthn,
+    WebauthnBuilder,
+};
+
+use super::download_tokens::hmac_secret_from_env;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// How long a challenge may go unanswered.
+pub const CEREMONY_TTL_SECS: i64 = 5 * 60;
+/// How long a grant token unlocks its paste.
+pub const GRANT_TTL_SECS: i64 = 10 * 60;
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum PasskeyError {
+    #[error("passkey ceremony could not be started: {0}")]
+    Setup(String),
+    #[error("unknown, expired or already answered passkey challenge")]
+    UnknownChallenge,
+    #[error("passkey assertion rejected: {0}")]
+    Rejected(String),
+}
+
+/// What a read presented as its passkey grant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasskeyProof {
+    /// No `?webauthn=` token.
+    Missing,
+    /// A token this server minted for the paste, still valid.
+    Verified,
+    /// A forged, expired or foreign token.
+    Invalid,
+}
+
+/// Checks an authenticator's answer against the ceremony state. Split out so
+/// tests can stand in for a real authenticator.
+pub trait AssertionVerifier: Send + Sync + 'static {
+    fn verify(
+        &self,
+        rp_id: &str,
+        credential: &PublicKeyCredential,
+        state: &PasskeyAuthentication,
+    ) -> Result<(), String>;
+}
+
+/// Verifies assertions with `webauthn-rs`.
+pub struct WebauthnRs;
+
+impl AssertionVerifier for WebauthnRs {
+    fn verify(
+        &self,
+        rp_id: &str,
+        credential: &PublicKeyCredential,
+        state: &PasskeyAuthentication,
+    ) -> Result<(), String> {
+        relying_party(rp_id)?
+            .finish_passkey_authentication(credential, state)
+            .map(|_| ())
+            .map_err(|error| error.to_string())
+    }
+}
+
+/// The relying party for `rp_id`: pages served from `https://<rp_id>` or any
+/// of its subdomains.
+pub fn relying_party(rp_id: &str) -> Result<Webauthn, String> {
+    let origin = Url::parse(&format!("https://{rp_id}")).map_err(|error| error.to_string())?;
+    WebauthnBuilder::new(rp_id, &origin)
+        .and_then(|builder| {
+            builder
+                .rp_name("copypaste.fyi")
+                .allow_subdomains(true)
+                .build()
+        })
+        .map_err(|error| error.to_string())
+}
+
+struct Ceremony {
+    paste_id: String,
+    rp_id: String,
+    state: PasskeyAuthentication,
+    expires_at: i64,
+}
+
+/// Pending ceremonies plus the grant signing secret, kept on Rocket managed
+/// state.
+pub struct Passkeys {
+    secret: Vec<u8>,
+    verifier: Box<dyn AssertionVerifier>,
+    pending: Mutex<HashMap<String, Ceremony>>,
+}
+
+impl Passkeys {
+    pub fn new(secret: Vec<u8>, verifier: Box<dyn AssertionVerifier>) -> Self {
+        Self {
+            secret,
+            verifier,
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Build from `COPYPASTE_WEBAUTHN_TOKEN_SECRET`, falling back to a random
+    /// 32-byte secret.
+    pub fn from_env() -> Self {
+        let secret = hmac_secret_from_env("COPYPASTE_WEBAUTHN_TOKEN_SECRET");
+        Self::new(secret, Box::new(WebauthnRs))
+    }
+
+    /// Start an assertion ceremony for paste `paste_id` against `passkeys`.
+    /// Returns the challenge id to answer with and the options to hand to
+    /// `navigator.credentials.get()`.
+    pub fn challenge(
+        &self,
+        paste_id: &str,
+        rp_id: &str,
+        passkeys: &[Passkey],
+        now: i64,
+    ) -> Result<(String, RequestChallengeResponse), PasskeyError> {
+        let (options, state) = relying_party(rp_id)
+            .map_err(PasskeyError::Setup)?
+            .start_passkey_authentication(passkeys)
+            .map_err(|error| PasskeyError::Setup(error.to_string()))?;
+
+        let mut id_bytes = [0u8; 16];
+        OsRng.fill_bytes(&mut id_bytes);
+        let challenge_id = hex::encode(id_bytes);
+
+        let mut pending = self.pending.lock().unwrap();
+        pending.retain(|_, ceremony| ceremony.expires_at > now);
+        pending.insert(
+            challenge_id.clone(),
+            Ceremony {
+                paste_id: paste_id.to_string(),
+                rp_id: rp_id.to_string(),
+                state,
+                expires_at: now + CEREMONY_TTL_SECS,
+            },
+        );
+        Ok((challenge_id, options))
+    }
+
+    /// Answer challenge `challenge_id` for paste `paste_id`. The challenge is
+    /// used up whatever the outcome; a verified assertion returns a grant
+    /// token and its expiry.
+    pub fn verify(
+        &self,
+        paste_id: &str,
+        challenge_id: &str,
+        credential: &PublicKeyCredential,
+        now: i64,
+    ) -> Result<(String, i64), PasskeyError> {
+        let ceremony = self
+            .pending
+            .lock()
+            .unwrap()
+            .remove(challenge_id)
+            .filter(|ceremony| ceremony.paste_id == paste_id && ceremony.expires_at > now)
+            .ok_or(PasskeyError::UnknownChallenge)?;
+        self.verifier
+            .verify(&ceremony.rp_id, credential, &ceremony.state)
+            .map_err(PasskeyError::Rejected)?;
+
+        let expires_at = now + GRANT_TTL_SECS;
+        let token = format!(
+            "{expires_at}.{}",
+            URL_SAFE_NO_PAD.encode(self.sign(paste_id, expires_at))
+        );
+        Ok((token, expires_at))
+    }
+
+    /// Check the grant token a read of `paste_id` presented, if any.
+    pub fn proof(&self, token: Option<&str>, paste_id: &str, now: i64) -> PasskeyProof {
+        let Some(token) = token else {
+            return PasskeyProof::Missing;
+        };
+        let Some((expires_at, signature)) = token.split_once('.') else {
+            return PasskeyProof::Invalid;
+        };
+        let (Ok(expires_at), Ok(signature)) =
+            (expires_at.parse::<i64>(), URL_SAFE_NO_PAD.decode(signature))
+        else {
+            return PasskeyProof::Invalid;
+        };
+        if expires_at > now && bool::from(self.sign(paste_id, expires_at).ct_eq(&signature)) {
+            PasskeyProof::Verified
+        } else {
+            PasskeyProof::Invalid
+        }
+    }
+
+    fn sign(&self, paste_id: &str, expires_at: i64) -> Vec<u8> {
+        let mut mac =
+            <HmacSha256 as Mac>::new_from_slice(&self.secret).expect("HMAC accepts any key size");
+        mac.update(format!("{paste_id}.{expires_at}").as_bytes());
+        mac.finalize().into_bytes().to_vec()
+    }
+}
+
+/// A serialized `webauthn-rs` passkey with a real P-256 public key, for
+/// tests that need one without running a registration ceremony.
+#[cfg(test)]
+pub(crate) const TEST_PASSKEY: &str = r#"{"cred":{"cred_id":"uZcVDBVS68E_MtAgeQpElJxldF_6cY9sSvbWqx_qRh8wiu42lyRBRmh5yFeD_r9k130dMbFHBHI9RTFgdJQIzQ","cred":{"type_":"ES256","key":{"EC_EC2":{"curve":"SECP256R1","x":[194,126,127,109,252,23,131,21,252,6,223,99,44,254,140,27,230,17,94,5,133,28,104,41,144,69,171,149,161,26,200,243],"y":[143,123,183,156,24,178,21,248,117,159,162,69,171,52,188,252,26,59,6,47,103,92,19,58,117,103,249,0,219,8,95,196]}}},"counter":0,"transports":null,"user_verified":true,"backup_eligible":false,"backup_state":false,"registration_policy":"required","extensions":{"cred_protect":"NotRequested","hmac_create_secret":"NotRequested"},"attestation":{"data":"None","metadata":"None"},"attestation_format":"None"}}"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Stub(Result<(), String>);
+
+    impl AssertionVerifier for Stub {
+        fn verify(
+            &self,
+            _rp_id: &str,
+            _credential: &PublicKeyCredential,
+            _state: &PasskeyAuthentication,
+        ) -> Result<(), String> {
+            self.0.clone()
+        }
+    }
+
+    fn passkeys(outcome: Result<(), String>) -> Passkeys {
+        Passkeys::new(b"secret".to_vec(), Box::new(Stub(outcome)))
+    }
+
+    fn passkey() -> Passkey {
+        serde_json::from_str(TEST_PASSKEY).expect("fixture passkey")
+    }
+
+    fn assertion() -> PublicKeyCredential {
+        serde_json::from_value(serde_json::json!({
+            "id": "uZcVDBVS68E_MtAgeQpElJxldF_6cY9sSvbWqx_qRh8wiu42lyRBRmh5yFeD_r9k130dMbFHBHI9RTFgdJQIzQ",
+            "rawId": "uZcVDBVS68E_MtAgeQpElJxldF_6cY9sSvbWqx_qRh8wiu42lyRBRmh5yFeD_r9k130dMbFHBHI9RTFgdJQIzQ",
+            "response": {
+                "authenticatorData": "AAAA",
+                "clientDataJSON": "e30",
+                "signature": "AAAA",
+                "userHandle": null
+            },
+            "type": "public-key"
+        }))
+        .expect("assertion shape")
+    }
+
+    #[test]
+    fn challenge_lists_the_paste_passkeys() {
+        let passkeys = passkeys(Ok(()));
+        let (challenge_id, options) = passkeys
+            .challenge("abc123", "copypaste.fyi", &[passkey()], 1_000)
+            .expect("challenge");
+        assert_eq!(challenge_id.len(), 32);
+
+        let options = serde_json::to_value(&options).unwrap();
+        assert_eq!(options["publicKey"]["rpId"], "copypaste.fyi");
+        assert_eq!(
+            options["publicKey"]["allowCredentials"][0]["id"],
+            "uZcVDBVS68E_MtAgeQpElJxldF_6cY9sSvbWqx_qRh8wiu42lyRBRmh5yFeD_r9k130dMbFHBHI9RTFgdJQIzQ"
+        );
+        assert!(passkeys
+            .challenge("abc123", "not a domain", &[passkey()], 1_000)
+            .is_err());
+    }
+
+    #[test]
+    fn verified_assertion_grants_the_paste_once() {
+        let passkeys = passkeys(Ok(()));
+        let (challenge_id, _) = passkeys
+            .challenge("abc123", "copypaste.fyi", &[passkey()], 1_000)
+            .unwrap();
+
+        let (token, expires_at) = passkeys
+            .verify("abc123", &challenge_id, &assertion(), 1_010)
+            .expect("stub accepts");
+        assert_eq!(expires_at, 1_010 + GRANT_TTL_SECS);
+        assert_eq!(
+            passkeys.verify("abc123", &challenge_id, &assertion(), 1_020),
+            Err(PasskeyError::UnknownChallenge)
+        );
+
+        assert_eq!(
+            passkeys.proof(Some(&token), "abc123", 1_020),
+            PasskeyProof::Verified
+        );
+        assert_eq!(
+            passkeys.proof(Some(&token), "other", 1_020),
+            PasskeyProof::Invalid
+        );
+        assert_eq!(
+            passkeys.proof(Some(&token), "abc123", expires_at),
+            PasskeyProof::Invalid
+        );
+        assert_eq!(
+            passkeys.proof(Some("1.AAAA"), "abc123", 0),
+            PasskeyProof::Invalid
+        );
+        assert_eq!(passkeys.proof(None, "abc123", 0), PasskeyProof::Missing);
+    }
+
+    #[test]
+    fn rejected_or_misdirected_assertions_grant_nothing() {
+        let rejecting = passkeys(Err("bad signature".into()));
+        let (challenge_id, _) = rejecting
+            .challenge("abc123", "copypaste.fyi", &[passkey()], 1_000)
+            .unwrap();
+        assert_eq!(
+            rejecting.verify("abc123", &challenge_id, &assertion(), 1_010),
+            Err(PasskeyError::Rejected("bad signature".into()))
+        );
+
+        let accepting = passkeys(Ok(()));
+        let (challenge_id, _) = accepting
+            .challenge("abc123", "copypaste.fyi", &[passkey()], 1_000)
+            .unwrap();
+        assert_eq!(
+            accepting.verify("other", &challenge_id, &assertion(), 1_010),
+            Err(PasskeyError::UnknownChallenge)
+        );
+        let (challenge_id, _) = accepting
+            .challenge("abc123", "copypaste.fyi", &[passkey()], 1_000)
+            .unwrap();
+        assert_eq!(
+            accepting.verify(
+                "abc123",
+                &challenge_id,
+                &assertion(),
+                1_000 + CEREMONY_TTL_SECS
+            ),
+            Err(PasskeyError::UnknownChallenge)
+        );
+    }
+
+    #[test]
+    fn real_verifier_rejects_a_forged_assertion() {
+        let passkeys = Passkeys::new(b"secret".to_vec(), Box::new(WebauthnRs));
+        let (challenge_id, _) = passkeys
+            .challenge("abc123", "copypaste.fyi", &[passkey()], 1_000)
+            .unwrap();
+        assert!(matches!(
+            passkeys.verify("abc123", &challenge_id, &assertion(), 1_010),
+            Err(PasskeyError::Rejected(_))
+        ));
+    }
+}
@@ -1,6 +1,10 @@
-use std::net::IpAddr;
-use std::time::Duration;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use hmac::{Hmac, Mac};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use sha2::Sha256;
 use url::{Host, Url};
 
 use crate::{WebhookConfig, WebhookProvider};
@@ -15,86 +19,231 @@ pub struct WebhookClient(pub reqwest::Client);
 
 impl WebhookClient {
     /// Build the shared client with a 5 s connect timeout and a 10 s overall
-    /// request timeout.
-    ///
+    /// request timeout, guarded by [`WebhookTargets::from_env`].
+    pub fn new() -> Self {
+        Self::with_targets(WebhookTargets::from_env())
+    }
+
     /// Redirects are disabled entirely: webhook URLs are validated against
     /// internal/private address ranges at paste-creation time, and following a
     /// redirect would let an attacker-controlled public URL 302 the request
     /// back into the internal network (SSRF).
-    pub fn new() -> Self {
+    ///
+    /// Host names are resolved through [`GuardedResolver`], so a name that
+    /// passed validation but has since been re-pointed at an internal address
+    /// (DNS rebinding) fails to connect instead of reaching it.
+    pub fn with_targets(targets: WebhookTargets) -> Self {
         let client = reqwest::Client::builder()
             .connect_timeout(Duration::from_secs(5))
             .timeout(Duration::from_secs(10))
             .redirect(reqwest::redirect::Policy::none())
+            .dns_resolver(Arc::new(GuardedResolver(Arc::new(targets))))
             .build()
             .expect("failed to build webhook HTTP client");
         WebhookClient(client)
     }
 }
 
-/// Validate a user-supplied webhook URL to prevent SSRF.
-///
-/// Rejects:
-/// - non-http(s) schemes,
-/// - IP-literal hosts in loopback / private / link-local / unique-local /
-///   unspecified ranges (127.0.0.0/8, 10/8, 172.16/12, 192.168/16, 169.254/16,
-///   0.0.0.0, ::1, ::, fc00::/7, fe80::/10, and IPv4-mapped equivalents),
-/// - hostnames that plainly target internal infrastructure (`localhost`,
-///   `*.localhost`, `*.internal`, `*.local`).
+/// Which destinations webhooks may be delivered to.
 ///
-/// The shared [`WebhookClient`] additionally disables redirects so a public
-/// URL cannot bounce the delivery into the internal network at send time.
+/// Only `https://` URLs are accepted unless `COPYPASTE_WEBHOOK_ALLOW_HTTP` is
+/// set. `COPYPASTE_WEBHOOK_ALLOWLIST` is a comma-separated list of host names
+/// and IP addresses exempt from the internal-address checks, for receivers
+/// that deliberately live on the private network.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WebhookTargets {
+    pub allow_http: bool,
+    pub allowlist: Vec<String>,
+}
+
+impl WebhookTargets {
+    pub fn from_env() -> Self {
+        let allow_http = std::env::var("COPYPASTE_WEBHOOK_ALLOW_HTTP")
+            .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "on"))
+            .unwrap_or(false);
+        let allowlist = std::env::var("COPYPASTE_WEBHOOK_ALLOWLIST")
+            .map(|v| {
+                v.split(',')
+                    .map(normalize_host)
+                    .filter(|entry| !entry.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            allow_http,
+            allowlist,
+        }
+    }
+
+    fn is_allowlisted(&self, host: &str) -> bool {
+        self.allowlist.contains(&normalize_host(host))
+    }
+
+    /// Checks that need no network access.
+    ///
+    /// Rejects:
+    /// - schemes other than https (and http when allowed),
+    /// - IP-literal hosts in loopback / private / link-local / unique-local /
+    ///   shared / multicast / unspecified ranges (127.0.0.0/8, 10/8,
+    ///   172.16/12, 192.168/16, 169.254/16 including the cloud metadata
+    ///   address, 100.64/10, 0.0.0.0, ::1, ::, fc00::/7, fe80::/10, and
+    ///   IPv4-mapped equivalents),
+    /// - hostnames that plainly target internal infrastructure (`localhost`,
+    ///   `*.localhost`, `*.internal`, `*.local`).
+    pub fn check_url(&self, raw: &str) -> Result<Url, String> {
+        let url =
+            Url::parse(raw.trim()).map_err(|e| format!("Webhook url is not a valid URL: {e}"))?;
+
+        match url.scheme() {
+            "https" => {}
+            "http" if self.allow_http => {}
+            "http" => return Err("Webhook url must use https".to_string()),
+            other => {
+                return Err(format!(
+                    "Webhook url scheme must be http or https, got '{other}'"
+                ))
+            }
+        }
+
+        let host = url
+            .host_str()
+            .ok_or_else(|| "Webhook url must include a host".to_string())?;
+        if self.is_allowlisted(host) {
+            return Ok(url);
+        }
+        match url.host() {
+            Some(Host::Ipv4(ip)) => self.check_addrs(host, [IpAddr::V4(ip)])?,
+            Some(Host::Ipv6(ip)) => self.check_addrs(host, [IpAddr::V6(ip)])?,
+            Some(Host::Domain(domain)) if is_forbidden_hostname(domain) => {
+                return Err(format!(
+                    "Webhook url must not target an internal hostname ('{domain}')"
+                ))
+            }
+            _ => {}
+        }
+        Ok(url)
+    }
+
+    /// [`check_url`](Self::check_url), then every address the host currently
+    /// resolves to. A host that does not resolve (yet) passes; the delivery
+    /// client resolves it again through [`GuardedResolver`] anyway.
+    pub async fn check_url_resolved(&self, raw: &str) -> Result<(), String> {
+        let url = self.check_url(raw)?;
+        let (Some(host), Some(port)) = (url.host_str(), url.port_or_known_default()) else {
+            return Ok(());
+        };
+        if self.is_allowlisted(host) {
+            return Ok(());
+        }
+        match tokio::net::lookup_host(format!("{host}:{port}")).await {
+            Ok(addrs) => self.check_addrs(host, addrs.map(|addr| addr.ip())),
+            Err(err) => {
+                log::debug!("webhook host {host} did not resolve at creation: {err}");
+                Ok(())
+            }
+        }
+    }
+
+    /// Reject `host` if any of its addresses is internal and neither the host
+    /// nor that address is allowlisted.
+    fn check_addrs(
+        &self,
+        host: &str,
+        addrs: impl IntoIterator<Item = IpAddr>,
+    ) -> Result<(), String> {
+        if self.is_allowlisted(host) {
+            return Ok(());
+        }
+        for ip in addrs {
+            if is_forbidden_ip(ip) && !self.is_allowlisted(&ip.to_string()) {
+                return Err(format!(
+                    "Webhook url must not target a private, loopback, or link-local address ({ip})"
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Validate a user-supplied webhook URL against [`WebhookTargets::from_env`]
+/// to prevent SSRF.
 pub fn validate_webhook_url(raw: &str) -> Result<(), String> {
-    let url = Url::parse(raw.trim()).map_err(|e| format!("Webhook url is not a valid URL: {e}"))?;
-
-    match url.scheme() {
-        "http" | "https" => {}
-        other => {
-            return Err(format!(
-                "Webhook url scheme must be http or https, got '{other}'"
-            ))
-        }
-    }
-
-    match url.host() {
-        None => Err("Webhook url must include a host".to_string()),
-        Some(Host::Ipv4(ip)) if is_forbidden_ip(IpAddr::V4(ip)) => Err(format!(
-            "Webhook url must not target a private, loopback, or link-local address ({ip})"
-        )),
-        Some(Host::Ipv6(ip)) if is_forbidden_ip(IpAddr::V6(ip)) => Err(format!(
-            "Webhook url must not target a private, loopback, or link-local address ({ip})"
-        )),
-        Some(Host::Domain(domain)) if is_forbidden_hostname(domain) => Err(format!(
-            "Webhook url must not target an internal hostname ('{domain}')"
-        )),
-        Some(_) => Ok(()),
+    WebhookTargets::from_env().check_url(raw).map(|_| ())
+}
+
+/// DNS resolver for the webhook client that refuses internal addresses.
+///
+/// This is the send-time half of the SSRF guard: it runs on every
+/// connection, after any re-resolution an attacker could have arranged.
+struct GuardedResolver(Arc<WebhookTargets>);
+
+impl Resolve for GuardedResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let targets = self.0.clone();
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> =
+                tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            targets.check_addrs(&host, addrs.iter().map(|addr| addr.ip()))?;
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
     }
 }
 
+fn normalize_host(host: &str) -> String {
+    host.trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .trim_end_matches('.')
+        .to_ascii_lowercase()
+}
+
 fn is_forbidden_ip(ip: IpAddr) -> bool {
     match ip {
         IpAddr::V4(v4) => {
+            let [a, b, c, _] = v4.octets();
             v4.is_loopback()
                 || v4.is_private()
                 || v4.is_link_local()
                 || v4.is_unspecified()
                 || v4.is_broadcast()
+                || v4.is_multicast()
+                || (a == 100 && (b & 0xc0) == 64) // 100.64.0.0/10 shared address space
+                || (a == 192 && b == 0 && c == 0) // 192.0.0.0/24 IETF protocol assignments
+                || (a == 198 && (b & 0xfe) == 18) // 198.18.0.0/15 benchmarking
+                || (a & 0xf0) == 240 // 240.0.0.0/4 reserved
         }
         IpAddr::V6(v6) => {
-            if let Some(mapped) = v6.to_ipv4_mapped() {
-                return is_forbidden_ip(IpAddr::V4(mapped));
+            if v6.is_loopback() || v6.is_unspecified() {
+                return true;
+            }
+            if let Some(embedded) = embedded_ipv4(v6) {
+                return is_forbidden_ip(IpAddr::V4(embedded));
             }
             let first_segment = v6.segments()[0];
             v6.is_loopback()
                 || v6.is_unspecified()
+                || v6.is_multicast()
                 || (first_segment & 0xfe00) == 0xfc00 // fc00::/7 unique local
                 || (first_segment & 0xffc0) == 0xfe80 // fe80::/10 link local
         }
     }
 }
 
+/// The IPv4 address carried by an IPv4-mapped (`::ffff:a.b.c.d`), NAT64
+/// (`64:ff9b::a.b.c.d`) or IPv4-compatible (`::a.b.c.d`) IPv6 address.
+fn embedded_ipv4(v6: Ipv6Addr) -> Option<Ipv4Addr> {
+    let [.., a, b, c, d] = v6.octets();
+    match v6.segments() {
+        [0, 0, 0, 0, 0, 0xffff, ..] | [0x64, 0xff9b, 0, 0, 0, 0, ..] | [0, 0, 0, 0, 0, 0, ..] => {
+            Some(Ipv4Addr::new(a, b, c, d))
+        }
+        _ => None,
+    }
+}
+
 fn is_forbidden_hostname(domain: &str) -> bool {
-    let normalized = domain.trim_end_matches('.').to_ascii_lowercase();
+    let normalized = normalize_host(domain);
     normalized == "localhost"
         || normalized.ends_with(".localhost")
         || normalized.ends_with(".internal")
@@ -107,10 +256,114 @@ impl Default for WebhookClient {
     }
 }
 
+/// Header carrying the 1-based delivery attempt, so receivers can tell a
+/// retry from a new event.
+pub const ATTEMPT_HEADER: &str = "X-Webhook-Attempt";
+
+/// `sha256=<hex>` HMAC of the signed string; see [`sign_payload`].
+pub const SIGNATURE_HEADER: &str = "X-Copypaste-Signature";
+/// Unix seconds at which the delivery was signed.
+pub const TIMESTAMP_HEADER: &str = "X-Copypaste-Timestamp";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Compute the `X-Copypaste-Signature` value for a delivery.
+///
+/// The signed string is `"{timestamp}.{body}"`: the decimal value of
+/// `X-Copypaste-Timestamp`, a literal `.`, then the exact request body bytes.
+/// Receivers should recompute it with their secret, compare in constant time
+/// and reject timestamps too far from their own clock to stop replays.
+pub fn sign_payload(secret: &[u8], timestamp: i64, body: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Per-webhook secret, falling back to `COPYPASTE_WEBHOOK_SECRET`.
+fn signing_secret(config: &WebhookConfig) -> Option<String> {
+    config.secret.clone().or_else(|| {
+        std::env::var("COPYPASTE_WEBHOOK_SECRET")
+            .ok()
+            .filter(|s| !s.is_empty())
+    })
+}
+
+/// Bounded exponential backoff for webhook delivery.
+///
+/// `COPYPASTE_WEBHOOK_RETRIES` (default 3) sets how many times a failed
+/// delivery is retried and `COPYPASTE_WEBHOOK_RETRY_BASE_MS` (default 1000)
+/// the first delay, doubled before each further retry (1 s, 2 s, 4 s).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub retries: u32,
+    pub base_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            retries: 3,
+            base_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        let parse = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .and_then(|value| value.trim().parse::<u64>().ok())
+        };
+        Self {
+            retries: parse("COPYPASTE_WEBHOOK_RETRIES")
+                .map(|n| n.min(10) as u32)
+                .unwrap_or(default.retries),
+            base_delay: parse("COPYPASTE_WEBHOOK_RETRY_BASE_MS")
+                .map(Duration::from_millis)
+                .unwrap_or(default.base_delay),
+        }
+    }
+
+    /// Delay before retry number `retry` (1-based).
+    fn delay(&self, retry: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(1u32.checked_shl(retry - 1).unwrap_or(u32::MAX))
+    }
+}
+
+/// Connection failures and 5xx responses may succeed later; anything else,
+/// notably a 4xx, will not.
+fn is_retryable(error: &reqwest::Error) -> bool {
+    error.is_connect()
+        || error
+            .status()
+            .is_some_and(|status| status.is_server_error())
+}
+
 #[derive(Clone, Copy)]
 pub enum WebhookEvent {
     Viewed,
     Consumed,
+    /// Removed by the sweeper after it had been read.
+    Expired,
+    /// Removed by the sweeper without ever being read.
+    ExpiredUnread,
+}
+
+impl WebhookEvent {
+    /// The event for a paste the sweeper removed after `access_count`
+    /// successful reads.
+    pub fn expired(access_count: u64) -> Self {
+        if access_count == 0 {
+            Self::ExpiredUnread
+        } else {
+            Self::Expired
+        }
+    }
 }
 
 pub fn trigger_webhook(
@@ -121,35 +374,81 @@ pub fn trigger_webhook(
     bundle_label: Option<String>,
 ) {
     let id = paste_id.to_string();
+    // Re-check at send time: the allowlist or scheme policy may have been
+    // tightened since the paste was created.
+    if let Err(reason) = WebhookTargets::from_env().check_url(&config.url) {
+        log::warn!("webhook for paste {id} refused: {reason}");
+        return;
+    }
+    let policy = RetryPolicy::from_env();
+    let secret = signing_secret(&config);
     tokio::spawn(async move {
-        if let Err(err) = send_webhook(&client, config, event, id, bundle_label).await {
-            eprintln!("webhook dispatch failed: {err}");
+        match send_webhook(&client, config, event, &id, bundle_label, policy, secret).await {
+            Ok(attempts) => {
+                log::info!("webhook for paste {id} delivered after {attempts} attempt(s)")
+            }
+            Err(err) => log::warn!("webhook for paste {id} failed permanently: {err}"),
         }
     });
 }
 
+/// Deliver one event, retrying per `policy` and signing every attempt when a
+/// `secret` is given. Returns the number of attempts the successful delivery
+/// took.
 async fn send_webhook(
     client: &reqwest::Client,
     config: WebhookConfig,
     event: WebhookEvent,
-    paste_id: String,
+    paste_id: &str,
     bundle_label: Option<String>,
-) -> Result<(), reqwest::Error> {
-    let message = resolve_webhook_message(&config, event, &paste_id, bundle_label.as_deref());
+    policy: RetryPolicy,
+    secret: Option<String>,
+) -> Result<u32, reqwest::Error> {
+    let message = resolve_webhook_message(&config, event, paste_id, bundle_label.as_deref());
     let payload = match config.provider {
         Some(WebhookProvider::Slack) | Some(WebhookProvider::Generic) | None => {
             serde_json::json!({ "text": message })
         }
         Some(WebhookProvider::Teams) => serde_json::json!({ "text": message }),
     };
+    let body = serde_json::to_vec(&payload).expect("webhook payload serializes");
 
-    client
-        .post(&config.url)
-        .json(&payload)
-        .send()
-        .await?
-        .error_for_status()?;
-    Ok(())
+    let mut attempt = 1;
+    loop {
+        let mut request = client
+            .post(&config.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(ATTEMPT_HEADER, attempt.to_string());
+        if let Some(secret) = secret.as_deref() {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or_default();
+            request = request
+                .header(TIMESTAMP_HEADER, timestamp.to_string())
+                .header(
+                    SIGNATURE_HEADER,
+                    sign_payload(secret.as_bytes(), timestamp, &body),
+                );
+        }
+        let result = request
+            .body(body.clone())
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        match result {
+            Ok(_) => return Ok(attempt),
+            Err(err) if attempt <= policy.retries && is_retryable(&err) => {
+                let delay = policy.delay(attempt);
+                log::warn!(
+                    "webhook for paste {paste_id} attempt {attempt} failed ({err}); retrying in {delay:?}"
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    }
 }
 
 fn resolve_webhook_message(
@@ -161,6 +460,7 @@ fn resolve_webhook_message(
     let template = match event {
         WebhookEvent::Viewed => config.view_template.as_deref(),
         WebhookEvent::Consumed => config.burn_template.as_deref(),
+        WebhookEvent::Expired | WebhookEvent::ExpiredUnread => None,
     };
 
     let default = match event {
@@ -178,6 +478,8 @@ fn resolve_webhook_message(
                 format!("Paste {paste_id} self-destructed")
             }
         }
+        WebhookEvent::Expired => format!("Paste {paste_id} expired"),
+        WebhookEvent::ExpiredUnread => format!("Paste {paste_id} expired without being read"),
     };
 
     if let Some(tpl) = template {
@@ -188,6 +490,7 @@ fn resolve_webhook_message(
             match event {
                 WebhookEvent::Viewed => "viewed",
                 WebhookEvent::Consumed => "consumed",
+                WebhookEvent::Expired | WebhookEvent::ExpiredUnread => "expired",
             },
         )
     } else {
@@ -227,6 +530,7 @@ mod tests {
             provider: Some(WebhookProvider::Generic),
             view_template: None,
             burn_template: None,
+            secret: None,
         }
     }
 
@@ -287,6 +591,244 @@ mod tests {
         assert_eq!(rendered, "id");
     }
 
+    fn fast_retries(retries: u32) -> RetryPolicy {
+        RetryPolicy {
+            retries,
+            base_delay: Duration::from_millis(1),
+        }
+    }
+
+    fn mock_config(server: &httpmock::MockServer) -> WebhookConfig {
+        WebhookConfig {
+            url: server.url("/hook"),
+            ..base_config()
+        }
+    }
+
+    #[tokio::test]
+    async fn send_webhook_retries_server_errors_until_success() {
+        use httpmock::prelude::*;
+        let server = MockServer::start();
+        let unavailable = server.mock(|when, then| {
+            when.method(POST).path("/hook").matches(|req| {
+                request_header(req, ATTEMPT_HEADER).is_some_and(|attempt| attempt != "3")
+            });
+            then.status(503);
+        });
+        let ok = server.mock(|when, then| {
+            when.method(POST).path("/hook").header(ATTEMPT_HEADER, "3");
+            then.status(200);
+        });
+
+        let attempts = send_webhook(
+            &reqwest::Client::new(),
+            mock_config(&server),
+            WebhookEvent::Viewed,
+            "abc",
+            None,
+            fast_retries(3),
+            None,
+        )
+        .await
+        .expect("third attempt succeeds");
+
+        assert_eq!(attempts, 3);
+        unavailable.assert_hits(2);
+        ok.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn send_webhook_treats_client_errors_as_terminal() {
+        use httpmock::prelude::*;
+        let server = MockServer::start();
+        let gone = server.mock(|when, then| {
+            when.method(POST).path("/hook");
+            then.status(410);
+        });
+
+        let err = send_webhook(
+            &reqwest::Client::new(),
+            mock_config(&server),
+            WebhookEvent::Consumed,
+            "abc",
+            None,
+            fast_retries(3),
+            None,
+        )
+        .await
+        .expect_err("4xx is not retried");
+
+        assert_eq!(err.status().map(|s| s.as_u16()), Some(410));
+        gone.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn send_webhook_gives_up_after_the_retry_budget() {
+        use httpmock::prelude::*;
+        let server = MockServer::start();
+        let failing = server.mock(|when, then| {
+            when.method(POST).path("/hook");
+            then.status(500);
+        });
+
+        let result = send_webhook(
+            &reqwest::Client::new(),
+            mock_config(&server),
+            WebhookEvent::Viewed,
+            "abc",
+            None,
+            fast_retries(2),
+            None,
+        )
+        .await;
+
+        assert!(result.is_err());
+        failing.assert_hits(3);
+    }
+
+    const TEST_SECRET: &str = "whsec_test";
+
+    fn request_header(req: &httpmock::prelude::HttpMockRequest, name: &str) -> Option<String> {
+        req.headers.as_ref().and_then(|headers| {
+            headers
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.clone())
+        })
+    }
+
+    /// Recompute the HMAC from the raw bytes the mock server received.
+    fn carries_valid_signature(req: &httpmock::prelude::HttpMockRequest) -> bool {
+        let (Some(signature), Some(timestamp)) = (
+            request_header(req, SIGNATURE_HEADER),
+            request_header(req, TIMESTAMP_HEADER),
+        ) else {
+            return false;
+        };
+        let body = req.body.clone().unwrap_or_default();
+        let mut mac = HmacSha256::new_from_slice(TEST_SECRET.as_bytes()).unwrap();
+        mac.update(format!("{timestamp}.").as_bytes());
+        mac.update(&body);
+        let expected = format!("sha256={}", hex::encode(mac.finalize().into_bytes()));
+        signature == expected && serde_json::from_slice::<serde_json::Value>(&body).is_ok()
+    }
+
+    #[tokio::test]
+    async fn send_webhook_signs_the_exact_body_sent() {
+        use httpmock::prelude::*;
+        let server = MockServer::start();
+        let signed = server.mock(|when, then| {
+            when.method(POST)
+                .path("/hook")
+                .header("content-type", "application/json")
+                .matches(carries_valid_signature);
+            then.status(200);
+        });
+
+        send_webhook(
+            &reqwest::Client::new(),
+            mock_config(&server),
+            WebhookEvent::Viewed,
+            "abc",
+            None,
+            fast_retries(0),
+            Some(TEST_SECRET.to_string()),
+        )
+        .await
+        .expect("signed delivery accepted");
+
+        signed.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn expiry_delivery_only_claims_unread_pastes_were_unread() {
+        use httpmock::prelude::*;
+        let server = MockServer::start();
+        let read = server.mock(|when, then| {
+            when.method(POST)
+                .path("/hook")
+                .json_body(serde_json::json!({ "text": "Paste read expired" }));
+            then.status(200);
+        });
+        let unread = server.mock(|when, then| {
+            when.method(POST).path("/hook").json_body(
+                serde_json::json!({ "text": "Paste unread expired without being read" }),
+            );
+            then.status(200);
+        });
+
+        for (id, access_count) in [("read", 3), ("unread", 0)] {
+            send_webhook(
+                &reqwest::Client::new(),
+                mock_config(&server),
+                WebhookEvent::expired(access_count),
+                id,
+                None,
+                fast_retries(0),
+                None,
+            )
+            .await
+            .expect("expiry delivery accepted");
+        }
+
+        read.assert_hits(1);
+        unread.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn send_webhook_omits_signature_without_a_secret() {
+        use httpmock::prelude::*;
+        let server = MockServer::start();
+        let unsigned = server.mock(|when, then| {
+            when.method(POST).path("/hook").matches(|req| {
+                request_header(req, SIGNATURE_HEADER).is_none()
+                    && request_header(req, TIMESTAMP_HEADER).is_none()
+            });
+            then.status(200);
+        });
+
+        send_webhook(
+            &reqwest::Client::new(),
+            mock_config(&server),
+            WebhookEvent::Viewed,
+            "abc",
+            None,
+            fast_retries(0),
+            None,
+        )
+        .await
+        .expect("unsigned delivery accepted");
+
+        unsigned.assert_hits(1);
+    }
+
+    #[test]
+    fn per_webhook_secret_overrides_the_global_one() {
+        let config = WebhookConfig {
+            secret: Some("own".into()),
+            ..base_config()
+        };
+        assert_eq!(signing_secret(&config).as_deref(), Some("own"));
+    }
+
+    #[test]
+    fn sign_payload_covers_timestamp_and_body() {
+        let a = sign_payload(b"k", 1, b"{}");
+        assert!(a.starts_with("sha256="));
+        assert_eq!(a.len(), "sha256=".len() + 64);
+        assert_ne!(a, sign_payload(b"k", 2, b"{}"));
+        assert_ne!(a, sign_payload(b"k", 1, b"{ }"));
+        assert_ne!(a, sign_payload(b"other", 1, b"{}"));
+    }
+
+    #[test]
+    fn retry_delays_double_from_the_base() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.delay(1), Duration::from_secs(1));
+        assert_eq!(policy.delay(2), Duration::from_secs(2));
+        assert_eq!(policy.delay(3), Duration::from_secs(4));
+    }
+
     #[test]
     fn webhook_client_new_builds_successfully() {
         // Smoke-test that building the shared client does not panic.
@@ -299,10 +841,10 @@ mod tests {
     fn validate_accepts_public_urls() {
         for url in [
             "https://example.com/webhook",
-            "http://example.com",
             "https://hooks.slack.com/services/T000/B000/XXX",
-            "http://8.8.8.8/notify",
+            "https://8.8.8.8/notify",
             "https://[2606:4700:4700::1111]/hook",
+            "https://[64:ff9b::8.8.8.8]/hook",
         ] {
             assert!(validate_webhook_url(url).is_ok(), "should accept {url}");
         }
@@ -328,15 +870,21 @@ mod tests {
     #[test]
     fn validate_rejects_private_ipv4_ranges() {
         for url in [
-            "http://127.0.0.1/hook",
-            "http://127.8.9.10:8080/hook",
-            "http://10.0.0.1/hook",
-            "http://172.16.5.5/hook",
-            "http://172.31.255.255/hook",
-            "http://192.168.1.1/hook",
-            "http://169.254.169.254/latest/meta-data",
-            "http://0.0.0.0/hook",
-            "http://255.255.255.255/hook",
+            "https://127.0.0.1/hook",
+            "https://127.8.9.10:8080/hook",
+            "https://10.0.0.1/hook",
+            "https://172.16.5.5/hook",
+            "https://172.31.255.255/hook",
+            "https://192.168.1.1/hook",
+            "https://169.254.169.254/latest/meta-data",
+            "https://0.0.0.0/hook",
+            "https://255.255.255.255/hook",
+            "https://100.100.100.200/latest/meta-data",
+            "https://224.0.0.1/hook",
+            "https://192.0.0.8/hook",
+            "https://198.18.0.1/hook",
+            "https://198.19.255.254/hook",
+            "https://240.0.0.1/hook",
         ] {
             assert!(validate_webhook_url(url).is_err(), "should reject {url}");
         }
@@ -345,13 +893,17 @@ mod tests {
     #[test]
     fn validate_rejects_private_ipv6_ranges() {
         for url in [
-            "http://[::1]/hook",
-            "http://[::]/hook",
-            "http://[fc00::1]/hook",
-            "http://[fd12:3456::1]/hook",
-            "http://[fe80::1]/hook",
-            "http://[::ffff:127.0.0.1]/hook",
-            "http://[::ffff:10.0.0.1]/hook",
+            "https://[::1]/hook",
+            "https://[::]/hook",
+            "https://[fc00::1]/hook",
+            "https://[fd12:3456::1]/hook",
+            "https://[fe80::1]/hook",
+            "https://[::ffff:127.0.0.1]/hook",
+            "https://[::ffff:10.0.0.1]/hook",
+            "https://[64:ff9b::127.0.0.1]/hook",
+            "https://[64:ff9b::a9fe:a9fe]/hook",
+            "https://[::127.0.0.1]/hook",
+            "https://[::192.168.1.1]/hook",
         ] {
             assert!(validate_webhook_url(url).is_err(), "should reject {url}");
         }
@@ -360,13 +912,13 @@ mod tests {
     #[test]
     fn validate_rejects_internal_hostnames() {
         for url in [
-            "http://localhost/hook",
-            "http://localhost:9200/hook",
-            "http://LOCALHOST/hook",
-            "http://foo.localhost/hook",
-            "http://metadata.internal/computeMetadata",
-            "http://printer.local/hook",
-            "http://db.internal./hook",
+            "https://localhost/hook",
+            "https://localhost:9200/hook",
+            "https://LOCALHOST/hook",
+            "https://foo.localhost/hook",
+            "https://metadata.internal/computeMetadata",
+            "https://printer.local/hook",
+            "https://db.internal./hook",
         ] {
             assert!(validate_webhook_url(url).is_err(), "should reject {url}");
         }
@@ -383,4 +935,106 @@ mod tests {
             assert!(validate_webhook_url(url).is_ok(), "should accept {url}");
         }
     }
+
+    #[test]
+    fn plain_http_is_refused_unless_allowed() {
+        let err = WebhookTargets::default()
+            .check_url("http://example.com/hook")
+            .unwrap_err();
+        assert!(err.contains("https"), "{err}");
+
+        let lenient = WebhookTargets {
+            allow_http: true,
+            ..Default::default()
+        };
+        assert!(lenient.check_url("http://example.com/hook").is_ok());
+        assert!(lenient.check_url("http://169.254.169.254/").is_err());
+        assert!(lenient.check_url("ftp://example.com/").is_err());
+    }
+
+    #[test]
+    fn allowlist_exempts_listed_hosts_and_addresses() {
+        let targets = WebhookTargets {
+            allow_http: false,
+            allowlist: vec!["localhost".into(), "10.0.0.7".into(), "::1".into()],
+        };
+        for url in [
+            "https://localhost:8443/hook",
+            "https://LOCALHOST./hook",
+            "https://10.0.0.7/hook",
+            "https://[::1]/hook",
+        ] {
+            assert!(targets.check_url(url).is_ok(), "should accept {url}");
+        }
+        for url in ["https://10.0.0.8/hook", "https://169.254.169.254/"] {
+            assert!(targets.check_url(url).is_err(), "should reject {url}");
+        }
+        assert!(targets.check_url("http://localhost/hook").is_err());
+    }
+
+    #[test]
+    fn resolved_addresses_decide_for_host_names() {
+        let targets = WebhookTargets::default();
+        let public: IpAddr = "93.184.215.14".parse().unwrap();
+        let private: IpAddr = "10.1.2.3".parse().unwrap();
+        assert!(targets.check_addrs("hooks.example.com", [public]).is_ok());
+        // One internal record is enough: the client may pick any of them.
+        assert!(targets
+            .check_addrs("rebind.example.com", [public, private])
+            .is_err());
+
+        let allowing_address = WebhookTargets {
+            allowlist: vec!["10.1.2.3".into()],
+            ..Default::default()
+        };
+        assert!(allowing_address
+            .check_addrs("rebind.example.com", [private])
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn creation_check_resolves_the_host() {
+        let targets = WebhookTargets::default();
+        assert!(targets
+            .check_url_resolved("https://8.8.8.8/hook")
+            .await
+            .is_ok());
+        assert!(targets
+            .check_url_resolved("https://169.254.169.254/latest/meta-data")
+            .await
+            .is_err());
+        assert!(targets
+            .check_url_resolved("https://10.20.30.40/hook")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn delivery_client_refuses_names_resolving_to_internal_addresses() {
+        use httpmock::prelude::*;
+        let server = MockServer::start();
+        let hook = server.mock(|when, then| {
+            when.method(POST).path("/hook");
+            then.status(200);
+        });
+        // `localhost` passes for a name that was public at creation time and
+        // now points at loopback.
+        let url = format!("http://localhost:{}/hook", server.port());
+
+        let guarded = WebhookClient::with_targets(WebhookTargets::default());
+        assert!(guarded.0.post(&url).send().await.is_err());
+        hook.assert_hits(0);
+
+        let allowlisted = WebhookClient::with_targets(WebhookTargets {
+            allow_http: true,
+            allowlist: vec!["localhost".into()],
+        });
+        allowlisted
+            .0
+            .post(&url)
+            .send()
+            .await
+            .expect("allowlisted host is reachable");
+        hook.assert_hits(1);
+    }
 }
@@ -0,0 +1,60 @@
+// Progressive enhancement for server-rendered paste pages: show <time>
+// values in the viewer's locale and count down to `data-expires`.
+(function () {
+  "use strict";
+
+  function remaining(seconds) {
+    if (seconds <= 0) {
+      return "expired";
+    }
+    var days = Math.floor(seconds / 86400);
+    var hours = Math.floor((seconds % 86400) / 3600);
+    var minutes = Math.floor((seconds % 3600) / 60);
+    var parts = [];
+    if (days) parts.push(days + "d");
+    if (days || hours) parts.push(hours + "h");
+    if (days || hours || minutes) parts.push(minutes + "m");
+    if (!days && !hours) parts.push((seconds % 60) + "s");
+    return "expires in " + parts.join(" ");
+  }
+
+  function init() {
+    var times = document.querySelectorAll("time[datetime]");
+    var countdowns = [];
+
+    times.forEach(function (el) {
+      var date = new Date(el.getAttribute("datetime"));
+      if (isNaN(date.getTime())) {
+        return;
+      }
+      el.title = el.textContent;
+      el.textContent = date.toLocaleString();
+
+      var expires = Number(el.getAttribute("data-expires"));
+      if (expires) {
+        var label = document.createElement("span");
+        label.className = "countdown";
+        el.insertAdjacentElement("afterend", label);
+        countdowns.push({ label: label, expires: expires });
+      }
+    });
+
+    if (!countdowns.length) {
+      return;
+    }
+    function tick() {
+      var now = Math.floor(Date.now() / 1000);
+      countdowns.forEach(function (c) {
+        c.label.textContent = " (" + remaining(c.expires - now) + ")";
+      });
+    }
+    tick();
+    setInterval(tick, 1000);
+  }
+
+  if (document.readyState === "loading") {
+    document.addEventListener("DOMContentLoaded", init);
+  } else {
+    init();
+  }
+})();
@@ -1,21 +1,33 @@
+use age::secrecy::ExposeSecret;
 use base64::Engine;
-use copypaste::server::crypto::decrypt_content;
-use copypaste::{EncryptionAlgorithm, StoredContent};
+use copypaste::server::crypto::{decrypt_content_sync, DecryptError, PasteBinding};
+use copypaste::{EncryptionAlgorithm, KdfKind, PasteFormat, StoredContent, ENVELOPE_V1};
+
+fn binding() -> PasteBinding<'static> {
+    PasteBinding::new("test-paste", PasteFormat::PlainText)
+}
 
 #[tokio::test]
 async fn encrypt_decrypt_roundtrip_aes_gcm() {
     let plaintext = "Hello, World! This is a test message.";
     let key = "test-key-12345678901234567890123456789012";
 
-    let encrypted =
-        copypaste::server::crypto::encrypt_content(plaintext, key, EncryptionAlgorithm::Aes256Gcm)
-            .await
-            .expect("encryption should succeed");
+    let encrypted = copypaste::server::crypto::encrypt_content(
+        plaintext,
+        key,
+        EncryptionAlgorithm::Aes256Gcm,
+        binding(),
+    )
+    .await
+    .expect("encryption should succeed");
 
     let StoredContent::Encrypted {
         ciphertext,
         nonce,
         salt,
+        kdf,
+        bound,
+        envelope_version,
         ..
     } = encrypted
     else {
@@ -27,9 +39,13 @@ async fn encrypt_decrypt_roundtrip_aes_gcm() {
         ciphertext,
         nonce,
         salt,
+        kdf,
+        bound,
+        envelope_version,
     };
 
-    let decrypted = decrypt_content(&stored_content, Some(key)).expect("decryption should succeed");
+    let decrypted = decrypt_content_sync(&stored_content, Some(key), binding())
+        .expect("decryption should succeed");
 
     assert_eq!(decrypted, plaintext);
 }
@@ -43,6 +59,7 @@ async fn encrypt_decrypt_roundtrip_chacha20() {
         plaintext,
         key,
         EncryptionAlgorithm::ChaCha20Poly1305,
+        binding(),
     )
     .await
     .expect("encryption should succeed");
@@ -51,6 +68,9 @@ async fn encrypt_decrypt_roundtrip_chacha20() {
         ciphertext,
         nonce,
         salt,
+        kdf,
+        bound,
+        envelope_version,
         ..
     } = encrypted
     else {
@@ -62,9 +82,13 @@ async fn encrypt_decrypt_roundtrip_chacha20() {
         ciphertext,
         nonce,
         salt,
+        kdf,
+        bound,
+        envelope_version,
     };
 
-    let decrypted = decrypt_content(&stored_content, Some(key)).expect("decryption should succeed");
+    let decrypted = decrypt_content_sync(&stored_content, Some(key), binding())
+        .expect("decryption should succeed");
 
     assert_eq!(decrypted, plaintext);
 }
@@ -78,6 +102,7 @@ async fn encrypt_decrypt_roundtrip_xchacha20() {
         plaintext,
         key,
         EncryptionAlgorithm::XChaCha20Poly1305,
+        binding(),
     )
     .await
     .expect("encryption should succeed");
@@ -86,6 +111,9 @@ async fn encrypt_decrypt_roundtrip_xchacha20() {
         ciphertext,
         nonce,
         salt,
+        kdf,
+        bound,
+        envelope_version,
         ..
     } = encrypted
     else {
@@ -97,9 +125,13 @@ async fn encrypt_decrypt_roundtrip_xchacha20() {
         ciphertext,
         nonce,
         salt,
+        kdf,
+        bound,
+        envelope_version,
     };
 
-    let decrypted = decrypt_content(&stored_content, Some(key)).expect("decryption should succeed");
+    let decrypted = decrypt_content_sync(&stored_content, Some(key), binding())
+        .expect("decryption should succeed");
 
     assert_eq!(decrypted, plaintext);
 }
@@ -113,11 +145,18 @@ async fn encrypt_decrypt_roundtrip_kyber_hybrid() {
         plaintext,
         key,
         EncryptionAlgorithm::KyberHybridAes256Gcm,
+        binding(),
     )
     .await
     .expect("encryption should succeed");
 
-    let StoredContent::Encrypted { ciphertext, .. } = encrypted else {
+    let StoredContent::Encrypted {
+        ciphertext,
+        bound,
+        envelope_version,
+        ..
+    } = encrypted
+    else {
         panic!("expected encrypted content");
     };
 
@@ -126,9 +165,13 @@ async fn encrypt_decrypt_roundtrip_kyber_hybrid() {
         ciphertext,
         nonce: String::new(),
         salt: String::new(),
+        kdf: KdfKind::default(),
+        bound,
+        envelope_version,
     };
 
-    let decrypted = decrypt_content(&stored_content, Some(key)).expect("decryption should succeed");
+    let decrypted = decrypt_content_sync(&stored_content, Some(key), binding())
+        .expect("decryption should succeed");
 
     assert_eq!(decrypted, plaintext);
 }
@@ -139,7 +182,7 @@ fn decrypt_plain_content() {
         text: "plain text content".to_string(),
     };
 
-    let result = decrypt_content(&content, None);
+    let result = decrypt_content_sync(&content, None, binding());
     assert_eq!(result.unwrap(), "plain text content");
 }
 
@@ -150,9 +193,12 @@ fn decrypt_encrypted_missing_key() {
         ciphertext: "dummy".to_string(),
         nonce: "dummy".to_string(),
         salt: "dummy".to_string(),
+        kdf: KdfKind::default(),
+        bound: false,
+        envelope_version: ENVELOPE_V1,
     };
 
-    let result = decrypt_content(&content, None);
+    let result = decrypt_content_sync(&content, None, binding());
     assert!(result.is_err());
 }
 
@@ -165,13 +211,15 @@ async fn decrypt_wrong_key_aes_gcm_returns_invalid_key() {
         plaintext,
         correct_key,
         EncryptionAlgorithm::Aes256Gcm,
+        binding(),
     )
     .await
     .expect("encryption should succeed");
 
-    let result = decrypt_content(
+    let result = decrypt_content_sync(
         &encrypted,
         Some("wrong-key-XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX"),
+        binding(),
     );
     assert!(result.is_err(), "decryption with wrong key must fail");
 }
@@ -181,10 +229,14 @@ async fn decrypt_tampered_ciphertext_aes_gcm_returns_invalid_key() {
     let plaintext = "tamper me";
     let key = "tamper-key-12345678901234567890123456789";
 
-    let encrypted =
-        copypaste::server::crypto::encrypt_content(plaintext, key, EncryptionAlgorithm::Aes256Gcm)
-            .await
-            .expect("encryption should succeed");
+    let encrypted = copypaste::server::crypto::encrypt_content(
+        plaintext,
+        key,
+        EncryptionAlgorithm::Aes256Gcm,
+        binding(),
+    )
+    .await
+    .expect("encryption should succeed");
 
     // Flip a byte in the ciphertext to break the AEAD tag
     let tampered = match encrypted {
@@ -193,6 +245,9 @@ async fn decrypt_tampered_ciphertext_aes_gcm_returns_invalid_key() {
             mut ciphertext,
             nonce,
             salt,
+            kdf,
+            bound,
+            envelope_version,
         } => {
             let mut decoded = base64::engine::general_purpose::STANDARD
                 .decode(&ciphertext)
@@ -204,12 +259,15 @@ async fn decrypt_tampered_ciphertext_aes_gcm_returns_invalid_key() {
                 ciphertext,
                 nonce,
                 salt,
+                kdf,
+                bound,
+                envelope_version,
             }
         }
         _ => panic!("expected encrypted"),
     };
 
-    let result = decrypt_content(&tampered, Some(key));
+    let result = decrypt_content_sync(&tampered, Some(key), binding());
     assert!(
         result.is_err(),
         "decryption of tampered ciphertext must fail"
@@ -221,10 +279,14 @@ async fn decrypt_truncated_ciphertext_aes_gcm_returns_invalid_key() {
     let plaintext = "truncate this";
     let key = "truncate-key-12345678901234567890123456";
 
-    let encrypted =
-        copypaste::server::crypto::encrypt_content(plaintext, key, EncryptionAlgorithm::Aes256Gcm)
-            .await
-            .expect("encryption should succeed");
+    let encrypted = copypaste::server::crypto::encrypt_content(
+        plaintext,
+        key,
+        EncryptionAlgorithm::Aes256Gcm,
+        binding(),
+    )
+    .await
+    .expect("encryption should succeed");
 
     // Truncate the ciphertext (removes the AEAD authentication tag)
     let truncated = match encrypted {
@@ -233,6 +295,9 @@ async fn decrypt_truncated_ciphertext_aes_gcm_returns_invalid_key() {
             mut ciphertext,
             nonce,
             salt,
+            kdf,
+            bound,
+            envelope_version,
         } => {
             let mut decoded = base64::engine::general_purpose::STANDARD
                 .decode(&ciphertext)
@@ -246,18 +311,63 @@ async fn decrypt_truncated_ciphertext_aes_gcm_returns_invalid_key() {
                 ciphertext,
                 nonce,
                 salt,
+                kdf,
+                bound,
+                envelope_version,
             }
         }
         _ => panic!("expected encrypted"),
     };
 
-    let result = decrypt_content(&truncated, Some(key));
+    let result = decrypt_content_sync(&truncated, Some(key), binding());
     assert!(
         result.is_err(),
         "decryption of truncated ciphertext must fail"
     );
 }
 
+/// Undecodable stored fields fail like a wrong key, never as a distinct
+/// error that would show the key was accepted.
+#[tokio::test]
+async fn decrypt_undecodable_fields_return_invalid_key() {
+    let key = "decode-key-1234567890123456789012345678";
+    let encrypted = copypaste::server::crypto::encrypt_content(
+        "decode me",
+        key,
+        EncryptionAlgorithm::ChaCha20Poly1305,
+        binding(),
+    )
+    .await
+    .expect("encryption should succeed");
+
+    for field in ["ciphertext", "nonce", "salt"] {
+        let mut damaged = encrypted.clone();
+        let StoredContent::Encrypted {
+            ciphertext,
+            nonce,
+            salt,
+            ..
+        } = &mut damaged
+        else {
+            panic!("expected encrypted");
+        };
+        let target = match field {
+            "ciphertext" => ciphertext,
+            "nonce" => nonce,
+            _ => salt,
+        };
+        *target = "%%% not base64 %%%".to_string();
+
+        for attempt in [key, "wrong-key"] {
+            let result = decrypt_content_sync(&damaged, Some(attempt), binding());
+            assert!(
+                matches!(result, Err(DecryptError::InvalidKey)),
+                "{field} with {attempt}: {result:?}"
+            );
+        }
+    }
+}
+
 /// Verify the new ML-KEM-768 blob uses exactly 3 pipe-delimited parts.
 /// No private key is stored; it is re-derived from the passphrase at decryption time.
 #[tokio::test]
@@ -269,6 +379,7 @@ async fn kyber_ciphertext_does_not_contain_private_key() {
         plaintext,
         key,
         copypaste::EncryptionAlgorithm::KyberHybridAes256Gcm,
+        binding(),
     )
     .await
     .expect("encryption should succeed");
@@ -345,8 +456,11 @@ fn kyber_legacy_simulation_blob_decrypts_successfully() {
         ciphertext: legacy_4part.clone(),
         nonce: String::new(),
         salt: String::new(),
+        kdf: KdfKind::default(),
+        bound: false,
+        envelope_version: ENVELOPE_V1,
     };
-    let decrypted = decrypt_content(&stored_4, Some(key))
+    let decrypted = decrypt_content_sync(&stored_4, Some(key), binding())
         .expect("legacy 4-part simulation blob must still decrypt");
     assert_eq!(decrypted, plaintext);
 
@@ -357,8 +471,11 @@ fn kyber_legacy_simulation_blob_decrypts_successfully() {
         ciphertext: legacy_5part,
         nonce: String::new(),
         salt: String::new(),
+        kdf: KdfKind::default(),
+        bound: false,
+        envelope_version: ENVELOPE_V1,
     };
-    let decrypted5 = decrypt_content(&stored_5, Some(key))
+    let decrypted5 = decrypt_content_sync(&stored_5, Some(key), binding())
         .expect("legacy 5-part simulation blob must still decrypt");
     assert_eq!(decrypted5, plaintext);
 }
@@ -373,13 +490,15 @@ async fn decrypt_wrong_key_kyber_returns_invalid_key() {
         plaintext,
         correct_key,
         EncryptionAlgorithm::KyberHybridAes256Gcm,
+        binding(),
     )
     .await
     .expect("encryption should succeed");
 
-    let result = decrypt_content(
+    let result = decrypt_content_sync(
         &encrypted,
         Some("wrong-kyber-key-XXXXXXXXXXXXXXXXXXXXXXXXX"),
+        binding(),
     );
     assert!(result.is_err(), "decryption with wrong key must fail");
 }
@@ -395,6 +514,7 @@ async fn kyber_same_passphrase_produces_different_kem_ciphertext() {
         plaintext,
         key,
         EncryptionAlgorithm::KyberHybridAes256Gcm,
+        binding(),
     )
     .await
     .expect("first encryption should succeed");
@@ -403,6 +523,7 @@ async fn kyber_same_passphrase_produces_different_kem_ciphertext() {
         plaintext,
         key,
         EncryptionAlgorithm::KyberHybridAes256Gcm,
+        binding(),
     )
     .await
     .expect("second encryption should succeed");
@@ -424,6 +545,68 @@ async fn kyber_same_passphrase_produces_different_kem_ciphertext() {
         ct1, ct2,
         "KEM ciphertexts must differ across encryptions (OsRng must be used)"
     );
+    for ct in [&ct1, &ct2] {
+        let kem_bytes = base64::engine::general_purpose::STANDARD
+            .decode(ct)
+            .expect("KEM ciphertext is base64");
+        assert_eq!(kem_bytes.len(), 1088, "ML-KEM-768 ciphertext size");
+    }
+    assert_eq!(
+        decrypt_content_sync(&enc1, Some(key), binding()).unwrap(),
+        plaintext
+    );
+    assert_eq!(
+        decrypt_content_sync(&enc2, Some(key), binding()).unwrap(),
+        plaintext
+    );
+}
+
+/// An age paste encrypts to the viewer's public recipient and only opens
+/// with the matching identity.
+#[tokio::test]
+async fn encrypt_decrypt_roundtrip_age() {
+    let plaintext = "Testing age X25519 encryption.";
+    let identity = age::x25519::Identity::generate();
+    let recipient = identity.to_public().to_string();
+
+    let encrypted = copypaste::server::crypto::encrypt_content(
+        plaintext,
+        &recipient,
+        EncryptionAlgorithm::Age,
+        binding(),
+    )
+    .await
+    .expect("encryption should succeed");
+
+    assert!(matches!(
+        encrypted,
+        StoredContent::Encrypted {
+            algorithm: EncryptionAlgorithm::Age,
+            ..
+        }
+    ));
+    let secret = identity.to_string();
+    let decrypted = decrypt_content_sync(&encrypted, Some(secret.expose_secret()), binding())
+        .expect("decryption should succeed");
+
+    assert_eq!(decrypted, plaintext);
+}
+
+#[tokio::test]
+async fn decrypt_wrong_identity_age_returns_invalid_key() {
+    let identity = age::x25519::Identity::generate();
+    let encrypted = copypaste::server::crypto::encrypt_content(
+        "secret age data",
+        &identity.to_public().to_string(),
+        EncryptionAlgorithm::Age,
+        binding(),
+    )
+    .await
+    .expect("encryption should succeed");
+
+    let wrong = age::x25519::Identity::generate().to_string();
+    let result = decrypt_content_sync(&encrypted, Some(wrong.expose_secret()), binding());
+    assert!(matches!(result, Err(DecryptError::InvalidKey)));
 }
 
 // OCaml verification behaviour tests
@@ -441,6 +624,7 @@ async fn ocaml_service_unavailable_does_not_block_by_default() {
         "hello world",
         "test-key-00000000000000000000000000000000",
         copypaste::EncryptionAlgorithm::Aes256Gcm,
+        binding(),
     )
     .await;
 
@@ -456,7 +640,7 @@ async fn ocaml_valid_false_blocks_when_strict_mode_enabled() {
 
     let server = MockServer::start();
     server.mock(|when, then| {
-        when.method(POST).path("/verify/encryption");
+        when.method(POST).path("/verify/signature");
         then.status(200)
             .header("content-type", "application/json")
             .body(r#"{"valid":false,"details":"test forced failure"}"#);
@@ -465,16 +649,12 @@ async fn ocaml_valid_false_blocks_when_strict_mode_enabled() {
     std::env::set_var("CRYPTO_VERIFIER_URL", server.base_url());
     std::env::set_var("COPYPASTE_REQUIRE_CRYPTO_VERIFICATION", "true");
 
-    let result = copypaste::server::crypto::encrypt_content(
-        "hello world",
-        "test-key-00000000000000000000000000000000",
-        copypaste::EncryptionAlgorithm::Aes256Gcm,
-    )
-    .await;
+    let result =
+        copypaste::server::crypto::verify_signature_with_ocaml("message", "c2ln", "cGs=").await;
 
     assert!(
         result.is_err(),
-        "encryption must fail when OCaml verifier returns valid=false in strict mode"
+        "verification must fail when OCaml verifier returns valid=false in strict mode"
     );
 }
 
@@ -484,17 +664,45 @@ async fn ocaml_service_unavailable_blocks_when_strict_mode_enabled() {
     std::env::set_var("COPYPASTE_REQUIRE_CRYPTO_VERIFICATION", "true");
     std::env::set_var("CRYPTO_VERIFIER_URL", "http://127.0.0.1:1");
 
+    let result =
+        copypaste::server::crypto::verify_signature_with_ocaml("message", "c2ln", "cGs=").await;
+
+    assert!(
+        result.is_err(),
+        "verification must fail when OCaml service is unreachable in strict mode"
+    );
+}
+
+#[tokio::test]
+async fn argon2_ciphertexts_skip_ocaml_verification_in_strict_mode() {
+    // The verifier cannot derive Argon2id keys, so asking it would fail every
+    // create in strict mode; such records are not sent at all.
+    use httpmock::prelude::*;
+
+    let server = MockServer::start();
+    let verify = server.mock(|when, then| {
+        when.method(POST).path("/verify/encryption");
+        then.status(200)
+            .header("content-type", "application/json")
+            .body(r#"{"valid":false,"details":"Unsupported key derivation: argon2id"}"#);
+    });
+
+    std::env::set_var("CRYPTO_VERIFIER_URL", server.base_url());
+    std::env::set_var("COPYPASTE_REQUIRE_CRYPTO_VERIFICATION", "true");
+
     let result = copypaste::server::crypto::encrypt_content(
         "hello world",
         "test-key-00000000000000000000000000000000",
-        copypaste::EncryptionAlgorithm::Aes256Gcm,
+        copypaste::EncryptionAlgorithm::ChaCha20Poly1305,
+        binding(),
     )
     .await;
 
     assert!(
-        result.is_err(),
-        "encryption must fail when OCaml service is unreachable in strict mode"
+        result.is_ok(),
+        "Argon2id encryption must not need the verifier"
     );
+    verify.assert_hits(0);
 }
 
 #[tokio::test]
@@ -516,6 +724,7 @@ async fn ocaml_valid_true_allows_encryption_in_strict_mode() {
         "hello world",
         "test-key-00000000000000000000000000000000",
         copypaste::EncryptionAlgorithm::Aes256Gcm,
+        binding(),
     )
     .await;
 
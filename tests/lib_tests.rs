@@ -1,5 +1,6 @@
 use copypaste::{
-    create_paste_store, EncryptionAlgorithm, PasteFormat, PasteMetadata, StoredContent, StoredPaste,
+    create_paste_store, EncryptionAlgorithm, KdfKind, PasteFormat, PasteMetadata, StoredContent,
+    StoredPaste, ENVELOPE_V1,
 };
 
 #[tokio::test]
@@ -14,6 +15,7 @@ async fn store_round_trip_plain() {
         created_at: 1,
         expires_at: None,
         burn_after_reading: false,
+        max_reads: None,
         bundle: metadata.bundle.clone(),
         bundle_parent: metadata.bundle_parent.clone(),
         bundle_label: metadata.bundle_label.clone(),
@@ -26,7 +28,7 @@ async fn store_round_trip_plain() {
         owner_token_hash: None,
     };
 
-    let id = store.create_paste(paste.clone()).await;
+    let id = store.create_paste(paste.clone()).await.unwrap();
     let stored = store.get_paste(&id).await.expect("paste should exist");
     assert!(matches!(stored.content, StoredContent::Plain { .. }));
     assert_eq!(stored.format, paste.format);
@@ -44,6 +46,7 @@ async fn store_expired_returns_error() {
         created_at: 10,
         expires_at: Some(5),
         burn_after_reading: false,
+        max_reads: None,
         bundle: metadata.bundle.clone(),
         bundle_parent: metadata.bundle_parent.clone(),
         bundle_label: metadata.bundle_label.clone(),
@@ -56,7 +59,7 @@ async fn store_expired_returns_error() {
         owner_token_hash: None,
     };
 
-    let id = store.create_paste(paste).await;
+    let id = store.create_paste(paste).await.unwrap();
     assert!(store.get_paste(&id).await.is_err());
 }
 
@@ -70,11 +73,15 @@ async fn store_handles_encrypted_variant() {
             ciphertext: "cipher".into(),
             nonce: "nonce".into(),
             salt: "salt".into(),
+            kdf: KdfKind::default(),
+            bound: true,
+            envelope_version: ENVELOPE_V1,
         },
         format: PasteFormat::Code,
         created_at: 0,
         expires_at: None,
         burn_after_reading: false,
+        max_reads: None,
         bundle: metadata.bundle.clone(),
         bundle_parent: metadata.bundle_parent.clone(),
         bundle_label: metadata.bundle_label.clone(),
@@ -87,7 +94,7 @@ async fn store_handles_encrypted_variant() {
         owner_token_hash: None,
     };
 
-    let id = store.create_paste(paste).await;
+    let id = store.create_paste(paste).await.unwrap();
     let stored = store
         .get_paste(&id)
         .await
@@ -105,11 +112,15 @@ async fn store_handles_chacha_variant() {
             ciphertext: "cipher".into(),
             nonce: "nonce".into(),
             salt: "salt".into(),
+            kdf: KdfKind::default(),
+            bound: true,
+            envelope_version: ENVELOPE_V1,
         },
         format: PasteFormat::Code,
         created_at: 0,
         expires_at: None,
         burn_after_reading: false,
+        max_reads: None,
         bundle: metadata.bundle.clone(),
         bundle_parent: metadata.bundle_parent.clone(),
         bundle_label: metadata.bundle_label.clone(),
@@ -122,7 +133,7 @@ async fn store_handles_chacha_variant() {
         owner_token_hash: None,
     };
 
-    let id = store.create_paste(paste).await;
+    let id = store.create_paste(paste).await.unwrap();
     let stored = store
         .get_paste(&id)
         .await
@@ -1,6 +1,6 @@
 use base64::engine::general_purpose;
 use base64::Engine;
-use copypaste::server::crypto::{decrypt_content, encrypt_content, DecryptError};
+use copypaste::server::crypto::{decrypt_content, encrypt_content, DecryptError, PasteBinding};
 use copypaste::server::handlers::build_rocket;
 use copypaste::server::render::format_json;
 use copypaste::server::time::current_timestamp;
@@ -15,6 +15,10 @@ use sha2::{Digest, Sha256};
 use std::sync::Arc;
 use urlencoding::encode;
 
+fn binding() -> PasteBinding<'static> {
+    PasteBinding::new("test-paste", PasteFormat::PlainText)
+}
+
 async fn rocket_client() -> Client {
     Client::tracked(build_rocket(create_paste_store()))
         .await
@@ -91,10 +95,12 @@ async fn post_encrypted_requires_key() {
 #[rocket::async_test]
 async fn raw_endpoint_requires_key_for_encrypted_content() {
     let store: SharedPasteStore = Arc::new(MemoryPasteStore::default());
+    let id = store.next_paste_id().await;
     let encrypted = encrypt_content(
         "stealth payload",
         "super-secret",
         EncryptionAlgorithm::Aes256Gcm,
+        PasteBinding::new(&id, PasteFormat::PlainText),
     )
     .await
     .expect("encryption successful");
@@ -106,6 +112,7 @@ async fn raw_endpoint_requires_key_for_encrypted_content() {
         created_at: current_timestamp(),
         expires_at: None,
         burn_after_reading: false,
+        max_reads: None,
         bundle: metadata.bundle.clone(),
         bundle_parent: metadata.bundle_parent.clone(),
         bundle_label: metadata.bundle_label.clone(),
@@ -118,7 +125,7 @@ async fn raw_endpoint_requires_key_for_encrypted_content() {
         owner_token_hash: None,
     };
 
-    let id = store.create_paste(paste).await;
+    store.create_paste_with_id(&id, paste).await.unwrap();
     let client = rocket_client_with_store(store.clone()).await;
 
     let missing_key = client.get(format!("/raw/{}", id)).dispatch().await;
@@ -161,6 +168,7 @@ async fn shared_secret_attestation_enforced() {
         created_at: current_timestamp(),
         expires_at: None,
         burn_after_reading: false,
+        max_reads: None,
         bundle: metadata.bundle.clone(),
         bundle_parent: metadata.bundle_parent.clone(),
         bundle_label: metadata.bundle_label.clone(),
@@ -173,7 +181,7 @@ async fn shared_secret_attestation_enforced() {
         owner_token_hash: None,
     };
 
-    let id = store.create_paste(paste).await;
+    let id = store.create_paste(paste).await.unwrap();
     let client = rocket_client_with_store(store.clone()).await;
 
     let missing = client.get(format!("/{}", id)).dispatch().await;
@@ -198,30 +206,51 @@ async fn shared_secret_attestation_enforced() {
 #[rocket::async_test]
 async fn encrypt_then_decrypt_roundtrip() {
     let key = "correct horse battery staple";
-    let stored = encrypt_content("super secret", key, EncryptionAlgorithm::Aes256Gcm)
+    let stored = encrypt_content(
+        "super secret",
+        key,
+        EncryptionAlgorithm::Aes256Gcm,
+        binding(),
+    )
+    .await
+    .expect("encrypt");
+    let decrypted = decrypt_content(&stored, Some(key), binding())
         .await
-        .expect("encrypt");
-    let decrypted = decrypt_content(&stored, Some(key)).expect("decrypt");
+        .expect("decrypt");
     assert_eq!(decrypted, "super secret");
 }
 
 #[rocket::async_test]
 async fn chacha_roundtrip() {
     let key = "tachyon-vector-2048";
-    let stored = encrypt_content("ghost signal", key, EncryptionAlgorithm::ChaCha20Poly1305)
+    let stored = encrypt_content(
+        "ghost signal",
+        key,
+        EncryptionAlgorithm::ChaCha20Poly1305,
+        binding(),
+    )
+    .await
+    .expect("encrypt");
+    let decrypted = decrypt_content(&stored, Some(key), binding())
         .await
-        .expect("encrypt");
-    let decrypted = decrypt_content(&stored, Some(key)).expect("decrypt");
+        .expect("decrypt");
     assert_eq!(decrypted, "ghost signal");
 }
 
 #[rocket::async_test]
 async fn xchacha_roundtrip() {
     let key = "tachyon-subroutine-7331";
-    let stored = encrypt_content("link shell", key, EncryptionAlgorithm::XChaCha20Poly1305)
+    let stored = encrypt_content(
+        "link shell",
+        key,
+        EncryptionAlgorithm::XChaCha20Poly1305,
+        binding(),
+    )
+    .await
+    .expect("encrypt");
+    let decrypted = decrypt_content(&stored, Some(key), binding())
         .await
-        .expect("encrypt");
-    let decrypted = decrypt_content(&stored, Some(key)).expect("decrypt");
+        .expect("decrypt");
     assert_eq!(decrypted, "link shell");
 }
 
@@ -231,10 +260,11 @@ async fn decrypt_requires_key_for_encrypted_content() {
         "classified",
         "moonbase",
         EncryptionAlgorithm::XChaCha20Poly1305,
+        binding(),
     )
     .await
     .expect("encrypt");
-    match decrypt_content(&stored, None) {
+    match decrypt_content(&stored, None, binding()).await {
         Err(DecryptError::MissingKey) => {}
         other => panic!("expected missing key error, got {:?}", other),
     }
@@ -242,10 +272,11 @@ async fn decrypt_requires_key_for_encrypted_content() {
 
 #[test]
 fn format_json_pretty_prints() {
-    let result = format_json(r#"{"foo":1,"bar":[true,false]}"#);
+    let result = format_json(r#"{"foo":1,"bar":[true,false]}"#, false);
     assert!(result.contains('\n'));
-    assert!(result.starts_with("<pre><code>"));
-    assert!(result.contains("&quot;foo&quot;"));
+    assert!(result.starts_with(r#"<pre><code class="language-json">"#));
+    assert!(result.contains("foo"));
+    assert!(result.find("foo") < result.find("bar"));
 }
 
 #[rocket::async_test]
@@ -265,6 +296,7 @@ async fn time_locked_paste_is_protected() {
         created_at: current_timestamp(),
         expires_at: None,
         burn_after_reading: false,
+        max_reads: None,
         bundle: metadata.bundle.clone(),
         bundle_parent: metadata.bundle_parent.clone(),
         bundle_label: metadata.bundle_label.clone(),
@@ -277,11 +309,11 @@ async fn time_locked_paste_is_protected() {
         owner_token_hash: None,
     };
 
-    let id = store.create_paste(paste).await;
+    let id = store.create_paste(paste).await.unwrap();
     let client = rocket_client_with_store(store.clone()).await;
 
     let gated = client.get(format!("/{id}")).dispatch().await;
-    assert_eq!(gated.status(), Status::Ok);
+    assert_eq!(gated.status(), Status::Locked);
     let gated_html = gated.into_string().await.expect("html body");
     assert!(gated_html.contains("Time-locked paste"));
 
@@ -354,6 +386,202 @@ async fn bundle_creation_renders_children() {
     assert!(html.contains("child-one"));
 }
 
+#[rocket::async_test]
+async fn bundle_children_are_stored_linked_and_decrypt_with_shared_key() {
+    let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+    let client = rocket_client_with_store(store.clone()).await;
+    let payload = json!({
+        "content": "parent encrypted",
+        "encryption": { "algorithm": "chacha20_poly1305", "key": "shared-pass" },
+        "bundle": {
+            "children": [
+                { "content": "first share", "label": "one" },
+                { "content": "second share", "label": "two" }
+            ]
+        }
+    });
+    let response = client
+        .post("/api/pastes")
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+    let created: serde_json::Value =
+        serde_json::from_str(&response.into_string().await.unwrap()).unwrap();
+    let parent_id = created["id"].as_str().unwrap();
+    let children = created["bundle"]["children"].as_array().unwrap();
+    assert_eq!(children.len(), 2);
+
+    let parent = store.get_paste(parent_id).await.expect("parent stored");
+    let pointers: Vec<_> = parent
+        .bundle
+        .expect("parent bundle")
+        .children
+        .into_iter()
+        .map(|pointer| pointer.id)
+        .collect();
+
+    for (child, expected) in children.iter().zip(["first share", "second share"]) {
+        let child_id = child["id"].as_str().unwrap();
+        assert!(!child_id.is_empty());
+        assert!(pointers.iter().any(|id| id == child_id));
+
+        let stored = store.get_paste(child_id).await.expect("child stored");
+        assert!(stored.burn_after_reading);
+        assert_eq!(stored.bundle_parent.as_deref(), Some(parent_id));
+        assert!(matches!(stored.content, StoredContent::Encrypted { .. }));
+
+        let link = child["url"].as_str().unwrap();
+        let raw = client
+            .get(format!("/raw{link}?key=shared-pass"))
+            .dispatch()
+            .await;
+        assert_eq!(raw.status(), Status::Ok);
+        assert_eq!(raw.into_string().await.unwrap(), expected);
+    }
+}
+
+#[rocket::async_test]
+async fn stego_request_stores_ciphertext_in_png_carrier() {
+    let store: SharedPasteStore = Arc::new(MemoryPasteStore::new());
+    let client = rocket_client_with_store(store.clone()).await;
+    let payload = json!({
+        "content": "hidden in plain sight",
+        "encryption": { "algorithm": "aes256_gcm", "key": "stego-pass" },
+        "stego": { "mode": "builtin", "carrier": "aurora" }
+    });
+    let response = client
+        .post("/api/pastes")
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+    let created: serde_json::Value =
+        serde_json::from_str(&response.into_string().await.unwrap()).unwrap();
+    let id = created["id"].as_str().unwrap();
+
+    let stored = store.get_paste(id).await.expect("paste stored");
+    let StoredContent::Stego {
+        ciphertext,
+        carrier_mime,
+        carrier_image,
+        payload_digest,
+        ..
+    } = &stored.content
+    else {
+        panic!("expected stego content, got {:?}", stored.content);
+    };
+    assert_eq!(carrier_mime, "image/png");
+
+    let png = general_purpose::STANDARD.decode(carrier_image).unwrap();
+    assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+    image::load_from_memory_with_format(&png, image::ImageFormat::Png)
+        .expect("carrier decodes as PNG");
+
+    let embedded = general_purpose::STANDARD.decode(ciphertext).unwrap();
+    assert_eq!(payload_digest, &hex::encode(Sha256::digest(&embedded)));
+
+    let raw = client
+        .get(format!("/raw/{id}?key=stego-pass"))
+        .dispatch()
+        .await;
+    assert_eq!(raw.into_string().await.unwrap(), "hidden in plain sight");
+}
+
+#[rocket::async_test]
+async fn concurrent_reads_of_burn_paste_reveal_it_once() {
+    let client = Arc::new(rocket_client().await);
+    let response = client
+        .post("/api/pastes")
+        .header(ContentType::JSON)
+        .body(json!({ "content": "for one viewer", "burn_after_reading": true }).to_string())
+        .dispatch()
+        .await;
+    let created: serde_json::Value =
+        serde_json::from_str(&response.into_string().await.unwrap()).unwrap();
+    let id = created["id"].as_str().unwrap().to_string();
+
+    let mut readers = rocket::tokio::task::JoinSet::new();
+    for n in 0..12 {
+        let client = client.clone();
+        // Mix the three read routes; they share the same burn semantics.
+        let path = match n % 3 {
+            0 => format!("/{id}"),
+            1 => format!("/raw/{id}"),
+            _ => format!("/api/pastes/{id}"),
+        };
+        readers.spawn(async move {
+            let response = client.get(path).dispatch().await;
+            let status = response.status();
+            let body = response.into_string().await.unwrap_or_default();
+            (status, body)
+        });
+    }
+
+    let mut revealed = 0;
+    while let Some(result) = readers.join_next().await {
+        let (status, body) = result.unwrap();
+        if status == Status::Ok {
+            assert!(body.contains("for one viewer"));
+            revealed += 1;
+        } else {
+            assert_eq!(status, Status::NotFound);
+            assert!(!body.contains("for one viewer"));
+        }
+    }
+    assert_eq!(revealed, 1);
+}
+
+#[rocket::async_test]
+async fn bundle_overview_marks_children_by_decryptability() {
+    let client = rocket_client().await;
+    let payload = json!({
+        "content": "parent encrypted",
+        "encryption": { "algorithm": "aes256_gcm", "key": "shared-pass" },
+        "bundle": {
+            "children": [
+                { "content": "for the parent key holder", "label": "mine" },
+                { "content": "for someone else", "label": "theirs", "key": "other-pass" }
+            ]
+        }
+    });
+    let response = client
+        .post("/api/pastes")
+        .header(ContentType::JSON)
+        .body(payload.to_string())
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+    let created: serde_json::Value =
+        serde_json::from_str(&response.into_string().await.unwrap()).unwrap();
+    let parent_id = created["id"].as_str().unwrap();
+    let theirs_id = created["bundle"]["children"][1]["id"].as_str().unwrap();
+
+    for _ in 0..2 {
+        let html = client
+            .get(format!("/{parent_id}?key=shared-pass"))
+            .dispatch()
+            .await
+            .into_string()
+            .await
+            .unwrap();
+        let mine = html.find("mine</a>").expect("first share");
+        let theirs = html.find("theirs</a>").expect("second share");
+        assert!(html[mine..theirs].contains("Available"));
+        assert!(html[theirs..].contains("Locked (different key)"));
+    }
+
+    // Rendering the overview twice did not burn the child.
+    let child = client
+        .get(format!("/raw/{theirs_id}?key=other-pass"))
+        .dispatch()
+        .await;
+    assert_eq!(child.status(), Status::Ok);
+    assert_eq!(child.into_string().await.unwrap(), "for someone else");
+}
+
 #[rocket::async_test]
 async fn health_endpoint_responds_quickly() {
     let client = rocket_client().await;